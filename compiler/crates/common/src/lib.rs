@@ -15,6 +15,7 @@ mod murmurhash;
 mod named_item;
 mod perf_logger;
 mod span;
+mod suggestions;
 
 pub use console_logger::{print_time, ConsoleLogEvent, ConsoleLogger};
 pub use location::{FileKey, Location, WithLocation};
@@ -22,3 +23,4 @@ pub use murmurhash::murmurhash;
 pub use named_item::{Named, NamedItem};
 pub use perf_logger::{PerfLogEvent, PerfLogger};
 pub use span::{Span, Spanned};
+pub use suggestions::{levenshtein_distance, suggested_names};
@@ -5,7 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-pub trait PerfLogger {
+pub trait PerfLogger: Send + Sync {
     type PerfLogEvent: PerfLogEvent;
     /// Create log event
     fn create_event(&self, name: impl Copy + Into<String>) -> Self::PerfLogEvent;
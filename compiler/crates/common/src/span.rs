@@ -46,6 +46,10 @@ impl Span {
             && (subspan.start + subspan.length) <= (self.start + self.length)
     }
 
+    /// Maps the span to an LSP range, where `line_offset` and `character_offset` are
+    /// the position of the start of `source` in the document, e.g. the position of the
+    /// GraphQL template literal in a JavaScript file. Spans are byte offsets into `source`,
+    /// and LSP characters are UTF-16 code units.
     pub fn to_range(self, source: &str, line_offset: usize, character_offset: usize) -> Range {
         let start = self.start as usize;
         let end = (self.start + self.length) as usize;
@@ -53,41 +57,31 @@ impl Span {
         let mut line = line_offset;
         // Zero-indexed character offset on the line
         let mut character = character_offset;
-        let mut start_position = Position::default();
-        let mut end_position = Position::default();
-        let mut chars = source.chars().enumerate().peekable();
+        let mut start_position = None;
+        let mut chars = source.char_indices().peekable();
 
         while let Some((index, chr)) = chars.next() {
-            let is_newline = match chr {
-                // Line terminators: https://www.ecma-international.org/ecma-262/#sec-line-terminators
-                '\u{000A}' | '\u{000D}' | '\u{2028}' | '\u{2029}' => match (chr, chars.peek()) {
-                    // <CLRF>
-                    ('\u{000D}', Some((_, '\u{000D}'))) => false,
-                    _ => true,
-                },
-                _ => false,
-            };
-
-            if is_newline {
-                // New line, increment the line offset and reset the
-                // character offset.
-                line += 1;
-                character = 0;
-            }
-            if index == start {
-                start_position = Position::new(line as u64, character as u64);
+            if start_position.is_none() && index >= start {
+                start_position = Some(Position::new(line as u64, character as u64));
             }
-            if index == end {
-                end_position = Position::new(line as u64, character as u64);
-                break;
+            if index >= end {
+                let end_position = Position::new(line as u64, character as u64);
+                return Range::new(start_position.unwrap_or(end_position), end_position);
             }
-            // Make sure to only increment the character offset if this
-            // isn't a newline.
-            if !is_newline {
-                character += 1;
+            match (chr, chars.peek()) {
+                // <CRLF> is a single line terminator, the line is incremented at the <LF>
+                ('\u{000D}', Some((_, '\u{000A}'))) => {}
+                // Line terminators: https://www.ecma-international.org/ecma-262/#sec-line-terminators
+                ('\u{000A}', _) | ('\u{000D}', _) | ('\u{2028}', _) | ('\u{2029}', _) => {
+                    line += 1;
+                    character = 0;
+                }
+                _ => character += chr.len_utf16(),
             }
         }
-        Range::new(start_position, end_position)
+        // The span ends at the end of the source, e.g. for an unexpected end of input
+        let end_position = Position::new(line as u64, character as u64);
+        Range::new(start_position.unwrap_or(end_position), end_position)
     }
 
     pub fn print(
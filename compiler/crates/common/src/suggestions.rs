@@ -0,0 +1,84 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use interner::StringKey;
+
+/// Returns up to `max_suggestions` of the `candidates` closest to `name`, ordered by
+/// their edit distance. Candidates more than about 40% of the name length away aren't
+/// suggested, so that unrelated names don't yield any suggestions.
+pub fn suggested_names(
+    name: StringKey,
+    candidates: impl IntoIterator<Item = StringKey>,
+    max_suggestions: usize,
+) -> Vec<StringKey> {
+    let name = name.lookup();
+    let threshold = name.chars().count() * 2 / 5 + 1;
+    let mut suggestions: Vec<(usize, StringKey)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = levenshtein_distance(name, candidate.lookup());
+            if distance <= threshold {
+                Some((distance, candidate))
+            } else {
+                None
+            }
+        })
+        .collect();
+    suggestions.sort_by(|(a_distance, a), (b_distance, b)| {
+        a_distance
+            .cmp(b_distance)
+            .then_with(|| a.lookup().cmp(b.lookup()))
+    });
+    suggestions
+        .into_iter()
+        .take(max_suggestions)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// The number of single character insertions, deletions and substitutions needed to
+/// change `a` into `b`. Differences in case count as a substitution.
+pub fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current_row = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        previous_row = current_row;
+    }
+    previous_row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interner::Intern;
+
+    #[test]
+    fn test_levenshtein_distance() {
+        assert_eq!(levenshtein_distance("name", "name"), 0);
+        assert_eq!(levenshtein_distance("nam", "name"), 1);
+        assert_eq!(levenshtein_distance("nmae", "name"), 2);
+        assert_eq!(levenshtein_distance("", "id"), 2);
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_suggested_names() {
+        let candidates = vec!["UserFragment".intern(), "UserProfile".intern()];
+        assert_eq!(
+            suggested_names("UserFragmnt".intern(), candidates.clone(), 1),
+            vec!["UserFragment".intern()]
+        );
+        assert!(suggested_names("Viewer".intern(), candidates, 1).is_empty());
+    }
+}
@@ -0,0 +1,24 @@
+/**
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+'use strict';
+
+const query = graphql`
+  query TestQuery {
+    friends(first: 1.23.4) {
+      count
+    }
+  }
+`;
+
+module.exports = {
+  query,
+  fragment: graphql`
+    fragment UserFragment on User {
+      name
+`,
+};
@@ -0,0 +1,38 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::FileKey;
+use extract_graphql::parse_chunks;
+use fixture_tests::Fixture;
+use graphql_syntax::parse;
+
+/// Prints the range of each syntax error in the document, i.e. where the error
+/// is shown in the editor for the GraphQL literals of a JavaScript file.
+pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
+    let mut errors = vec![];
+    for (index, source) in parse_chunks(fixture.content)?.iter().enumerate() {
+        let file_key = FileKey::new(&format!("{}:{}", fixture.file_name, index));
+        if let Err(syntax_errors) = parse(&source.text, file_key) {
+            for error in syntax_errors {
+                let range = error.location.span().to_range(
+                    &source.text,
+                    source.line_index,
+                    source.column_index,
+                );
+                errors.push(format!(
+                    "{} at {}:{}-{}:{}",
+                    error.kind,
+                    range.start.line,
+                    range.start.character,
+                    range.end.line,
+                    range.end.character
+                ));
+            }
+        }
+    }
+    Ok(errors.join("\n"))
+}
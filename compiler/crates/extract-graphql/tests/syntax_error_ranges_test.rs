@@ -0,0 +1,13 @@
+// @generated SignedSource<<864fbba833c10d0aa0fde89c0e02fd68>>
+
+mod syntax_error_ranges;
+
+use syntax_error_ranges::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn multi_line_document() {
+    let input = include_str!("syntax_error_ranges/fixtures/multi-line-document.js");
+    let expected = include_str!("syntax_error_ranges/fixtures/multi-line-document.expected");
+    test_fixture(transform_fixture, "multi-line-document.js", "syntax_error_ranges/fixtures/multi-line-document.expected", input, expected);
+}
@@ -8,7 +8,7 @@
 use crate::errors::{ValidationError, ValidationMessage, ValidationResult};
 use crate::ir::*;
 use crate::signatures::{build_signatures, FragmentSignature, FragmentSignatures};
-use common::{Location, NamedItem, Span, WithLocation};
+use common::{suggested_names, Location, NamedItem, Span, WithLocation};
 use errors::{try2, try3, try_map};
 use fnv::{FnvBuildHasher, FnvHashMap, FnvHashSet};
 use graphql_syntax::{List, OperationKind};
@@ -428,9 +428,18 @@ impl<'schema, 'signatures> Builder<'schema, 'signatures> {
         let signature = match self.signatures.get(&spread.name.value) {
             Some(fragment) => fragment,
             None => {
+                let suggestion =
+                    suggested_names(spread.name.value, self.signatures.keys().copied(), 1).pop();
+                let message = match suggestion {
+                    Some(suggestion) => ValidationMessage::UndefinedFragmentWithSuggestion {
+                        fragment_name: spread.name.value,
+                        suggestion,
+                    },
+                    None => ValidationMessage::UndefinedFragment(spread.name.value),
+                };
                 return Err(self
                     .record_error(ValidationError::new(
-                        ValidationMessage::UndefinedFragment(spread.name.value),
+                        message,
                         vec![self.location.with_span(spread.span)],
                     ))
                     .into());
@@ -9,7 +9,7 @@ use common::{FileKey, Location};
 use fnv::FnvHashMap;
 use graphql_syntax::{GraphQLSource, OperationKind};
 use interner::StringKey;
-use schema::{Type, TypeReference};
+use schema::{RelayDirectiveArgumentKind, Type, TypeReference};
 use std::fmt;
 use thiserror::Error;
 
@@ -109,6 +109,11 @@ pub enum ValidationMessage {
     UnknownType(StringKey),
     #[error("Undefined fragment '{0}'")]
     UndefinedFragment(StringKey),
+    #[error("Undefined fragment '{fragment_name}', did you mean '{suggestion}'?")]
+    UndefinedFragmentWithSuggestion {
+        fragment_name: StringKey,
+        suggestion: StringKey,
+    },
     #[error("Expected an object, interface, or union, found '{0:?}'")]
     ExpectedCompositeType(Type),
     #[error("Expected type '{0:?}")]
@@ -161,6 +166,11 @@ pub enum ValidationMessage {
     },
     #[error("Expected operation variables to be defined")]
     ExpectedVariablesToBeDefined(),
+    #[error("Variable '${variable_name}' is used by operation '{operation_name}' but isn't defined by it")]
+    UndefinedVariable {
+        variable_name: StringKey,
+        operation_name: StringKey,
+    },
     #[error("Expected argument definition to have an input type (scalar, enum, or input object), found type '{0}'")]
     ExpectedFragmentArgumentToHaveInputType(StringKey),
     #[error("Expected variable definition to have an input type (scalar, enum, or input object), found type '{0}'")]
@@ -182,6 +192,12 @@ pub enum ValidationMessage {
     #[error("Invalid values passed to '@arguments', supported options include 'type' and 'defaultValue', got '{0}'")]
     InvalidArgumentsKeys(String),
 
+    #[error("Missing required argument '{argument_name}' of fragment '{fragment_name}', arguments without a default value must be passed with '@arguments'.")]
+    MissingRequiredFragmentArgument {
+        argument_name: StringKey,
+        fragment_name: StringKey,
+    },
+
     #[error("Unexpected arguments on '__typename' field")]
     InvalidArgumentsOnTypenameField(),
 
@@ -191,6 +207,15 @@ pub enum ValidationMessage {
     #[error("Unexpected directive: '{0}'. This directive can only be used on fields/fragments that are fetched from the server schema, but it is used inside a client-only selection.")]
     InvalidServerOnlyDirectiveInClientFields(StringKey),
 
+    #[error("Unexpected @required directive on non-nullable field '{field_name}'. @required can only be used on nullable fields.")]
+    RequiredOnNonNullField { field_name: StringKey },
+
+    #[error("Expected the 'action' argument of @required on field '{field_name}' to be one of {valid_actions}.")]
+    InvalidRequiredAction {
+        field_name: StringKey,
+        valid_actions: String,
+    },
+
     #[error("@{connection_directive_name} used on invalid field '{connection_field_name}'. Expected the return type to be a non-plural interface or object, got '{connection_type_string}'.")]
     InvalidConnectionFieldType {
         connection_directive_name: StringKey,
@@ -291,8 +316,14 @@ pub enum ValidationMessage {
         filters_arg_name: StringKey,
     },
 
-    #[error("Expected the `{0}` argument to @relay to be a boolean literal if specified.")]
-    InvalidRelayDirectiveArg(StringKey),
+    #[error(
+        "Expected the `{argument_name}` argument to @{directive_name} to be {expected} if specified."
+    )]
+    InvalidRelayDirectiveArgument {
+        directive_name: StringKey,
+        argument_name: StringKey,
+        expected: RelayDirectiveArgumentKind,
+    },
     #[error("Cannot use @relay(mask: false) on fragment spreads for fragments with directives.")]
     InvalidUnmaskOnFragmentWithDirectives(),
     #[error("Cannot use @relay(mask: false) on fragment spreads for fragments with @argumentDefinitions.")]
@@ -363,6 +394,9 @@ pub enum ValidationMessage {
     #[error("Found conflicting @module selections: use a unique alias on the parent fields")]
     ConflictingModuleSelections,
 
+    #[error("Expected the fields selected as '{response_key}' to be the same field with the same arguments, use a distinct alias for each of them")]
+    ConflictingFieldSelections { response_key: StringKey },
+
     #[error("Invalid use of @{directive_name}, the provided label is not unique. Specify a unique 'label' as a literal string.")]
     LabelNotUniqueForDeferStream { directive_name: StringKey },
     #[error(
@@ -475,6 +509,19 @@ pub enum ValidationMessage {
     #[error("The root of subscription '{subscription_name}' must be a simple selection.")]
     GenerateSubscriptionNameSimpleSelection { subscription_name: StringKey },
 
+    #[error(
+        "Subscription '{subscription_name}' must select exactly one root field, it doesn't select any besides introspection fields"
+    )]
+    MissingSubscriptionRootField { subscription_name: StringKey },
+
+    #[error(
+        "Subscription '{subscription_name}' must select exactly one root field, but it also selects '{response_key}'"
+    )]
+    MultipleSubscriptionRootFields {
+        subscription_name: StringKey,
+        response_key: StringKey,
+    },
+
     #[error(
         "Live query expects 'polling_interval' or 'config_id' as an argument to @live_query to for root field {query_name}"
     )]
@@ -15,6 +15,15 @@ pub trait Transformer {
     const VISIT_ARGUMENTS: bool;
     const VISIT_DIRECTIVES: bool;
 
+    /// Whether the transform should keep visiting the program. Transforms that
+    /// only need to find out if the program contains something can return false
+    /// once they know, which aborts the visitation of the remaining operations and
+    /// fragments by `default_transform_program`. The nodes that were already
+    /// transformed are preserved and the remaining ones are kept as they are.
+    fn should_continue(&self) -> bool {
+        true
+    }
+
     fn transform_program<'s>(&mut self, program: &Program<'s>) -> TransformedValue<Program<'s>> {
         self.default_transform_program(program)
     }
@@ -26,6 +35,10 @@ pub trait Transformer {
         let mut next_program = Program::new(program.schema());
         let mut has_changes = false;
         for operation in program.operations() {
+            if !self.should_continue() {
+                next_program.insert_operation(Arc::clone(operation));
+                continue;
+            }
             match self.transform_operation(operation) {
                 Transformed::Delete => has_changes = true,
                 Transformed::Keep => next_program.insert_operation(Arc::clone(operation)),
@@ -36,6 +49,10 @@ pub trait Transformer {
             }
         }
         for fragment in program.fragments() {
+            if !self.should_continue() {
+                next_program.insert_fragment(Arc::clone(fragment));
+                continue;
+            }
             match self.transform_fragment(fragment) {
                 Transformed::Delete => has_changes = true,
                 Transformed::Keep => next_program.insert_fragment(Arc::clone(fragment)),
@@ -523,3 +540,87 @@ impl<T> Into<Transformed<T>> for TransformedValue<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build;
+    use common::FileKey;
+    use graphql_syntax::parse;
+    use interner::{Intern, StringKey};
+    use schema::Schema;
+    use test_schema::TEST_SCHEMA;
+
+    /// Removes the first selection of a field, then stops visiting the program
+    struct RemoveFirstField<'s> {
+        schema: &'s Schema,
+        field_name: StringKey,
+        removed: bool,
+    }
+
+    impl<'s> Transformer for RemoveFirstField<'s> {
+        const NAME: &'static str = "RemoveFirstFieldTransform";
+        const VISIT_ARGUMENTS: bool = false;
+        const VISIT_DIRECTIVES: bool = false;
+
+        fn should_continue(&self) -> bool {
+            !self.removed
+        }
+
+        fn transform_scalar_field(&mut self, field: &ScalarField) -> Transformed<Selection> {
+            if !self.removed && self.schema.field(field.definition.item).name == self.field_name {
+                self.removed = true;
+                Transformed::Delete
+            } else {
+                Transformed::Keep
+            }
+        }
+    }
+
+    fn count_scalar_fields(selections: &[Selection]) -> usize {
+        selections
+            .iter()
+            .map(|selection| match selection {
+                Selection::ScalarField(_) => 1,
+                Selection::LinkedField(field) => count_scalar_fields(&field.selections),
+                Selection::InlineFragment(_)
+                | Selection::FragmentSpread(_)
+                | Selection::Condition(_) => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_should_continue_stops_visiting_the_program() {
+        let text = "query FirstQuery { me { id name } }
+            query SecondQuery { me { id name } }
+            fragment UserFragment on User { id name }";
+        let document = parse(text, FileKey::new("test.graphql")).unwrap();
+        let program = Program::from_definitions(
+            &TEST_SCHEMA,
+            build(&TEST_SCHEMA, &document.definitions).unwrap(),
+        );
+        let mut transform = RemoveFirstField {
+            schema: &TEST_SCHEMA,
+            field_name: "name".intern(),
+            removed: false,
+        };
+
+        let next_program = transform
+            .transform_program(&program)
+            .replace_or_else(|| program.clone());
+        // Only the first `name` is removed, the definitions after it are kept as they are
+        let scalar_field_count: usize = next_program
+            .operations()
+            .map(|operation| count_scalar_fields(&operation.selections))
+            .chain(
+                next_program
+                    .fragments()
+                    .map(|fragment| count_scalar_fields(&fragment.selections)),
+            )
+            .sum();
+        assert_eq!(next_program.operations().count(), 2);
+        assert_eq!(next_program.fragments().count(), 1);
+        assert_eq!(scalar_field_count, 5);
+    }
+}
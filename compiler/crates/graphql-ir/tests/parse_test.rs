@@ -1,4 +1,4 @@
-// @generated SignedSource<<7477e4591b12a0bc4aebae640f66e375>>
+// @generated SignedSource<<dc05686f5c31709bb161b3dc77423526>>
 
 mod parse;
 
@@ -313,6 +313,20 @@ fn literal_object_argument_invalid() {
     test_fixture(transform_fixture, "literal-object-argument.invalid.graphql", "parse/fixtures/literal-object-argument.invalid.expected", input, expected);
 }
 
+#[test]
+fn literal_object_argument_missing_fields_invalid() {
+    let input = include_str!("parse/fixtures/literal-object-argument-missing-fields.invalid.graphql");
+    let expected = include_str!("parse/fixtures/literal-object-argument-missing-fields.invalid.expected");
+    test_fixture(transform_fixture, "literal-object-argument-missing-fields.invalid.graphql", "parse/fixtures/literal-object-argument-missing-fields.invalid.expected", input, expected);
+}
+
+#[test]
+fn literal_scalar_argument_invalid() {
+    let input = include_str!("parse/fixtures/literal-scalar-argument.invalid.graphql");
+    let expected = include_str!("parse/fixtures/literal-scalar-argument.invalid.expected");
+    test_fixture(transform_fixture, "literal-scalar-argument.invalid.graphql", "parse/fixtures/literal-scalar-argument.invalid.expected", input, expected);
+}
+
 #[test]
 fn null_values() {
     let input = include_str!("parse/fixtures/null-values.graphql");
@@ -362,6 +376,13 @@ fn undefined_fragment_invalid() {
     test_fixture(transform_fixture, "undefined-fragment.invalid.graphql", "parse/fixtures/undefined-fragment.invalid.expected", input, expected);
 }
 
+#[test]
+fn undefined_fragment_with_suggestion_invalid() {
+    let input = include_str!("parse/fixtures/undefined-fragment-with-suggestion.invalid.graphql");
+    let expected = include_str!("parse/fixtures/undefined-fragment-with-suggestion.invalid.expected");
+    test_fixture(transform_fixture, "undefined-fragment-with-suggestion.invalid.graphql", "parse/fixtures/undefined-fragment-with-suggestion.invalid.expected", input, expected);
+}
+
 #[test]
 fn undefined_type_invalid() {
     let input = include_str!("parse/fixtures/undefined-type.invalid.graphql");
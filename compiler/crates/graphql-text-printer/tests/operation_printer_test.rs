@@ -1,4 +1,4 @@
-// @generated SignedSource<<a60416ae2e6ab7dea59da3c3da781539>>
+// @generated SignedSource<<856a9474dd5116069745efb8d6a27b67>>
 
 mod operation_printer;
 
@@ -12,6 +12,13 @@ fn multiple_queries_with_same_fragment() {
     test_fixture(transform_fixture, "multiple-queries-with-same-fragment.graphql", "operation_printer/fixtures/multiple-queries-with-same-fragment.expected", input, expected);
 }
 
+#[test]
+fn query_with_comments() {
+    let input = include_str!("operation_printer/fixtures/query-with-comments.graphql");
+    let expected = include_str!("operation_printer/fixtures/query-with-comments.expected");
+    test_fixture(transform_fixture, "query-with-comments.graphql", "operation_printer/fixtures/query-with-comments.expected", input, expected);
+}
+
 #[test]
 fn query_with_fragment_spreads() {
     let input = include_str!("operation_printer/fixtures/query-with-fragment-spreads.graphql");
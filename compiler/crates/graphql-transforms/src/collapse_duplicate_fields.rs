@@ -0,0 +1,193 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::node_identifier::LocationAgnosticPartialEq;
+use common::{Location, WithLocation};
+use fnv::FnvHashMap;
+use graphql_ir::{
+    Argument, LinkedField, Program, Selection, TransformedValue, Transformer, ValidationError,
+    ValidationMessage, ValidationResult,
+};
+use interner::StringKey;
+use schema::{FieldID, Schema};
+use std::sync::Arc;
+
+/// A transform that collapses the selections of the same field with the same
+/// arguments and directives, whatever their alias, e.g. `name`, `a: name` and
+/// `b: name`:
+/// - the duplicates are merged into the first selection, which keeps its response
+///   key, joining the selections of linked fields,
+/// - a field with a different definition or arguments than an earlier field with the
+///   same response key is reported, as both would be written to the same key of the
+///   response, e.g. `friend: friends(first: 1)` and `friend: friends(first: 2)`.
+///
+/// The response keys of the collapsed aliases are not selected anymore, so this
+/// transform is not part of the default pipelines: it is meant for the pipelines
+/// that read each field by its first response key. Duplicates with different
+/// directives, e.g. `@__clientField`, are kept. Only the fields of the same selection
+/// set are compared, not those of the inline fragments and conditions it contains.
+pub fn collapse_duplicate_fields<'s>(program: &Program<'s>) -> ValidationResult<Program<'s>> {
+    let mut transform = CollapseDuplicateFields {
+        schema: program.schema(),
+        errors: vec![],
+    };
+    let next_program = transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone());
+    if transform.errors.is_empty() {
+        Ok(next_program)
+    } else {
+        Err(transform.errors)
+    }
+}
+
+struct CollapseDuplicateFields<'s> {
+    schema: &'s Schema,
+    errors: Vec<ValidationError>,
+}
+
+impl<'s> CollapseDuplicateFields<'s> {
+    /// Merges the duplicate fields of `selections`, returns `None` if there are none
+    fn collapse_fields(&mut self, selections: &[Selection]) -> Option<Vec<Selection>> {
+        let mut next_selections: Vec<Selection> = Vec::with_capacity(selections.len());
+        let mut fields_by_response_key: FnvHashMap<StringKey, usize> = Default::default();
+        let mut has_changes = false;
+        for selection in selections {
+            let response_key = match selection {
+                Selection::LinkedField(field) => field.alias_or_name(self.schema),
+                Selection::ScalarField(field) => field.alias_or_name(self.schema),
+                Selection::FragmentSpread(_)
+                | Selection::InlineFragment(_)
+                | Selection::Condition(_) => {
+                    next_selections.push(selection.clone());
+                    continue;
+                }
+            };
+            if let Some(index) = fields_by_response_key.get(&response_key) {
+                let previous = &next_selections[*index];
+                if !is_same_field(previous, selection) {
+                    self.errors.push(ValidationError::new(
+                        ValidationMessage::ConflictingFieldSelections { response_key },
+                        vec![field_location(selection), field_location(previous)],
+                    ));
+                    continue;
+                }
+            }
+            let duplicate_index = next_selections
+                .iter()
+                .position(|previous| is_duplicate_field(previous, selection));
+            match duplicate_index {
+                Some(index) => {
+                    if let (Selection::LinkedField(previous), Selection::LinkedField(field)) =
+                        (&next_selections[index], selection)
+                    {
+                        let mut selections = previous.selections.clone();
+                        selections.extend(field.selections.iter().cloned());
+                        let merged = LinkedField {
+                            selections,
+                            ..LinkedField::clone(previous)
+                        };
+                        next_selections[index] = Selection::LinkedField(Arc::new(merged));
+                    }
+                    has_changes = true;
+                }
+                None => {
+                    fields_by_response_key
+                        .entry(response_key)
+                        .or_insert_with(|| next_selections.len());
+                    next_selections.push(selection.clone());
+                }
+            }
+        }
+        if has_changes {
+            Some(next_selections)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'s> Transformer for CollapseDuplicateFields<'s> {
+    const NAME: &'static str = "CollapseDuplicateFieldsTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_selections(
+        &mut self,
+        selections: &[Selection],
+    ) -> TransformedValue<Vec<Selection>> {
+        // The selections of the merged fields are collapsed by the transform of the
+        // merged field
+        match self.collapse_fields(selections) {
+            Some(next_selections) => TransformedValue::Replace(
+                self.transform_list(&next_selections, Self::transform_selection)
+                    .replace_or_else(|| next_selections.clone()),
+            ),
+            None => self.transform_list(selections, Self::transform_selection),
+        }
+    }
+}
+
+/// Checks if two fields with the same response key are the same field of the schema
+/// with the same arguments
+fn is_same_field(a: &Selection, b: &Selection) -> bool {
+    match (field_definition(a), field_definition(b)) {
+        (Some((a_field, a_arguments)), Some((b_field, b_arguments))) => {
+            a_field == b_field && a_arguments.location_agnostic_eq(b_arguments)
+        }
+        _ => false,
+    }
+}
+
+/// Checks if two selections are the same field of the schema with the same arguments
+/// and directives, whatever their alias
+fn is_duplicate_field(a: &Selection, b: &Selection) -> bool {
+    match (a, b) {
+        (Selection::LinkedField(a), Selection::LinkedField(b)) => {
+            a.definition.item == b.definition.item
+                && a.arguments.location_agnostic_eq(&b.arguments)
+                && a.directives.location_agnostic_eq(&b.directives)
+        }
+        (Selection::ScalarField(a), Selection::ScalarField(b)) => {
+            a.definition.item == b.definition.item
+                && a.arguments.location_agnostic_eq(&b.arguments)
+                && a.directives.location_agnostic_eq(&b.directives)
+        }
+        _ => false,
+    }
+}
+
+fn field_definition(selection: &Selection) -> Option<(FieldID, &Vec<Argument>)> {
+    match selection {
+        Selection::LinkedField(field) => Some((field.definition.item, &field.arguments)),
+        Selection::ScalarField(field) => Some((field.definition.item, &field.arguments)),
+        Selection::FragmentSpread(_) | Selection::InlineFragment(_) | Selection::Condition(_) => {
+            None
+        }
+    }
+}
+
+/// The location of the alias of a field, or of its name without alias
+fn field_location(selection: &Selection) -> Location {
+    match selection {
+        Selection::LinkedField(field) => alias_or_name_location(field.alias, field.definition),
+        Selection::ScalarField(field) => alias_or_name_location(field.alias, field.definition),
+        Selection::FragmentSpread(_) | Selection::InlineFragment(_) | Selection::Condition(_) => {
+            unreachable!("Expected a field")
+        }
+    }
+}
+
+fn alias_or_name_location<T>(
+    alias: Option<WithLocation<StringKey>>,
+    definition: WithLocation<T>,
+) -> Location {
+    match alias {
+        Some(alias) => alias.location,
+        None => definition.location,
+    }
+}
@@ -6,6 +6,7 @@
  */
 
 mod directives;
+mod split_defer_stream;
 
 use super::get_applied_fragment_name;
 use crate::util::{remove_directive, replace_directive};
@@ -18,6 +19,7 @@ use graphql_ir::{
 };
 use interner::{Intern, StringKey};
 use lazy_static::lazy_static;
+pub use split_defer_stream::split_defer_stream;
 use std::{collections::HashMap, sync::Arc};
 
 pub struct DeferStreamConstants {
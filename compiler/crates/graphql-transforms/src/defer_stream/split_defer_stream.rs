@@ -0,0 +1,156 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use super::DEFER_STREAM_CONSTANTS;
+use crate::match_::{attach_split_operation_metadata, SplitOperationMetaData};
+use common::{NamedItem, WithLocation};
+use fnv::FnvHashMap;
+use graphql_ir::{
+    Directive, FragmentDefinition, InlineFragment, LinkedField, OperationDefinition, Program,
+    Selection, Transformed, TransformedValue, Transformer,
+};
+use graphql_syntax::OperationKind;
+use interner::StringKey;
+use schema::Type;
+use std::sync::Arc;
+
+/// A transform that creates a split operation for each deferred fragment spread and
+/// streamed field, named after the label of its `@defer` or `@stream` directive.
+/// The split operations are marked like the split operations of `@module`, so that
+/// `skip_split_operation` removes them before the queries are sent to the server.
+///
+/// Expects the labels and deferred fragment spreads of `transform_defer_stream`.
+pub fn split_defer_stream<'s>(program: &Program<'s>) -> Program<'s> {
+    let mut transform = SplitDeferStreamTransform {
+        program,
+        current_document_name: None,
+        split_operations: Default::default(),
+    };
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+type SplitOperations = FnvHashMap<StringKey, (SplitOperationMetaData, OperationDefinition)>;
+
+struct SplitDeferStreamTransform<'s> {
+    program: &'s Program<'s>,
+    current_document_name: Option<StringKey>,
+    split_operations: SplitOperations,
+}
+
+impl<'s> SplitDeferStreamTransform<'s> {
+    fn add_split_operation(
+        &mut self,
+        directive: &Directive,
+        derived_from: StringKey,
+        type_: Type,
+        selections: &[Selection],
+    ) {
+        let label_arg = match directive.arguments.named(DEFER_STREAM_CONSTANTS.label_arg) {
+            Some(label_arg) => label_arg,
+            None => return,
+        };
+        let label = label_arg.value.item.expect_string_literal();
+        let parent_source = self
+            .current_document_name
+            .expect("We expect the parent name to be defined here.");
+        let split_operation = self.split_operations.entry(label).or_insert_with(|| {
+            (
+                SplitOperationMetaData {
+                    derived_from,
+                    parent_sources: Default::default(),
+                },
+                OperationDefinition {
+                    name: WithLocation::new(label_arg.value.location, label),
+                    type_,
+                    variable_definitions: vec![],
+                    directives: vec![],
+                    selections: selections.to_vec(),
+                    kind: OperationKind::Query,
+                },
+            )
+        });
+        split_operation.0.parent_sources.insert(parent_source);
+    }
+}
+
+impl<'s> Transformer for SplitDeferStreamTransform<'s> {
+    const NAME: &'static str = "SplitDeferStreamTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_program<'ss>(&mut self, program: &Program<'ss>) -> TransformedValue<Program<'ss>> {
+        for operation in program.operations() {
+            self.transform_operation(operation);
+        }
+        for fragment in program.fragments() {
+            self.transform_fragment(fragment);
+        }
+
+        if self.split_operations.is_empty() {
+            TransformedValue::Keep
+        } else {
+            let mut next_program = program.clone();
+            for (_, (metadata, mut operation)) in self.split_operations.drain() {
+                attach_split_operation_metadata(&mut operation, metadata);
+                next_program.insert_operation(Arc::new(operation))
+            }
+            TransformedValue::Replace(next_program)
+        }
+    }
+
+    fn transform_operation(
+        &mut self,
+        operation: &OperationDefinition,
+    ) -> Transformed<OperationDefinition> {
+        self.current_document_name = Some(operation.name.item);
+        self.default_transform_operation(operation)
+    }
+
+    fn transform_fragment(
+        &mut self,
+        fragment: &FragmentDefinition,
+    ) -> Transformed<FragmentDefinition> {
+        self.current_document_name = Some(fragment.name.item);
+        self.default_transform_fragment(fragment)
+    }
+
+    /// `transform_defer_stream` wraps deferred fragment spreads with an inline fragment
+    /// with the `@defer` directive, the spread is selected by the split operation.
+    fn transform_inline_fragment(&mut self, fragment: &InlineFragment) -> Transformed<Selection> {
+        if let Some(defer) = fragment.directives.named(DEFER_STREAM_CONSTANTS.defer_name) {
+            if let [Selection::FragmentSpread(spread)] = fragment.selections.as_slice() {
+                if let Some(deferred_fragment) = self.program.fragment(spread.fragment.item) {
+                    self.add_split_operation(
+                        defer,
+                        deferred_fragment.name.item,
+                        deferred_fragment.type_condition,
+                        &fragment.selections,
+                    );
+                }
+            }
+        }
+        self.default_transform_inline_fragment(fragment)
+    }
+
+    fn transform_linked_field(&mut self, field: &LinkedField) -> Transformed<Selection> {
+        if let Some(stream) = field.directives.named(DEFER_STREAM_CONSTANTS.stream_name) {
+            let derived_from = self
+                .current_document_name
+                .expect("We expect the parent name to be defined here.");
+            let field_type = self
+                .program
+                .schema()
+                .field(field.definition.item)
+                .type_
+                .inner();
+            self.add_split_operation(stream, derived_from, field_type, &field.selections);
+        }
+        self.default_transform_linked_field(field)
+    }
+}
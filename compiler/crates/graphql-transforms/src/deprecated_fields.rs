@@ -0,0 +1,96 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::Location;
+use graphql_ir::{LinkedField, Program, ScalarField, Visitor};
+use interner::{Intern, StringKey};
+use schema::{AstValue, FieldID, Schema};
+
+/// A selection of a field that is `@deprecated` in the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecatedFieldUsage {
+    /// The location of the name of the selected field
+    pub location: Location,
+    pub field: FieldID,
+    /// The `reason` argument of `@deprecated`, `None` if the deprecation has no reason
+    pub reason: Option<String>,
+}
+
+impl DeprecatedFieldUsage {
+    /// The message of the warnings reported for the usage
+    pub fn message(&self, schema: &Schema) -> String {
+        let field_name = schema.field(self.field).name;
+        match &self.reason {
+            Some(reason) => format!("The field `{}` is deprecated: {}", field_name, reason),
+            None => format!("The field `{}` is deprecated", field_name),
+        }
+    }
+}
+
+/// Collects the selections of fields that are `@deprecated` in the schema, e.g. to
+/// report warnings for them. The usages are returned in the order they are visited:
+/// the selections of the operations, then those of the fragments.
+pub fn collect_deprecated_fields(program: &Program<'_>) -> Vec<DeprecatedFieldUsage> {
+    let mut visitor = CollectDeprecatedFields {
+        schema: program.schema(),
+        deprecated_directive_name: "deprecated".intern(),
+        reason_arg_name: "reason".intern(),
+        usages: vec![],
+    };
+    visitor.visit_program(program);
+    visitor.usages
+}
+
+struct CollectDeprecatedFields<'s> {
+    schema: &'s Schema,
+    deprecated_directive_name: StringKey,
+    reason_arg_name: StringKey,
+    usages: Vec<DeprecatedFieldUsage>,
+}
+
+impl<'s> CollectDeprecatedFields<'s> {
+    fn collect_field(&mut self, field_id: FieldID, location: Location) {
+        let field = self.schema.field(field_id);
+        let deprecated_directive = match field
+            .directives
+            .iter()
+            .find(|directive| directive.name == self.deprecated_directive_name)
+        {
+            Some(deprecated_directive) => deprecated_directive,
+            None => return,
+        };
+        let reason = deprecated_directive
+            .arguments
+            .iter()
+            .find(|argument| argument.name == self.reason_arg_name)
+            .and_then(|argument| match &argument.value {
+                AstValue::String(reason) => Some(reason.clone()),
+                _ => None,
+            });
+        self.usages.push(DeprecatedFieldUsage {
+            location,
+            field: field_id,
+            reason,
+        });
+    }
+}
+
+impl<'s> Visitor for CollectDeprecatedFields<'s> {
+    const NAME: &'static str = "CollectDeprecatedFields";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn visit_linked_field(&mut self, field: &LinkedField) {
+        self.collect_field(field.definition.item, field.definition.location);
+        self.default_visit_linked_field(field);
+    }
+
+    fn visit_scalar_field(&mut self, field: &ScalarField) {
+        self.collect_field(field.definition.item, field.definition.location);
+        self.default_visit_scalar_field(field);
+    }
+}
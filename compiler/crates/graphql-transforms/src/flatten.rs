@@ -28,6 +28,12 @@ type SeenLinkedFields = FnvHashMap<PointerAddress, Arc<LinkedField>>;
 /// with the exception that it never flattens the inline fragment with relay
 /// directives (@defer, @__clientExtensions).
 ///
+/// When not `is_for_codegen`, an inline fragment only selecting an inline fragment with
+/// the same type condition (or without one) is merged with it into a single inline
+/// fragment with the directives of both, e.g.
+/// `... on User @a { ... on User @b { name } }` becomes `... on User @a @b { name }`,
+/// unless they have a directive with the same name or a relay directive.
+///
 pub fn flatten<'s>(program: &Program<'s>, is_for_codegen: bool) -> Program<'s> {
     let mut next_program = Program::new(program.schema());
     let mut transform = FlattenTransform::new(program, is_for_codegen);
@@ -133,11 +139,18 @@ impl<'s> FlattenTransform<'s> {
                     Some(type_condition) => TypeReference::Named(type_condition),
                     None => parent_type.clone(),
                 };
-                Selection::InlineFragment(Arc::new(InlineFragment {
+                let next_node = InlineFragment {
                     type_condition: node.type_condition,
                     directives: node.directives.clone(),
                     selections: self.tranform_selections(&node.selections, &next_parent_type),
-                }))
+                };
+                if self.is_for_codegen {
+                    Selection::InlineFragment(Arc::new(next_node))
+                } else {
+                    Selection::InlineFragment(Arc::new(
+                        merge_nested_inline_fragment(&next_node).unwrap_or(next_node),
+                    ))
+                }
             }
             Selection::LinkedField(node) => {
                 Selection::LinkedField(self.transform_linked_field(node))
@@ -288,3 +301,38 @@ fn should_flatten_inline_fragment<'s>(
         }
     }
 }
+
+/// Merges an inline fragment only selecting an inline fragment with the same type
+/// condition, if their directives can be combined.
+fn merge_nested_inline_fragment(inline_fragment: &InlineFragment) -> Option<InlineFragment> {
+    let nested_fragment = match inline_fragment.selections.as_slice() {
+        [Selection::InlineFragment(nested_fragment)] => nested_fragment,
+        _ => return None,
+    };
+    if nested_fragment.type_condition.is_some()
+        && nested_fragment.type_condition != inline_fragment.type_condition
+    {
+        return None;
+    }
+    let directives = inline_fragment
+        .directives
+        .iter()
+        .chain(nested_fragment.directives.iter());
+    let has_custom_directives = directives
+        .clone()
+        .any(is_relay_custom_inline_fragment_directive);
+    let has_duplicate_directives = inline_fragment.directives.iter().any(|directive| {
+        nested_fragment
+            .directives
+            .iter()
+            .any(|nested_directive| nested_directive.name.item == directive.name.item)
+    });
+    if has_custom_directives || has_duplicate_directives {
+        return None;
+    }
+    Some(InlineFragment {
+        type_condition: inline_fragment.type_condition,
+        directives: directives.cloned().collect(),
+        selections: nested_fragment.selections.clone(),
+    })
+}
@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::INTERNAL_METADATA_DIRECTIVE;
+use common::WithLocation;
+use fnv::FnvHashMap;
+use graphql_ir::{
+    Argument, ConstantValue, Directive, OperationDefinition, Program, Transformed, Transformer,
+    Value,
+};
+use graphql_text_printer::print_full_operation;
+use interner::{Intern, StringKey};
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+
+lazy_static! {
+    pub static ref OPERATION_ID_METADATA_KEY: StringKey = "operationId".intern();
+}
+
+/// The stable ID of each operation for persisted queries, by operation name
+pub type OperationIds = FnvHashMap<StringKey, String>;
+
+/// Computes the ID of each operation of `operation_text_program`, the program
+/// printed to send to the server, as the SHA-256 hash of its printed text including
+/// the fragments it spreads. The IDs only depend on the text, so identical
+/// operations get the same ID in every run.
+pub fn generate_operation_ids(operation_text_program: &Program<'_>) -> OperationIds {
+    operation_text_program
+        .operations()
+        .map(|operation| {
+            let text = print_full_operation(operation_text_program, operation);
+            (operation.name.item, sha256(&text))
+        })
+        .collect()
+}
+
+/// A transform that attaches the ID of each operation in `operation_ids` as
+/// `@__metadata(operationId: "<id>")`, e.g. to expose it in the metadata of the
+/// generated normalization artifacts. Operations without an ID are kept as is.
+pub fn generate_operation_id_metadata<'s>(
+    program: &Program<'s>,
+    operation_ids: &OperationIds,
+) -> Program<'s> {
+    let mut transformer = GenerateOperationIdMetadata { operation_ids };
+    transformer
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+struct GenerateOperationIdMetadata<'a> {
+    operation_ids: &'a OperationIds,
+}
+
+impl<'a> Transformer for GenerateOperationIdMetadata<'a> {
+    const NAME: &'static str = "GenerateOperationIdMetadata";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_operation(
+        &mut self,
+        operation: &OperationDefinition,
+    ) -> Transformed<OperationDefinition> {
+        let id = match self.operation_ids.get(&operation.name.item) {
+            Some(id) => id,
+            None => return Transformed::Keep,
+        };
+        let location = operation.name.location;
+        let mut next_directives = operation.directives.clone();
+        next_directives.push(Directive {
+            name: WithLocation::new(location, *INTERNAL_METADATA_DIRECTIVE),
+            arguments: vec![Argument {
+                name: WithLocation::new(location, *OPERATION_ID_METADATA_KEY),
+                value: WithLocation::new(
+                    location,
+                    Value::Constant(ConstantValue::String(id.as_str().intern())),
+                ),
+            }],
+        });
+        Transformed::Replace(OperationDefinition {
+            directives: next_directives,
+            ..operation.clone()
+        })
+    }
+}
+
+fn sha256(data: &str) -> String {
+    let mut hash = Sha256::new();
+    hash.input(data);
+    hex::encode(hash.result())
+}
@@ -0,0 +1,286 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::{FileKey, Location, Span, WithLocation};
+use fnv::FnvHashMap;
+use graphql_ir::{
+    FragmentDefinition, FragmentSpread, InlineFragment, OperationDefinition, Program, Selection,
+    Transformed, Transformer, Variable, VariableDefinition, Visitor,
+};
+use graphql_text_printer::print_fragment;
+use interner::{Intern, StringKey};
+use schema::{Schema, Type};
+use std::sync::Arc;
+
+/// A transform that hoists inline fragments with identical selections into shared
+/// fragments, to reduce the size of the generated artifacts. Inline fragments that
+/// occur at least `min_occurrences` times in the program are replaced by a spread of
+/// a generated fragment with the same type condition and selections.
+///
+/// Only inline fragments with a type condition and without directives are hoisted.
+/// The variables referenced by the generated fragments are global variables, like
+/// the `used_global_variables` of the fragments built from a document, so the inline
+/// fragments referencing the arguments of their fragment are kept.
+pub fn hoist_inline_fragments<'s>(program: &Program<'s>, min_occurrences: usize) -> Program<'s> {
+    let mut counter = InlineFragmentCounter {
+        schema: program.schema(),
+        occurrences: Default::default(),
+        local_variables: Default::default(),
+    };
+    counter.visit_program(program);
+
+    let mut transform = HoistInlineFragments {
+        program,
+        occurrences: counter.occurrences,
+        min_occurrences,
+        hoisted_fragments: Default::default(),
+        next_fragment_index: 0,
+        document_location: empty_location(),
+        local_variables: Default::default(),
+    };
+    let mut next_program = transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone());
+    for (_, fragment) in transform.hoisted_fragments.into_iter() {
+        next_program.insert_fragment(fragment);
+    }
+    next_program
+}
+
+/// The names of the arguments of a fragment, which the generated fragments can't use
+fn local_variables(fragment: &FragmentDefinition) -> Vec<StringKey> {
+    fragment
+        .variable_definitions
+        .iter()
+        .map(|variable| variable.name.item)
+        .collect()
+}
+
+fn empty_location() -> Location {
+    Location::new(FileKey::new(""), Span::new(0, 0))
+}
+
+/// Identifies inline fragments by their printed type condition and selections, which
+/// ignores the locations of the selections.
+fn fingerprint(schema: &Schema, type_condition: Type, fragment: &InlineFragment) -> String {
+    print_fragment(
+        schema,
+        &FragmentDefinition {
+            name: WithLocation::new(empty_location(), "_".intern()),
+            variable_definitions: Vec::new(),
+            used_global_variables: Vec::new(),
+            type_condition,
+            directives: Vec::new(),
+            selections: fragment.selections.clone(),
+        },
+    )
+}
+
+/// Returns the type condition of an inline fragment that can be hoisted, given the
+/// arguments of the fragment containing it
+fn hoistable_type_condition(
+    schema: &Schema,
+    fragment: &InlineFragment,
+    local_variables: &[StringKey],
+) -> Option<Type> {
+    if !fragment.directives.is_empty()
+        || used_variables(schema, fragment)
+            .iter()
+            .any(|variable| local_variables.contains(&variable.name.item))
+    {
+        return None;
+    }
+    fragment.type_condition
+}
+
+/// The variables used by the selections of an inline fragment, with the narrowest
+/// type of their usages
+fn used_variables(schema: &Schema, fragment: &InlineFragment) -> Vec<VariableDefinition> {
+    let mut collector = VariableCollector {
+        schema,
+        variables: Vec::new(),
+    };
+    collector.visit_inline_fragment(fragment);
+    collector.variables
+}
+
+struct VariableCollector<'s> {
+    schema: &'s Schema,
+    variables: Vec<VariableDefinition>,
+}
+
+impl<'s> Visitor for VariableCollector<'s> {
+    const NAME: &'static str = "VariableCollector";
+    const VISIT_ARGUMENTS: bool = true;
+    const VISIT_DIRECTIVES: bool = true;
+
+    fn visit_variable(&mut self, value: &Variable) {
+        match self
+            .variables
+            .iter_mut()
+            .find(|variable| variable.name.item == value.name.item)
+        {
+            Some(variable) => {
+                if self
+                    .schema
+                    .is_type_subtype_of(&value.type_, &variable.type_)
+                {
+                    variable.type_ = value.type_.clone();
+                }
+            }
+            None => self.variables.push(VariableDefinition {
+                name: value.name,
+                type_: value.type_.clone(),
+                directives: Default::default(),
+                default_value: None,
+            }),
+        }
+    }
+}
+
+struct InlineFragmentCounter<'s> {
+    schema: &'s Schema,
+    occurrences: FnvHashMap<String, usize>,
+    /// The arguments of the fragment being visited
+    local_variables: Vec<StringKey>,
+}
+
+impl<'s> Visitor for InlineFragmentCounter<'s> {
+    const NAME: &'static str = "InlineFragmentCounter";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn visit_operation(&mut self, operation: &OperationDefinition) {
+        self.local_variables = Vec::new();
+        self.default_visit_operation(operation);
+    }
+
+    fn visit_fragment(&mut self, fragment: &FragmentDefinition) {
+        self.local_variables = local_variables(fragment);
+        self.default_visit_fragment(fragment);
+    }
+
+    fn visit_inline_fragment(&mut self, fragment: &InlineFragment) {
+        if let Some(type_condition) =
+            hoistable_type_condition(self.schema, fragment, &self.local_variables)
+        {
+            *self
+                .occurrences
+                .entry(fingerprint(self.schema, type_condition, fragment))
+                .or_default() += 1;
+        }
+        self.default_visit_inline_fragment(fragment);
+    }
+}
+
+struct HoistInlineFragments<'s> {
+    program: &'s Program<'s>,
+    /// Number of occurrences of each inline fragment in the original program
+    occurrences: FnvHashMap<String, usize>,
+    min_occurrences: usize,
+    /// Fragments generated so far, by the fingerprint of the inline fragments they replace
+    hoisted_fragments: Vec<(String, Arc<FragmentDefinition>)>,
+    next_fragment_index: usize,
+    /// Location of the name of the document being transformed, used for generated nodes
+    document_location: Location,
+    /// The arguments of the fragment being transformed
+    local_variables: Vec<StringKey>,
+}
+
+impl<'s> HoistInlineFragments<'s> {
+    fn generate_fragment_name(&mut self) -> StringKey {
+        loop {
+            let name = format!("HoistedInlineFragment{}", self.next_fragment_index).intern();
+            self.next_fragment_index += 1;
+            if self.program.fragment(name).is_none() {
+                return name;
+            }
+        }
+    }
+
+    fn hoisted_fragment_name(
+        &mut self,
+        fingerprint: String,
+        type_condition: Type,
+        used_global_variables: Vec<VariableDefinition>,
+        selections: Vec<Selection>,
+    ) -> StringKey {
+        if let Some((_, fragment)) = self
+            .hoisted_fragments
+            .iter()
+            .find(|(hoisted_fingerprint, _)| *hoisted_fingerprint == fingerprint)
+        {
+            return fragment.name.item;
+        }
+        let name = self.generate_fragment_name();
+        self.hoisted_fragments.push((
+            fingerprint,
+            Arc::new(FragmentDefinition {
+                name: WithLocation::new(self.document_location, name),
+                variable_definitions: Vec::new(),
+                used_global_variables,
+                type_condition,
+                directives: Vec::new(),
+                selections,
+            }),
+        ));
+        name
+    }
+}
+
+impl<'s> Transformer for HoistInlineFragments<'s> {
+    const NAME: &'static str = "HoistInlineFragmentsTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_operation(
+        &mut self,
+        operation: &OperationDefinition,
+    ) -> Transformed<OperationDefinition> {
+        self.document_location = operation.name.location;
+        self.local_variables = Vec::new();
+        self.default_transform_operation(operation)
+    }
+
+    fn transform_fragment(
+        &mut self,
+        fragment: &FragmentDefinition,
+    ) -> Transformed<FragmentDefinition> {
+        self.document_location = fragment.name.location;
+        self.local_variables = local_variables(fragment);
+        self.default_transform_fragment(fragment)
+    }
+
+    fn transform_inline_fragment(&mut self, fragment: &InlineFragment) -> Transformed<Selection> {
+        let schema = self.program.schema();
+        let type_condition = match hoistable_type_condition(schema, fragment, &self.local_variables)
+        {
+            Some(type_condition) => type_condition,
+            None => return self.default_transform_inline_fragment(fragment),
+        };
+        let fingerprint = fingerprint(schema, type_condition, fragment);
+        if self.occurrences.get(&fingerprint).copied().unwrap_or(0) < self.min_occurrences {
+            return self.default_transform_inline_fragment(fragment);
+        }
+
+        // Nested inline fragments are hoisted into their own fragments as well
+        let selections = self
+            .transform_selections(&fragment.selections)
+            .replace_or_else(|| fragment.selections.clone());
+        let name = self.hoisted_fragment_name(
+            fingerprint,
+            type_condition,
+            used_variables(schema, fragment),
+            selections,
+        );
+        Transformed::Replace(Selection::FragmentSpread(Arc::new(FragmentSpread {
+            fragment: WithLocation::new(self.document_location, name),
+            arguments: Vec::new(),
+            directives: Vec::new(),
+        })))
+    }
+}
@@ -0,0 +1,274 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::WithLocation;
+use fnv::{FnvHashMap, FnvHashSet};
+use graphql_ir::{
+    Argument, ConstantValue, FragmentDefinition, LinkedField, OperationDefinition, Program,
+    ScalarField, Selection, Transformed, TransformedValue, Transformer, Value, Variable,
+    VariableDefinition, Visitor,
+};
+use interner::{Intern, StringKey};
+use schema::{FieldID, Schema, TypeReference};
+use std::sync::Arc;
+
+/// An opt-in transform that hoists the literal values of field arguments repeated at
+/// least `min_occurrences` times in the selections of an operation, e.g. `first: 10`,
+/// into a variable of the operation with that value as default. Each occurrence is
+/// replaced with a reference to the variable, which has the type of the argument, so
+/// the query selects the same data when the variable isn't passed.
+///
+/// The variables are named after the first argument with the value, e.g. `$first`,
+/// suffixed with a number if the name is used by any variable of the program. Only the
+/// operations are changed: the fragments are shared by operations, and the arguments
+/// of directives are kept as is.
+pub fn hoist_repeated_arguments<'s>(program: &Program<'s>, min_occurrences: usize) -> Program<'s> {
+    let mut variable_names = VariableNamesVisitor::default();
+    variable_names.visit_program(program);
+    let mut transform = HoistRepeatedArguments {
+        schema: program.schema(),
+        min_occurrences,
+        used_variable_names: variable_names.names,
+        hoisted_values: Default::default(),
+    };
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+/// A literal value and the type of the arguments it is passed to
+type HoistedValueKey = (TypeReference, ConstantValue);
+
+struct HoistRepeatedArguments<'s> {
+    schema: &'s Schema,
+    min_occurrences: usize,
+    used_variable_names: FnvHashSet<StringKey>,
+    /// The variables replacing the hoisted values of the current operation
+    hoisted_values: FnvHashMap<HoistedValueKey, Variable>,
+}
+
+impl<'s> HoistRepeatedArguments<'s> {
+    /// Returns a name based on `argument_name` that isn't used by any variable
+    fn generate_variable_name(&mut self, argument_name: StringKey) -> StringKey {
+        let mut variable_name = argument_name;
+        let mut suffix = 1;
+        while self.used_variable_names.contains(&variable_name) {
+            suffix += 1;
+            variable_name = format!("{}_{}", argument_name, suffix).intern();
+        }
+        self.used_variable_names.insert(variable_name);
+        variable_name
+    }
+
+    /// Replaces the hoisted values of the arguments of a field with their variable
+    fn transform_field_arguments(
+        &self,
+        field_id: FieldID,
+        arguments: &[Argument],
+    ) -> TransformedValue<Vec<Argument>> {
+        let mut has_changes = false;
+        let next_arguments = arguments
+            .iter()
+            .map(|argument| {
+                let variable = hoistable_value_key(field_id, self.schema, argument)
+                    .and_then(|key| self.hoisted_values.get(&key));
+                match variable {
+                    Some(variable) => {
+                        has_changes = true;
+                        Argument {
+                            name: argument.name,
+                            value: WithLocation::new(
+                                argument.value.location,
+                                Value::Variable(Variable {
+                                    name: WithLocation::new(
+                                        argument.value.location,
+                                        variable.name.item,
+                                    ),
+                                    type_: variable.type_.clone(),
+                                }),
+                            ),
+                        }
+                    }
+                    None => argument.clone(),
+                }
+            })
+            .collect();
+        if has_changes {
+            TransformedValue::Replace(next_arguments)
+        } else {
+            TransformedValue::Keep
+        }
+    }
+}
+
+impl<'s> Transformer for HoistRepeatedArguments<'s> {
+    const NAME: &'static str = "HoistRepeatedArgumentsTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_fragment(&mut self, _: &FragmentDefinition) -> Transformed<FragmentDefinition> {
+        Transformed::Keep
+    }
+
+    fn transform_operation(
+        &mut self,
+        operation: &OperationDefinition,
+    ) -> Transformed<OperationDefinition> {
+        let mut counter = ArgumentValuesVisitor {
+            schema: self.schema,
+            values: vec![],
+            counts: Default::default(),
+        };
+        counter.visit_operation(operation);
+
+        self.hoisted_values.clear();
+        let mut variable_definitions = vec![];
+        for (key, argument) in counter.values {
+            if counter.counts[&key] < self.min_occurrences {
+                continue;
+            }
+            let variable_name = self.generate_variable_name(argument.name.item);
+            let name = WithLocation::new(argument.value.location, variable_name);
+            variable_definitions.push(VariableDefinition {
+                name,
+                type_: key.0.clone(),
+                default_value: Some(key.1.clone()),
+                directives: vec![],
+            });
+            self.hoisted_values
+                .insert(key.clone(), Variable { name, type_: key.0 });
+        }
+        if variable_definitions.is_empty() {
+            return Transformed::Keep;
+        }
+
+        let selections = self
+            .transform_selections(&operation.selections)
+            .replace_or_else(|| operation.selections.clone());
+        let mut next_variable_definitions = operation.variable_definitions.clone();
+        next_variable_definitions.extend(variable_definitions);
+        Transformed::Replace(OperationDefinition {
+            variable_definitions: next_variable_definitions,
+            selections,
+            ..operation.clone()
+        })
+    }
+
+    fn transform_scalar_field(&mut self, field: &ScalarField) -> Transformed<Selection> {
+        match self.transform_field_arguments(field.definition.item, &field.arguments) {
+            TransformedValue::Replace(arguments) => {
+                Transformed::Replace(Selection::ScalarField(Arc::new(ScalarField {
+                    arguments,
+                    ..field.clone()
+                })))
+            }
+            TransformedValue::Keep => Transformed::Keep,
+        }
+    }
+
+    fn transform_linked_field(&mut self, field: &LinkedField) -> Transformed<Selection> {
+        let arguments = self.transform_field_arguments(field.definition.item, &field.arguments);
+        let selections = self.transform_selections(&field.selections);
+        if arguments.should_keep() && selections.should_keep() {
+            return Transformed::Keep;
+        }
+        Transformed::Replace(Selection::LinkedField(Arc::new(LinkedField {
+            arguments: arguments.replace_or_else(|| field.arguments.clone()),
+            selections: selections.replace_or_else(|| field.selections.clone()),
+            ..field.clone()
+        })))
+    }
+}
+
+/// Returns the key of the value of a field argument if it can be hoisted, which are
+/// the literal values other than `null`
+fn hoistable_value_key(
+    field_id: FieldID,
+    schema: &Schema,
+    argument: &Argument,
+) -> Option<HoistedValueKey> {
+    match &argument.value.item {
+        Value::Constant(value) if !value.is_null() => {
+            let definition = schema.field(field_id).arguments.named(argument.name.item)?;
+            Some((definition.type_.clone(), value.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Counts the hoistable values of the field arguments of an operation, in the order of
+/// their first occurrence
+struct ArgumentValuesVisitor<'s> {
+    schema: &'s Schema,
+    values: Vec<(HoistedValueKey, Argument)>,
+    counts: FnvHashMap<HoistedValueKey, usize>,
+}
+
+impl<'s> ArgumentValuesVisitor<'s> {
+    fn count_arguments(&mut self, field_id: FieldID, arguments: &[Argument]) {
+        for argument in arguments {
+            if let Some(key) = hoistable_value_key(field_id, self.schema, argument) {
+                let count = self.counts.entry(key.clone()).or_insert(0);
+                if *count == 0 {
+                    self.values.push((key, argument.clone()));
+                }
+                *count += 1;
+            }
+        }
+    }
+}
+
+impl<'s> Visitor for ArgumentValuesVisitor<'s> {
+    const NAME: &'static str = "ArgumentValuesVisitor";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn visit_scalar_field(&mut self, field: &ScalarField) {
+        self.count_arguments(field.definition.item, &field.arguments);
+    }
+
+    fn visit_linked_field(&mut self, field: &LinkedField) {
+        self.count_arguments(field.definition.item, &field.arguments);
+        self.default_visit_linked_field(field);
+    }
+}
+
+/// Collects the names of the variables defined or used anywhere in the program
+#[derive(Default)]
+struct VariableNamesVisitor {
+    names: FnvHashSet<StringKey>,
+}
+
+impl Visitor for VariableNamesVisitor {
+    const NAME: &'static str = "VariableNamesVisitor";
+    const VISIT_ARGUMENTS: bool = true;
+    const VISIT_DIRECTIVES: bool = true;
+
+    fn visit_operation(&mut self, operation: &OperationDefinition) {
+        self.names.extend(
+            operation
+                .variable_definitions
+                .iter()
+                .map(|variable| variable.name.item),
+        );
+        self.default_visit_operation(operation);
+    }
+
+    fn visit_fragment(&mut self, fragment: &FragmentDefinition) {
+        self.names.extend(
+            fragment
+                .variable_definitions
+                .iter()
+                .map(|variable| variable.name.item),
+        );
+        self.default_visit_fragment(fragment);
+    }
+
+    fn visit_variable(&mut self, variable: &Variable) {
+        self.names.insert(variable.name.item);
+    }
+}
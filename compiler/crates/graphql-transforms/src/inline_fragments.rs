@@ -5,14 +5,26 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use common::WithLocation;
 use graphql_ir::{
-    FragmentDefinition, FragmentSpread, InlineFragment, Program, ScalarField, Selection,
-    Transformed, Transformer,
+    FragmentDefinition, FragmentSpread, InlineFragment, OperationDefinition, Program, ScalarField,
+    Selection, Transformed, TransformedValue, Transformer, Variable, VariableDefinition,
 };
-use interner::StringKey;
-use std::collections::HashMap;
+use interner::{Intern, StringKey};
+use std::collections::{HashMap, HashSet};
+use std::mem;
 use std::sync::Arc;
 
+/// A transform that replaces the fragment spreads of operations with inline
+/// fragments containing the selections of the fragments, and removes the fragments.
+///
+/// The local variables of inlined fragments (their `@argumentDefinitions`) are
+/// renamed to fresh names, e.g. `$size` of `ProfilePicture` to
+/// `$ProfilePicture_size`, so that they don't clobber the variables of the operation
+/// or of other inlined fragments. The renamed variables are added to the variable
+/// definitions of the operation, with the type and default value of the argument
+/// definition. Arguments passed with `@arguments` are expected to be applied by
+/// `apply_fragment_arguments` beforehand.
 pub fn inline_fragments<'s>(program: &Program<'s>) -> Program<'s> {
     let mut transform = InlineFragmentsTransform::new(program);
     transform
@@ -20,11 +32,18 @@ pub fn inline_fragments<'s>(program: &Program<'s>) -> Program<'s> {
         .replace_or_else(|| program.clone())
 }
 
-type Seen = HashMap<StringKey, Arc<InlineFragment>>;
+/// The inline fragment of each fragment, with the renamed local variables of the
+/// fragment and of the fragments it spreads.
+type Seen = HashMap<StringKey, (Arc<InlineFragment>, Vec<VariableDefinition>)>;
 
 struct InlineFragmentsTransform<'s> {
     program: &'s Program<'s>,
     seen: Seen,
+    /// The variable names that fresh names of local variables must not collide with
+    reserved_variable_names: HashSet<StringKey>,
+    /// The renamed local variables of the fragments inlined into the operation being
+    /// transformed
+    local_variables: Vec<VariableDefinition>,
 }
 
 impl<'s> InlineFragmentsTransform<'s> {
@@ -32,13 +51,20 @@ impl<'s> InlineFragmentsTransform<'s> {
         Self {
             program,
             seen: Default::default(),
+            reserved_variable_names: program
+                .operations()
+                .flat_map(|operation| &operation.variable_definitions)
+                .map(|variable_definition| variable_definition.name.item)
+                .collect(),
+            local_variables: Default::default(),
         }
     }
 
     fn transform_fragment_spread(&mut self, spread: &FragmentSpread) -> Arc<InlineFragment> {
         // If we've already created an InlineFragment for this fragment name before,
         // share it
-        if let Some(prev) = self.seen.get(&spread.fragment.item) {
+        if let Some((prev, local_variables)) = self.seen.get(&spread.fragment.item) {
+            self.local_variables.extend(local_variables.iter().cloned());
             return Arc::clone(prev);
         };
         // Otherwise create the InlineFragment equivalent of the fragment (recursively
@@ -46,11 +72,14 @@ impl<'s> InlineFragmentsTransform<'s> {
         // that we overwrite once we finish.
         self.seen.insert(
             spread.fragment.item,
-            Arc::new(InlineFragment {
-                type_condition: None,
-                directives: Default::default(),
-                selections: Default::default(),
-            }),
+            (
+                Arc::new(InlineFragment {
+                    type_condition: None,
+                    directives: Default::default(),
+                    selections: Default::default(),
+                }),
+                vec![],
+            ),
         );
         let fragment = self
             .program
@@ -61,15 +90,86 @@ impl<'s> InlineFragmentsTransform<'s> {
                     spread.fragment.item
                 )
             });
-        let selections = self.transform_selections(&fragment.selections);
+        let parent_local_variables = mem::take(&mut self.local_variables);
+        // Rename the local variables before inlining the spreads of the fragment, the
+        // variables of the spread fragments aren't in the scope of this fragment
+        let renamed_selections = self.rename_local_variables(fragment);
+        let fragment_selections = match &renamed_selections {
+            TransformedValue::Keep => &fragment.selections,
+            TransformedValue::Replace(selections) => selections,
+        };
+        let selections = self.transform_selections(fragment_selections);
         let result = Arc::new(InlineFragment {
             type_condition: Some(fragment.type_condition),
             directives: spread.directives.clone(),
-            selections: selections.replace_or_else(|| fragment.selections.clone()),
+            selections: selections.replace_or_else(|| fragment_selections.clone()),
         });
-        self.seen.insert(spread.fragment.item, Arc::clone(&result));
+        let local_variables = mem::replace(&mut self.local_variables, parent_local_variables);
+        self.local_variables.extend(local_variables.iter().cloned());
+        self.seen
+            .insert(spread.fragment.item, (Arc::clone(&result), local_variables));
         result
     }
+
+    /// Renames the local variables used by the selections of the fragment to fresh
+    /// names, and adds the renamed variables to `local_variables`.
+    fn rename_local_variables(
+        &mut self,
+        fragment: &FragmentDefinition,
+    ) -> TransformedValue<Vec<Selection>> {
+        if fragment.variable_definitions.is_empty() {
+            return TransformedValue::Keep;
+        }
+        let renamed_variables = fragment
+            .variable_definitions
+            .iter()
+            .map(|variable_definition| {
+                let fresh_name =
+                    self.fresh_variable_name(fragment.name.item, variable_definition.name.item);
+                (variable_definition.name.item, fresh_name)
+            })
+            .collect();
+        let mut transform = RenameVariablesTransform {
+            renamed_variables,
+            used_variables: Default::default(),
+        };
+        let selections = transform.transform_selections(&fragment.selections);
+        for variable_definition in &fragment.variable_definitions {
+            if transform
+                .used_variables
+                .contains(&variable_definition.name.item)
+            {
+                self.local_variables.push(VariableDefinition {
+                    name: WithLocation::new(
+                        variable_definition.name.location,
+                        transform.renamed_variables[&variable_definition.name.item],
+                    ),
+                    type_: variable_definition.type_.clone(),
+                    default_value: variable_definition.default_value.clone(),
+                    directives: vec![],
+                });
+            }
+        }
+        selections
+    }
+
+    /// Returns `<fragment name>_<variable name>`, with a numeric suffix if the name
+    /// is already used.
+    fn fresh_variable_name(
+        &mut self,
+        fragment_name: StringKey,
+        variable_name: StringKey,
+    ) -> StringKey {
+        let base_name = format!("{}_{}", fragment_name, variable_name);
+        let mut fresh_name = base_name.as_str().intern();
+        let mut suffix = 1;
+        while self.reserved_variable_names.contains(&fresh_name) {
+            fresh_name = format!("{}_{}", base_name, suffix).intern();
+            suffix += 1;
+        }
+        self.reserved_variable_names.insert(fresh_name);
+        fresh_name
+    }
 }
 
 impl<'s> Transformer for InlineFragmentsTransform<'s> {
@@ -84,6 +184,35 @@ impl<'s> Transformer for InlineFragmentsTransform<'s> {
         Transformed::Delete
     }
 
+    fn transform_operation(
+        &mut self,
+        operation: &OperationDefinition,
+    ) -> Transformed<OperationDefinition> {
+        let transformed = self.default_transform_operation(operation);
+        let local_variables = mem::take(&mut self.local_variables);
+        if local_variables.is_empty() {
+            return transformed;
+        }
+        let mut next_operation = match transformed {
+            Transformed::Keep => operation.clone(),
+            Transformed::Replace(next_operation) => next_operation,
+            Transformed::Delete => return Transformed::Delete,
+        };
+        for variable_definition in local_variables {
+            // A fragment that is spread multiple times adds its variables once
+            if !next_operation
+                .variable_definitions
+                .iter()
+                .any(|existing| existing.name.item == variable_definition.name.item)
+            {
+                next_operation
+                    .variable_definitions
+                    .push(variable_definition);
+            }
+        }
+        Transformed::Replace(next_operation)
+    }
+
     fn transform_selection(&mut self, selection: &Selection) -> Transformed<Selection> {
         match selection {
             Selection::FragmentSpread(selection) => Transformed::Replace(
@@ -97,3 +226,29 @@ impl<'s> Transformer for InlineFragmentsTransform<'s> {
         Transformed::Keep
     }
 }
+
+/// Renames the references to variables within selections
+struct RenameVariablesTransform {
+    renamed_variables: HashMap<StringKey, StringKey>,
+    /// The names of the renamed variables that are referenced
+    used_variables: HashSet<StringKey>,
+}
+
+impl Transformer for RenameVariablesTransform {
+    const NAME: &'static str = "RenameVariablesTransform";
+    const VISIT_ARGUMENTS: bool = true;
+    const VISIT_DIRECTIVES: bool = true;
+
+    fn transform_variable(&mut self, variable: &Variable) -> TransformedValue<Variable> {
+        match self.renamed_variables.get(&variable.name.item) {
+            Some(renamed) => {
+                self.used_variables.insert(variable.name.item);
+                TransformedValue::Replace(Variable {
+                    name: WithLocation::new(variable.name.location, *renamed),
+                    type_: variable.type_.clone(),
+                })
+            }
+            None => TransformedValue::Keep,
+        }
+    }
+}
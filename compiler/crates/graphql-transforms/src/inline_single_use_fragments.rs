@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::REFETCHABLE_CONSTANTS;
+use common::NamedItem;
+use graphql_ir::{
+    FragmentDefinition, FragmentSpread, InlineFragment, Program, Selection, Transformed,
+    Transformer, Visitor,
+};
+use interner::StringKey;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// A transform that inlines the fragments spread exactly once in the program into
+/// their only spread, and removes them. The spread is replaced with an inline
+/// fragment with the type condition of the fragment and the directives of the
+/// spread, e.g. `...Foo @defer` becomes `... on User @defer { <selections of Foo> }`.
+///
+/// Fragments with arguments or spread with arguments are kept, arguments are expected
+/// to be applied by `apply_fragment_arguments` beforehand. `@refetchable` fragments
+/// are kept as well. Data masking relies on the spreads, so this is only suitable for
+/// the text of the operations sent to the server.
+pub fn inline_single_use_fragments<'s>(program: &Program<'s>) -> Program<'s> {
+    let mut counter = FragmentSpreadCounter::default();
+    counter.visit_program(program);
+    let inlined_fragments: HashSet<StringKey> = program
+        .fragments()
+        .filter(|fragment| {
+            let name = fragment.name.item;
+            counter.spread_counts.get(&name) == Some(&1)
+                && !counter.spreads_with_arguments.contains(&name)
+                && fragment.variable_definitions.is_empty()
+                && !is_refetchable(fragment)
+        })
+        .map(|fragment| fragment.name.item)
+        .collect();
+    if inlined_fragments.is_empty() {
+        return program.clone();
+    }
+    let mut transform = InlineSingleUseFragments {
+        program,
+        inlined_fragments,
+    };
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+fn is_refetchable(fragment: &FragmentDefinition) -> bool {
+    fragment
+        .directives
+        .named(REFETCHABLE_CONSTANTS.refetchable_name)
+        .is_some()
+        || fragment
+            .directives
+            .named(REFETCHABLE_CONSTANTS.refetchable_metadata_name)
+            .is_some()
+}
+
+/// Counts the spreads of each fragment in the operations and fragments of the
+/// program, a fragment spread by a fragment is counted once however many times the
+/// spreading fragment is used.
+#[derive(Default)]
+struct FragmentSpreadCounter {
+    spread_counts: HashMap<StringKey, usize>,
+    spreads_with_arguments: HashSet<StringKey>,
+}
+
+impl Visitor for FragmentSpreadCounter {
+    const NAME: &'static str = "FragmentSpreadCounter";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn visit_fragment_spread(&mut self, spread: &FragmentSpread) {
+        *self.spread_counts.entry(spread.fragment.item).or_insert(0) += 1;
+        if !spread.arguments.is_empty() {
+            self.spreads_with_arguments.insert(spread.fragment.item);
+        }
+    }
+}
+
+struct InlineSingleUseFragments<'s> {
+    program: &'s Program<'s>,
+    inlined_fragments: HashSet<StringKey>,
+}
+
+impl<'s> Transformer for InlineSingleUseFragments<'s> {
+    const NAME: &'static str = "InlineSingleUseFragmentsTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_fragment(
+        &mut self,
+        fragment: &FragmentDefinition,
+    ) -> Transformed<FragmentDefinition> {
+        if self.inlined_fragments.contains(&fragment.name.item) {
+            Transformed::Delete
+        } else {
+            self.default_transform_fragment(fragment)
+        }
+    }
+
+    fn transform_fragment_spread(&mut self, spread: &FragmentSpread) -> Transformed<Selection> {
+        if !self.inlined_fragments.contains(&spread.fragment.item) {
+            return Transformed::Keep;
+        }
+        let fragment = self
+            .program
+            .fragment(spread.fragment.item)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Fragment spread unable to resolve fragment `{}`.",
+                    spread.fragment.item
+                )
+            });
+        // The fragment is only spread here, so its spreads are only visited once
+        let selections = self
+            .transform_selections(&fragment.selections)
+            .replace_or_else(|| fragment.selections.clone());
+        Transformed::Replace(Selection::InlineFragment(Arc::new(InlineFragment {
+            type_condition: Some(fragment.type_condition),
+            directives: spread.directives.clone(),
+            selections,
+        })))
+    }
+}
@@ -13,29 +13,40 @@
 mod applied_fragment_name;
 mod apply_fragment_arguments;
 mod client_extensions;
+mod collapse_duplicate_fields;
 mod connections;
 mod defer_stream;
+mod deprecated_fields;
 mod flatten;
 mod generate_id_field;
 mod generate_live_query_metadata;
+mod generate_operation_ids;
 mod generate_preloadable_metadata;
 mod generate_subscription_name_metadata;
 mod generate_typename;
 mod handle_fields;
 mod hash_arguments;
+mod hoist_inline_fragments;
+mod hoist_repeated_arguments;
 mod inline_data_fragment;
 mod inline_fragments;
+mod inline_single_use_fragments;
 mod mask;
 mod match_;
 mod node_identifier;
+mod normalize_directives;
 mod refetchable_fragment;
 mod relay_directive;
 mod relay_early_flush;
 mod remove_base_fragments;
 mod root_variables;
+mod skip_client_directives;
 mod skip_client_extensions;
+mod skip_empty_selections;
+mod skip_internal_directives;
 mod skip_redundant_nodes;
 mod skip_split_operation;
+mod skip_subsumed_fragment_spreads;
 mod skip_unreachable_node;
 mod skip_unused_variables;
 mod sort_selections;
@@ -54,16 +65,22 @@ lazy_static! {
 pub use applied_fragment_name::get_applied_fragment_name;
 pub use apply_fragment_arguments::apply_fragment_arguments;
 pub use client_extensions::{client_extensions, CLIENT_EXTENSION_DIRECTIVE_NAME};
+pub use collapse_duplicate_fields::collapse_duplicate_fields;
 pub use connections::{
     extract_connection_metadata_from_directive, ConnectionConstants, ConnectionInterface,
 };
 pub use connections::{FB_CONNECTION_INTERFACE, OSS_CONNECTION_INTERFACE};
 pub use defer_stream::{
-    transform_defer_stream, DeferDirective, StreamDirective, DEFER_STREAM_CONSTANTS,
+    split_defer_stream, transform_defer_stream, DeferDirective, StreamDirective,
+    DEFER_STREAM_CONSTANTS,
 };
+pub use deprecated_fields::{collect_deprecated_fields, DeprecatedFieldUsage};
 pub use flatten::flatten;
 pub use generate_id_field::generate_id_field;
 pub use generate_live_query_metadata::generate_live_query_metadata;
+pub use generate_operation_ids::{
+    generate_operation_id_metadata, generate_operation_ids, OperationIds, OPERATION_ID_METADATA_KEY,
+};
 pub use generate_preloadable_metadata::generate_preloadable_metadata;
 pub use generate_subscription_name_metadata::generate_subscription_name_metadata;
 pub use generate_typename::{generate_typename, TYPE_DISCRIMINATOR_DIRECTIVE_NAME};
@@ -72,13 +89,17 @@ pub use handle_fields::{
     handle_field_transform, HandleFieldConstants,
 };
 pub use hash_arguments::hash_arguments;
+pub use hoist_inline_fragments::hoist_inline_fragments;
+pub use hoist_repeated_arguments::hoist_repeated_arguments;
 pub use inline_data_fragment::{inline_data_fragment, INLINE_DATA_CONSTANTS};
 pub use inline_fragments::inline_fragments;
+pub use inline_single_use_fragments::inline_single_use_fragments;
 pub use mask::mask;
 pub use match_::{
     split_module_import, transform_match, validate_module_conflicts, MATCH_CONSTANTS,
 };
 pub use node_identifier::NodeIdentifier;
+pub use normalize_directives::normalize_directives;
 pub use refetchable_fragment::{
     extract_refetch_metadata_from_directive, transform_refetchable_fragment,
     CONSTANTS as REFETCHABLE_CONSTANTS,
@@ -86,12 +107,16 @@ pub use refetchable_fragment::{
 pub use relay_directive::RelayDirective;
 pub use relay_early_flush::relay_early_flush;
 pub use remove_base_fragments::remove_base_fragments;
+pub use skip_client_directives::{skip_client_directives, CLIENT_DIRECTIVE_NAMES};
 pub use skip_client_extensions::skip_client_extensions;
+pub use skip_empty_selections::skip_empty_selections;
+pub use skip_internal_directives::{skip_internal_directives, INTERNAL_DIRECTIVE_NAMES};
 pub use skip_redundant_nodes::skip_redundant_nodes;
 pub use skip_split_operation::skip_split_operation;
+pub use skip_subsumed_fragment_spreads::skip_subsumed_fragment_spreads;
 pub use skip_unreachable_node::skip_unreachable_node;
 pub use skip_unused_variables::skip_unused_variables;
-pub use sort_selections::sort_selections;
+pub use sort_selections::{sort_selections, sort_selections_except};
 pub use transform_connections::transform_connections;
 pub use unwrap_custom_directive_selection::unwrap_custom_directive_selection;
 pub use util::{extract_variable_name, generate_abstract_type_refinement_key, remove_directive};
@@ -14,5 +14,6 @@ mod validate_module_conflicts;
 pub use constants::MATCH_CONSTANTS;
 pub use match_transform::transform_match;
 pub use split_module_import::split_module_import;
+pub(crate) use split_module_import::{attach_split_operation_metadata, SplitOperationMetaData};
 pub use utils::get_normalization_operation_name;
 pub use validate_module_conflicts::validate_module_conflicts;
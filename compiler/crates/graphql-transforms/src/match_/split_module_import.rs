@@ -27,9 +27,9 @@ pub fn split_module_import<'s>(
         .replace_or_else(|| program.clone())
 }
 
-struct SplitOperationMetaData {
-    derived_from: StringKey,
-    parent_sources: FnvHashSet<StringKey>,
+pub(crate) struct SplitOperationMetaData {
+    pub(crate) derived_from: StringKey,
+    pub(crate) parent_sources: FnvHashSet<StringKey>,
 }
 type SplitOperations = FnvHashMap<StringKey, (SplitOperationMetaData, OperationDefinition)>;
 
@@ -152,7 +152,8 @@ impl<'s, 'a> Transformer for SplitModuleImportTransform<'s, 'a> {
     }
 }
 
-fn attach_split_operation_metadata(
+/// Marks a split operation with the `@__module` directive, see `skip_split_operation`
+pub(crate) fn attach_split_operation_metadata(
     operation: &mut OperationDefinition,
     mut metadata: SplitOperationMetaData,
 ) {
@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::node_identifier::LocationAgnosticPartialEq;
+use graphql_ir::{
+    Condition, Directive, Program, Selection, Transformed, TransformedValue, Transformer,
+};
+use std::sync::Arc;
+
+/// A transform that sorts the directives of each node by name and arguments, and
+/// removes the exact duplicates, i.e. directives with the same name and arguments,
+/// so that the artifacts don't depend on the order or repetition of the directives in
+/// the source.
+///
+/// `@include` and `@skip` are represented as conditions: a condition directly nested
+/// in an identical condition, e.g. from `@include(if: $a) @include(if: $a)`, is
+/// collapsed, while conditions with different values, e.g. from
+/// `@include(if: $a) @include(if: $b)`, are kept.
+pub fn normalize_directives<'s>(program: &Program<'s>) -> Program<'s> {
+    let mut transform = NormalizeDirectives;
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+struct NormalizeDirectives;
+
+impl Transformer for NormalizeDirectives {
+    const NAME: &'static str = "NormalizeDirectivesTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = true;
+
+    fn transform_directives(
+        &mut self,
+        directives: &[Directive],
+    ) -> TransformedValue<Vec<Directive>> {
+        let mut next_directives = directives.to_vec();
+        next_directives.sort();
+        next_directives.dedup_by(|directive, previous| directive.location_agnostic_eq(previous));
+        if next_directives == directives {
+            TransformedValue::Keep
+        } else {
+            TransformedValue::Replace(next_directives)
+        }
+    }
+
+    fn transform_condition(&mut self, condition: &Condition) -> Transformed<Selection> {
+        let transformed = self.default_transform_condition(condition);
+        let next_condition = match &transformed {
+            Transformed::Keep => condition,
+            Transformed::Replace(Selection::Condition(next_condition)) => next_condition,
+            Transformed::Replace(_) | Transformed::Delete => return transformed,
+        };
+        match next_condition.selections.as_slice() {
+            [Selection::Condition(nested_condition)]
+                if nested_condition.passing_value == next_condition.passing_value
+                    && nested_condition
+                        .value
+                        .location_agnostic_eq(&next_condition.value) =>
+            {
+                // The nested condition is already normalized
+                Transformed::Replace(Selection::Condition(Arc::clone(nested_condition)))
+            }
+            _ => transformed,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::relay_directive::RELAY_DIRECTIVE_NAME;
+use crate::ConnectionConstants;
+use fnv::FnvHashSet;
+use graphql_ir::{Directive, Program, Transformed, Transformer};
+use interner::StringKey;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// The Relay directives of the documents that only the compiler knows about.
+    pub static ref CLIENT_DIRECTIVE_NAMES: FnvHashSet<StringKey> = {
+        let connection_constants = ConnectionConstants::default();
+        vec![
+            connection_constants.connection_directive_name,
+            connection_constants.stream_connection_directive_name,
+            *RELAY_DIRECTIVE_NAME,
+        ]
+        .into_iter()
+        .collect()
+    };
+}
+
+/// A transform that removes the directives named in `directive_names`, e.g. Relay
+/// directives like `@connection` or `@relay` that a GraphQL server doesn't know
+/// about. Intended for use when e.g. printing queries to send to a GraphQL server.
+/// Only the directives are removed, the selections they are on are kept.
+pub fn skip_client_directives<'s>(
+    program: &Program<'s>,
+    directive_names: &FnvHashSet<StringKey>,
+) -> Program<'s> {
+    let mut transform = SkipClientDirectives { directive_names };
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+pub struct SkipClientDirectives<'a> {
+    directive_names: &'a FnvHashSet<StringKey>,
+}
+
+impl<'a> Transformer for SkipClientDirectives<'a> {
+    const NAME: &'static str = "SkipClientDirectivesTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = true;
+
+    fn transform_directive(&mut self, directive: &Directive) -> Transformed<Directive> {
+        if self.directive_names.contains(&directive.name.item) {
+            Transformed::Delete
+        } else {
+            Transformed::Keep
+        }
+    }
+}
@@ -0,0 +1,149 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use fnv::FnvHashMap;
+use graphql_ir::{
+    Condition, FragmentDefinition, FragmentSpread, InlineFragment, LinkedField,
+    OperationDefinition, Program, Selection, Transformed, TransformedValue, Transformer,
+};
+use interner::StringKey;
+use std::sync::Arc;
+
+/// A transform that removes the composite selections left with empty selection sets by
+/// other transforms, e.g. a linked field whose selections were all skipped, which are
+/// invalid to print. The removal is propagated upward: fragments without selections are
+/// removed with their spreads, and so are operations without selections. Scalar fields
+/// don't have selections and are always kept.
+pub fn skip_empty_selections<'s>(program: &Program<'s>) -> Program<'s> {
+    let fragments = program
+        .fragments()
+        .map(|fragment| (fragment.name.item, (Arc::clone(fragment), None)))
+        .collect();
+    let mut transform = SkipEmptySelections { fragments };
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+type VisitedFragments = FnvHashMap<
+    StringKey,
+    (
+        Arc<FragmentDefinition>,
+        Option<Transformed<FragmentDefinition>>,
+    ),
+>;
+
+struct SkipEmptySelections {
+    /// The fragments of the program, with the result of their transform once a spread
+    /// or the fragment itself is visited
+    fragments: VisitedFragments,
+}
+
+impl SkipEmptySelections {
+    /// Transforms the fragment `name` once, returns whether it is removed
+    fn is_fragment_deleted(&mut self, name: StringKey) -> bool {
+        let fragment = match self.fragments.get(&name) {
+            Some((_, Some(transformed))) => return is_deleted(transformed),
+            Some((fragment, None)) => Arc::clone(fragment),
+            // The fragments of other programs, e.g. base fragments, are kept
+            None => return false,
+        };
+        let transformed = self.transform_fragment_selections(&fragment);
+        let deleted = is_deleted(&transformed);
+        if let Some((_, visited)) = self.fragments.get_mut(&name) {
+            *visited = Some(transformed);
+        }
+        deleted
+    }
+
+    fn transform_fragment_selections(
+        &mut self,
+        fragment: &FragmentDefinition,
+    ) -> Transformed<FragmentDefinition> {
+        if fragment.selections.is_empty() {
+            return Transformed::Delete;
+        }
+        match self.transform_selections(&fragment.selections) {
+            TransformedValue::Keep => Transformed::Keep,
+            TransformedValue::Replace(selections) if selections.is_empty() => Transformed::Delete,
+            TransformedValue::Replace(selections) => Transformed::Replace(FragmentDefinition {
+                selections,
+                ..fragment.clone()
+            }),
+        }
+    }
+}
+
+fn is_deleted<T>(transformed: &Transformed<T>) -> bool {
+    match transformed {
+        Transformed::Delete => true,
+        Transformed::Keep | Transformed::Replace(_) => false,
+    }
+}
+
+impl Transformer for SkipEmptySelections {
+    const NAME: &'static str = "SkipEmptySelectionsTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_fragment(
+        &mut self,
+        fragment: &FragmentDefinition,
+    ) -> Transformed<FragmentDefinition> {
+        let name = fragment.name.item;
+        if self.is_fragment_deleted(name) {
+            return Transformed::Delete;
+        }
+        match &self.fragments[&name].1 {
+            Some(transformed) => transformed.clone(),
+            None => Transformed::Keep,
+        }
+    }
+
+    fn transform_operation(
+        &mut self,
+        operation: &OperationDefinition,
+    ) -> Transformed<OperationDefinition> {
+        if operation.selections.is_empty() {
+            Transformed::Delete
+        } else {
+            self.default_transform_operation(operation)
+        }
+    }
+
+    fn transform_fragment_spread(&mut self, spread: &FragmentSpread) -> Transformed<Selection> {
+        if self.is_fragment_deleted(spread.fragment.item) {
+            Transformed::Delete
+        } else {
+            Transformed::Keep
+        }
+    }
+
+    fn transform_linked_field(&mut self, field: &LinkedField) -> Transformed<Selection> {
+        if field.selections.is_empty() {
+            Transformed::Delete
+        } else {
+            self.default_transform_linked_field(field)
+        }
+    }
+
+    fn transform_inline_fragment(&mut self, fragment: &InlineFragment) -> Transformed<Selection> {
+        if fragment.selections.is_empty() {
+            Transformed::Delete
+        } else {
+            self.default_transform_inline_fragment(fragment)
+        }
+    }
+
+    fn transform_condition(&mut self, condition: &Condition) -> Transformed<Selection> {
+        if condition.selections.is_empty() {
+            Transformed::Delete
+        } else {
+            self.default_transform_condition(condition)
+        }
+    }
+}
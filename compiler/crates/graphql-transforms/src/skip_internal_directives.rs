@@ -0,0 +1,76 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::{
+    ConnectionConstants, HandleFieldConstants, CLIENT_EXTENSION_DIRECTIVE_NAME,
+    INLINE_DATA_CONSTANTS, INTERNAL_METADATA_DIRECTIVE, MATCH_CONSTANTS, REFETCHABLE_CONSTANTS,
+};
+use fnv::FnvHashSet;
+use graphql_ir::{Directive, Program, Transformed, Transformer};
+use interner::StringKey;
+use lazy_static::lazy_static;
+
+const INTERNAL_DIRECTIVE_PREFIX: &str = "__";
+
+lazy_static! {
+    /// The metadata directives added by the transforms of this crate for the
+    /// generation of the artifacts, which the server doesn't know about.
+    pub static ref INTERNAL_DIRECTIVE_NAMES: FnvHashSet<StringKey> = {
+        let connection_constants = ConnectionConstants::default();
+        let handle_field_constants = HandleFieldConstants::default();
+        vec![
+            *INTERNAL_METADATA_DIRECTIVE,
+            *CLIENT_EXTENSION_DIRECTIVE_NAME,
+            connection_constants.connection_metadata_directive_name,
+            handle_field_constants.handle_field_directive_name,
+            INLINE_DATA_CONSTANTS.internal_directive_name,
+            MATCH_CONSTANTS.custom_module_directive_name,
+            REFETCHABLE_CONSTANTS.refetchable_metadata_name,
+            REFETCHABLE_CONSTANTS.refetchable_operation_metadata_name,
+        ]
+        .into_iter()
+        .collect()
+    };
+}
+
+/// A transform that removes the internal metadata directives named in
+/// `directive_names`, e.g. `@__clientField`, from all nodes. Intended for use when
+/// e.g. printing queries to send to a GraphQL server.
+///
+/// Internal directives are prefixed with `__` by convention, a name reserved by the
+/// GraphQL spec, so only the names with that prefix are removed and the directives
+/// of the user are always kept.
+pub fn skip_internal_directives<'s>(
+    program: &Program<'s>,
+    directive_names: &FnvHashSet<StringKey>,
+) -> Program<'s> {
+    let mut transform = SkipInternalDirectives { directive_names };
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+pub struct SkipInternalDirectives<'a> {
+    directive_names: &'a FnvHashSet<StringKey>,
+}
+
+impl<'a> Transformer for SkipInternalDirectives<'a> {
+    const NAME: &'static str = "SkipInternalDirectivesTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = true;
+
+    fn transform_directive(&mut self, directive: &Directive) -> Transformed<Directive> {
+        let name = directive.name.item;
+        if name.lookup().starts_with(INTERNAL_DIRECTIVE_PREFIX)
+            && self.directive_names.contains(&name)
+        {
+            Transformed::Delete
+        } else {
+            Transformed::Keep
+        }
+    }
+}
@@ -7,20 +7,28 @@
 
 use crate::match_::MATCH_CONSTANTS;
 use common::NamedItem;
+use fnv::FnvHashSet;
 use graphql_ir::{FragmentDefinition, OperationDefinition, Program, Transformed, Transformer};
+use interner::StringKey;
 
 /// A transform that removes field `splitOperations`. Intended for use when e.g.
-/// printing queries to send to a GraphQL server.
-pub fn skip_split_operation<'s>(program: &Program<'s>) -> Program<'s> {
-    let mut transform = SkipSplitOperation {};
+/// printing queries to send to a GraphQL server. Split operations named in `keep`
+/// are preserved, e.g. to persist them for use on the server.
+pub fn skip_split_operation<'s>(
+    program: &Program<'s>,
+    keep: &FnvHashSet<StringKey>,
+) -> Program<'s> {
+    let mut transform = SkipSplitOperation { keep };
     transform
         .transform_program(program)
         .replace_or_else(|| program.clone())
 }
 
-pub struct SkipSplitOperation;
+pub struct SkipSplitOperation<'a> {
+    keep: &'a FnvHashSet<StringKey>,
+}
 
-impl Transformer for SkipSplitOperation {
+impl<'a> Transformer for SkipSplitOperation<'a> {
     const NAME: &'static str = "SkipSplitOperationTransform";
     const VISIT_ARGUMENTS: bool = false;
     const VISIT_DIRECTIVES: bool = false;
@@ -33,6 +41,7 @@ impl Transformer for SkipSplitOperation {
             .directives
             .named(MATCH_CONSTANTS.custom_module_directive_name)
             .is_some()
+            && !self.keep.contains(&operation.name.item)
         {
             Transformed::Delete
         } else {
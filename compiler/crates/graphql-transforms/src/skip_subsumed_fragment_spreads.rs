@@ -0,0 +1,178 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::node_identifier::{LocationAgnosticPartialEq, NodeIdentifier};
+use graphql_ir::{
+    FragmentDefinition, FragmentSpread, Program, Selection, Transformed, TransformedValue,
+    Transformer,
+};
+use std::sync::Arc;
+
+/// A transform that removes the fragment spreads whose selections are already selected
+/// by a sibling spread: a spread of `B` is removed next to a spread of `A` if both
+/// fragments have the same type condition and the selections of `B` are a subset of
+/// the selections of `A`, e.g. `...A ...B` becomes `...A`.
+///
+/// Spreads with arguments or directives other than `@include`/`@skip` are kept. The
+/// subsuming spread must be unconditional, while the removed spread may be
+/// conditional: `...A ...B @include(if: $cond)` becomes `...A`, but
+/// `...A @include(if: $cond) ...B` is kept as is.
+///
+/// Data masking relies on the spreads, so this is only suitable for the text of
+/// the operations sent to the server.
+pub fn skip_subsumed_fragment_spreads<'s>(program: &Program<'s>) -> Program<'s> {
+    let mut transform = SkipSubsumedFragmentSpreads { program };
+    transform
+        .transform_program(program)
+        .replace_or_else(|| program.clone())
+}
+
+struct SkipSubsumedFragmentSpreads<'s> {
+    program: &'s Program<'s>,
+}
+
+impl<'s> SkipSubsumedFragmentSpreads<'s> {
+    /// The fragment of an unconditional spread without arguments or directives
+    fn unconditional_fragment(&self, selection: &Selection) -> Option<&'s Arc<FragmentDefinition>> {
+        match selection {
+            Selection::FragmentSpread(spread) => self.spread_fragment(spread),
+            _ => None,
+        }
+    }
+
+    /// The fragment of a spread without arguments or directives, or of the only spread
+    /// selected by a condition
+    fn removable_fragment(&self, selection: &Selection) -> Option<&'s Arc<FragmentDefinition>> {
+        match selection {
+            Selection::FragmentSpread(spread) => self.spread_fragment(spread),
+            Selection::Condition(condition) => match condition.selections.as_slice() {
+                [Selection::FragmentSpread(spread)] => self.spread_fragment(spread),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn spread_fragment(&self, spread: &FragmentSpread) -> Option<&'s Arc<FragmentDefinition>> {
+        if spread.arguments.is_empty() && spread.directives.is_empty() {
+            self.program.fragment(spread.fragment.item)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether the selections of `fragment` are a subset of the selections of
+    /// `other_fragment`
+    fn is_subsumed_by(
+        &self,
+        fragment: &FragmentDefinition,
+        other_fragment: &FragmentDefinition,
+    ) -> bool {
+        fragment.type_condition == other_fragment.type_condition
+            && are_subsumed_by(
+                self.program,
+                &fragment.selections,
+                &other_fragment.selections,
+            )
+    }
+}
+
+impl<'s> Transformer for SkipSubsumedFragmentSpreads<'s> {
+    const NAME: &'static str = "SkipSubsumedFragmentSpreadsTransform";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_selections(
+        &mut self,
+        selections: &[Selection],
+    ) -> TransformedValue<Vec<Selection>> {
+        // Visit the spreads from the last one, so that the first of two spreads of
+        // fragments with the same selections is kept
+        let mut removed = vec![false; selections.len()];
+        for (index, selection) in selections.iter().enumerate().rev() {
+            let fragment = match self.removable_fragment(selection) {
+                Some(fragment) => fragment,
+                None => continue,
+            };
+            removed[index] = selections.iter().enumerate().any(|(other_index, other)| {
+                other_index != index
+                    && !removed[other_index]
+                    && self
+                        .unconditional_fragment(other)
+                        .map_or(false, |other_fragment| {
+                            self.is_subsumed_by(fragment, other_fragment)
+                        })
+            });
+        }
+
+        let mut has_changes = false;
+        let mut next_selections = Vec::with_capacity(selections.len());
+        for (selection, is_removed) in selections.iter().zip(removed) {
+            if is_removed {
+                has_changes = true;
+                continue;
+            }
+            match self.transform_selection(selection) {
+                Transformed::Keep => next_selections.push(selection.clone()),
+                Transformed::Replace(next_selection) => {
+                    has_changes = true;
+                    next_selections.push(next_selection);
+                }
+                Transformed::Delete => has_changes = true,
+            }
+        }
+        if has_changes {
+            TransformedValue::Replace(next_selections)
+        } else {
+            TransformedValue::Keep
+        }
+    }
+}
+
+/// Returns whether each of `selections` is also selected by `other_selections`
+fn are_subsumed_by(
+    program: &Program<'_>,
+    selections: &[Selection],
+    other_selections: &[Selection],
+) -> bool {
+    selections.iter().all(|selection| {
+        other_selections
+            .iter()
+            .any(|other_selection| is_subsumed_by(program, selection, other_selection))
+    })
+}
+
+fn is_subsumed_by(
+    program: &Program<'_>,
+    selection: &Selection,
+    other_selection: &Selection,
+) -> bool {
+    if !NodeIdentifier::are_equal(program.schema(), selection, other_selection) {
+        return false;
+    }
+    match (selection, other_selection) {
+        (Selection::ScalarField(field), Selection::ScalarField(other_field)) => {
+            field.definition.item == other_field.definition.item
+                && field.arguments.location_agnostic_eq(&other_field.arguments)
+        }
+        (Selection::LinkedField(field), Selection::LinkedField(other_field)) => {
+            field.definition.item == other_field.definition.item
+                && field.arguments.location_agnostic_eq(&other_field.arguments)
+                && are_subsumed_by(program, &field.selections, &other_field.selections)
+        }
+        (Selection::InlineFragment(fragment), Selection::InlineFragment(other_fragment)) => {
+            are_subsumed_by(program, &fragment.selections, &other_fragment.selections)
+        }
+        (Selection::Condition(condition), Selection::Condition(other_condition)) => {
+            are_subsumed_by(program, &condition.selections, &other_condition.selections)
+        }
+        (Selection::FragmentSpread(spread), Selection::FragmentSpread(other_spread)) => spread
+            .directives
+            .location_agnostic_eq(&other_spread.directives),
+        _ => false,
+    }
+}
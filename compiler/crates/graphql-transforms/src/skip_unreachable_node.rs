@@ -54,7 +54,7 @@ impl Transformer for SkipUnreachableNodeTransform {
         // @include(if: false)  => remove
         // @skip(if: true)      => remove
         // @include(if: true)   => replace with contents
-        // @include(if: false)  => replace with contents
+        // @skip(if: false)     => replace with contents
         //
         // Removal of a condition or spread can result in a FragmentDefinition being deleted.
 
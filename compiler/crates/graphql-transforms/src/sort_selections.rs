@@ -6,18 +6,36 @@
  */
 
 use crate::util::PointerAddress;
+use fnv::FnvHashSet;
 use graphql_ir::{
     FragmentDefinition, OperationDefinition, Program, Selection, Transformed, TransformedValue,
     Transformer,
 };
+use interner::StringKey;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 type Seen = HashMap<PointerAddress, Transformed<Selection>>;
 
 ///
-/// Sorts selections in the fragments and queries (and their selections)
+/// Sorts selections in the fragments and queries (and their selections), so that
+/// the generated artifacts don't depend on the order of the selections in the source:
+/// `__typename` first, then fragment spreads, inline fragments, linked fields, scalar
+/// fields and conditions. Fields are sorted by alias (or name), arguments and directives.
 ///
 pub fn sort_selections<'s>(program: &Program<'s>) -> Program<'s> {
-    let mut transform = SortSelectionsTransform::new(program);
+    sort_selections_except(program, &Default::default())
+}
+
+///
+/// Like `sort_selections`, but keeps the order of the selections of the
+/// `preserved_operations`, e.g. operations whose response is read in order.
+/// The fragments spread by these operations are still sorted.
+///
+pub fn sort_selections_except<'s>(
+    program: &Program<'s>,
+    preserved_operations: &FnvHashSet<StringKey>,
+) -> Program<'s> {
+    let mut transform = SortSelectionsTransform::new(program, preserved_operations);
     transform
         .transform_program(program)
         .replace_or_else(|| program.clone())
@@ -26,15 +44,44 @@ pub fn sort_selections<'s>(program: &Program<'s>) -> Program<'s> {
 struct SortSelectionsTransform<'s> {
     seen: Seen,
     program: &'s Program<'s>,
+    preserved_operations: &'s FnvHashSet<StringKey>,
 }
 
 impl<'s> SortSelectionsTransform<'s> {
-    pub fn new(program: &'s Program<'s>) -> Self {
+    pub fn new(program: &'s Program<'s>, preserved_operations: &'s FnvHashSet<StringKey>) -> Self {
         Self {
             seen: Default::default(),
             program,
+            preserved_operations,
+        }
+    }
+
+    fn is_typename(&self, selection: &Selection) -> bool {
+        match selection {
+            Selection::ScalarField(field) => {
+                field.definition.item == self.program.schema().typename_field()
+            }
+            _ => false,
         }
     }
+
+    fn compare_selections(&self, a: &Selection, b: &Selection) -> Ordering {
+        // `__typename` sorts before any other selection
+        let typename_ordering = self.is_typename(b).cmp(&self.is_typename(a));
+        typename_ordering.then_with(|| match (a, b) {
+            (Selection::ScalarField(a), Selection::ScalarField(b)) => a
+                .alias_or_name(self.program.schema())
+                .cmp(&b.alias_or_name(self.program.schema()))
+                .then_with(|| a.arguments.cmp(&b.arguments))
+                .then_with(|| a.directives.cmp(&b.directives)),
+            (Selection::LinkedField(a), Selection::LinkedField(b)) => a
+                .alias_or_name(self.program.schema())
+                .cmp(&b.alias_or_name(self.program.schema()))
+                .then_with(|| a.arguments.cmp(&b.arguments))
+                .then_with(|| a.directives.cmp(&b.directives)),
+            _ => a.cmp(b),
+        })
+    }
 }
 
 impl<'s> Transformer for SortSelectionsTransform<'s> {
@@ -49,19 +96,7 @@ impl<'s> Transformer for SortSelectionsTransform<'s> {
         let mut next_selections = self
             .transform_list(selections, Self::transform_selection)
             .replace_or_else(|| selections.to_vec());
-        next_selections.sort_unstable_by(|a, b| match (a, b) {
-            (Selection::ScalarField(a), Selection::ScalarField(b)) => a
-                .alias_or_name(self.program.schema())
-                .cmp(&b.alias_or_name(self.program.schema()))
-                .then_with(|| a.arguments.cmp(&b.arguments))
-                .then_with(|| a.directives.cmp(&b.directives)),
-            (Selection::LinkedField(a), Selection::LinkedField(b)) => a
-                .alias_or_name(self.program.schema())
-                .cmp(&b.alias_or_name(self.program.schema()))
-                .then_with(|| a.arguments.cmp(&b.arguments))
-                .then_with(|| a.directives.cmp(&b.directives)),
-            _ => a.cmp(b),
-        });
+        next_selections.sort_unstable_by(|a, b| self.compare_selections(a, b));
         TransformedValue::Replace(next_selections)
     }
 
@@ -87,6 +122,9 @@ impl<'s> Transformer for SortSelectionsTransform<'s> {
         &mut self,
         operation: &OperationDefinition,
     ) -> Transformed<OperationDefinition> {
+        if self.preserved_operations.contains(&operation.name.item) {
+            return Transformed::Keep;
+        }
         self.transform_selections(&operation.selections)
             .map(|selections| OperationDefinition {
                 selections,
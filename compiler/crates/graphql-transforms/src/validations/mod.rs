@@ -7,12 +7,22 @@
 
 mod disallow_id_as_alias;
 mod validate_connections;
+mod validate_fragment_arguments;
+mod validate_refetchable_query_names;
 mod validate_relay_directives;
+mod validate_required_directives;
 mod validate_server_only_directives;
+mod validate_subscription_root_fields;
 mod validate_unused_variables;
+mod validate_variable_usages;
 
 pub use disallow_id_as_alias::disallow_id_as_alias;
 pub use validate_connections::validate_connections;
+pub use validate_fragment_arguments::validate_fragment_arguments;
+pub use validate_refetchable_query_names::validate_refetchable_query_names;
 pub use validate_relay_directives::validate_relay_directives;
+pub use validate_required_directives::validate_required_directives;
 pub use validate_server_only_directives::validate_server_only_directives;
+pub use validate_subscription_root_fields::validate_subscription_root_fields;
 pub use validate_unused_variables::validate_unused_variables;
+pub use validate_variable_usages::validate_variable_usages;
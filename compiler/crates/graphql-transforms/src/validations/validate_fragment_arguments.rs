@@ -0,0 +1,63 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::NamedItem;
+use graphql_ir::{
+    FragmentSpread, Program, ValidationError, ValidationMessage, ValidationResult, Validator,
+};
+
+/// Validates that the fragment spreads pass the required arguments of the spread
+/// fragments, i.e. the non-null arguments of `@argumentDefinitions` without a default
+/// value. Passing an argument the fragment doesn't declare is already an error when
+/// building the IR.
+pub fn validate_fragment_arguments(program: &Program<'_>) -> ValidationResult<()> {
+    let mut validator = ValidateFragmentArguments { program };
+    validator.validate_program(program)
+}
+
+struct ValidateFragmentArguments<'s> {
+    program: &'s Program<'s>,
+}
+
+impl<'s> Validator for ValidateFragmentArguments<'s> {
+    const NAME: &'static str = "ValidateFragmentArguments";
+    const VALIDATE_ARGUMENTS: bool = false;
+    const VALIDATE_DIRECTIVES: bool = false;
+
+    fn validate_fragment_spread(&mut self, spread: &FragmentSpread) -> ValidationResult<()> {
+        let fragment = match self.program.fragment(spread.fragment.item) {
+            Some(fragment) => fragment,
+            None => return Ok(()),
+        };
+        let errors: Vec<ValidationError> = fragment
+            .variable_definitions
+            .iter()
+            .filter(|variable_definition| {
+                variable_definition.type_.is_non_null()
+                    && variable_definition.default_value.is_none()
+                    && spread
+                        .arguments
+                        .named(variable_definition.name.item)
+                        .is_none()
+            })
+            .map(|variable_definition| {
+                ValidationError::new(
+                    ValidationMessage::MissingRequiredFragmentArgument {
+                        argument_name: variable_definition.name.item,
+                        fragment_name: fragment.name.item,
+                    },
+                    vec![spread.fragment.location],
+                )
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::REFETCHABLE_CONSTANTS;
+use common::{NamedItem, WithLocation};
+use fnv::FnvHashMap;
+use graphql_ir::{Program, ValidationError, ValidationMessage, ValidationResult};
+use interner::StringKey;
+
+/// Validates that the `queryName` of each `@refetchable` fragment of the program is
+/// unique, as a query is generated with that name for each of them. The fragments
+/// sharing a name are sorted by fragment name, and each fragment after the first is
+/// reported with the location of the first one. Invalid `queryName` values are
+/// reported by the refetchable transform.
+pub fn validate_refetchable_query_names(program: &Program<'_>) -> ValidationResult<()> {
+    let mut fragments_by_query_name: FnvHashMap<StringKey, Vec<WithLocation<StringKey>>> =
+        Default::default();
+    for fragment in program.fragments() {
+        let query_name = fragment
+            .directives
+            .named(REFETCHABLE_CONSTANTS.refetchable_name)
+            .and_then(|directive| {
+                directive
+                    .arguments
+                    .named(REFETCHABLE_CONSTANTS.query_name_arg)
+            })
+            .and_then(|argument| argument.value.item.get_string_literal());
+        if let Some(query_name) = query_name {
+            fragments_by_query_name
+                .entry(query_name)
+                .or_default()
+                .push(fragment.name);
+        }
+    }
+
+    let mut errors = vec![];
+    for (query_name, mut fragment_names) in fragments_by_query_name {
+        fragment_names.sort_by_key(|fragment_name| fragment_name.item.lookup());
+        let (first_fragment_name, other_fragment_names) = fragment_names.split_first().unwrap();
+        for fragment_name in other_fragment_names {
+            errors.push(ValidationError::new(
+                ValidationMessage::DuplicateRefetchableOperation {
+                    query_name,
+                    fragment_name: fragment_name.item,
+                    previous_fragment_name: first_fragment_name.item,
+                },
+                vec![fragment_name.location, first_fragment_name.location],
+            ));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
@@ -5,7 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use crate::relay_directive::{MASK_ARG_NAME, PLURAL_ARG_NAME, RELAY_DIRECTIVE_NAME};
+use crate::relay_directive::{MASK_ARG_NAME, RELAY_DIRECTIVE_NAME};
 use common::NamedItem;
 use errors::validate;
 use fnv::FnvHashMap;
@@ -14,6 +14,7 @@ use graphql_ir::{
     ValidationError, ValidationMessage, ValidationResult, Validator, Value, VariableDefinition,
 };
 use interner::StringKey;
+use schema::{get_relay_directive_argument, RelayDirectiveArgumentKind};
 
 pub fn validate_relay_directives<'s>(program: &Program<'s>) -> ValidationResult<()> {
     let mut validator = RelayDirectiveValidation::new(program);
@@ -25,7 +26,8 @@ enum ArgumentDefinition<'ir> {
     Local(&'ir VariableDefinition),
 }
 
-// This validtes both @relay(plural) and @relay(mask) usages
+// This validtes both @relay(plural) and @relay(mask) usages, and the literal
+// arguments of the other Relay directives
 struct RelayDirectiveValidation<'s> {
     program: &'s Program<'s>,
     // TODO(T63626938): This assumes that each document is processed serially (not in parallel or concurrently)
@@ -117,42 +119,18 @@ impl<'s> RelayDirectiveValidation<'s> {
             Err(errs)
         }
     }
-
-    fn validate_relay_directives(&self, directives: &[Directive]) -> ValidationResult<()> {
-        let mut errs = vec![];
-        if let Some(directive) = find_relay_directive(directives) {
-            for arg in &directive.arguments {
-                if arg.name.item == *PLURAL_ARG_NAME || arg.name.item == *MASK_ARG_NAME {
-                    match arg.value.item {
-                        Value::Constant(ConstantValue::Boolean(_))
-                        | Value::Constant(ConstantValue::Null()) => {}
-                        _ => errs.push(ValidationError::new(
-                            ValidationMessage::InvalidRelayDirectiveArg(arg.name.item),
-                            vec![arg.value.location],
-                        )),
-                    }
-                }
-            }
-        }
-        if errs.is_empty() {
-            Ok(())
-        } else {
-            Err(errs)
-        }
-    }
 }
 
 impl<'s> Validator for RelayDirectiveValidation<'s> {
     const NAME: &'static str = "RelayDirectiveValidation";
     const VALIDATE_ARGUMENTS: bool = false;
-    const VALIDATE_DIRECTIVES: bool = false;
+    const VALIDATE_DIRECTIVES: bool = true;
 
     fn validate_fragment(&mut self, fragment: &FragmentDefinition) -> ValidationResult<()> {
         // Initialize arguments state for @relay(mask: false),
         self.current_reachable_arguments = Default::default();
         validate!(
             self.default_validate_fragment(fragment),
-            self.validate_relay_directives(&fragment.directives),
             if self.current_reachable_arguments.is_empty() {
                 Ok(())
             } else {
@@ -185,6 +163,45 @@ impl<'s> Validator for RelayDirectiveValidation<'s> {
         )
     }
 
+    /// Validates the literal arguments of the Relay directives, e.g. that
+    /// `@relay(plural: $plural)` doesn't use a variable.
+    fn validate_directive(&mut self, directive: &Directive) -> ValidationResult<()> {
+        let mut errs = vec![];
+        for arg in &directive.arguments {
+            if let Some(relay_argument) =
+                get_relay_directive_argument(directive.name.item, arg.name.item)
+            {
+                let is_valid = match (&arg.value.item, relay_argument.kind) {
+                    (Value::Constant(ConstantValue::Null()), _)
+                    | (
+                        Value::Constant(ConstantValue::Boolean(_)),
+                        RelayDirectiveArgumentKind::Boolean,
+                    )
+                    | (
+                        Value::Constant(ConstantValue::String(_)),
+                        RelayDirectiveArgumentKind::String,
+                    ) => true,
+                    _ => false,
+                };
+                if !is_valid {
+                    errs.push(ValidationError::new(
+                        ValidationMessage::InvalidRelayDirectiveArgument {
+                            directive_name: directive.name.item,
+                            argument_name: arg.name.item,
+                            expected: relay_argument.kind,
+                        },
+                        vec![arg.value.location],
+                    ));
+                }
+            }
+        }
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(errs)
+        }
+    }
+
     fn validate_fragment_spread(&mut self, spread: &FragmentSpread) -> ValidationResult<()> {
         validate!(
             if let Some(directive) = find_relay_directive(&spread.directives) {
@@ -198,11 +215,8 @@ impl<'s> Validator for RelayDirectiveValidation<'s> {
                                 self.validate_unmask_fragment_spread(spread)
                             }
                         }
-                        Value::Constant(ConstantValue::Null()) => Ok(()),
-                        _ => Err(vec![ValidationError::new(
-                            ValidationMessage::InvalidRelayDirectiveArg(arg.name.item),
-                            vec![spread.fragment.location, arg.value.location],
-                        )]),
+                        // Other values are reported by `validate_directive`
+                        _ => Ok(()),
                     }
                 } else {
                     Ok(())
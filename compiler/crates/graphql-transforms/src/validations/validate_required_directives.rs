@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::NamedItem;
+use errors::validate;
+use graphql_ir::{
+    ConstantValue, Directive, LinkedField, Program, ScalarField, ValidationError,
+    ValidationMessage, ValidationResult, Validator, Value,
+};
+use interner::{Intern, StringKey};
+use schema::FieldID;
+
+/// The actions the client can take when a `@required` field is null
+const VALID_ACTIONS: [&str; 3] = ["NONE", "LOG", "THROW"];
+
+/// Validates the usages of `@required`:
+/// - The directive can only be used on fields that are nullable in the schema.
+/// - The `action` argument must be one of `NONE`, `LOG` or `THROW`, which excludes
+///   variables since the action has to be known at compile time.
+pub fn validate_required_directives<'s>(program: &Program<'s>) -> ValidationResult<()> {
+    let mut validator = RequiredDirectivesValidation::new(program);
+    validator.validate_program(program)
+}
+
+struct RequiredDirectivesValidation<'s> {
+    program: &'s Program<'s>,
+    required_directive_name: StringKey,
+    action_arg_name: StringKey,
+}
+
+impl<'s> RequiredDirectivesValidation<'s> {
+    fn new(program: &'s Program<'s>) -> Self {
+        Self {
+            program,
+            required_directive_name: "required".intern(),
+            action_arg_name: "action".intern(),
+        }
+    }
+
+    fn validate_field(&self, field_id: FieldID, directives: &[Directive]) -> ValidationResult<()> {
+        let directive = match directives.named(self.required_directive_name) {
+            Some(directive) => directive,
+            None => return Ok(()),
+        };
+        let field = self.program.schema().field(field_id);
+        let mut errors = vec![];
+        if field.type_.is_non_null() {
+            errors.push(ValidationError::new(
+                ValidationMessage::RequiredOnNonNullField {
+                    field_name: field.name,
+                },
+                vec![directive.name.location],
+            ));
+        }
+        if let Some(action_arg) = directive.arguments.named(self.action_arg_name) {
+            let is_valid_action = match &action_arg.value.item {
+                Value::Constant(ConstantValue::Enum(action)) => VALID_ACTIONS
+                    .iter()
+                    .any(|valid_action| action.lookup() == *valid_action),
+                _ => false,
+            };
+            if !is_valid_action {
+                errors.push(ValidationError::new(
+                    ValidationMessage::InvalidRequiredAction {
+                        field_name: field.name,
+                        valid_actions: VALID_ACTIONS.join(", "),
+                    },
+                    vec![action_arg.value.location],
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl<'s> Validator for RequiredDirectivesValidation<'s> {
+    const NAME: &'static str = "RequiredDirectivesValidation";
+    const VALIDATE_ARGUMENTS: bool = false;
+    const VALIDATE_DIRECTIVES: bool = false;
+
+    fn validate_linked_field(&mut self, field: &LinkedField) -> ValidationResult<()> {
+        validate!(
+            self.validate_field(field.definition.item, &field.directives),
+            self.validate_selections(&field.selections)
+        )
+    }
+
+    fn validate_scalar_field(&mut self, field: &ScalarField) -> ValidationResult<()> {
+        self.validate_field(field.definition.item, &field.directives)
+    }
+}
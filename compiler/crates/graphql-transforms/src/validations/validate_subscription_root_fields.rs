@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::Location;
+use fnv::FnvHashSet;
+use graphql_ir::{
+    OperationDefinition, Program, Selection, ValidationError, ValidationMessage, ValidationResult,
+};
+use graphql_syntax::OperationKind;
+use interner::StringKey;
+
+/// Validates that each subscription selects exactly one root field, after expanding
+/// the inline fragments, conditions and fragment spreads at its root. The `__typename`
+/// introspection field isn't a root field. Each extra root field is reported with
+/// the location of the first one, at the location of the root selection containing
+/// it, e.g. the fragment spread selecting it.
+pub fn validate_subscription_root_fields(program: &Program<'_>) -> ValidationResult<()> {
+    let mut errors = vec![];
+    for operation in program.operations() {
+        if operation.kind == OperationKind::Subscription {
+            validate_subscription(program, operation, &mut errors);
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn validate_subscription(
+    program: &Program<'_>,
+    operation: &OperationDefinition,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut root_fields = RootFields {
+        program,
+        fields: vec![],
+        response_keys: Default::default(),
+        visited_fragments: Default::default(),
+    };
+    root_fields.collect(&operation.selections, None);
+
+    let (first_field, extra_fields) = match root_fields.fields.split_first() {
+        Some(fields) => fields,
+        None => {
+            errors.push(ValidationError::new(
+                ValidationMessage::MissingSubscriptionRootField {
+                    subscription_name: operation.name.item,
+                },
+                vec![operation.name.location],
+            ));
+            return;
+        }
+    };
+    for (response_key, location) in extra_fields {
+        errors.push(ValidationError::new(
+            ValidationMessage::MultipleSubscriptionRootFields {
+                subscription_name: operation.name.item,
+                response_key: *response_key,
+            },
+            vec![*location, first_field.1],
+        ));
+    }
+}
+
+/// Collects the root fields of an operation, one per response key, with the location
+/// of the root selection containing them
+struct RootFields<'s> {
+    program: &'s Program<'s>,
+    fields: Vec<(StringKey, Location)>,
+    response_keys: FnvHashSet<StringKey>,
+    visited_fragments: FnvHashSet<StringKey>,
+}
+
+impl<'s> RootFields<'s> {
+    fn collect(&mut self, selections: &[Selection], spread_location: Option<Location>) {
+        let schema = self.program.schema();
+        for selection in selections {
+            let (response_key, field_location) = match selection {
+                Selection::LinkedField(field) => (
+                    field.alias_or_name(schema),
+                    field
+                        .alias
+                        .map_or(field.definition.location, |alias| alias.location),
+                ),
+                Selection::ScalarField(field) => {
+                    if field.definition.item == schema.typename_field() {
+                        continue;
+                    }
+                    (
+                        field.alias_or_name(schema),
+                        field
+                            .alias
+                            .map_or(field.definition.location, |alias| alias.location),
+                    )
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.collect(&fragment.selections, spread_location);
+                    continue;
+                }
+                Selection::Condition(condition) => {
+                    self.collect(&condition.selections, spread_location);
+                    continue;
+                }
+                Selection::FragmentSpread(spread) => {
+                    if !self.visited_fragments.insert(spread.fragment.item) {
+                        continue;
+                    }
+                    // Missing fragments are reported when building the program
+                    if let Some(fragment) = self.program.fragment(spread.fragment.item) {
+                        self.collect(
+                            &fragment.selections,
+                            spread_location.or(Some(spread.fragment.location)),
+                        );
+                    }
+                    continue;
+                }
+            };
+            if self.response_keys.insert(response_key) {
+                self.fields
+                    .push((response_key, spread_location.unwrap_or(field_location)));
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::NamedItem;
+use fnv::{FnvHashMap, FnvHashSet};
+use graphql_ir::{
+    FragmentDefinition, FragmentSpread, OperationDefinition, Program, ValidationError,
+    ValidationMessage, ValidationResult, Validator, Variable, Visitor,
+};
+use interner::StringKey;
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+pub fn validate_variable_usages(program: &Program<'_>) -> ValidationResult<()> {
+    ValidateVariableUsages::new(program).validate_program(program)
+}
+
+type VisitedFragments = FnvHashMap<StringKey, Arc<Vec<Variable>>>;
+
+struct ValidateVariableUsages<'s> {
+    program: &'s Program<'s>,
+    visited_fragments: VisitedFragments,
+}
+
+impl<'s> ValidateVariableUsages<'s> {
+    fn new(program: &'s Program<'s>) -> Self {
+        Self {
+            program,
+            visited_fragments: Default::default(),
+        }
+    }
+}
+
+/// Validates that the variables used by the fragments an operation spreads, directly or
+/// through other fragments, are defined by the operation with a type compatible with
+/// each of their usages, e.g. in field arguments, directive arguments and the fields of
+/// input objects. The usages in the operation itself are validated when building it.
+/// The variables passed to `@arguments` are only loosely checked, as in the builder.
+impl<'s> Validator for ValidateVariableUsages<'s> {
+    const NAME: &'static str = "ValidateVariableUsages";
+    const VALIDATE_ARGUMENTS: bool = false;
+    const VALIDATE_DIRECTIVES: bool = false;
+
+    fn validate_operation(&mut self, operation: &OperationDefinition) -> ValidationResult<()> {
+        let mut visitor = VariableUsagesVisitor::new(
+            self.program,
+            &mut self.visited_fragments,
+            Default::default(),
+        );
+        visitor.visit_operation(operation);
+
+        let schema = self.program.schema();
+        let mut errors = vec![];
+        for usage in visitor.usages {
+            match operation.variable_definitions.named(usage.name.item) {
+                Some(definition) => {
+                    // A variable with a non-null default value can be used as non-null
+                    let non_null_type = definition.type_.non_null();
+                    let effective_type = if definition.has_non_null_default_value() {
+                        &non_null_type
+                    } else {
+                        &definition.type_
+                    };
+                    if !schema.is_type_subtype_of(effective_type, &usage.type_) {
+                        errors.push(ValidationError::new(
+                            ValidationMessage::InvalidVariableUsage {
+                                defined_type: schema.get_type_string(&definition.type_),
+                                used_type: schema.get_type_string(&usage.type_),
+                            },
+                            vec![usage.name.location, definition.name.location],
+                        ));
+                    }
+                }
+                None => errors.push(ValidationError::new(
+                    ValidationMessage::UndefinedVariable {
+                        variable_name: usage.name.item,
+                        operation_name: operation.name.item,
+                    },
+                    vec![usage.name.location],
+                )),
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_fragment(&mut self, _: &FragmentDefinition) -> ValidationResult<()> {
+        Ok(())
+    }
+}
+
+/// Collects every usage of the variables that aren't local to the visited definition,
+/// including those of the fragments it spreads transitively, each fragment once.
+struct VariableUsagesVisitor<'s> {
+    usages: Vec<Variable>,
+    program: &'s Program<'s>,
+    visited_fragments: &'s mut VisitedFragments,
+    local_variables: FnvHashSet<StringKey>,
+    spread_fragments: FnvHashSet<StringKey>,
+}
+
+impl<'s> VariableUsagesVisitor<'s> {
+    fn new(
+        program: &'s Program<'s>,
+        visited_fragments: &'s mut VisitedFragments,
+        local_variables: FnvHashSet<StringKey>,
+    ) -> Self {
+        Self {
+            usages: vec![],
+            program,
+            visited_fragments,
+            local_variables,
+            spread_fragments: Default::default(),
+        }
+    }
+
+    /// The usages of the global variables of a fragment, computed once
+    fn fragment_usages(&mut self, fragment: &FragmentDefinition) -> Arc<Vec<Variable>> {
+        if let Some(usages) = self.visited_fragments.get(&fragment.name.item) {
+            return Arc::clone(usages);
+        }
+        // Break cycles by caching an empty list until the fragment is visited
+        self.visited_fragments
+            .insert(fragment.name.item, Default::default());
+        let local_variables = FnvHashSet::from_iter(
+            fragment
+                .variable_definitions
+                .iter()
+                .map(|variable| variable.name.item),
+        );
+        let mut visitor =
+            VariableUsagesVisitor::new(self.program, self.visited_fragments, local_variables);
+        visitor.visit_fragment(fragment);
+        let usages = Arc::new(visitor.usages);
+        self.visited_fragments
+            .insert(fragment.name.item, Arc::clone(&usages));
+        usages
+    }
+}
+
+impl<'s> Visitor for VariableUsagesVisitor<'s> {
+    const NAME: &'static str = "VariableUsagesVisitor";
+    const VISIT_ARGUMENTS: bool = true;
+    const VISIT_DIRECTIVES: bool = true;
+
+    fn visit_fragment_spread(&mut self, spread: &FragmentSpread) {
+        self.visit_directives(&spread.directives);
+        if !self.spread_fragments.insert(spread.fragment.item) {
+            return;
+        }
+        // Missing fragments are reported when building the program
+        if let Some(fragment) = self.program.fragment(spread.fragment.item) {
+            let usages = self.fragment_usages(fragment);
+            self.usages.extend(usages.iter().cloned());
+        }
+    }
+
+    fn visit_variable(&mut self, variable: &Variable) {
+        if !self.local_variables.contains(&variable.name.item) {
+            self.usages.push(variable.clone());
+        }
+    }
+}
@@ -0,0 +1,27 @@
+// @generated SignedSource<<35e65d327f02025b0c1a48503b59dc60>>
+
+mod collapse_duplicate_fields;
+
+use collapse_duplicate_fields::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn aliased_duplicates() {
+    let input = include_str!("collapse_duplicate_fields/fixtures/aliased-duplicates.graphql");
+    let expected = include_str!("collapse_duplicate_fields/fixtures/aliased-duplicates.expected");
+    test_fixture(transform_fixture, "aliased-duplicates.graphql", "collapse_duplicate_fields/fixtures/aliased-duplicates.expected", input, expected);
+}
+
+#[test]
+fn conflicting_duplicates_invalid() {
+    let input = include_str!("collapse_duplicate_fields/fixtures/conflicting-duplicates.invalid.graphql");
+    let expected = include_str!("collapse_duplicate_fields/fixtures/conflicting-duplicates.invalid.expected");
+    test_fixture(transform_fixture, "conflicting-duplicates.invalid.graphql", "collapse_duplicate_fields/fixtures/conflicting-duplicates.invalid.expected", input, expected);
+}
+
+#[test]
+fn identical_duplicates() {
+    let input = include_str!("collapse_duplicate_fields/fixtures/identical-duplicates.graphql");
+    let expected = include_str!("collapse_duplicate_fields/fixtures/identical-duplicates.expected");
+    test_fixture(transform_fixture, "identical-duplicates.graphql", "collapse_duplicate_fields/fixtures/identical-duplicates.expected", input, expected);
+}
@@ -0,0 +1,44 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::FileKey;
+use fixture_tests::Fixture;
+use graphql_ir::{build, Program};
+use graphql_syntax::parse;
+use graphql_transforms::collect_deprecated_fields;
+use test_schema::test_schema_with_extensions;
+
+pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
+    let parts: Vec<_> = fixture.content.split("%extensions%").collect();
+    if let [base, extensions] = parts.as_slice() {
+        let file_key = FileKey::new(fixture.file_name);
+        let ast = parse(base, file_key).unwrap();
+        let schema = test_schema_with_extensions(extensions);
+        let ir = build(&schema, &ast.definitions).unwrap();
+        let program = Program::from_definitions(&schema, ir);
+        let usages = collect_deprecated_fields(&program);
+
+        let mut printed = usages
+            .iter()
+            .map(|usage| {
+                let span = usage.location.span();
+                let start = span.start as usize;
+                let end = start + span.length as usize;
+                format!(
+                    "`{}` selects the deprecated field `{}`: {}",
+                    base[start..end].trim(),
+                    schema.field(usage.field).name,
+                    usage.reason.as_deref().unwrap_or("<no reason>")
+                )
+            })
+            .collect::<Vec<_>>();
+        printed.sort();
+        Ok(printed.join("\n"))
+    } else {
+        panic!("Expected exactly one %extensions% section marker.")
+    }
+}
@@ -0,0 +1,13 @@
+// @generated SignedSource<<c171ea6e3ef4a413706a8f706453b481>>
+
+mod collect_deprecated_fields;
+
+use collect_deprecated_fields::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn deprecated_fields() {
+    let input = include_str!("collect_deprecated_fields/fixtures/deprecated-fields.graphql");
+    let expected = include_str!("collect_deprecated_fields/fixtures/deprecated-fields.expected");
+    test_fixture(transform_fixture, "deprecated-fields.graphql", "collect_deprecated_fields/fixtures/deprecated-fields.expected", input, expected);
+}
@@ -22,7 +22,8 @@ directive @__clientExtension(
   label: String!
   if: Boolean = true
 ) on INLINE_FRAGMENT
-directive @serverInlineDirective on INLINE_FRAGMENT"#,
+directive @serverInlineDirective on INLINE_FRAGMENT
+directive @otherInlineDirective on INLINE_FRAGMENT"#,
     );
     let ir = build(&schema, &ast.definitions).unwrap();
     let context = Program::from_definitions(&schema, ir);
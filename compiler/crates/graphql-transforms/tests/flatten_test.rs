@@ -1,4 +1,4 @@
-// @generated SignedSource<<6c6c88dc6a0dd0d4ec176290a7df100f>>
+// @generated SignedSource<<c8c75435a7bf855c274c0c902ff98b70>>
 
 mod flatten;
 
@@ -54,6 +54,13 @@ fn flattens_matching_fragment_types() {
     test_fixture(transform_fixture, "flattens-matching-fragment-types.graphql", "flatten/fixtures/flattens-matching-fragment-types.expected", input, expected);
 }
 
+#[test]
+fn flattens_same_type_inline_fragments() {
+    let input = include_str!("flatten/fixtures/flattens-same-type-inline-fragments.graphql");
+    let expected = include_str!("flatten/fixtures/flattens-same-type-inline-fragments.expected");
+    test_fixture(transform_fixture, "flattens-same-type-inline-fragments.graphql", "flatten/fixtures/flattens-same-type-inline-fragments.expected", input, expected);
+}
+
 #[test]
 fn inline_fragment_with_directives() {
     let input = include_str!("flatten/fixtures/inline-fragment-with-directives.graphql");
@@ -68,6 +75,13 @@ fn inline_fragment_with_directives_text() {
     test_fixture(transform_fixture, "inline-fragment-with-directives-text.graphql", "flatten/fixtures/inline-fragment-with-directives-text.expected", input, expected);
 }
 
+#[test]
+fn keeps_inline_fragments_on_other_types() {
+    let input = include_str!("flatten/fixtures/keeps-inline-fragments-on-other-types.graphql");
+    let expected = include_str!("flatten/fixtures/keeps-inline-fragments-on-other-types.expected");
+    test_fixture(transform_fixture, "keeps-inline-fragments-on-other-types.graphql", "flatten/fixtures/keeps-inline-fragments-on-other-types.expected", input, expected);
+}
+
 #[test]
 fn linked_handle_field() {
     let input = include_str!("flatten/fixtures/linked-handle-field.graphql");
@@ -89,6 +103,13 @@ fn match_field_overlap() {
     test_fixture(transform_fixture, "match-field-overlap.graphql", "flatten/fixtures/match-field-overlap.expected", input, expected);
 }
 
+#[test]
+fn preserves_inline_fragment_directives() {
+    let input = include_str!("flatten/fixtures/preserves-inline-fragment-directives.graphql");
+    let expected = include_str!("flatten/fixtures/preserves-inline-fragment-directives.expected");
+    test_fixture(transform_fixture, "preserves-inline-fragment-directives.graphql", "flatten/fixtures/preserves-inline-fragment-directives.expected", input, expected);
+}
+
 #[test]
 fn scalar_handle_field() {
     let input = include_str!("flatten/fixtures/scalar-handle-field.graphql");
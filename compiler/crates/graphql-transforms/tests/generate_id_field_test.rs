@@ -1,4 +1,4 @@
-// @generated SignedSource<<c6fe1d12692d749209289cad05f5d2ee>>
+// @generated SignedSource<<8e06337acae853350846c91607fece85>>
 
 mod generate_id_field;
 
@@ -12,6 +12,13 @@ fn abstract_plural() {
     test_fixture(transform_fixture, "abstract-plural.graphql", "generate_id_field/fixtures/abstract-plural.expected", input, expected);
 }
 
+#[test]
+fn node_type() {
+    let input = include_str!("generate_id_field/fixtures/node-type.graphql");
+    let expected = include_str!("generate_id_field/fixtures/node-type.expected");
+    test_fixture(transform_fixture, "node-type.graphql", "generate_id_field/fixtures/node-type.expected", input, expected);
+}
+
 #[test]
 fn node_union() {
     let input = include_str!("generate_id_field/fixtures/node-union.graphql");
@@ -19,6 +26,13 @@ fn node_union() {
     test_fixture(transform_fixture, "node-union.graphql", "generate_id_field/fixtures/node-union.expected", input, expected);
 }
 
+#[test]
+fn non_node_type() {
+    let input = include_str!("generate_id_field/fixtures/non-node-type.graphql");
+    let expected = include_str!("generate_id_field/fixtures/non-node-type.expected");
+    test_fixture(transform_fixture, "non-node-type.graphql", "generate_id_field/fixtures/non-node-type.expected", input, expected);
+}
+
 #[test]
 fn non_node_union() {
     let input = include_str!("generate_id_field/fixtures/non-node-union.graphql");
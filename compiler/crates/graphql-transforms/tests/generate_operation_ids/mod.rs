@@ -0,0 +1,39 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::FileKey;
+use fixture_tests::Fixture;
+use graphql_ir::{build, Program};
+use graphql_syntax::parse;
+use graphql_text_printer::print_operation;
+use graphql_transforms::{generate_operation_id_metadata, generate_operation_ids};
+use test_schema::TEST_SCHEMA;
+
+fn build_program(fixture: &Fixture) -> Program<'static> {
+    let file_key = FileKey::new(fixture.file_name);
+    let ast = parse(fixture.content, file_key).unwrap();
+    let ir = build(&TEST_SCHEMA, &ast.definitions).unwrap();
+    Program::from_definitions(&TEST_SCHEMA, ir)
+}
+
+pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
+    let program = build_program(fixture);
+    let operation_ids = generate_operation_ids(&program);
+    // Identical operations, from another build of the same source, get the same IDs
+    assert_eq!(
+        operation_ids,
+        generate_operation_ids(&build_program(fixture))
+    );
+
+    let next_program = generate_operation_id_metadata(&program, &operation_ids);
+    let mut printed = next_program
+        .operations()
+        .map(|def| print_operation(&TEST_SCHEMA, def))
+        .collect::<Vec<_>>();
+    printed.sort();
+    Ok(printed.join("\n\n"))
+}
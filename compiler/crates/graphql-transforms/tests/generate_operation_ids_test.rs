@@ -0,0 +1,13 @@
+// @generated SignedSource<<a3cd868f5545a870293c5bc41c18f37d>>
+
+mod generate_operation_ids;
+
+use generate_operation_ids::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn operation_ids() {
+    let input = include_str!("generate_operation_ids/fixtures/operation-ids.graphql");
+    let expected = include_str!("generate_operation_ids/fixtures/operation-ids.expected");
+    test_fixture(transform_fixture, "operation-ids.graphql", "generate_operation_ids/fixtures/operation-ids.expected", input, expected);
+}
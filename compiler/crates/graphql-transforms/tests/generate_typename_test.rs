@@ -1,4 +1,4 @@
-// @generated SignedSource<<4b9b772191dd8d40c7cb1a4239acad70>>
+// @generated SignedSource<<9f45da93de1c4533c90ae415af3fab9f>>
 
 mod generate_typename;
 
@@ -18,3 +18,10 @@ fn type_name_exists() {
     let expected = include_str!("generate_typename/fixtures/type-name-exists.expected");
     test_fixture(transform_fixture, "type-name-exists.graphql", "generate_typename/fixtures/type-name-exists.expected", input, expected);
 }
+
+#[test]
+fn union_and_concrete_types() {
+    let input = include_str!("generate_typename/fixtures/union-and-concrete-types.graphql");
+    let expected = include_str!("generate_typename/fixtures/union-and-concrete-types.expected");
+    test_fixture(transform_fixture, "union-and-concrete-types.graphql", "generate_typename/fixtures/union-and-concrete-types.expected", input, expected);
+}
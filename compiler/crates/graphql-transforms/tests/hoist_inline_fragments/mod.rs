@@ -0,0 +1,50 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::FileKey;
+use fixture_tests::Fixture;
+use graphql_ir::{build, Program};
+use graphql_syntax::parse;
+use graphql_text_printer::{print_fragment, print_operation};
+use graphql_transforms::{hoist_inline_fragments, inline_fragments};
+use test_schema::TEST_SCHEMA;
+
+pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
+    let file_key = FileKey::new(fixture.file_name);
+    let ast = parse(fixture.content, file_key).unwrap();
+    let ir = build(&TEST_SCHEMA, &ast.definitions).unwrap();
+    let program = Program::from_definitions(&TEST_SCHEMA, ir);
+    let next_program = hoist_inline_fragments(&program, 2);
+
+    // Inlining the hoisted fragments again results in the original operations
+    assert_eq!(
+        print_operations(&inline_fragments(&program)),
+        print_operations(&inline_fragments(&next_program)),
+    );
+
+    let mut printed = print_operations(&next_program);
+    printed.extend(next_program.fragments().map(|def| {
+        let mut printed_fragment = print_fragment(&TEST_SCHEMA, def);
+        for variable in &def.used_global_variables {
+            printed_fragment.push_str(&format!(
+                "\n# uses global variable ${}: {}",
+                variable.name.item,
+                TEST_SCHEMA.get_type_string(&variable.type_)
+            ));
+        }
+        printed_fragment
+    }));
+    printed.sort();
+    Ok(printed.join("\n\n"))
+}
+
+fn print_operations(program: &Program<'_>) -> Vec<String> {
+    program
+        .operations()
+        .map(|def| print_operation(&TEST_SCHEMA, def))
+        .collect()
+}
@@ -0,0 +1,20 @@
+// @generated SignedSource<<d0ebe740ff0d57ba20f092733be9efa0>>
+
+mod hoist_inline_fragments;
+
+use hoist_inline_fragments::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn hoist_duplicate_inline_fragments() {
+    let input = include_str!("hoist_inline_fragments/fixtures/hoist-duplicate-inline-fragments.graphql");
+    let expected = include_str!("hoist_inline_fragments/fixtures/hoist-duplicate-inline-fragments.expected");
+    test_fixture(transform_fixture, "hoist-duplicate-inline-fragments.graphql", "hoist_inline_fragments/fixtures/hoist-duplicate-inline-fragments.expected", input, expected);
+}
+
+#[test]
+fn hoist_inline_fragments_with_variables() {
+    let input = include_str!("hoist_inline_fragments/fixtures/hoist-inline-fragments-with-variables.graphql");
+    let expected = include_str!("hoist_inline_fragments/fixtures/hoist-inline-fragments-with-variables.expected");
+    test_fixture(transform_fixture, "hoist-inline-fragments-with-variables.graphql", "hoist_inline_fragments/fixtures/hoist-inline-fragments-with-variables.expected", input, expected);
+}
@@ -0,0 +1,20 @@
+// @generated SignedSource<<94d246fd27e321262d1b0dadf9620274>>
+
+mod hoist_repeated_arguments;
+
+use hoist_repeated_arguments::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn avoids_used_variable_names() {
+    let input = include_str!("hoist_repeated_arguments/fixtures/avoids-used-variable-names.graphql");
+    let expected = include_str!("hoist_repeated_arguments/fixtures/avoids-used-variable-names.expected");
+    test_fixture(transform_fixture, "avoids-used-variable-names.graphql", "hoist_repeated_arguments/fixtures/avoids-used-variable-names.expected", input, expected);
+}
+
+#[test]
+fn hoists_repeated_arguments() {
+    let input = include_str!("hoist_repeated_arguments/fixtures/hoists-repeated-arguments.graphql");
+    let expected = include_str!("hoist_repeated_arguments/fixtures/hoists-repeated-arguments.expected");
+    test_fixture(transform_fixture, "hoists-repeated-arguments.graphql", "hoist_repeated_arguments/fixtures/hoists-repeated-arguments.expected", input, expected);
+}
@@ -1,4 +1,4 @@
-// @generated SignedSource<<9bc6cfd8a7845af0280e35c8f7d2c8ca>>
+// @generated SignedSource<<7666d9e1fadf24d098cf8f0dc5e7cd41>>
 
 mod inline_fragments;
 
@@ -11,3 +11,10 @@ fn inlines_nested_fragments() {
     let expected = include_str!("inline_fragments/fixtures/inlines-nested-fragments.expected");
     test_fixture(transform_fixture, "inlines-nested-fragments.graphql", "inline_fragments/fixtures/inlines-nested-fragments.expected", input, expected);
 }
+
+#[test]
+fn renames_fragment_arguments() {
+    let input = include_str!("inline_fragments/fixtures/renames-fragment-arguments.graphql");
+    let expected = include_str!("inline_fragments/fixtures/renames-fragment-arguments.expected");
+    test_fixture(transform_fixture, "renames-fragment-arguments.graphql", "inline_fragments/fixtures/renames-fragment-arguments.expected", input, expected);
+}
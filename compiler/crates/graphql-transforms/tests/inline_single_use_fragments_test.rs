@@ -0,0 +1,20 @@
+// @generated SignedSource<<baee40d4a6102521b0f3f5946c2e1f3d>>
+
+mod inline_single_use_fragments;
+
+use inline_single_use_fragments::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn inlines_single_use_fragments() {
+    let input = include_str!("inline_single_use_fragments/fixtures/inlines-single-use-fragments.graphql");
+    let expected = include_str!("inline_single_use_fragments/fixtures/inlines-single-use-fragments.expected");
+    test_fixture(transform_fixture, "inlines-single-use-fragments.graphql", "inline_single_use_fragments/fixtures/inlines-single-use-fragments.expected", input, expected);
+}
+
+#[test]
+fn keeps_multi_use_fragments() {
+    let input = include_str!("inline_single_use_fragments/fixtures/keeps-multi-use-fragments.graphql");
+    let expected = include_str!("inline_single_use_fragments/fixtures/keeps-multi-use-fragments.expected");
+    test_fixture(transform_fixture, "keeps-multi-use-fragments.graphql", "inline_single_use_fragments/fixtures/keeps-multi-use-fragments.expected", input, expected);
+}
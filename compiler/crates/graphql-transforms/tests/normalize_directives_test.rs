@@ -0,0 +1,20 @@
+// @generated SignedSource<<2fa43b36fd4dfd1b0d163bd9c9c99b96>>
+
+mod normalize_directives;
+
+use normalize_directives::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn collapses_duplicate_directives() {
+    let input = include_str!("normalize_directives/fixtures/collapses-duplicate-directives.graphql");
+    let expected = include_str!("normalize_directives/fixtures/collapses-duplicate-directives.expected");
+    test_fixture(transform_fixture, "collapses-duplicate-directives.graphql", "normalize_directives/fixtures/collapses-duplicate-directives.expected", input, expected);
+}
+
+#[test]
+fn keeps_distinct_directives() {
+    let input = include_str!("normalize_directives/fixtures/keeps-distinct-directives.graphql");
+    let expected = include_str!("normalize_directives/fixtures/keeps-distinct-directives.expected");
+    test_fixture(transform_fixture, "keeps-distinct-directives.graphql", "normalize_directives/fixtures/keeps-distinct-directives.expected", input, expected);
+}
@@ -0,0 +1,13 @@
+// @generated SignedSource<<d31ffd4bbefca867d6e4827f708fa60b>>
+
+mod skip_client_directives;
+
+use skip_client_directives::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn relay_directives() {
+    let input = include_str!("skip_client_directives/fixtures/relay-directives.graphql");
+    let expected = include_str!("skip_client_directives/fixtures/relay-directives.expected");
+    test_fixture(transform_fixture, "relay-directives.graphql", "skip_client_directives/fixtures/relay-directives.expected", input, expected);
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::FileKey;
+use fixture_tests::Fixture;
+use graphql_ir::{build, LinkedField, Program, ScalarField, Selection, Transformed, Transformer};
+use graphql_syntax::parse;
+use graphql_text_printer::{print_fragment, print_operation};
+use graphql_transforms::skip_empty_selections;
+use interner::Intern;
+use std::sync::Arc;
+use test_schema::TEST_SCHEMA;
+
+pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
+    let file_key = FileKey::new(fixture.file_name);
+    let ast = parse(fixture.content, file_key).unwrap();
+    let ir = build(&TEST_SCHEMA, &ast.definitions).unwrap();
+    let program = Program::from_definitions(&TEST_SCHEMA, ir);
+
+    let program = SimulatedDeletion
+        .transform_program(&program)
+        .replace_or_else(|| program.clone());
+    let next_program = skip_empty_selections(&program);
+
+    let mut printed = next_program
+        .operations()
+        .map(|def| print_operation(&TEST_SCHEMA, def))
+        .chain(
+            next_program
+                .fragments()
+                .map(|def| print_fragment(&TEST_SCHEMA, def)),
+        )
+        .collect::<Vec<_>>();
+    printed.sort();
+    Ok(printed.join("\n\n"))
+}
+
+/// Simulates the transforms leaving empty selections behind: scalar fields with the
+/// alias `deleted` are removed, and linked fields with the alias `emptied` lose their
+/// selections without being removed.
+struct SimulatedDeletion;
+
+impl Transformer for SimulatedDeletion {
+    const NAME: &'static str = "SimulatedDeletion";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn transform_scalar_field(&mut self, field: &ScalarField) -> Transformed<Selection> {
+        match field.alias {
+            Some(alias) if alias.item == "deleted".intern() => Transformed::Delete,
+            _ => Transformed::Keep,
+        }
+    }
+
+    fn transform_linked_field(&mut self, field: &LinkedField) -> Transformed<Selection> {
+        match field.alias {
+            Some(alias) if alias.item == "emptied".intern() => {
+                Transformed::Replace(Selection::LinkedField(Arc::new(LinkedField {
+                    selections: vec![],
+                    ..field.clone()
+                })))
+            }
+            _ => self.default_transform_linked_field(field),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+// @generated SignedSource<<c94eed7fe7501be4808aa7216af42e06>>
+
+mod skip_empty_selections;
+
+use skip_empty_selections::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn nested_empty_selections() {
+    let input = include_str!("skip_empty_selections/fixtures/nested-empty-selections.graphql");
+    let expected = include_str!("skip_empty_selections/fixtures/nested-empty-selections.expected");
+    test_fixture(transform_fixture, "nested-empty-selections.graphql", "skip_empty_selections/fixtures/nested-empty-selections.expected", input, expected);
+}
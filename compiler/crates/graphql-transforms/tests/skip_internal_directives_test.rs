@@ -0,0 +1,13 @@
+// @generated SignedSource<<0d1da746b38f4a15a48c5f4d9cb29dee>>
+
+mod skip_internal_directives;
+
+use skip_internal_directives::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn internal_directives() {
+    let input = include_str!("skip_internal_directives/fixtures/internal-directives.graphql");
+    let expected = include_str!("skip_internal_directives/fixtures/internal-directives.expected");
+    test_fixture(transform_fixture, "internal-directives.graphql", "skip_internal_directives/fixtures/internal-directives.expected", input, expected);
+}
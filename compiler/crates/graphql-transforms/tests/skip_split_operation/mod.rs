@@ -0,0 +1,47 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::FileKey;
+use fixture_tests::Fixture;
+use fnv::FnvHashSet;
+use graphql_ir::{build, Program};
+use graphql_syntax::parse;
+use graphql_transforms::{skip_split_operation, split_module_import, transform_match};
+use interner::Intern;
+use test_schema::TEST_SCHEMA;
+
+const KEEP_PREFIX: &str = "# keep:";
+
+/// Split operations to keep are listed in `# keep: <name>` comments.
+pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
+    let keep: FnvHashSet<_> = fixture
+        .content
+        .lines()
+        .filter(|line| line.starts_with(KEEP_PREFIX))
+        .map(|line| line[KEEP_PREFIX.len()..].trim().intern())
+        .collect();
+
+    let file_key = FileKey::new(fixture.file_name);
+    let ast = parse(fixture.content, file_key).unwrap();
+    let ir = build(&TEST_SCHEMA, &ast.definitions).unwrap();
+    let program = Program::from_definitions(&TEST_SCHEMA, ir);
+    let program = transform_match(&program).unwrap();
+    let program = split_module_import(&program, &Default::default());
+    let next_program = skip_split_operation(&program, &keep);
+
+    let mut printed = next_program
+        .operations()
+        .map(|def| format!("operation {}", def.name.item))
+        .chain(
+            next_program
+                .fragments()
+                .map(|def| format!("fragment {}", def.name.item)),
+        )
+        .collect::<Vec<_>>();
+    printed.sort();
+    Ok(printed.join("\n"))
+}
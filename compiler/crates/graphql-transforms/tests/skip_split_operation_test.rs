@@ -0,0 +1,27 @@
+// @generated SignedSource<<2910efd372aa49702cf22fa8f7110de2>>
+
+mod skip_split_operation;
+
+use skip_split_operation::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn keeps_fragments() {
+    let input = include_str!("skip_split_operation/fixtures/keeps-fragments.graphql");
+    let expected = include_str!("skip_split_operation/fixtures/keeps-fragments.expected");
+    test_fixture(transform_fixture, "keeps-fragments.graphql", "skip_split_operation/fixtures/keeps-fragments.expected", input, expected);
+}
+
+#[test]
+fn keeps_split_operations_in_allowlist() {
+    let input = include_str!("skip_split_operation/fixtures/keeps-split-operations-in-allowlist.graphql");
+    let expected = include_str!("skip_split_operation/fixtures/keeps-split-operations-in-allowlist.expected");
+    test_fixture(transform_fixture, "keeps-split-operations-in-allowlist.graphql", "skip_split_operation/fixtures/keeps-split-operations-in-allowlist.expected", input, expected);
+}
+
+#[test]
+fn skips_split_operations() {
+    let input = include_str!("skip_split_operation/fixtures/skips-split-operations.graphql");
+    let expected = include_str!("skip_split_operation/fixtures/skips-split-operations.expected");
+    test_fixture(transform_fixture, "skips-split-operations.graphql", "skip_split_operation/fixtures/skips-split-operations.expected", input, expected);
+}
@@ -0,0 +1,20 @@
+// @generated SignedSource<<68e18eea1e3a0d998b6b4a2f5e50d6b0>>
+
+mod skip_subsumed_fragment_spreads;
+
+use skip_subsumed_fragment_spreads::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn keeps_conditional_and_different_fragment_spreads() {
+    let input = include_str!("skip_subsumed_fragment_spreads/fixtures/keeps-conditional-and-different-fragment-spreads.graphql");
+    let expected = include_str!("skip_subsumed_fragment_spreads/fixtures/keeps-conditional-and-different-fragment-spreads.expected");
+    test_fixture(transform_fixture, "keeps-conditional-and-different-fragment-spreads.graphql", "skip_subsumed_fragment_spreads/fixtures/keeps-conditional-and-different-fragment-spreads.expected", input, expected);
+}
+
+#[test]
+fn skips_subsumed_fragment_spreads() {
+    let input = include_str!("skip_subsumed_fragment_spreads/fixtures/skips-subsumed-fragment-spreads.graphql");
+    let expected = include_str!("skip_subsumed_fragment_spreads/fixtures/skips-subsumed-fragment-spreads.expected");
+    test_fixture(transform_fixture, "skips-subsumed-fragment-spreads.graphql", "skip_subsumed_fragment_spreads/fixtures/skips-subsumed-fragment-spreads.expected", input, expected);
+}
@@ -1,10 +1,17 @@
-// @generated SignedSource<<93029bdc388d8f29dc72479a1c2bc93d>>
+// @generated SignedSource<<c7715cd6982c6b2a2ca50c9b3ef9551b>>
 
 mod skip_unreachable_nodes;
 
 use skip_unreachable_nodes::transform_fixture;
 use fixture_tests::test_fixture;
 
+#[test]
+fn keeps_contents_of_passing_conditions() {
+    let input = include_str!("skip_unreachable_nodes/fixtures/keeps-contents-of-passing-conditions.graphql");
+    let expected = include_str!("skip_unreachable_nodes/fixtures/keeps-contents-of-passing-conditions.expected");
+    test_fixture(transform_fixture, "keeps-contents-of-passing-conditions.graphql", "skip_unreachable_nodes/fixtures/keeps-contents-of-passing-conditions.expected", input, expected);
+}
+
 #[test]
 fn keeps_other_fields() {
     let input = include_str!("skip_unreachable_nodes/fixtures/keeps-other-fields.graphql");
@@ -7,10 +7,11 @@
 
 use common::FileKey;
 use fixture_tests::Fixture;
+use fnv::FnvHashSet;
 use graphql_ir::{build, Program};
 use graphql_syntax::parse;
-use graphql_text_printer::print_fragment;
-use graphql_transforms::sort_selections;
+use graphql_text_printer::{print_fragment, print_operation};
+use graphql_transforms::sort_selections_except;
 use test_schema::TEST_SCHEMA;
 
 pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
@@ -18,7 +19,13 @@ pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
     let ast = parse(fixture.content, file_key).unwrap();
     let ir = build(&TEST_SCHEMA, &ast.definitions).unwrap();
     let program = Program::from_definitions(&TEST_SCHEMA, ir);
-    let next_program = sort_selections(&program);
+    // The order of the selections of operations named `Preserved...` is preserved
+    let preserved_operations: FnvHashSet<_> = program
+        .operations()
+        .map(|operation| operation.name.item)
+        .filter(|name| name.lookup().starts_with("Preserved"))
+        .collect();
+    let next_program = sort_selections_except(&program, &preserved_operations);
 
     assert_eq!(
         next_program.fragments().count(),
@@ -27,6 +34,11 @@ pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
     let mut printed = next_program
         .fragments()
         .map(|def| print_fragment(&TEST_SCHEMA, def))
+        .chain(
+            next_program
+                .operations()
+                .map(|def| print_operation(&TEST_SCHEMA, def)),
+        )
         .collect::<Vec<_>>();
     printed.sort();
     Ok(printed.join("\n\n"))
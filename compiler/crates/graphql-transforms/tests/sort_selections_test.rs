@@ -1,4 +1,4 @@
-// @generated SignedSource<<8b5811fb68ab2dec3e74463ff65df5c5>>
+// @generated SignedSource<<66574afba24a6228ad08c71d299b706b>>
 
 mod sort_selections;
 
@@ -11,3 +11,10 @@ fn sort_selections_transform() {
     let expected = include_str!("sort_selections/fixtures/sort-selections-transform.expected");
     test_fixture(transform_fixture, "sort-selections-transform.graphql", "sort_selections/fixtures/sort-selections-transform.expected", input, expected);
 }
+
+#[test]
+fn sort_shuffled_selections() {
+    let input = include_str!("sort_selections/fixtures/sort-shuffled-selections.graphql");
+    let expected = include_str!("sort_selections/fixtures/sort-shuffled-selections.expected");
+    test_fixture(transform_fixture, "sort-shuffled-selections.graphql", "sort_selections/fixtures/sort-shuffled-selections.expected", input, expected);
+}
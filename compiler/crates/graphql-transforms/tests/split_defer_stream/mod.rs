@@ -0,0 +1,51 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use common::FileKey;
+use fixture_tests::Fixture;
+use fnv::FnvHashSet;
+use graphql_ir::{build, Program};
+use graphql_syntax::parse;
+use graphql_text_printer::{print_fragment, print_operation};
+use graphql_transforms::{
+    skip_split_operation, split_defer_stream, transform_defer_stream,
+    unwrap_custom_directive_selection,
+};
+use test_schema::TEST_SCHEMA;
+
+pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
+    let file_key = FileKey::new(fixture.file_name);
+    let ast = parse(fixture.content, file_key).unwrap();
+    let ir = build(&TEST_SCHEMA, &ast.definitions).unwrap();
+    let program = Program::from_definitions(&TEST_SCHEMA, ir);
+    let program = transform_defer_stream(&program).unwrap();
+    let next_program = split_defer_stream(&program);
+
+    // The split operations aren't sent to the server
+    assert_eq!(
+        skip_split_operation(&next_program, &FnvHashSet::default())
+            .operations()
+            .count(),
+        program.operations().count()
+    );
+
+    let next_program = unwrap_custom_directive_selection(&next_program);
+    let mut printed = next_program
+        .operations()
+        .map(|def| print_operation(&TEST_SCHEMA, def))
+        .collect::<Vec<_>>();
+    printed.sort();
+
+    let mut printed_fragments = next_program
+        .fragments()
+        .map(|def| print_fragment(&TEST_SCHEMA, def))
+        .collect::<Vec<_>>();
+    printed_fragments.sort();
+    printed.extend(printed_fragments);
+
+    Ok(printed.join("\n\n"))
+}
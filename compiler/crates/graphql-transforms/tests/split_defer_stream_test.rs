@@ -0,0 +1,20 @@
+// @generated SignedSource<<ca44effe1cdeab0e05febebfdf224091>>
+
+mod split_defer_stream;
+
+use split_defer_stream::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn field_with_stream() {
+    let input = include_str!("split_defer_stream/fixtures/field-with-stream.graphql");
+    let expected = include_str!("split_defer_stream/fixtures/field-with-stream.expected");
+    test_fixture(transform_fixture, "field-with-stream.graphql", "split_defer_stream/fixtures/field-with-stream.expected", input, expected);
+}
+
+#[test]
+fn fragment_spread_with_defer() {
+    let input = include_str!("split_defer_stream/fixtures/fragment-spread-with-defer.graphql");
+    let expected = include_str!("split_defer_stream/fixtures/fragment-spread-with-defer.expected");
+    test_fixture(transform_fixture, "fragment-spread-with-defer.graphql", "split_defer_stream/fixtures/fragment-spread-with-defer.expected", input, expected);
+}
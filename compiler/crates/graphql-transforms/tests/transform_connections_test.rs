@@ -1,4 +1,4 @@
-// @generated SignedSource<<117e68db8fa12517cdbb39d01f55be6e>>
+// @generated SignedSource<<4bda0bfa13358b4357b17aadd8dff776>>
 
 mod transform_connections;
 
@@ -40,6 +40,13 @@ fn connection_generate_filters() {
     test_fixture(transform_fixture, "connection-generate-filters.graphql", "transform_connections/fixtures/connection-generate-filters.expected", input, expected);
 }
 
+#[test]
+fn connection_invalid_type_invalid() {
+    let input = include_str!("transform_connections/fixtures/connection-invalid-type.invalid.graphql");
+    let expected = include_str!("transform_connections/fixtures/connection-invalid-type.invalid.expected");
+    test_fixture(transform_fixture, "connection-invalid-type.invalid.graphql", "transform_connections/fixtures/connection-invalid-type.invalid.expected", input, expected);
+}
+
 #[test]
 fn connection_with_aliased_edges_page_info() {
     let input = include_str!("transform_connections/fixtures/connection-with-aliased-edges-page-info.graphql");
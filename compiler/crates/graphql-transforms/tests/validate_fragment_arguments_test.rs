@@ -0,0 +1,27 @@
+// @generated SignedSource<<0b953b60d56cf3bcc28c93fd77d5a080>>
+
+mod validate_fragment_arguments;
+
+use validate_fragment_arguments::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn missing_required_fragment_arguments_invalid() {
+    let input = include_str!("validate_fragment_arguments/fixtures/missing-required-fragment-arguments.invalid.graphql");
+    let expected = include_str!("validate_fragment_arguments/fixtures/missing-required-fragment-arguments.invalid.expected");
+    test_fixture(transform_fixture, "missing-required-fragment-arguments.invalid.graphql", "validate_fragment_arguments/fixtures/missing-required-fragment-arguments.invalid.expected", input, expected);
+}
+
+#[test]
+fn passes_required_fragment_arguments() {
+    let input = include_str!("validate_fragment_arguments/fixtures/passes-required-fragment-arguments.graphql");
+    let expected = include_str!("validate_fragment_arguments/fixtures/passes-required-fragment-arguments.expected");
+    test_fixture(transform_fixture, "passes-required-fragment-arguments.graphql", "validate_fragment_arguments/fixtures/passes-required-fragment-arguments.expected", input, expected);
+}
+
+#[test]
+fn unknown_fragment_argument_invalid() {
+    let input = include_str!("validate_fragment_arguments/fixtures/unknown-fragment-argument.invalid.graphql");
+    let expected = include_str!("validate_fragment_arguments/fixtures/unknown-fragment-argument.invalid.expected");
+    test_fixture(transform_fixture, "unknown-fragment-argument.invalid.graphql", "validate_fragment_arguments/fixtures/unknown-fragment-argument.invalid.expected", input, expected);
+}
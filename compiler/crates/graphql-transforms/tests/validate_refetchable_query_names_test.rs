@@ -0,0 +1,20 @@
+// @generated SignedSource<<ca2a9a5515211624cf8eec2102449c64>>
+
+mod validate_refetchable_query_names;
+
+use validate_refetchable_query_names::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn duplicate_query_names_invalid() {
+    let input = include_str!("validate_refetchable_query_names/fixtures/duplicate-query-names.invalid.graphql");
+    let expected = include_str!("validate_refetchable_query_names/fixtures/duplicate-query-names.invalid.expected");
+    test_fixture(transform_fixture, "duplicate-query-names.invalid.graphql", "validate_refetchable_query_names/fixtures/duplicate-query-names.invalid.expected", input, expected);
+}
+
+#[test]
+fn unique_query_names() {
+    let input = include_str!("validate_refetchable_query_names/fixtures/unique-query-names.graphql");
+    let expected = include_str!("validate_refetchable_query_names/fixtures/unique-query-names.expected");
+    test_fixture(transform_fixture, "unique-query-names.graphql", "validate_refetchable_query_names/fixtures/unique-query-names.expected", input, expected);
+}
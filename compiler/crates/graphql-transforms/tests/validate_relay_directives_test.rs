@@ -1,4 +1,4 @@
-// @generated SignedSource<<1b80bb82d987736fe0be979d030070cd>>
+// @generated SignedSource<<f607e0feea3ed7ba2e1263d3f5762eb7>>
 
 mod validate_relay_directives;
 
@@ -54,6 +54,20 @@ fn plural_fragment_variables_invalid() {
     test_fixture(transform_fixture, "plural-fragment-variables.invalid.graphql", "validate_relay_directives/fixtures/plural-fragment-variables.invalid.expected", input, expected);
 }
 
+#[test]
+fn relay_directive_arguments() {
+    let input = include_str!("validate_relay_directives/fixtures/relay-directive-arguments.graphql");
+    let expected = include_str!("validate_relay_directives/fixtures/relay-directive-arguments.expected");
+    test_fixture(transform_fixture, "relay-directive-arguments.graphql", "validate_relay_directives/fixtures/relay-directive-arguments.expected", input, expected);
+}
+
+#[test]
+fn relay_directive_arguments_variables_invalid() {
+    let input = include_str!("validate_relay_directives/fixtures/relay-directive-arguments-variables.invalid.graphql");
+    let expected = include_str!("validate_relay_directives/fixtures/relay-directive-arguments-variables.invalid.expected");
+    test_fixture(transform_fixture, "relay-directive-arguments-variables.invalid.graphql", "validate_relay_directives/fixtures/relay-directive-arguments-variables.invalid.expected", input, expected);
+}
+
 #[test]
 fn unmasked_spread() {
     let input = include_str!("validate_relay_directives/fixtures/unmasked-spread.graphql");
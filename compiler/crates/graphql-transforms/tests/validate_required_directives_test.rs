@@ -0,0 +1,27 @@
+// @generated SignedSource<<755122b7cd89788a0b8724512b9d278a>>
+
+mod validate_required_directives;
+
+use validate_required_directives::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn required_on_non_null_field_invalid() {
+    let input = include_str!("validate_required_directives/fixtures/required-on-non-null-field.invalid.graphql");
+    let expected = include_str!("validate_required_directives/fixtures/required-on-non-null-field.invalid.expected");
+    test_fixture(transform_fixture, "required-on-non-null-field.invalid.graphql", "validate_required_directives/fixtures/required-on-non-null-field.invalid.expected", input, expected);
+}
+
+#[test]
+fn required_on_nullable_field() {
+    let input = include_str!("validate_required_directives/fixtures/required-on-nullable-field.graphql");
+    let expected = include_str!("validate_required_directives/fixtures/required-on-nullable-field.expected");
+    test_fixture(transform_fixture, "required-on-nullable-field.graphql", "validate_required_directives/fixtures/required-on-nullable-field.expected", input, expected);
+}
+
+#[test]
+fn required_with_variable_action_invalid() {
+    let input = include_str!("validate_required_directives/fixtures/required-with-variable-action.invalid.graphql");
+    let expected = include_str!("validate_required_directives/fixtures/required-with-variable-action.invalid.expected");
+    test_fixture(transform_fixture, "required-with-variable-action.invalid.graphql", "validate_required_directives/fixtures/required-with-variable-action.invalid.expected", input, expected);
+}
@@ -0,0 +1,27 @@
+// @generated SignedSource<<5d5fa247283c01f1673d8dfe658cb85d>>
+
+mod validate_subscription_root_fields;
+
+use validate_subscription_root_fields::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn multiple_root_fields_invalid() {
+    let input = include_str!("validate_subscription_root_fields/fixtures/multiple-root-fields.invalid.graphql");
+    let expected = include_str!("validate_subscription_root_fields/fixtures/multiple-root-fields.invalid.expected");
+    test_fixture(transform_fixture, "multiple-root-fields.invalid.graphql", "validate_subscription_root_fields/fixtures/multiple-root-fields.invalid.expected", input, expected);
+}
+
+#[test]
+fn no_root_field_invalid() {
+    let input = include_str!("validate_subscription_root_fields/fixtures/no-root-field.invalid.graphql");
+    let expected = include_str!("validate_subscription_root_fields/fixtures/no-root-field.invalid.expected");
+    test_fixture(transform_fixture, "no-root-field.invalid.graphql", "validate_subscription_root_fields/fixtures/no-root-field.invalid.expected", input, expected);
+}
+
+#[test]
+fn single_root_field() {
+    let input = include_str!("validate_subscription_root_fields/fixtures/single-root-field.graphql");
+    let expected = include_str!("validate_subscription_root_fields/fixtures/single-root-field.expected");
+    test_fixture(transform_fixture, "single-root-field.graphql", "validate_subscription_root_fields/fixtures/single-root-field.expected", input, expected);
+}
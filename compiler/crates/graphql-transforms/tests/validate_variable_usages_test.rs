@@ -0,0 +1,27 @@
+// @generated SignedSource<<c1397b7ae783e471428391520a6447d9>>
+
+mod validate_variable_usages;
+
+use validate_variable_usages::transform_fixture;
+use fixture_tests::test_fixture;
+
+#[test]
+fn compatible_variable_usages() {
+    let input = include_str!("validate_variable_usages/fixtures/compatible-variable-usages.graphql");
+    let expected = include_str!("validate_variable_usages/fixtures/compatible-variable-usages.expected");
+    test_fixture(transform_fixture, "compatible-variable-usages.graphql", "validate_variable_usages/fixtures/compatible-variable-usages.expected", input, expected);
+}
+
+#[test]
+fn undefined_variable_invalid() {
+    let input = include_str!("validate_variable_usages/fixtures/undefined-variable.invalid.graphql");
+    let expected = include_str!("validate_variable_usages/fixtures/undefined-variable.invalid.expected");
+    test_fixture(transform_fixture, "undefined-variable.invalid.graphql", "validate_variable_usages/fixtures/undefined-variable.invalid.expected", input, expected);
+}
+
+#[test]
+fn variable_type_mismatch_invalid() {
+    let input = include_str!("validate_variable_usages/fixtures/variable-type-mismatch.invalid.graphql");
+    let expected = include_str!("validate_variable_usages/fixtures/variable-type-mismatch.invalid.expected");
+    test_fixture(transform_fixture, "variable-type-mismatch.invalid.graphql", "validate_variable_usages/fixtures/variable-type-mismatch.invalid.expected", input, expected);
+}
@@ -10,14 +10,17 @@ use fnv::FnvHashSet;
 use graphql_ir::{Program, ValidationResult};
 use graphql_transforms::{
     apply_fragment_arguments, client_extensions, disallow_id_as_alias, flatten, generate_id_field,
-    generate_live_query_metadata, generate_preloadable_metadata,
-    generate_subscription_name_metadata, generate_typename, handle_field_transform,
-    inline_data_fragment, inline_fragments, mask, relay_early_flush, remove_base_fragments,
-    skip_client_extensions, skip_redundant_nodes, skip_split_operation, skip_unreachable_node,
+    generate_live_query_metadata, generate_operation_id_metadata, generate_operation_ids,
+    generate_preloadable_metadata, generate_subscription_name_metadata, generate_typename,
+    handle_field_transform, hoist_inline_fragments, inline_data_fragment, inline_fragments,
+    inline_single_use_fragments, mask, relay_early_flush, remove_base_fragments,
+    skip_client_directives, skip_client_extensions, skip_empty_selections,
+    skip_internal_directives, skip_redundant_nodes, skip_split_operation, skip_unreachable_node,
     skip_unused_variables, split_module_import, transform_connections, transform_defer_stream,
     transform_match, transform_refetchable_fragment, unwrap_custom_directive_selection,
     validate_module_conflicts, validate_relay_directives, validate_server_only_directives,
-    validate_unused_variables, ConnectionInterface,
+    validate_unused_variables, ConnectionInterface, OperationIds, CLIENT_DIRECTIVE_NAMES,
+    INTERNAL_DIRECTIVE_NAMES,
 };
 use interner::StringKey;
 
@@ -27,6 +30,9 @@ pub struct Programs<'schema> {
     pub normalization: Program<'schema>,
     pub operation_text: Program<'schema>,
     pub typegen: Program<'schema>,
+    /// The stable IDs of the operations, computed from the `operation_text` program,
+    /// empty unless the project enables `generate_operation_ids`
+    pub operation_ids: OperationIds,
 }
 
 pub fn apply_transforms<'schema>(
@@ -34,6 +40,9 @@ pub fn apply_transforms<'schema>(
     program: Program<'schema>,
     base_fragment_names: &FnvHashSet<StringKey>,
     connection_interface: &ConnectionInterface,
+    should_inline_single_use_fragments: bool,
+    should_generate_operation_ids: bool,
+    hoist_inline_fragments_min_occurrences: Option<usize>,
     perf_logger: &impl PerfLogger,
 ) -> ValidationResult<Programs<'schema>> {
     // common
@@ -62,8 +71,23 @@ pub fn apply_transforms<'schema>(
     )?;
     let normalization_program =
         apply_normalization_transforms(project_name, &operation_program, perf_logger)?;
-    let operation_text_program =
-        apply_operation_text_transforms(project_name, &operation_program, perf_logger)?;
+    let operation_text_program = apply_operation_text_transforms(
+        project_name,
+        &operation_program,
+        should_inline_single_use_fragments,
+        hoist_inline_fragments_min_occurrences,
+        perf_logger,
+    )?;
+    // The IDs are computed from the printed operations, and attached to the
+    // normalization operations, from which the request parameters are generated
+    let (normalization_program, operation_ids) = if should_generate_operation_ids {
+        let operation_ids = generate_operation_ids(&operation_text_program);
+        let normalization_program =
+            generate_operation_id_metadata(&normalization_program, &operation_ids);
+        (normalization_program, operation_ids)
+    } else {
+        (normalization_program, OperationIds::default())
+    };
     let typegen_program =
         apply_typegen_transforms(project_name, &program, base_fragment_names, perf_logger)?;
 
@@ -73,6 +97,7 @@ pub fn apply_transforms<'schema>(
         normalization: normalization_program,
         operation_text: operation_text_program,
         typegen: typegen_program,
+        operation_ids,
     })
 }
 
@@ -239,6 +264,8 @@ fn apply_normalization_transforms<'schema>(
 fn apply_operation_text_transforms<'schema>(
     project_name: &str,
     program: &Program<'schema>,
+    should_inline_single_use_fragments: bool,
+    hoist_inline_fragments_min_occurrences: Option<usize>,
     perf_logger: &impl PerfLogger,
 ) -> ValidationResult<Program<'schema>> {
     // JS compiler
@@ -256,17 +283,39 @@ fn apply_operation_text_transforms<'schema>(
     log_event.string("project", project_name.to_string());
 
     let program = log_event.time("relay_early_flush", || relay_early_flush(&program))?;
-    let program = log_event.time("skip_split_operation", || skip_split_operation(&program));
+    let program = log_event.time("skip_split_operation", || {
+        skip_split_operation(&program, &Default::default())
+    });
     let program = log_event.time("skip_client_extensions", || {
         skip_client_extensions(&program)
     });
+    let program = log_event.time("skip_client_directives", || {
+        skip_client_directives(&program, &CLIENT_DIRECTIVE_NAMES)
+    });
     let program = log_event.time("skip_unreachable_node", || skip_unreachable_node(&program));
     let program = log_event.time("generate_typename", || generate_typename(&program, false));
+    let program = if should_inline_single_use_fragments {
+        log_event.time("inline_single_use_fragments", || {
+            inline_single_use_fragments(&program)
+        })
+    } else {
+        program
+    };
     let program = log_event.time("flatten", || flatten(&program, false));
+    let program = log_event.time("skip_empty_selections", || skip_empty_selections(&program));
+    let program = log_event.time("skip_internal_directives", || {
+        skip_internal_directives(&program, &INTERNAL_DIRECTIVE_NAMES)
+    });
     let program = log_event.time("skip_unused_variables", || skip_unused_variables(&program));
     let program = log_event.time("unwrap_custom_directive_selection", || {
         unwrap_custom_directive_selection(&program)
     });
+    let program = match hoist_inline_fragments_min_occurrences {
+        Some(min_occurrences) => log_event.time("hoist_inline_fragments", || {
+            hoist_inline_fragments(&program, min_occurrences)
+        }),
+        None => program,
+    };
     perf_logger.complete_event(log_event);
 
     Ok(program)
@@ -5,26 +5,322 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use crate::compiler_state::CompilerState;
-use crate::config::ProjectConfig;
-use schema::Schema;
+use crate::compiler_state::{CompilerState, ProjectName, SchemaSources};
+use crate::config::{Config, ProjectConfig};
+use crate::errors::{ConfigValidationError, Error, Result, SchemaValidationError};
+use interner::StringKey;
+use rayon::prelude::*;
+use schema::{Definition, Schema};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 pub fn build_schema(compiler_state: &CompilerState, project_config: &ProjectConfig) -> Schema {
-    let relay_extensions = String::from(schema::RELAY_EXTENSIONS);
-    let mut extensions = vec![&relay_extensions];
+    try_build_schema(compiler_state, project_config).unwrap()
+}
+
+/// Builds the schemas of the active projects and their base projects, see
+/// `Config::schema_projects`. The schemas are independent of each other, so they are
+/// built in parallel, at most `Config::max_concurrent_projects` at a time.
+/// Returns a config error naming the projects without any schema file.
+pub fn build_schemas(
+    config: &Config,
+    compiler_state: &CompilerState,
+) -> Result<HashMap<ProjectName, Schema>> {
+    validate_schema_sources(config, &compiler_state.schemas)?;
+    let schema_projects = config.schema_projects();
+    Ok(config.install_project_thread_pool(|| {
+        schema_projects
+            .into_par_iter()
+            .map(|project_config| {
+                (
+                    project_config.name,
+                    build_schema(compiler_state, project_config),
+                )
+            })
+            .collect()
+    }))
+}
+
+/// Like `build_schemas`, but returns the errors of the schemas that fail to build
+/// instead of panicking, so that the projects with a valid schema can still be checked.
+pub fn try_build_schemas(
+    config: &Config,
+    compiler_state: &CompilerState,
+) -> Result<HashMap<ProjectName, Result<Schema>>> {
+    validate_schema_sources(config, &compiler_state.schemas)?;
+    let schema_projects = config.schema_projects();
+    Ok(config.install_project_thread_pool(|| {
+        schema_projects
+            .into_par_iter()
+            .map(|project_config| {
+                (
+                    project_config.name,
+                    try_build_schema(compiler_state, project_config),
+                )
+            })
+            .collect()
+    }))
+}
+
+/// Checks that each of the schema projects has at least one schema file, e.g. a
+/// `schema_dir` may not contain any.
+fn validate_schema_sources(config: &Config, schema_sources: &SchemaSources) -> Result<()> {
+    let mut project_names: Vec<ProjectName> = config
+        .schema_projects()
+        .into_iter()
+        .map(|project_config| project_config.name)
+        .filter(|project_name| {
+            schema_sources
+                .get(project_name)
+                .map_or(true, |sources| sources.is_empty())
+        })
+        .collect();
+    if project_names.is_empty() {
+        return Ok(());
+    }
+    project_names.sort_by_key(|project_name| project_name.lookup());
+    Err(Error::ConfigFileValidation {
+        config_path: config.config_path.clone(),
+        validation_errors: project_names
+            .into_iter()
+            .map(|project_name| ConfigValidationError::ProjectSchemaMissing { project_name })
+            .collect(),
+    })
+}
+
+/// Like `build_schema`, but returns the errors of invalid schema sources instead of
+/// panicking, e.g. while the schema is being edited in watch mode.
+/// The schema files of the project are merged, the extensions of the project and its
+/// base project are applied after them. A type defined in more than one of these files
+/// is an error naming the conflicting files.
+pub fn try_build_schema(
+    compiler_state: &CompilerState,
+    project_config: &ProjectConfig,
+) -> Result<Schema> {
+    let mut extension_sources: Vec<(&PathBuf, &String)> = Vec::new();
     if let Some(project_extensions) = compiler_state.extensions.get(&project_config.name) {
-        extensions.extend(project_extensions);
+        extension_sources.extend(project_extensions.iter());
     }
     if let Some(base_project_name) = project_config.base {
         if let Some(base_project_extensions) = compiler_state.extensions.get(&base_project_name) {
-            extensions.extend(base_project_extensions);
+            extension_sources.extend(base_project_extensions.iter());
+        }
+    }
+    let schema_sources: Vec<(&PathBuf, &String)> = compiler_state.schemas[&project_config.name]
+        .iter()
+        .collect();
+
+    merge_schema_sources(&schema_sources, &extension_sources).map_err(|validation_errors| {
+        Error::SchemaValidation {
+            project_name: project_config.name,
+            validation_errors,
+        }
+    })
+}
+
+fn merge_schema_sources(
+    schema_sources: &[(&PathBuf, &String)],
+    extension_sources: &[(&PathBuf, &String)],
+) -> std::result::Result<Schema, Vec<SchemaValidationError>> {
+    let mut errors = Vec::new();
+    let mut type_definition_files: HashMap<StringKey, Vec<PathBuf>> = HashMap::new();
+    let mut parse_sources = |sources: &[(&PathBuf, &String)]| {
+        let mut definitions = Vec::new();
+        for (file, text) in sources {
+            match schema::parse_definitions(text) {
+                Ok(file_definitions) => {
+                    for type_name in file_definitions.iter().filter_map(defined_type_name) {
+                        type_definition_files
+                            .entry(type_name)
+                            .or_default()
+                            .push((*file).clone());
+                    }
+                    definitions.extend(file_definitions);
+                }
+                Err(error) => errors.push(SchemaValidationError::InvalidSchemaFile {
+                    file: (*file).clone(),
+                    error,
+                }),
+            }
         }
+        definitions
+    };
+
+    let mut server_definitions =
+        schema::parse_definitions(schema::BUILTINS).expect("Expected the builtins to parse");
+    server_definitions.extend(parse_sources(schema_sources));
+    let mut extension_definitions = schema::parse_definitions(schema::RELAY_EXTENSIONS)
+        .expect("Expected the Relay extensions to parse");
+    extension_definitions.extend(parse_sources(extension_sources));
+
+    let mut conflicts: Vec<(StringKey, Vec<PathBuf>)> = type_definition_files
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .collect();
+    conflicts.sort_by_key(|(type_name, _)| type_name.lookup());
+    errors.extend(conflicts.into_iter().map(|(type_name, files)| {
+        SchemaValidationError::ConflictingTypeDefinitions { type_name, files }
+    }));
+
+    if !errors.is_empty() {
+        return Err(errors);
     }
-    let mut schema_sources = Vec::new();
-    schema_sources.extend(
-        compiler_state.schemas[&project_config.name]
+    Schema::build(&server_definitions, &extension_definitions)
+        .map_err(|error| vec![SchemaValidationError::InvalidSchema { error }])
+}
+
+/// The name of the type defined by a definition, type extensions don't define a type.
+fn defined_type_name(definition: &Definition) -> Option<StringKey> {
+    match definition {
+        Definition::ObjectTypeDefinition { name, .. }
+        | Definition::InterfaceTypeDefinition { name, .. }
+        | Definition::UnionTypeDefinition { name, .. }
+        | Definition::InputObjectTypeDefinition { name, .. }
+        | Definition::EnumTypeDefinition { name, .. }
+        | Definition::ScalarTypeDefinition { name, .. } => Some(*name),
+        Definition::SchemaDefinition { .. }
+        | Definition::DirectiveDefinition { .. }
+        | Definition::ObjectTypeExtension { .. }
+        | Definition::InterfaceTypeExtension { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use interner::Intern;
+
+    fn merge(
+        schema_sources: &[(&str, &str)],
+        extension_sources: &[(&str, &str)],
+    ) -> std::result::Result<Schema, Vec<SchemaValidationError>> {
+        let schema_sources = to_sources(schema_sources);
+        let extension_sources = to_sources(extension_sources);
+        merge_schema_sources(&to_refs(&schema_sources), &to_refs(&extension_sources))
+    }
+
+    fn to_sources(sources: &[(&str, &str)]) -> Vec<(PathBuf, String)> {
+        sources
             .iter()
-            .map(String::as_str),
-    );
-    schema::build_schema_with_extensions(&schema_sources, &extensions).unwrap()
+            .map(|(file, text)| (PathBuf::from(file), text.to_string()))
+            .collect()
+    }
+
+    fn to_refs(sources: &[(PathBuf, String)]) -> Vec<(&PathBuf, &String)> {
+        sources.iter().map(|(file, text)| (file, text)).collect()
+    }
+
+    #[test]
+    fn test_merge_schema_files_and_extensions() {
+        let schema = merge(
+            &[
+                ("graphql/query.graphql", "type Query { me: User }"),
+                ("graphql/user.graphql", "type User { id: ID name: String }"),
+            ],
+            &[(
+                "graphql/extensions/user.graphql",
+                "extend type User { isSelected: Boolean } type Local { id: ID }",
+            )],
+        )
+        .unwrap();
+
+        let user = schema.get_type("User".intern()).unwrap();
+        assert!(schema.named_field(user, "name".intern()).is_some());
+        let is_selected = schema.named_field(user, "isSelected".intern()).unwrap();
+        assert!(schema.field(is_selected).is_extension);
+        assert!(schema.is_extension_type(schema.get_type("Local".intern()).unwrap()));
+    }
+
+    #[test]
+    fn test_merge_conflicting_type_definitions() {
+        let errors = merge(
+            &[
+                ("graphql/query.graphql", "type Query { me: User }"),
+                ("graphql/user.graphql", "type User { id: ID }"),
+            ],
+            &[(
+                "graphql/extensions/user.graphql",
+                "type User { name: String }",
+            )],
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| format!("{}", error))
+                .collect::<Vec<_>>(),
+            vec![
+                "The type `User` is defined in multiple schema files: `graphql/user.graphql`, `graphql/extensions/user.graphql`."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_invalid_extension_file() {
+        let errors = merge(
+            &[
+                ("graphql/query.graphql", "type Query { me: User }"),
+                ("graphql/user.graphql", "type User { id: ID }"),
+            ],
+            &[(
+                "graphql/extensions/user.graphql",
+                "extend type User { isSelected: }",
+            )],
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SchemaValidationError::InvalidSchemaFile { file, .. } => {
+                assert_eq!(file, &PathBuf::from("graphql/extensions/user.graphql"))
+            }
+            error => panic!("Expected an invalid schema file error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_validate_schema_sources_of_project_without_schema() {
+        let config = Config::from_string_for_test(
+            r#"
+                {
+                    "sources": {
+                        "src/public": "public",
+                        "src/internal": "internal"
+                    },
+                    "projects": {
+                        "public": {
+                            "schema": "graphql/public.graphql"
+                        },
+                        "internal": {
+                            "schemaDir": "graphql/internal"
+                        }
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+        let mut public_sources = IndexMap::new();
+        public_sources.insert(
+            PathBuf::from("graphql/public.graphql"),
+            "type Query { id: ID }".to_string(),
+        );
+        let mut schema_sources = SchemaSources::default();
+        schema_sources.insert("public".intern(), public_sources);
+        schema_sources.insert("internal".intern(), IndexMap::new());
+
+        let error = validate_schema_sources(&config, &schema_sources)
+            .err()
+            .unwrap();
+        assert_eq!(
+            format!("{}", error),
+            "Config `/virtual/root/virtual_config.json` is invalid:\n - Project `internal` has no schema, no schema files were found at its `schema` or `schema_dir`."
+        );
+
+        schema_sources.remove(&"internal".intern());
+        assert!(validate_schema_sources(&config, &schema_sources).is_err());
+    }
 }
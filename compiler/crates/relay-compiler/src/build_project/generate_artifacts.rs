@@ -7,7 +7,7 @@
 
 use super::apply_transforms::Programs;
 pub use super::artifact_content::ArtifactContent;
-use crate::config::ProjectConfig;
+use crate::config::{ProjectConfig, GENERATED_DIRECTORY_NAME};
 use crate::errors::BuildProjectError;
 use common::{FileKey, NamedItem};
 use graphql_ir::{FragmentDefinition, OperationDefinition};
@@ -206,13 +206,15 @@ fn path_for_js_artifact(
             } else {
                 output.join(source_file.get_dir())
             }
-            .join(format!("{}.graphql.js", definition_name))
+            .join(project_config.artifact_file_name(definition_name))
         } else {
-            output.join(format!("{}.graphql.js", definition_name))
+            output.join(project_config.artifact_file_name(definition_name))
         }
     } else {
-        let path = source_file.get_dir();
-        path.join(format!("__generated__/{}.graphql.js", definition_name))
+        source_file
+            .get_dir()
+            .join(GENERATED_DIRECTORY_NAME)
+            .join(project_config.artifact_file_name(definition_name))
     }
 }
 
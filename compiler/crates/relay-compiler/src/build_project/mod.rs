@@ -25,13 +25,15 @@ use crate::parse_sources::GraphQLAsts;
 pub use apply_transforms::apply_transforms;
 pub use apply_transforms::Programs;
 use build_ir::BuildIRResult;
-pub use build_schema::build_schema;
+pub use build_schema::{build_schema, build_schemas, try_build_schema, try_build_schemas};
 use common::{PerfLogEvent, PerfLogger};
 pub use generate_artifacts::{generate_artifacts, Artifact, ArtifactContent};
 use generate_extra_artifacts::generate_extra_artifacts;
 use graphql_ir::{Program, Sources, ValidationError};
-use graphql_transforms::FB_CONNECTION_INTERFACE;
-use log::info;
+use graphql_transforms::{
+    collect_deprecated_fields, DeprecatedFieldUsage, FB_CONNECTION_INTERFACE,
+};
+use log::{info, warn};
 use persist_operations::persist_operations;
 use schema::Schema;
 pub use validate::validate;
@@ -74,6 +76,13 @@ fn build_programs<'a>(
         )
     })?;
 
+    // The selections of deprecated fields are reported without failing the build
+    log_event.time("deprecated_fields_time", || {
+        for usage in collect_deprecated_fields(&program) {
+            warn!("{}", print_deprecated_field_usage(&schema, &usage, sources));
+        }
+    });
+
     // Apply various chains of transforms to create a set of output programs.
     let programs = log_event.time("apply_transforms_time", || {
         add_error_sources(
@@ -82,6 +91,9 @@ fn build_programs<'a>(
                 program,
                 &base_fragment_names,
                 &*FB_CONNECTION_INTERFACE,
+                project_config.inline_single_use_fragments,
+                project_config.generate_operation_ids,
+                project_config.hoist_inline_fragments,
                 perf_logger,
             ),
             sources,
@@ -91,7 +103,7 @@ fn build_programs<'a>(
     Ok(programs)
 }
 
-pub async fn check_project<'schema>(
+pub fn check_project<'schema>(
     project_config: &ProjectConfig,
     compiler_state: &CompilerState,
     graphql_asts: &GraphQLAsts<'_>,
@@ -201,6 +213,23 @@ pub async fn build_project(
     Ok(())
 }
 
+fn print_deprecated_field_usage(
+    schema: &Schema,
+    usage: &DeprecatedFieldUsage,
+    sources: &Sources<'_>,
+) -> String {
+    match sources.get(&usage.location.file()) {
+        Some(source) => format!(
+            "{}:\n{}",
+            usage.message(schema),
+            usage
+                .location
+                .print(&source.text, source.line_index + 1, source.column_index + 1)
+        ),
+        None => usage.message(schema),
+    }
+}
+
 fn add_error_sources<T>(
     result: Result<T, Vec<ValidationError>>,
     sources: &Sources<'_>,
@@ -8,8 +8,10 @@
 use errors::try_all;
 use graphql_ir::{Program, ValidationResult};
 use graphql_transforms::{
-    disallow_id_as_alias, validate_connections, validate_relay_directives,
-    validate_server_only_directives, ConnectionInterface,
+    disallow_id_as_alias, validate_connections, validate_fragment_arguments,
+    validate_refetchable_query_names, validate_relay_directives, validate_required_directives,
+    validate_server_only_directives, validate_subscription_root_fields, validate_variable_usages,
+    ConnectionInterface,
 };
 
 pub fn validate(
@@ -20,7 +22,12 @@ pub fn validate(
         disallow_id_as_alias(program),
         validate_server_only_directives(program),
         validate_connections(program, connection_interface),
+        validate_fragment_arguments(program),
+        validate_refetchable_query_names(program),
         validate_relay_directives(program),
+        validate_required_directives(program),
+        validate_subscription_root_fields(program),
+        validate_variable_usages(program),
     ])?;
 
     Ok(())
@@ -5,14 +5,17 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use crate::build_project::{build_project, build_schema, check_project};
+use crate::build_project::{build_project, build_schemas, check_project};
 use crate::compiler_state::{CompilerState, ProjectName};
-use crate::config::Config;
+use crate::config::{Config, ProjectConfig};
 use crate::errors::{Error, Result};
 use crate::parse_sources::parse_sources;
+use crate::relay_ignore::relay_ignore_changed;
+use crate::watchman::{FileSourceResult, FileSourceSubscription};
 use crate::{artifact_map::ArtifactMap, watchman::FileSource};
 use common::{PerfLogEvent, PerfLogger};
 use log::{error, info};
+use rayon::prelude::*;
 use schema::Schema;
 use std::collections::HashMap;
 
@@ -49,13 +52,9 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
         &self,
         compiler_state: &CompilerState,
         setup_event: &impl PerfLogEvent,
-    ) -> HashMap<ProjectName, Schema> {
+    ) -> Result<HashMap<ProjectName, Schema>> {
         let timer = setup_event.start("build_schemas");
-        let mut schemas = HashMap::new();
-        self.config.for_each_project(|project_config| {
-            let schema = build_schema(compiler_state, project_config);
-            schemas.insert(project_config.name, schema);
-        });
+        let schemas = build_schemas(&self.config, compiler_state);
         setup_event.stop(timer);
         schemas
     }
@@ -70,7 +69,7 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
         let (mut compiler_state, mut subscription) = file_source
             .subscribe(&setup_event, self.perf_logger)
             .await?;
-        let schemas = self.build_schemas(&compiler_state, &setup_event);
+        let schemas = self.build_schemas(&compiler_state, &setup_event)?;
         callback(
             self.check_projects(&mut compiler_state, &schemas, &setup_event)
                 .await,
@@ -79,7 +78,7 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
         self.perf_logger.complete_event(setup_event);
 
         loop {
-            if let Some(file_source_changes) = subscription.next_change().await? {
+            if let Some(file_source_changes) = subscription.next_change(self.perf_logger).await? {
                 let incremental_check_event =
                     self.perf_logger.create_event("incremental_check_event");
                 let incremental_check_time =
@@ -87,12 +86,14 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
 
                 // TODO Single change to file in VSCode sometimes produces
                 // 2 watchman change events for the same file
-                let had_new_changes = compiler_state.add_pending_file_source_changes(
-                    &self.config,
-                    &file_source_changes,
-                    &incremental_check_event,
-                    self.perf_logger,
-                )?;
+                let had_new_changes = self
+                    .apply_file_source_changes(
+                        &mut compiler_state,
+                        &subscription,
+                        &file_source_changes,
+                        &incremental_check_event,
+                    )
+                    .await?;
                 if had_new_changes {
                     // Clear out existing errors
                     callback(Ok(()));
@@ -133,7 +134,7 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
         self.perf_logger.complete_event(setup_event);
 
         loop {
-            if let Some(file_source_changes) = subscription.next_change().await? {
+            if let Some(file_source_changes) = subscription.next_change(self.perf_logger).await? {
                 let incremental_build_event =
                     self.perf_logger.create_event("incremental_build_event");
                 let incremental_build_time =
@@ -143,12 +144,14 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
                 // 2 watchman change events for the same file
 
                 info!("\n\n[watch-mode] Change detected");
-                let had_new_changes = compiler_state.add_pending_file_source_changes(
-                    &self.config,
-                    &file_source_changes,
-                    &incremental_build_event,
-                    self.perf_logger,
-                )?;
+                let had_new_changes = self
+                    .apply_file_source_changes(
+                        &mut compiler_state,
+                        &subscription,
+                        &file_source_changes,
+                        &incremental_build_event,
+                    )
+                    .await?;
 
                 if had_new_changes {
                     if let Err(errors) = self
@@ -170,6 +173,48 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
         }
     }
 
+    /// Merges the file source changes into the compiler state. If the `.relayignore`
+    /// file changed, the state is rebuilt from all files instead, since previously
+    /// ignored files have to be read. The state is also rebuilt from the changes
+    /// of a fresh instance, e.g. after reconnecting to Watchman, so that the files deleted
+    /// in between are removed. Returns a boolean indicating if there are new changes to
+    /// check.
+    async fn apply_file_source_changes(
+        &self,
+        compiler_state: &mut CompilerState,
+        subscription: &FileSourceSubscription<'_>,
+        file_source_changes: &FileSourceResult,
+        event: &impl PerfLogEvent,
+    ) -> Result<bool> {
+        if file_source_changes.is_fresh_instance {
+            info!("[watch-mode] Reconnected to the file source, rebuilding the compiler state");
+            *compiler_state = CompilerState::from_file_source_changes(
+                &self.config,
+                file_source_changes,
+                event,
+                self.perf_logger,
+            )?;
+            Ok(true)
+        } else if relay_ignore_changed(file_source_changes) {
+            info!("[watch-mode] `.relayignore` changed, rebuilding the compiler state");
+            let all_files = subscription.query_all_files(event).await?;
+            *compiler_state = CompilerState::from_file_source_changes(
+                &self.config,
+                &all_files,
+                event,
+                self.perf_logger,
+            )?;
+            Ok(true)
+        } else {
+            compiler_state.add_pending_file_source_changes(
+                &self.config,
+                file_source_changes,
+                event,
+                self.perf_logger,
+            )
+        }
+    }
+
     async fn check_projects(
         &self,
         compiler_state: &mut CompilerState,
@@ -179,55 +224,52 @@ impl<'perf, T: PerfLogger> Compiler<'perf, T> {
         let graphql_asts =
             setup_event.time("parse_sources_time", || parse_sources(&compiler_state))?;
 
-        let mut build_project_errors = vec![];
-
-        match self.config.only_project {
+        let project_configs: Vec<&ProjectConfig> = match self.config.only_project {
             Some(project_key) => {
                 let project_config =
                     self.config.projects.get(&project_key).unwrap_or_else(|| {
                         panic!("Expected the project {} to exist", &project_key)
                     });
-                let schema = schemas.get(&project_config.name).unwrap();
-                check_project(
-                    project_config,
-                    compiler_state,
-                    &graphql_asts,
-                    schema,
-                    self.perf_logger,
-                )
-                .await
-                .map_err(|err| {
-                    build_project_errors.push(err);
-                })
-                .ok();
+                vec![project_config]
             }
-            None => {
-                for project_config in self.config.projects.values() {
-                    if compiler_state.project_has_pending_changes(project_config.name) {
-                        let schema = schemas.get(&project_config.name).unwrap();
-                        // TODO: consider running all projects in parallel
-                        check_project(
-                            project_config,
-                            compiler_state,
-                            &graphql_asts,
-                            schema,
-                            self.perf_logger,
-                        )
-                        .await
-                        .map_err(|err| {
-                            build_project_errors.push(err);
-                        })
-                        .ok();
-                    }
-                }
-            }
-        }
+            None => self
+                .config
+                .projects
+                .values()
+                .filter(|project_config| {
+                    compiler_state.project_has_pending_changes(project_config.name)
+                })
+                .collect(),
+        };
 
-        if build_project_errors.is_empty() {
+        // Checking a project only reads the compiler state, the parsed sources and
+        // its own schema, so the projects are checked in parallel, at most
+        // `Config::max_concurrent_projects` at a time.
+        let compiler_state = &*compiler_state;
+        let graphql_asts = &graphql_asts;
+        let perf_logger = self.perf_logger;
+        let check_project_errors: Vec<_> = self.config.install_project_thread_pool(|| {
+            project_configs
+                .into_par_iter()
+                .filter_map(|project_config| {
+                    let schema = schemas.get(&project_config.name).unwrap();
+                    check_project(
+                        project_config,
+                        compiler_state,
+                        graphql_asts,
+                        schema,
+                        perf_logger,
+                    )
+                    .err()
+                })
+                .collect()
+        });
+
+        if check_project_errors.is_empty() {
             Ok(())
         } else {
             Err(Error::BuildProjectsErrors {
-                errors: build_project_errors,
+                errors: check_project_errors,
             })
         }
     }
@@ -8,19 +8,20 @@
 use crate::artifact_map::ArtifactMap;
 use crate::config::Config;
 use crate::errors::{Error, Result};
+use crate::relay_ignore::RelayIgnore;
 use crate::watchman::{
-    categorize_files, extract_graphql_strings_from_file, read_to_string, Clock, FileGroup,
-    FileSourceResult,
+    categorize_files, extract_graphql_strings_from_file, read_to_string, Clock, File as SourceFile,
+    FileGroup, FileSourceResult,
 };
 use common::{PerfLogEvent, PerfLogger};
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use graphql_syntax::GraphQLSource;
 use indexmap::IndexMap;
 use interner::StringKey;
 use io::BufReader;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{fs::File, io};
 
 /// Name of a compiler project.
@@ -84,7 +85,7 @@ impl GraphQLSources {
         !self.grouped_processed_sources.is_empty()
     }
 
-    fn set_pending_source_set(
+    pub(crate) fn set_pending_source_set(
         &mut self,
         source_set_name: SourceSetName,
         source_set: GraphQLSourceSet,
@@ -120,7 +121,8 @@ impl GraphQLSources {
     }
 }
 
-pub type SchemaSources = FnvHashMap<ProjectName, Vec<String>>;
+/// The schema or extension sources of each project, keyed by the file they were read from.
+pub type SchemaSources = FnvHashMap<ProjectName, IndexMap<PathBuf, String>>;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CompilerState {
@@ -128,7 +130,12 @@ pub struct CompilerState {
     pub schemas: SchemaSources,
     pub extensions: SchemaSources,
     pub artifacts: FnvHashMap<ProjectName, ArtifactMap>,
-    pub clock: Clock,
+    /// The Watchman clock of the last changes, `None` if the changes didn't come from Watchman
+    pub clock: Option<Clock>,
+    /// The paths ignored by the `.relayignore` file, read once when the state is
+    /// built from all files and not part of saved states.
+    #[serde(skip)]
+    pub relay_ignore: RelayIgnore,
 }
 
 fn merge_schema_sources(
@@ -137,21 +144,78 @@ fn merge_schema_sources(
 ) -> SchemaSources {
     let mut next_schemas: SchemaSources = current_schemas;
     for (project_name, schema_sources) in new_schemas {
-        next_schemas.insert(project_name, schema_sources.to_owned());
+        next_schemas
+            .entry(project_name)
+            .or_insert_with(Default::default)
+            .extend(schema_sources);
     }
     next_schemas
 }
 
+/// Reads the schema or extension files that still exist, deleted files are
+/// removed from the state by `remove_deleted_schema_sources`.
+fn read_schema_sources(
+    resolved_root: &Path,
+    files: &[SourceFile],
+) -> Result<IndexMap<PathBuf, String>> {
+    files
+        .iter()
+        .filter(|file| file.exists)
+        .map(|file| Ok((file.name.clone(), read_to_string(resolved_root, file)?)))
+        .collect()
+}
+
+/// Returns the names of the projects whose schema or extension files are
+/// part of the file source changes.
+pub fn get_projects_with_schema_changes(
+    config: &Config,
+    file_source_changes: &FileSourceResult,
+) -> FnvHashSet<ProjectName> {
+    categorize_files(config, &file_source_changes.files)
+        .into_iter()
+        .filter_map(|(category, _)| match category {
+            FileGroup::Schema { project_name } | FileGroup::Extension { project_name } => {
+                Some(project_name)
+            }
+            FileGroup::Source { .. } | FileGroup::Generated => None,
+        })
+        .collect()
+}
+
 impl CompilerState {
+    /// Builds the state from the file source changes, reading the `.relayignore` file
+    /// of their root to skip the ignored files.
     pub fn from_file_source_changes(
         config: &Config,
         file_source_changes: &FileSourceResult,
         setup_event: &impl PerfLogEvent,
         perf_logger: &impl PerfLogger,
     ) -> Result<Self> {
-        let categorized = setup_event.time("categorize_files_time", || {
-            categorize_files(config, &file_source_changes.files)
-        });
+        let relay_ignore = RelayIgnore::load(&file_source_changes.resolved_root);
+        Self::from_file_source_changes_with_ignore(
+            config,
+            file_source_changes,
+            relay_ignore,
+            setup_event,
+            perf_logger,
+        )
+    }
+
+    fn from_file_source_changes_with_ignore(
+        config: &Config,
+        file_source_changes: &FileSourceResult,
+        relay_ignore: RelayIgnore,
+        setup_event: &impl PerfLogEvent,
+        perf_logger: &impl PerfLogger,
+    ) -> Result<Self> {
+        let files: Vec<SourceFile> = file_source_changes
+            .files
+            .iter()
+            .filter(|file| !relay_ignore.is_ignored(&file.name))
+            .cloned()
+            .collect();
+        let categorized =
+            setup_event.time("categorize_files_time", || categorize_files(config, &files));
 
         let artifacts = FnvHashMap::default();
         let mut schemas = FnvHashMap::default();
@@ -167,10 +231,10 @@ impl CompilerState {
                     let sources = files
                         .par_iter()
                         .filter_map(|file| {
-                            let exists = *file.exists;
+                            let exists = file.exists;
                             if !exists {
                                 return Some(Ok((
-                                    (*file.name).to_owned(),
+                                    file.name.clone(),
                                     FileState {
                                         graphql_sources: Vec::new(),
                                         exists,
@@ -185,10 +249,10 @@ impl CompilerState {
                                 // NOTE: Some of the JS files might not contain any graphql, so we
                                 // ignore them here by returning None.
                                 // Note that we explicitly track deleted files during watch mode
-                                // by checking the `exists` flag from watchman
+                                // by checking the `exists` flag from the file source
                                 Ok(graphql_strings) if graphql_strings.is_empty() => None,
                                 Ok(graphql_strings) => Some(Ok((
-                                    (*file.name).to_owned(),
+                                    file.name.clone(),
                                     FileState {
                                         graphql_sources: graphql_strings,
                                         exists,
@@ -202,17 +266,13 @@ impl CompilerState {
                     graphql_sources.set_pending_source_set(source_set_name, sources);
                 }
                 FileGroup::Schema { project_name } => {
-                    let schema_sources = files
-                        .iter()
-                        .map(|file| read_to_string(&file_source_changes.resolved_root, file))
-                        .collect::<Result<Vec<String>>>()?;
+                    let schema_sources =
+                        read_schema_sources(&file_source_changes.resolved_root, &files)?;
                     schemas.insert(project_name, schema_sources);
                 }
                 FileGroup::Extension { project_name } => {
-                    let extension_sources: Vec<String> = files
-                        .iter()
-                        .map(|file| read_to_string(&file_source_changes.resolved_root, file))
-                        .collect::<Result<Vec<String>>>()?;
+                    let extension_sources =
+                        read_schema_sources(&file_source_changes.resolved_root, &files)?;
                     extensions.insert(project_name, extension_sources);
                 }
                 FileGroup::Generated => {
@@ -227,6 +287,7 @@ impl CompilerState {
             extensions,
             schemas,
             clock: file_source_changes.clock.clone(),
+            relay_ignore,
         })
     }
 
@@ -252,25 +313,30 @@ impl CompilerState {
         setup_event: &impl PerfLogEvent,
         perf_logger: &impl PerfLogger,
     ) -> Result<bool> {
-        let pending_compiler_state = CompilerState::from_file_source_changes(
+        let pending_compiler_state = CompilerState::from_file_source_changes_with_ignore(
             config,
             file_source_changes,
+            self.relay_ignore.clone(),
             setup_event,
             perf_logger,
         )?;
 
         if !pending_compiler_state.schemas.is_empty() {
-            // TODO support watching schema changes
             self.schemas =
                 merge_schema_sources(self.schemas.to_owned(), pending_compiler_state.schemas);
         }
         if !pending_compiler_state.extensions.is_empty() {
-            // TODO support watching extension changes
             self.extensions = merge_schema_sources(
                 self.extensions.to_owned(),
                 pending_compiler_state.extensions,
             );
         }
+        self.remove_deleted_schema_sources(config, file_source_changes);
+        // The state includes the changes until this clock, so a saved state only needs
+        // to query the changes after it
+        if file_source_changes.clock.is_some() {
+            self.clock = file_source_changes.clock.clone();
+        }
 
         let pending_graphql_sources = pending_compiler_state.graphql_sources;
         if !pending_graphql_sources.has_pending_sources() {
@@ -286,6 +352,26 @@ impl CompilerState {
         Ok(true)
     }
 
+    /// Removes the schema and extension files deleted by the file source changes.
+    fn remove_deleted_schema_sources(
+        &mut self,
+        config: &Config,
+        file_source_changes: &FileSourceResult,
+    ) {
+        for (category, files) in categorize_files(config, &file_source_changes.files) {
+            let (sources, project_name) = match category {
+                FileGroup::Schema { project_name } => (&mut self.schemas, project_name),
+                FileGroup::Extension { project_name } => (&mut self.extensions, project_name),
+                FileGroup::Source { .. } | FileGroup::Generated => continue,
+            };
+            if let Some(project_sources) = sources.get_mut(&project_name) {
+                for file in files.iter().filter(|file| !file.exists) {
+                    project_sources.remove(&file.name);
+                }
+            }
+        }
+    }
+
     pub fn commit_pending_file_source_changes(&mut self) {
         self.graphql_sources.commit_pending_sources();
     }
@@ -8,12 +8,21 @@
 use crate::build_project::generate_extra_artifacts::GenerateExtraArtifactsFn;
 use crate::compiler_state::{ProjectName, SourceSetName};
 use crate::errors::{ConfigValidationError, Error, Result};
+use glob::Pattern;
 use interner::StringKey;
+use rayon::{Scope, ThreadPool, ThreadPoolBuilder};
 use regex::Regex;
 use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+
+/// The directory next to each source file the artifacts are written to if their
+/// project has no `output` directory
+pub const GENERATED_DIRECTORY_NAME: &str = "__generated__";
+
+/// The extension of the artifacts if their project has no `artifactExtension`
+pub const DEFAULT_ARTIFACT_EXTENSION: &str = "js";
 
 /// The full compiler config. This is a combination of:
 /// - the configuration file
@@ -24,6 +33,8 @@ pub struct Config {
     /// Root directory of all projects to compile. Any other paths in the
     /// compiler should be relative to this root unless otherwise noted.
     pub root_dir: PathBuf,
+    /// Path of the config file, referenced by the errors of the config.
+    pub config_path: PathBuf,
     pub sources: HashMap<PathBuf, SourceSetName>,
     pub blacklist: Vec<String>,
     pub projects: HashMap<ProjectName, ProjectConfig>,
@@ -37,6 +48,16 @@ pub struct Config {
     pub load_saved_state_file: Option<PathBuf>,
     /// Function to genetate extra
     pub generate_extra_operation_artifacts: Option<GenerateExtraArtifactsFn>,
+    /// Maximum number of projects that are checked, and whose schemas are built,
+    /// concurrently. Defaults to the number of logical CPUs, setting it to 1 checks
+    /// the projects one after another, e.g. for reproducible timings.
+    pub max_concurrent_projects: usize,
+    /// The threads the projects are checked with, built once with
+    /// `max_concurrent_projects` threads when the config is loaded.
+    project_thread_pool: ThreadPool,
+    /// The types that the values of custom scalars, e.g. `DateTime`, have in the
+    /// client, e.g. `string`.
+    pub custom_scalars: HashMap<StringKey, String>,
 }
 
 impl Config {
@@ -45,20 +66,74 @@ impl Config {
     where
         F: FnMut(&ProjectConfig) -> (),
     {
+        for project in self.active_projects() {
+            func(project)
+        }
+    }
+
+    /// The active projects in this Config, only the `only_project` if it's set
+    pub fn active_projects(&self) -> Vec<&ProjectConfig> {
         match self.only_project {
             Some(project_key) => {
                 let project_config = self
                     .projects
                     .get(&project_key)
                     .unwrap_or_else(|| panic!("Expected the project {} to exist", &project_key));
-                func(project_config)
+                vec![project_config]
             }
-            None => {
-                for project in self.projects.values() {
-                    func(project)
+            None => self.projects.values().collect(),
+        }
+    }
+
+    /// Runs `f` in the thread pool with `max_concurrent_projects` threads, bounding the
+    /// parallel iterators of `f` that check the projects or build their schemas.
+    pub fn install_project_thread_pool<T: Send>(&self, f: impl FnOnce() -> T + Send) -> T {
+        self.project_thread_pool.install(f)
+    }
+
+    /// Like `install_project_thread_pool`, but `f` runs on the current thread and only
+    /// the tasks it spawns in the scope run in the thread pool, so that `f` can handle
+    /// their results as they arrive.
+    pub fn project_thread_pool_scope<'scope, T>(&self, f: impl FnOnce(&Scope<'scope>) -> T) -> T {
+        self.project_thread_pool.in_place_scope(f)
+    }
+
+    /// Checks if a path, relative to the root, is in the artifact directory of a
+    /// project or in a `__generated__` directory. Changes to these files are made by
+    /// the compiler, so the file sources ignore them to avoid feedback loops.
+    pub fn is_artifact_path(&self, path: &Path) -> bool {
+        self.projects.values().any(|project_config| {
+            project_config
+                .artifact_directory()
+                .map_or(false, |artifact_directory| {
+                    path.starts_with(artifact_directory)
+                })
+        }) || path.components().any(|component| match component {
+            Component::Normal(component) => component == GENERATED_DIRECTORY_NAME,
+            _ => false,
+        })
+    }
+
+    /// The projects whose schema is used while the active projects are compiled: the
+    /// active projects and, transitively, their base projects.
+    pub fn schema_projects(&self) -> Vec<&ProjectConfig> {
+        let mut schema_projects: Vec<&ProjectConfig> = Vec::new();
+        for project_config in self.active_projects() {
+            let mut next_project = Some(project_config);
+            while let Some(project_config) = next_project {
+                if schema_projects
+                    .iter()
+                    .any(|schema_project| schema_project.name == project_config.name)
+                {
+                    break;
                 }
+                schema_projects.push(project_config);
+                next_project = project_config
+                    .base
+                    .and_then(|base_project_name| self.projects.get(&base_project_name));
             }
         }
+        schema_projects
     }
 
     pub fn load(root_dir: PathBuf, config_path: PathBuf) -> Result<Self> {
@@ -99,7 +174,9 @@ impl Config {
             .map(|(project_name, config_file_project)| {
                 let schema_location =
                     match (config_file_project.schema, config_file_project.schema_dir) {
-                        (Some(schema_file), None) => Ok(SchemaLocation::File(schema_file)),
+                        (Some(schema_files), None) if !schema_files.is_empty() => {
+                            Ok(SchemaLocation::Files(schema_files.into()))
+                        }
                         (None, Some(schema_dir)) => Ok(SchemaLocation::Directory(schema_dir)),
                         _ => Err(Error::ConfigFileValidation {
                             config_path: config_path.clone(),
@@ -127,6 +204,21 @@ impl Config {
                     },
                 }?;
 
+                let excludes = config_file_project
+                    .excludes
+                    .iter()
+                    .map(|glob| {
+                        Pattern::new(glob).map_err(|error| Error::ConfigFileValidation {
+                            config_path: config_path.clone(),
+                            validation_errors: vec![ConfigValidationError::InvalidExcludeGlob {
+                                project_name,
+                                glob: glob.clone(),
+                                error,
+                            }],
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
                 let project_config = ProjectConfig {
                     name: project_name,
                     base: config_file_project.base,
@@ -138,12 +230,33 @@ impl Config {
                     enum_module_suffix: config_file_project.enum_module_suffix,
                     optional_input_fields: config_file_project.optional_input_fields,
                     persist: config_file_project.persist,
+                    excludes,
+                    inline_single_use_fragments: config_file_project.inline_single_use_fragments,
+                    generate_operation_ids: config_file_project.generate_operation_ids,
+                    hoist_inline_fragments: config_file_project.hoist_inline_fragments,
+                    artifact_extension: config_file_project
+                        .artifact_extension
+                        .unwrap_or_else(|| DEFAULT_ARTIFACT_EXTENSION.to_string()),
                 };
                 Ok((project_name, project_config))
             })
             .collect::<Result<HashMap<_, _>>>()?;
+        let max_concurrent_projects = config_file
+            .max_concurrent_projects
+            .unwrap_or_else(num_cpus::get);
+        let project_thread_pool = ThreadPoolBuilder::new()
+            .num_threads(max_concurrent_projects.max(1))
+            .build()
+            .map_err(|error| Error::ConfigFileValidation {
+                config_path: config_path.clone(),
+                validation_errors: vec![ConfigValidationError::ProjectThreadPoolBuild {
+                    max_concurrent_projects,
+                    error,
+                }],
+            })?;
         let config = Self {
             root_dir,
+            config_path: config_path.clone(),
             sources: config_file.sources,
             blacklist: config_file.blacklist,
             projects,
@@ -153,6 +266,9 @@ impl Config {
             only_project: None,
             load_saved_state_file: None,
             generate_extra_operation_artifacts: None,
+            max_concurrent_projects,
+            project_thread_pool,
+            custom_scalars: config_file.custom_scalars,
         };
 
         let mut validation_errors = Vec::new();
@@ -218,18 +334,20 @@ impl Config {
 
         for (&project_name, project) in &self.projects {
             match &project.schema_location {
-                SchemaLocation::File(schema_file) => {
-                    let abs_schema_file = self.root_dir.join(schema_file);
-                    if !abs_schema_file.exists() {
-                        errors.push(ConfigValidationError::SchemaFileNotExistent {
-                            project_name,
-                            schema_file: abs_schema_file.clone(),
-                        });
-                    } else if !abs_schema_file.is_file() {
-                        errors.push(ConfigValidationError::SchemaFileNotFile {
-                            project_name,
-                            schema_file: abs_schema_file.clone(),
-                        });
+                SchemaLocation::Files(schema_files) => {
+                    for schema_file in schema_files {
+                        let abs_schema_file = self.root_dir.join(schema_file);
+                        if !abs_schema_file.exists() {
+                            errors.push(ConfigValidationError::SchemaFileNotExistent {
+                                project_name,
+                                schema_file: abs_schema_file.clone(),
+                            });
+                        } else if !abs_schema_file.is_file() {
+                            errors.push(ConfigValidationError::SchemaFileNotFile {
+                                project_name,
+                                schema_file: abs_schema_file.clone(),
+                            });
+                        }
                     }
                 }
                 SchemaLocation::Directory(schema_dir) => {
@@ -255,6 +373,7 @@ impl fmt::Debug for Config {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let Config {
             root_dir,
+            config_path,
             sources,
             blacklist,
             projects,
@@ -264,9 +383,13 @@ impl fmt::Debug for Config {
             only_project,
             load_saved_state_file,
             generate_extra_operation_artifacts,
+            max_concurrent_projects,
+            project_thread_pool: _,
+            custom_scalars,
         } = self;
         f.debug_struct("Config")
             .field("root_dir", root_dir)
+            .field("config_path", config_path)
             .field("sources", sources)
             .field("blacklist", blacklist)
             .field("projects", projects)
@@ -283,6 +406,8 @@ impl fmt::Debug for Config {
                     &"None"
                 },
             )
+            .field("max_concurrent_projects", max_concurrent_projects)
+            .field("custom_scalars", custom_scalars)
             .finish()
     }
 }
@@ -299,11 +424,35 @@ pub struct ProjectConfig {
     pub enum_module_suffix: Option<String>,
     pub optional_input_fields: Vec<StringKey>,
     pub persist: Option<PersistConfig>,
+    pub excludes: Vec<Pattern>,
+    pub inline_single_use_fragments: bool,
+    /// When set, a stable ID is computed for each operation and added to the
+    /// metadata of its artifact, see `Programs::operation_ids`
+    pub generate_operation_ids: bool,
+    /// When set, the inline fragments occurring at least this many times in the
+    /// text of the operations are hoisted into shared fragments
+    pub hoist_inline_fragments: Option<usize>,
+    /// The extension of the artifact files, e.g. `js` for `Foo.graphql.js`
+    pub artifact_extension: String,
+}
+
+impl ProjectConfig {
+    /// The directory the artifacts of the project are written to, `None` if they are
+    /// written to a `__generated__` directory next to each source file.
+    pub fn artifact_directory(&self) -> Option<&PathBuf> {
+        self.output.as_ref()
+    }
+
+    /// The name of the artifact file of an operation or fragment
+    pub fn artifact_file_name(&self, definition_name: StringKey) -> String {
+        format!("{}.graphql.{}", definition_name, self.artifact_extension)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum SchemaLocation {
-    File(PathBuf),
+    /// One or more schema files that are merged into a single schema.
+    Files(Vec<PathBuf>),
     Directory(PathBuf),
 }
 
@@ -328,6 +477,16 @@ struct ConfigFile {
 
     /// Configuration of projects to compile.
     projects: HashMap<ProjectName, ConfigFileProject>,
+
+    /// Maximum number of projects that are checked concurrently, defaults to the
+    /// number of logical CPUs. Set it to 1 to check the projects serially.
+    #[serde(default)]
+    max_concurrent_projects: Option<usize>,
+
+    /// A mapping from the names of custom scalars to the type of their values in
+    /// the client, e.g. `{"DateTime": "string"}`.
+    #[serde(default)]
+    custom_scalars: HashMap<StringKey, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -360,16 +519,47 @@ struct ConfigFileProject {
     #[serde(default)]
     extensions: Vec<PathBuf>,
 
-    /// Path to the schema.graphql or a directory containing a schema broken up
+    /// Path to the schema.graphql, a list of paths to *.graphql files that are
+    /// merged into one schema, or a directory containing a schema broken up
     /// in multiple *.graphql files.
     /// Exactly 1 of these options needs to be defined.
-    schema: Option<PathBuf>,
+    schema: Option<SchemaFiles>,
     schema_dir: Option<PathBuf>,
 
     /// If this option is set, the compiler will persist queries using this
     /// config.
     persist: Option<PersistConfig>,
 
+    /// Glob patterns (relative to the root) of source files in this project's
+    /// source directories that should not be compiled, for example
+    /// `src/**/node_modules/**`.
+    #[serde(default)]
+    excludes: Vec<String>,
+
+    /// When set, fragments spread exactly once are inlined into the text of
+    /// the operations sent to the server, to reduce its size. The fragments
+    /// still have their own reader artifacts.
+    #[serde(default)]
+    inline_single_use_fragments: bool,
+
+    /// When set, each operation gets a stable ID for persisted queries, the
+    /// SHA-256 hash of the text sent to the server, which is added to the
+    /// `metadata` of the request parameters as `operationId`.
+    #[serde(default)]
+    generate_operation_ids: bool,
+
+    /// When set, the inline fragments with the same type condition and selections
+    /// that occur at least this many times in the text of the operations sent to
+    /// the server are replaced by spreads of shared fragments, to reduce its size.
+    #[serde(default)]
+    hoist_inline_fragments: Option<usize>,
+
+    /// The extension of the artifact files, without the leading dot. Defaults to
+    /// `js`, e.g. `ts` generates `Foo.graphql.ts`. The artifacts are still required
+    /// as `Foo.graphql`.
+    #[serde(default)]
+    artifact_extension: Option<String>,
+
     /// # For Flow type generation
     /// When set, enum values are imported from a module with this suffix.
     /// For example, an enum Foo and this property set to ".test" would be
@@ -385,6 +575,32 @@ struct ConfigFileProject {
     optional_input_fields: Vec<StringKey>,
 }
 
+/// The `schema` of a project is either a single path or a list of paths.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SchemaFiles {
+    Single(PathBuf),
+    Multiple(Vec<PathBuf>),
+}
+
+impl SchemaFiles {
+    fn is_empty(&self) -> bool {
+        match self {
+            SchemaFiles::Single(_) => false,
+            SchemaFiles::Multiple(schema_files) => schema_files.is_empty(),
+        }
+    }
+}
+
+impl From<SchemaFiles> for Vec<PathBuf> {
+    fn from(schema_files: SchemaFiles) -> Self {
+        match schema_files {
+            SchemaFiles::Single(schema_file) => vec![schema_file],
+            SchemaFiles::Multiple(schema_files) => schema_files,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct PersistConfig {
@@ -8,7 +8,9 @@
 use crate::compiler_state::ProjectName;
 pub use graphql_ir::{ValidationError, ValidationErrorWithSources};
 pub use graphql_syntax::SyntaxErrorWithSource;
+use interner::StringKey;
 use persist_query::PersistError;
+use schema::SchemaError;
 use serde_json::error::Error as SerdeError;
 use std::io;
 use std::path::PathBuf;
@@ -43,6 +45,19 @@ pub enum Error {
         validation_errors: Vec<ConfigValidationError>,
     },
 
+    #[error(
+        "Schema of project `{project_name}` is invalid:{}",
+        validation_errors
+            .iter()
+            .map(|err| format!("\n - {}", err))
+            .collect::<Vec<_>>()
+            .join("")
+    )]
+    SchemaValidation {
+        project_name: ProjectName,
+        validation_errors: Vec<SchemaValidationError>,
+    },
+
     #[error(
         "Failed parsing GraphQL:{}",
         errors
@@ -90,6 +105,15 @@ pub enum Error {
     #[error("Watchman query returned no results.")]
     EmptyQueryResult,
 
+    #[error("File watcher error.")]
+    Notify {
+        #[from]
+        source: notify::Error,
+    },
+
+    #[error("File watcher stopped sending changes.")]
+    FileWatcherStopped,
+
     #[error("Failed to read file: `{file}`.")]
     FileRead {
         file: PathBuf,
@@ -123,6 +147,9 @@ pub enum ConfigValidationError {
     #[error("Project `{project_name}` needs to define exactly one of `schema` or `schema_dir`.")]
     ProjectNeedsSchemaXorSchemaDir { project_name: ProjectName },
 
+    #[error("Project `{project_name}` has no schema, no schema files were found at its `schema` or `schema_dir`.")]
+    ProjectSchemaMissing { project_name: ProjectName },
+
     #[error(
         "The `schema` configured for project `{project_name}` does not exist at `{schema_file}`."
     )]
@@ -160,6 +187,41 @@ pub enum ConfigValidationError {
         project_name: ProjectName,
         error: regex::Error,
     },
+
+    #[error("The glob `{glob}` in `excludes` for project `{project_name}` is invalid.\n {error}.")]
+    InvalidExcludeGlob {
+        project_name: ProjectName,
+        glob: String,
+        error: glob::PatternError,
+    },
+
+    #[error("Failed to start the threads for `maxConcurrentProjects` ({max_concurrent_projects}).\n {error}.")]
+    ProjectThreadPoolBuild {
+        max_concurrent_projects: usize,
+        error: rayon::ThreadPoolBuildError,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum SchemaValidationError {
+    #[error(
+        "The type `{type_name}` is defined in multiple schema files: {}.",
+        files
+            .iter()
+            .map(|file| format!("`{}`", file.display()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )]
+    ConflictingTypeDefinitions {
+        type_name: StringKey,
+        files: Vec<PathBuf>,
+    },
+
+    #[error("Failed to parse the schema file `{file}`: {error}")]
+    InvalidSchemaFile { file: PathBuf, error: SchemaError },
+
+    #[error("{error}")]
+    InvalidSchema { error: SchemaError },
 }
 
 #[derive(Debug, Error)]
@@ -16,12 +16,16 @@ pub mod compiler_state;
 pub mod config;
 pub mod errors;
 mod parse_sources;
+pub mod relay_ignore;
 mod watchman;
 
 pub use build_project::{
-    apply_transforms, build_schema, check_project,
-    generate_extra_artifacts::GenerateExtraArtifactArgs, validate, Artifact, ArtifactContent,
-    Programs,
+    apply_transforms, build_schema, build_schemas, check_project,
+    generate_extra_artifacts::GenerateExtraArtifactArgs, try_build_schema, try_build_schemas,
+    validate, Artifact, ArtifactContent, Programs,
+};
+pub use parse_sources::{parse_sources, parse_sources_isolating_syntax_errors};
+pub use watchman::{
+    is_relevant_file, source_file_projects, File, FileSource, FileSourceResult,
+    FileSourceSubscription,
 };
-pub use parse_sources::parse_sources;
-pub use watchman::{FileSource, FileSourceResult, FileSourceSubscription};
@@ -6,7 +6,8 @@
  */
 
 use crate::compiler_state::{CompilerState, GraphQLSources, SourceSetName};
-use crate::errors::{Error, Result};
+use crate::errors::{Error, Result, SyntaxErrorWithSource};
+use crate::relay_ignore::RelayIgnore;
 use common::FileKey;
 use fnv::{FnvHashMap, FnvHashSet};
 use graphql_ir::Sources;
@@ -16,8 +17,30 @@ use interner::StringKey;
 /// Parses all source files for all projects into ASTs and builds up a Sources map that can
 /// be used to print errors with source code listing.
 /// Additionally collects the set of definition names that changed,given the compiler state
+/// Files ignored by the `.relayignore` file are skipped, including the ones of a saved state.
+/// Fails with the syntax errors of all sources if any source can't be parsed.
 pub fn parse_sources<'state>(compiler_state: &'state CompilerState) -> Result<GraphQLAsts<'state>> {
-    GraphQLAsts::from_graphql_sources(&compiler_state.graphql_sources)
+    let mut graphql_asts = parse_sources_isolating_syntax_errors(compiler_state);
+    let syntax_errors = graphql_asts.take_syntax_errors();
+    if syntax_errors.is_empty() {
+        Ok(graphql_asts)
+    } else {
+        Err(Error::SyntaxErrors {
+            errors: syntax_errors,
+        })
+    }
+}
+
+/// Like `parse_sources`, but the GraphQL sources that can't be parsed are skipped rather
+/// than failing, so that the rest of the projects can still be checked while a file is
+/// being edited. Their syntax errors are available with `take_syntax_errors`.
+pub fn parse_sources_isolating_syntax_errors<'state>(
+    compiler_state: &'state CompilerState,
+) -> GraphQLAsts<'state> {
+    GraphQLAsts::from_graphql_sources(
+        &compiler_state.graphql_sources,
+        &compiler_state.relay_ignore,
+    )
 }
 
 #[derive(Debug)]
@@ -25,10 +48,14 @@ pub struct GraphQLAsts<'state> {
     grouped_asts: FnvHashMap<SourceSetName, Vec<ExecutableDefinition>>,
     grouped_changed_definition_names: FnvHashMap<SourceSetName, FnvHashSet<StringKey>>,
     graphql_source_strings: Sources<'state>,
+    syntax_errors: Vec<SyntaxErrorWithSource>,
 }
 
 impl<'state> GraphQLAsts<'state> {
-    fn from_graphql_sources(graphql_sources: &'state GraphQLSources) -> Result<Self> {
+    fn from_graphql_sources(
+        graphql_sources: &'state GraphQLSources,
+        relay_ignore: &RelayIgnore,
+    ) -> Self {
         let mut grouped_asts = FnvHashMap::default();
         let mut graphql_source_strings: Sources<'state> = FnvHashMap::default();
         let mut grouped_changed_definition_names = FnvHashMap::default();
@@ -51,6 +78,9 @@ impl<'state> GraphQLAsts<'state> {
                 graphql_sources.pending_sources_for_source_set(*source_set_name);
 
             for (file_name, file_state) in source_set.iter() {
+                if relay_ignore.is_ignored(file_name) {
+                    continue;
+                }
                 let mut definitions_for_file = Vec::new();
 
                 // Check for graphql strings in the pending sources for
@@ -114,7 +144,7 @@ impl<'state> GraphQLAsts<'state> {
 
             for (file_name, file_state) in source_set.iter() {
                 // Only parse the file if it isn't already been parsed.
-                if !parsed_files.contains(file_name) {
+                if !parsed_files.contains(file_name) && !relay_ignore.is_ignored(file_name) {
                     let mut definitions_for_file = Vec::new();
                     for (index, graphql_source) in file_state.graphql_sources.iter().enumerate() {
                         let file_key =
@@ -136,19 +166,20 @@ impl<'state> GraphQLAsts<'state> {
             }
         }
 
-        if syntax_errors.is_empty() {
-            Ok(Self {
-                grouped_asts,
-                grouped_changed_definition_names,
-                graphql_source_strings,
-            })
-        } else {
-            Err(Error::SyntaxErrors {
-                errors: syntax_errors,
-            })
+        Self {
+            grouped_asts,
+            grouped_changed_definition_names,
+            graphql_source_strings,
+            syntax_errors,
         }
     }
 
+    /// Takes the syntax errors of the GraphQL sources that couldn't be parsed, the
+    /// definitions of these sources are missing from the ASTs.
+    pub fn take_syntax_errors(&mut self) -> Vec<SyntaxErrorWithSource> {
+        std::mem::take(&mut self.syntax_errors)
+    }
+
     pub fn sources(&self) -> &Sources<'state> {
         &self.graphql_source_strings
     }
@@ -164,3 +195,125 @@ impl<'state> GraphQLAsts<'state> {
         self.grouped_changed_definition_names[&source_set_name].clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler_state::FileState;
+    use crate::config::Config;
+    use crate::watchman::{File, FileSourceResult};
+    use common::{ConsoleLogEvent, ConsoleLogger};
+    use graphql_syntax::GraphQLSource;
+    use indexmap::IndexMap;
+    use interner::Intern;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn file_state(text: &str) -> FileState {
+        FileState {
+            graphql_sources: vec![GraphQLSource::new(text, 0, 0)],
+            exists: true,
+        }
+    }
+
+    #[test]
+    fn test_isolates_syntax_errors_of_each_file() {
+        let source_set_name = "test".intern();
+        let mut source_set = IndexMap::new();
+        source_set.insert(
+            PathBuf::from("src/User.js"),
+            file_state("fragment User_user on User { name }"),
+        );
+        source_set.insert(
+            PathBuf::from("src/Broken.js"),
+            file_state("fragment Broken_user on User { name "),
+        );
+        source_set.insert(
+            PathBuf::from("src/Query.js"),
+            file_state("query QueryTest { me { ...User_user } }"),
+        );
+        let mut graphql_sources = GraphQLSources::default();
+        graphql_sources.set_pending_source_set(source_set_name, source_set);
+
+        let mut graphql_asts =
+            GraphQLAsts::from_graphql_sources(&graphql_sources, &RelayIgnore::default());
+        let mut definition_names: Vec<_> = graphql_asts
+            .asts_for_source_set(source_set_name)
+            .iter()
+            .map(|definition| match definition {
+                ExecutableDefinition::Operation(operation) => {
+                    operation.name.as_ref().unwrap().value
+                }
+                ExecutableDefinition::Fragment(fragment) => fragment.name.value,
+            })
+            .collect();
+        definition_names.sort_by_key(|name| name.lookup());
+        assert_eq!(
+            definition_names,
+            vec!["QueryTest".intern(), "User_user".intern()]
+        );
+        // The sources of all files can be used to print errors
+        assert_eq!(graphql_asts.sources().len(), 3);
+
+        let syntax_errors = graphql_asts.take_syntax_errors();
+        assert!(!syntax_errors.is_empty());
+        assert!(syntax_errors
+            .iter()
+            .all(|error| error.error.location.file().lookup() == "src/Broken.js:0"));
+        assert!(graphql_asts.take_syntax_errors().is_empty());
+    }
+
+    #[test]
+    fn test_excluded_files_have_no_syntax_errors() {
+        let root_dir = std::env::temp_dir().join("relay_compiler_excluded_files");
+        fs::create_dir_all(root_dir.join("src/node_modules/pkg")).unwrap();
+        fs::write(
+            root_dir.join("src/Query.js"),
+            "graphql`query QueryTest { me { name } }`;",
+        )
+        .unwrap();
+        fs::write(
+            root_dir.join("src/node_modules/pkg/Broken.js"),
+            "graphql`fragment Broken_user on User { name `;",
+        )
+        .unwrap();
+        let config = Config::from_string_for_test(
+            r#"
+                {
+                    "sources": {
+                        "src": "app"
+                    },
+                    "projects": {
+                        "app": {
+                            "schema": "schema.graphql",
+                            "excludes": ["**/node_modules/**"]
+                        }
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+        let file = |name: &str| File {
+            name: name.into(),
+            exists: true,
+        };
+        let file_source_changes = FileSourceResult {
+            files: vec![file("src/Query.js"), file("src/node_modules/pkg/Broken.js")],
+            resolved_root: root_dir,
+            clock: None,
+            is_fresh_instance: false,
+        };
+        let compiler_state = CompilerState::from_file_source_changes(
+            &config,
+            &file_source_changes,
+            &ConsoleLogEvent,
+            &ConsoleLogger,
+        )
+        .unwrap();
+
+        let mut graphql_asts = parse_sources_isolating_syntax_errors(&compiler_state);
+        assert!(graphql_asts.take_syntax_errors().is_empty());
+        assert_eq!(graphql_asts.asts_for_source_set("app".intern()).len(), 1);
+        assert_eq!(graphql_asts.sources().len(), 1);
+    }
+}
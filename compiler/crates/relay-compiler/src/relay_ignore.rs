@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Support for a `.relayignore` file at the root directory, listing paths the
+//! compiler should ignore with the syntax of `.gitignore` files.
+
+use crate::watchman::FileSourceResult;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::warn;
+use std::path::Path;
+
+/// The name of the file listing the ignored paths, relative to the root directory
+pub const RELAY_IGNORE_FILE_NAME: &str = ".relayignore";
+
+/// The paths ignored by the `.relayignore` file of the root directory.
+#[derive(Debug, Clone)]
+pub struct RelayIgnore {
+    gitignore: Gitignore,
+}
+
+impl Default for RelayIgnore {
+    fn default() -> Self {
+        Self {
+            gitignore: Gitignore::empty(),
+        }
+    }
+}
+
+impl RelayIgnore {
+    /// Reads the `.relayignore` file of `root_dir`, ignoring nothing if there is none.
+    /// Invalid patterns are skipped with a warning rather than failing the compilation.
+    pub fn load(root_dir: &Path) -> Self {
+        let path = root_dir.join(RELAY_IGNORE_FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::from_text(root_dir, &text),
+            Err(err) => {
+                if path.exists() {
+                    warn!("Unable to read `{:?}`, no paths are ignored: {}", path, err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    fn from_text(root_dir: &Path, text: &str) -> Self {
+        let mut builder = GitignoreBuilder::new(root_dir);
+        for line in text.lines() {
+            if let Err(err) = builder.add_line(None, line) {
+                warn!(
+                    "Skipping invalid pattern in `{}`: {}",
+                    RELAY_IGNORE_FILE_NAME, err
+                );
+            }
+        }
+        match builder.build() {
+            Ok(gitignore) => Self { gitignore },
+            Err(err) => {
+                warn!(
+                    "Unable to build the patterns of `{}`, no paths are ignored: {}",
+                    RELAY_IGNORE_FILE_NAME, err
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Checks if a file, relative to the root directory, or one of its parent
+    /// directories is ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.gitignore
+            .matched_path_or_any_parents(path, false)
+            .is_ignore()
+    }
+}
+
+/// Checks if the `.relayignore` file is one of the changed files, in which case the
+/// compiler state has to be rebuilt from all files.
+pub fn relay_ignore_changed(file_source_changes: &FileSourceResult) -> bool {
+    file_source_changes
+        .files
+        .iter()
+        .any(|file| file.name == Path::new(RELAY_IGNORE_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watchman::File;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_ignores_generated_directory() {
+        let relay_ignore = RelayIgnore::from_text(
+            Path::new("/repo"),
+            "# generated by the test runner\nsrc/generated/\n**/__fixtures__/*.js\n!src/__fixtures__/Keep.js",
+        );
+        assert!(relay_ignore.is_ignored(Path::new("src/generated/Foo.js")));
+        assert!(relay_ignore.is_ignored(Path::new("src/generated/nested/Foo.js")));
+        assert!(relay_ignore.is_ignored(Path::new("src/nested/__fixtures__/Foo.js")));
+        assert!(!relay_ignore.is_ignored(Path::new("src/__fixtures__/Keep.js")));
+        assert!(!relay_ignore.is_ignored(Path::new("src/Foo.js")));
+        assert!(!relay_ignore.is_ignored(Path::new("lib/generated/Foo.js")));
+    }
+
+    #[test]
+    fn test_ignores_nothing_by_default() {
+        assert!(!RelayIgnore::default().is_ignored(Path::new("src/generated/Foo.js")));
+    }
+
+    #[test]
+    fn test_relay_ignore_changed() {
+        let changes = |names: &[&str]| FileSourceResult {
+            files: names
+                .iter()
+                .map(|name| File {
+                    name: PathBuf::from(name),
+                    exists: true,
+                })
+                .collect(),
+            resolved_root: PathBuf::from("/repo"),
+            clock: None,
+            is_fresh_instance: false,
+        };
+        assert!(relay_ignore_changed(&changes(&[
+            "src/Foo.js",
+            ".relayignore"
+        ])));
+        assert!(!relay_ignore_changed(&changes(&["src/Foo.js"])));
+        assert!(!relay_ignore_changed(&changes(&["src/.relayignore"])));
+    }
+}
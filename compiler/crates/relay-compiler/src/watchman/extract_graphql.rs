@@ -5,18 +5,18 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use super::{read_to_string, WatchmanFile};
+use super::{read_to_string, File};
 use crate::errors::{Error, Result};
 use graphql_syntax::GraphQLSource;
-use watchman_client::prelude::*;
+use std::path::Path;
 
 /// Reads and extracts `graphql` tagged literals from a file.
 pub fn extract_graphql_strings_from_file(
-    resolved_root: &ResolvedRoot,
-    file: &WatchmanFile,
+    resolved_root: &Path,
+    file: &File,
 ) -> Result<Vec<GraphQLSource>> {
-    if !(*file.exists) {
-        unreachable!("Can't read from non-existent file: {:?}", *file.name);
+    if !file.exists {
+        unreachable!("Can't read from non-existent file: {:?}", file.name);
     }
     let contents = read_to_string(resolved_root, file)?;
     let definitions =
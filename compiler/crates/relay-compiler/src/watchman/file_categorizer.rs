@@ -5,35 +5,84 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use super::File;
 use super::FileGroup;
-use super::WatchmanFile;
 use crate::compiler_state::{ProjectName, SourceSetName};
-use crate::config::{Config, SchemaLocation};
+use crate::config::{Config, SchemaLocation, GENERATED_DIRECTORY_NAME};
+use crate::relay_ignore::RELAY_IGNORE_FILE_NAME;
+use glob::{MatchOptions, Pattern};
 use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
-use std::path::{Component, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
-/// The watchman query returns a list of files, but for the compiler we
+/// The file source returns a list of files, but for the compiler we
 /// need to categorize these files into multiple groups of files like
 /// schema files, extensions and sources by their source set name.
 ///
 /// See `FileGroup` for all groups of files.
-pub fn categorize_files(
-    config: &Config,
-    files: &[WatchmanFile],
-) -> HashMap<FileGroup, Vec<WatchmanFile>> {
+/// Source files excluded by all the projects compiling their source set, see
+/// `source_file_projects`, are dropped, as well as the `.relayignore` file, which
+/// isn't part of any group.
+pub fn categorize_files(config: &Config, files: &[File]) -> HashMap<FileGroup, Vec<File>> {
     let categorizer = FileCategorizer::from_config(config);
     let mut categorized = HashMap::new();
     for file in files {
+        if file.name == Path::new(RELAY_IGNORE_FILE_NAME) {
+            continue;
+        }
+        let file_group = categorizer.categorize(&file.name);
+        if let FileGroup::Source { .. } = file_group {
+            if source_file_projects(config, &file.name).is_empty() {
+                continue;
+            }
+        }
         categorized
-            .entry(categorizer.categorize(&file.name))
+            .entry(file_group)
             .or_insert_with(Vec::new)
             .push(file.clone());
     }
     categorized
 }
 
+/// The projects compiling a source file, sorted by name. These are the project of the
+/// most specific entry of the `sources` containing the file, and the projects using
+/// that project as their base, unless the file matches their `excludes`.
+pub fn source_file_projects(config: &Config, path: &Path) -> Vec<ProjectName> {
+    let source_set_name = match config
+        .sources
+        .iter()
+        .filter(|(source_dir, _)| path.starts_with(source_dir))
+        .max_by_key(|(source_dir, _)| source_dir.components().count())
+    {
+        Some((_, source_set_name)) => *source_set_name,
+        None => return vec![],
+    };
+    let mut project_names: Vec<ProjectName> = config
+        .projects
+        .values()
+        .filter(|project_config| {
+            (project_config.name == source_set_name || project_config.base == Some(source_set_name))
+                && !matches_excludes(&project_config.excludes, path)
+        })
+        .map(|project_config| project_config.name)
+        .collect();
+    project_names.sort_by_key(|project_name| project_name.lookup());
+    project_names
+}
+
+fn matches_excludes(excludes: &[Pattern], path: &Path) -> bool {
+    excludes.iter().any(|pattern| {
+        pattern.matches_path_with(
+            path,
+            MatchOptions {
+                require_literal_separator: true,
+                ..Default::default()
+            },
+        )
+    })
+}
+
 /// The FileCategorizer is created from a Config and categorizes files found by
 /// Watchman into what kind of files they are, such as source files of a
 /// specific source file group or generated files from some project.
@@ -68,10 +117,13 @@ impl FileCategorizer {
         let schema_file_mapping: HashMap<PathBuf, ProjectName> = config
             .projects
             .iter()
-            .filter_map(
+            .flat_map(
                 |(&project_name, project_config)| match &project_config.schema_location {
-                    SchemaLocation::File(schema_file) => Some((schema_file.clone(), project_name)),
-                    SchemaLocation::Directory(_) => None,
+                    SchemaLocation::Files(schema_files) => schema_files
+                        .iter()
+                        .map(|schema_file| (schema_file.clone(), project_name))
+                        .collect(),
+                    SchemaLocation::Directory(_) => vec![],
                 },
             )
             .collect();
@@ -81,7 +133,7 @@ impl FileCategorizer {
                 .iter()
                 .filter_map(|(&project_name, project_config)| {
                     match &project_config.schema_location {
-                        SchemaLocation::File(_) => None,
+                        SchemaLocation::Files(_) => None,
                         SchemaLocation::Directory(schema_dir) => {
                             Some((schema_dir.clone(), project_name))
                         }
@@ -90,11 +142,11 @@ impl FileCategorizer {
                 .collect(),
         );
 
-        let default_generated_dir = OsStr::new("__generated__");
+        let default_generated_dir = OsStr::new(GENERATED_DIRECTORY_NAME);
         let generated_dir_paths: HashSet<PathBuf> = config
             .projects
-            .iter()
-            .filter_map(|(_, project_config)| project_config.output.clone())
+            .values()
+            .filter_map(|project_config| project_config.artifact_directory().cloned())
             .collect();
 
         Self {
@@ -258,4 +310,108 @@ mod tests {
             },
         );
     }
+    #[test]
+    fn test_categorize_files_with_excludes() {
+        let config = Config::from_string_for_test(
+            r#"
+                {
+                    "sources": {
+                        "src/js": "public",
+                        "src/js/internal": "internal"
+                    },
+                    "projects": {
+                        "public": {
+                            "schema": "graphql/public.graphql",
+                            "excludes": ["src/js/**/node_modules/**"]
+                        },
+                        "internal": {
+                            "schema": "graphql/internal.graphql"
+                        }
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+        let file = |name: &str| File {
+            name: name.into(),
+            exists: true,
+        };
+        let categorized = categorize_files(
+            &config,
+            &[
+                file("src/js/a.js"),
+                file("src/js/node_modules/pkg/b.js"),
+                file("src/js/nested/node_modules/pkg/c.js"),
+                file("src/js/internal/node_modules/pkg/d.js"),
+            ],
+        );
+
+        let names = |source_set_name: &str| -> Vec<PathBuf> {
+            categorized[&FileGroup::Source {
+                source_set_name: source_set_name.intern(),
+            }]
+                .iter()
+                .map(|file| file.name.clone())
+                .collect()
+        };
+        assert_eq!(names("public"), vec![PathBuf::from("src/js/a.js")]);
+        // Excludes only apply to the sources of the project defining them.
+        assert_eq!(
+            names("internal"),
+            vec![PathBuf::from("src/js/internal/node_modules/pkg/d.js")]
+        );
+    }
+
+    #[test]
+    fn test_categorize_files_with_excludes_of_base_projects() {
+        let config = Config::from_string_for_test(
+            r#"
+                {
+                    "sources": {
+                        "src/shared": "shared"
+                    },
+                    "projects": {
+                        "shared": {
+                            "schema": "graphql/shared.graphql",
+                            "excludes": ["src/shared/legacy/**", "**/node_modules/**"]
+                        },
+                        "app": {
+                            "schema": "graphql/app.graphql",
+                            "base": "shared",
+                            "excludes": ["**/node_modules/**"]
+                        }
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+        let file = |name: &str| File {
+            name: name.into(),
+            exists: true,
+        };
+        let categorized = categorize_files(
+            &config,
+            &[
+                file("src/shared/a.js"),
+                file("src/shared/legacy/b.js"),
+                file("src/shared/node_modules/pkg/c.js"),
+            ],
+        );
+
+        let names: Vec<PathBuf> = categorized[&FileGroup::Source {
+            source_set_name: "shared".intern(),
+        }]
+            .iter()
+            .map(|file| file.name.clone())
+            .collect();
+        // A source is kept while one of the projects compiling its source set doesn't
+        // exclude it
+        assert_eq!(
+            names,
+            vec![
+                PathBuf::from("src/shared/a.js"),
+                PathBuf::from("src/shared/legacy/b.js")
+            ]
+        );
+    }
 }
@@ -5,28 +5,47 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use super::notify_file_source::{NotifyFileSource, NotifyFileSourceSubscription};
 use super::Clock;
 use super::{
     query_builder::{get_all_roots, get_watchman_expr},
-    WatchmanFile,
+    File, WatchmanFile,
 };
 use crate::errors::{Error, Result};
+use crate::relay_ignore::RelayIgnore;
 use crate::{compiler_state::CompilerState, config::Config};
 use common::{PerfLogEvent, PerfLogger};
+use log::{info, warn};
+use std::cmp;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::time::{delay_until, Instant};
 use watchman_client::prelude::*;
 use watchman_client::{Subscription as WatchmanSubscription, SubscriptionData};
 
-pub struct FileSource<'config> {
-    client: Client,
-    config: &'config Config,
-    resolved_root: ResolvedRoot,
+/// The delay before the first attempt to reconnect to Watchman after the connection
+/// was lost, which is doubled after each failed attempt up to `MAX_RECONNECT_DELAY`
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Provides the files to compile, using Watchman if it's available and
+/// falling back to native file system notifications otherwise.
+pub enum FileSource<'config> {
+    Watchman(WatchmanFileSource<'config>),
+    Notify(NotifyFileSource<'config>),
 }
 
 #[derive(Debug)]
 pub struct FileSourceResult {
-    pub files: Vec<WatchmanFile>,
-    pub resolved_root: ResolvedRoot,
-    pub clock: Clock,
+    pub files: Vec<File>,
+    /// Absolute path the names of the `files` are relative to.
+    pub resolved_root: PathBuf,
+    /// The Watchman clock of the changes, `None` for other file sources.
+    pub clock: Option<Clock>,
+    /// Whether the `files` are all files to compile rather than the changes since the
+    /// previous result, e.g. after reconnecting to Watchman. The files deleted in
+    /// between aren't part of them, so the compiler state is rebuilt from them.
+    pub is_fresh_instance: bool,
 }
 
 impl<'config> FileSource<'config> {
@@ -34,6 +53,112 @@ impl<'config> FileSource<'config> {
         config: &'config Config,
         perf_logger_event: &impl PerfLogEvent,
     ) -> Result<FileSource<'config>> {
+        match WatchmanFileSource::connect(config, perf_logger_event).await {
+            Ok(file_source) => Ok(FileSource::Watchman(file_source)),
+            Err(Error::Watchman { source }) => {
+                warn!(
+                    "Unable to connect to Watchman, falling back to native file watching: {}",
+                    source
+                );
+                let file_source = NotifyFileSource::connect(config, perf_logger_event)?;
+                Ok(FileSource::Notify(file_source))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Executes a point query (as opposed to a subscription) to find all files
+    /// to compile and returns the result.
+    pub async fn query(
+        &self,
+        perf_logger_event: &impl PerfLogEvent,
+        perf_logger: &impl PerfLogger,
+    ) -> Result<CompilerState> {
+        match self {
+            FileSource::Watchman(file_source) => {
+                file_source.query(perf_logger_event, perf_logger).await
+            }
+            FileSource::Notify(file_source) => file_source.query(perf_logger_event, perf_logger),
+        }
+    }
+
+    /// Starts a subscription sending updates since the returned compiler state.
+    pub async fn subscribe(
+        self,
+        perf_logger_event: &impl PerfLogEvent,
+        perf_logger: &impl PerfLogger,
+    ) -> Result<(CompilerState, FileSourceSubscription<'config>)> {
+        match self {
+            FileSource::Watchman(file_source) => {
+                let (compiler_state, subscription) = file_source
+                    .subscribe(perf_logger_event, perf_logger)
+                    .await?;
+                Ok((
+                    compiler_state,
+                    FileSourceSubscription::Watchman(subscription),
+                ))
+            }
+            FileSource::Notify(file_source) => {
+                let (compiler_state, subscription) =
+                    file_source.subscribe(perf_logger_event, perf_logger)?;
+                Ok((compiler_state, FileSourceSubscription::Notify(subscription)))
+            }
+        }
+    }
+}
+
+pub enum FileSourceSubscription<'config> {
+    Watchman(WatchmanFileSourceSubscription<'config>),
+    Notify(NotifyFileSourceSubscription<'config>),
+}
+
+impl<'config> FileSourceSubscription<'config> {
+    /// Awaits changes from the file source and provides the next set of changes
+    /// if there were any changes to files. If the Watchman connection is lost, the
+    /// error is returned and the following calls reconnect to Watchman, see
+    /// `WatchmanFileSourceSubscription::next_change`.
+    pub async fn next_change(
+        &mut self,
+        perf_logger: &impl PerfLogger,
+    ) -> Result<Option<FileSourceResult>> {
+        match self {
+            FileSourceSubscription::Watchman(subscription) => {
+                subscription.next_change(perf_logger).await
+            }
+            FileSourceSubscription::Notify(subscription) => subscription.next_change().await,
+        }
+    }
+
+    /// Queries all files to compile, e.g. to rebuild the compiler state from scratch.
+    /// The changes the subscription provides afterwards are relative to the state of the
+    /// files before the query, so some of the queried changes may be provided again.
+    pub async fn query_all_files(
+        &self,
+        perf_logger_event: &impl PerfLogEvent,
+    ) -> Result<FileSourceResult> {
+        match self {
+            FileSourceSubscription::Watchman(subscription) => {
+                subscription
+                    .file_source
+                    .query_file_result(None, perf_logger_event)
+                    .await
+            }
+            FileSourceSubscription::Notify(subscription) => subscription.query_all_files(),
+        }
+    }
+}
+
+pub struct WatchmanFileSource<'config> {
+    client: Client,
+    config: &'config Config,
+    resolved_root: ResolvedRoot,
+}
+
+impl<'config> WatchmanFileSource<'config> {
+    async fn connect(
+        config: &'config Config,
+        perf_logger_event: &impl PerfLogEvent,
+    ) -> Result<WatchmanFileSource<'config>> {
         let connect_timer = perf_logger_event.start("file_source_connect_time");
         let client = Connector::new().connect().await?;
         let canonical_root = CanonicalPath::canonicalize(&config.root_dir).map_err(|err| {
@@ -52,17 +177,17 @@ impl<'config> FileSource<'config> {
         })
     }
 
-    /// Executes a point query (as opposed to a subscription) to find all files
-    /// to compile and returns the result.
-    pub async fn query(
+    async fn query(
         &self,
         perf_logger_event: &impl PerfLogEvent,
         perf_logger: &impl PerfLogger,
     ) -> Result<CompilerState> {
         if let Some(saved_state_path) = &self.config.load_saved_state_file {
             let mut compiler_state = CompilerState::deserialize_from_file(&saved_state_path)?;
+            // The ignored paths aren't saved, they may have changed since then
+            compiler_state.relay_ignore = RelayIgnore::load(&self.resolved_root.path());
             let file_source_result = self
-                .query_file_result(Some(compiler_state.clock.clone()), perf_logger_event)
+                .query_file_result(compiler_state.clock.clone(), perf_logger_event)
                 .await?;
             compiler_state.add_pending_file_source_changes(
                 &self.config,
@@ -83,39 +208,48 @@ impl<'config> FileSource<'config> {
         }
     }
 
-    /// Starts a subscription sending updates since the given clock.
-    pub async fn subscribe(
+    async fn subscribe(
         self,
         perf_logger_event: &impl PerfLogEvent,
         perf_logger: &impl PerfLogger,
-    ) -> Result<(CompilerState, FileSourceSubscription<'config>)> {
+    ) -> Result<(CompilerState, WatchmanFileSourceSubscription<'config>)> {
         let compiler_state = self.query(perf_logger_event, perf_logger).await?;
 
-        let expression = get_watchman_expr(&self.config);
-
         let file_source_result = self
-            .query_file_result(Some(compiler_state.clock.clone()), perf_logger_event)
+            .query_file_result(compiler_state.clock.clone(), perf_logger_event)
             .await?;
 
+        let subscription = self.subscribe_since(file_source_result.clock).await?;
+
+        Ok((
+            compiler_state,
+            WatchmanFileSourceSubscription {
+                file_source: self,
+                subscription: Some(subscription),
+                reconnect_at: Instant::now(),
+                reconnect_delay: INITIAL_RECONNECT_DELAY,
+            },
+        ))
+    }
+
+    /// Starts a Watchman subscription sending updates since `clock`.
+    async fn subscribe_since(
+        &self,
+        clock: Option<Clock>,
+    ) -> Result<WatchmanSubscription<WatchmanFile>> {
+        let expression = get_watchman_expr(&self.config);
         let (subscription, _initial) = self
             .client
             .subscribe::<WatchmanFile>(
                 &self.resolved_root,
                 SubscribeRequest {
                     expression: Some(expression),
-                    since: Some(file_source_result.clock.clone()),
+                    since: clock,
                     ..Default::default()
                 },
             )
             .await?;
-
-        Ok((
-            compiler_state,
-            FileSourceSubscription {
-                file_source: self,
-                subscription,
-            },
-        ))
+        Ok(subscription)
     }
 
     /// Internal method to issue a watchman query, returning a raw
@@ -156,32 +290,103 @@ impl<'config> FileSource<'config> {
 
         let files = query_result.files.ok_or_else(|| Error::EmptyQueryResult)?;
         Ok(FileSourceResult {
-            files,
-            resolved_root: self.resolved_root.clone(),
-            clock: query_result.clock,
+            files: files.iter().map(File::from).collect(),
+            resolved_root: self.resolved_root.path(),
+            clock: Some(query_result.clock),
+            is_fresh_instance: false,
         })
     }
 }
 
-pub struct FileSourceSubscription<'config> {
-    file_source: FileSource<'config>,
-    subscription: WatchmanSubscription<WatchmanFile>,
+pub struct WatchmanFileSourceSubscription<'config> {
+    file_source: WatchmanFileSource<'config>,
+    /// `None` while the connection to Watchman is lost
+    subscription: Option<WatchmanSubscription<WatchmanFile>>,
+    /// When to attempt to reconnect to Watchman next
+    reconnect_at: Instant,
+    /// The delay before the attempt after `reconnect_at`, if that one fails
+    reconnect_delay: Duration,
 }
 
-impl<'config> FileSourceSubscription<'config> {
+impl<'config> WatchmanFileSourceSubscription<'config> {
     /// Awaits changes from Watchman and provides the next set of changes
-    /// if there were any changes to files
-    pub async fn next_change(&mut self) -> Result<Option<FileSourceResult>> {
-        let update = self.subscription.next().await?;
+    /// if there were any changes to files.
+    ///
+    /// If the subscription fails, e.g. because Watchman was restarted, the error is
+    /// returned and the following calls reconnect with exponential backoff. Changes
+    /// may have been missed while disconnected, so once reconnected all files are
+    /// provided as a fresh instance, see `FileSourceResult::is_fresh_instance`.
+    async fn next_change(
+        &mut self,
+        perf_logger: &impl PerfLogger,
+    ) -> Result<Option<FileSourceResult>> {
+        let subscription = match &mut self.subscription {
+            Some(subscription) => subscription,
+            None => return self.reconnect(perf_logger).await.map(Some),
+        };
+        let update = match subscription.next().await {
+            Ok(update) => update,
+            Err(err) => {
+                self.subscription = None;
+                self.reconnect_delay = INITIAL_RECONNECT_DELAY;
+                self.reconnect_at = Instant::now() + self.reconnect_delay;
+                return Err(err.into());
+            }
+        };
         if let SubscriptionData::FilesChanged(changes) = update {
             if let Some(files) = changes.files {
                 return Ok(Some(FileSourceResult {
-                    files,
-                    resolved_root: self.file_source.resolved_root.clone(),
-                    clock: changes.clock,
+                    files: files.iter().map(File::from).collect(),
+                    resolved_root: self.file_source.resolved_root.path(),
+                    clock: Some(changes.clock),
+                    is_fresh_instance: false,
                 }));
             }
         }
         Ok(None)
     }
+
+    /// Reconnects to Watchman, retrying until it succeeds, and returns all files.
+    /// The time of the next attempt is kept when the returned future is dropped, so
+    /// awaiting other events in between doesn't delay reconnecting.
+    async fn reconnect(&mut self, perf_logger: &impl PerfLogger) -> Result<FileSourceResult> {
+        loop {
+            delay_until(self.reconnect_at).await;
+            let reconnect_event = perf_logger.create_event("watchman_reconnect");
+            let result = self.try_reconnect(&reconnect_event).await;
+            perf_logger.complete_event(reconnect_event);
+            match result {
+                Ok(file_source_result) => {
+                    info!("Reconnected to Watchman");
+                    return Ok(file_source_result);
+                }
+                Err(err) => {
+                    self.reconnect_at = Instant::now() + self.reconnect_delay;
+                    warn!(
+                        "Unable to reconnect to Watchman, retrying in {:?}: {}",
+                        self.reconnect_delay, err
+                    );
+                    self.reconnect_delay = cmp::min(self.reconnect_delay * 2, MAX_RECONNECT_DELAY);
+                }
+            }
+        }
+    }
+
+    async fn try_reconnect(
+        &mut self,
+        perf_logger_event: &impl PerfLogEvent,
+    ) -> Result<FileSourceResult> {
+        let file_source =
+            WatchmanFileSource::connect(self.file_source.config, perf_logger_event).await?;
+        let mut file_source_result = file_source
+            .query_file_result(None, perf_logger_event)
+            .await?;
+        file_source_result.is_fresh_instance = true;
+        let subscription = file_source
+            .subscribe_since(file_source_result.clock.clone())
+            .await?;
+        self.file_source = file_source;
+        self.subscription = Some(subscription);
+        Ok(file_source_result)
+    }
 }
@@ -13,12 +13,14 @@ mod extract_graphql;
 mod file_categorizer;
 mod file_group;
 mod file_source;
+mod notify_file_source;
 mod query_builder;
 mod watchman_file;
 
 pub use self::extract_graphql::extract_graphql_strings_from_file;
-pub use file_categorizer::categorize_files;
+pub use file_categorizer::{categorize_files, source_file_projects};
 pub use file_group::FileGroup;
 pub use file_source::{FileSource, FileSourceResult, FileSourceSubscription};
+pub use query_builder::is_relevant_file;
 pub use watchman_client::prelude::Clock;
-pub use watchman_file::{read_to_string, WatchmanFile};
+pub use watchman_file::{read_to_string, File, WatchmanFile};
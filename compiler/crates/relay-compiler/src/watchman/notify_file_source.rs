@@ -0,0 +1,306 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A file source based on native file system notifications, used when
+//! Watchman isn't available.
+
+use super::query_builder::{get_all_roots, is_ignored_dir, is_relevant_file};
+use super::{File, FileSourceResult};
+use crate::errors::{Error, Result};
+use crate::{compiler_state::CompilerState, config::Config};
+use common::{PerfLogEvent, PerfLogger};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::{future, StreamExt};
+use log::warn;
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+/// Rapid changes to the same file within this delay are reported as a single change.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(100);
+
+pub struct NotifyFileSource<'config> {
+    config: &'config Config,
+    root_dir: PathBuf,
+}
+
+impl<'config> NotifyFileSource<'config> {
+    pub fn connect(
+        config: &'config Config,
+        perf_logger_event: &impl PerfLogEvent,
+    ) -> Result<NotifyFileSource<'config>> {
+        let connect_timer = perf_logger_event.start("file_source_connect_time");
+        let root_dir =
+            std::fs::canonicalize(&config.root_dir).map_err(|err| Error::CanonicalizeRoot {
+                root: config.root_dir.clone(),
+                source: err,
+            })?;
+        perf_logger_event.stop(connect_timer);
+        Ok(Self { config, root_dir })
+    }
+
+    /// Walks the roots of the config to find all files to compile. Saved states
+    /// aren't supported since there is no clock to find the changes since then.
+    pub fn query(
+        &self,
+        perf_logger_event: &impl PerfLogEvent,
+        perf_logger: &impl PerfLogger,
+    ) -> Result<CompilerState> {
+        if self.config.load_saved_state_file.is_some() {
+            warn!("Saved states require Watchman, ignoring the saved state file.");
+        }
+        let query_timer = perf_logger_event.start("notify_query_time");
+        let files = query_files(self.config, &self.root_dir)?;
+        perf_logger_event.stop(query_timer);
+        CompilerState::from_file_source_changes(
+            self.config,
+            &FileSourceResult {
+                files,
+                resolved_root: self.root_dir.clone(),
+                clock: None,
+                is_fresh_instance: false,
+            },
+            perf_logger_event,
+            perf_logger,
+        )
+    }
+
+    pub fn subscribe(
+        self,
+        perf_logger_event: &impl PerfLogEvent,
+        perf_logger: &impl PerfLogger,
+    ) -> Result<(CompilerState, NotifyFileSourceSubscription<'config>)> {
+        // Start watching before the query, so no changes are missed in between
+        let (sender, receiver) = channel();
+        let mut watcher = watcher(sender, DEBOUNCE_DELAY)?;
+        let roots = get_all_roots(self.config);
+        // The `.relayignore` file is at the root directory, which isn't watched
+        // unless it's one of the roots
+        if !roots.iter().any(|root| root.as_os_str().is_empty()) {
+            watcher.watch(&self.root_dir, RecursiveMode::NonRecursive)?;
+        }
+        for root in roots {
+            let root = self.root_dir.join(root);
+            if root.exists() {
+                watcher.watch(root, RecursiveMode::Recursive)?;
+            }
+        }
+        // Forward the events from the watcher thread so they can be awaited
+        let (async_sender, async_receiver) = unbounded();
+        thread::spawn(move || {
+            for event in receiver {
+                if async_sender.unbounded_send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let compiler_state = self.query(perf_logger_event, perf_logger)?;
+        Ok((
+            compiler_state,
+            NotifyFileSourceSubscription {
+                config: self.config,
+                root_dir: self.root_dir,
+                _watcher: watcher,
+                receiver: async_receiver,
+                stopped: false,
+            },
+        ))
+    }
+}
+
+pub struct NotifyFileSourceSubscription<'config> {
+    config: &'config Config,
+    root_dir: PathBuf,
+    // The watcher stops sending events once it's dropped
+    _watcher: RecommendedWatcher,
+    receiver: UnboundedReceiver<DebouncedEvent>,
+    /// Whether the watcher stopped sending events, which is only reported once
+    stopped: bool,
+}
+
+impl<'config> NotifyFileSourceSubscription<'config> {
+    /// Awaits file system events and provides the next set of changes
+    /// if there were any changes to files relevant to the compiler. Once the watcher
+    /// stopped, which is returned as an error once, there are no more changes.
+    pub async fn next_change(&mut self) -> Result<Option<FileSourceResult>> {
+        if self.stopped {
+            return future::pending().await;
+        }
+        let mut events = match self.receiver.next().await {
+            Some(event) => vec![event],
+            None => {
+                self.stopped = true;
+                return Err(Error::FileWatcherStopped);
+            }
+        };
+        // Handle all events that are already available as a single change
+        while let Ok(Some(event)) = self.receiver.try_next() {
+            events.push(event);
+        }
+
+        let mut changed_paths = BTreeSet::new();
+        for event in events {
+            match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Remove(path) => {
+                    changed_paths.insert(path);
+                }
+                DebouncedEvent::Rename(from, to) => {
+                    changed_paths.insert(from);
+                    changed_paths.insert(to);
+                }
+                DebouncedEvent::Rescan => {
+                    // Events were missed, so treat every file as changed
+                    return self.query_all_files().map(Some);
+                }
+                DebouncedEvent::Error(error, _) => return Err(error.into()),
+                DebouncedEvent::NoticeWrite(_)
+                | DebouncedEvent::NoticeRemove(_)
+                | DebouncedEvent::Chmod(_) => {}
+            }
+        }
+
+        let files = changed_files(self.config, &self.root_dir, changed_paths);
+        if files.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(FileSourceResult {
+                files,
+                resolved_root: self.root_dir.clone(),
+                clock: None,
+                is_fresh_instance: false,
+            }))
+        }
+    }
+
+    /// Returns all files in the roots of the config relevant to the compiler
+    pub fn query_all_files(&self) -> Result<FileSourceResult> {
+        Ok(FileSourceResult {
+            files: query_files(self.config, &self.root_dir)?,
+            resolved_root: self.root_dir.clone(),
+            clock: None,
+            is_fresh_instance: false,
+        })
+    }
+}
+
+/// Returns the changed files relevant to the compiler, relative to the root. The
+/// artifacts aren't relevant, so the artifacts written by a compilation don't trigger
+/// another one.
+fn changed_files(config: &Config, root_dir: &Path, changed_paths: BTreeSet<PathBuf>) -> Vec<File> {
+    changed_paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.strip_prefix(root_dir).ok()?;
+            if path.is_dir() || !is_relevant_file(config, name) {
+                return None;
+            }
+            Some(File {
+                name: name.to_owned(),
+                exists: path.exists(),
+            })
+        })
+        .collect()
+}
+
+/// Returns all files in the roots of the config relevant to the compiler.
+fn query_files(config: &Config, root_dir: &Path) -> Result<Vec<File>> {
+    let mut files = vec![];
+    for root in get_all_roots(config) {
+        let root = root_dir.join(root);
+        if root.is_dir() {
+            walk_dir(config, root_dir, &root, &mut files)?;
+        }
+    }
+    Ok(files)
+}
+
+fn walk_dir(config: &Config, root_dir: &Path, dir: &Path, files: &mut Vec<File>) -> Result<()> {
+    let entries = std::fs::read_dir(dir).map_err(|err| Error::FileRead {
+        file: dir.to_owned(),
+        source: err,
+    })?;
+    for entry in entries {
+        let path = entry
+            .map_err(|err| Error::FileRead {
+                file: dir.to_owned(),
+                source: err,
+            })?
+            .path();
+        if path.is_dir() {
+            // Skip the directories without relevant files, e.g. `node_modules`
+            let is_ignored = path
+                .strip_prefix(root_dir)
+                .map_or(false, |name| is_ignored_dir(config, name));
+            if !is_ignored {
+                walk_dir(config, root_dir, &path, files)?;
+            }
+        } else if let Ok(name) = path.strip_prefix(root_dir) {
+            if is_relevant_file(config, name) {
+                files.push(File {
+                    name: name.to_owned(),
+                    exists: true,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignores_artifact_changes() {
+        let config = Config::from_string_for_test(
+            r#"
+                {
+                    "sources": {
+                        "src": "public",
+                        "lib": "internal"
+                    },
+                    "projects": {
+                        "public": {
+                            "schema": "graphql/public.graphql"
+                        },
+                        "internal": {
+                            "schema": "graphql/public.graphql",
+                            "output": "lib/generated",
+                            "artifactExtension": "ts"
+                        }
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+        let root_dir = Path::new("/repo");
+        let changed_paths = vec![
+            "src/Foo.js",
+            "src/__generated__/Foo_user.graphql.js",
+            "lib/Bar.js",
+            "lib/generated/BarQuery.graphql.js",
+            "lib/generated/nested/BarQuery.graphql.js",
+        ]
+        .into_iter()
+        .map(|path| root_dir.join(path))
+        .collect();
+        let names: Vec<PathBuf> = changed_files(&config, root_dir, changed_paths)
+            .into_iter()
+            .map(|file| file.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![PathBuf::from("lib/Bar.js"), PathBuf::from("src/Foo.js")]
+        );
+    }
+}
@@ -5,8 +5,10 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use crate::config::{Config, SchemaLocation};
-use std::path::PathBuf;
+use crate::config::{Config, SchemaLocation, GENERATED_DIRECTORY_NAME};
+use crate::relay_ignore::RELAY_IGNORE_FILE_NAME;
+use glob::{MatchOptions, Pattern};
+use std::path::{Path, PathBuf};
 use watchman_client::prelude::*;
 
 pub fn get_watchman_expr(config: &Config) -> Expr {
@@ -21,6 +23,18 @@ pub fn get_watchman_expr(config: &Config) -> Expr {
                 .collect(),
         ),
     ];
+    // not an artifact, the changes made by the compiler itself shouldn't trigger
+    // another compilation
+    let mut artifact_exprs: Vec<Expr> = get_artifact_directories(&config)
+        .into_iter()
+        .map(|path| Expr::DirName(DirNameTerm { path, depth: None }))
+        .collect();
+    artifact_exprs.push(Expr::Match(MatchTerm {
+        glob: format!("**/{}/**", GENERATED_DIRECTORY_NAME),
+        wholename: true,
+        ..Default::default()
+    }));
+    sources_conditions.push(Expr::Not(Box::new(expr_any(artifact_exprs))));
     // not blacklisted by any glob
     if !config.blacklist.is_empty() {
         sources_conditions.push(Expr::Not(Box::new(expr_any(
@@ -39,7 +53,14 @@ pub fn get_watchman_expr(config: &Config) -> Expr {
     }
     let sources_expr = Expr::All(sources_conditions);
 
-    let mut expressions = vec![sources_expr];
+    let mut expressions = vec![
+        sources_expr,
+        // changes to the ignored paths require rebuilding the compiler state
+        Expr::Name(NameTerm {
+            paths: vec![RELAY_IGNORE_FILE_NAME.into()],
+            wholename: true,
+        }),
+    ];
 
     let schema_file_paths = get_schema_file_paths(&config);
     if !schema_file_paths.is_empty() {
@@ -69,6 +90,66 @@ pub fn get_watchman_expr(config: &Config) -> Expr {
     ])
 }
 
+/// Checks if a path relative to the root matches the Watchman expression from
+/// `get_watchman_expr`, for file sources that can't use Watchman queries.
+pub fn is_relevant_file(config: &Config, path: &Path) -> bool {
+    let in_any_dir = |dirs: Vec<PathBuf>| dirs.iter().any(|dir| path.starts_with(dir));
+    let has_extension = |extension: &str| path.extension().map_or(false, |ext| ext == extension);
+
+    let is_source = has_extension("js")
+        && in_any_dir(get_source_roots(config))
+        && !config.is_artifact_path(path)
+        && !config
+            .blacklist
+            .iter()
+            .any(|item| matches_blacklist_item(item, path));
+
+    is_source
+        || path == Path::new(RELAY_IGNORE_FILE_NAME)
+        || get_schema_file_paths(config)
+            .iter()
+            .any(|schema_file| schema_file == path)
+        || (has_extension("graphql")
+            && (in_any_dir(get_schema_dir_paths(config))
+                || in_any_dir(get_extension_roots(config))))
+}
+
+/// Checks if a directory relative to the root can't contain any file relevant to the
+/// compiler, so that file sources walking the roots don't need to read it, e.g.
+/// `node_modules` or `__generated__` directories. These are the artifact directories
+/// and the directories whose files are all excluded by an item of the `blacklist`
+/// ending in `/**`, unless they contain schema or extension files.
+pub fn is_ignored_dir(config: &Config, dir: &Path) -> bool {
+    let has_graphql_files = get_schema_file_paths(config)
+        .into_iter()
+        .chain(get_schema_dir_paths(config))
+        .chain(get_extension_roots(config))
+        .any(|path| path.starts_with(dir) || dir.starts_with(&path));
+    if has_graphql_files {
+        return false;
+    }
+    config.is_artifact_path(dir)
+        || config.blacklist.iter().any(|item| {
+            item.ends_with("/**") && {
+                let dir_item = &item[..item.len() - "/**".len()];
+                dir.ancestors()
+                    .any(|ancestor| matches_blacklist_item(dir_item, ancestor))
+            }
+        })
+}
+
+fn matches_blacklist_item(item: &str, path: &Path) -> bool {
+    Pattern::new(item).map_or(false, |pattern| {
+        pattern.matches_path_with(
+            path,
+            MatchOptions {
+                require_literal_separator: true,
+                ..Default::default()
+            },
+        )
+    })
+}
+
 /// Compute all root paths that we need to query Watchman with. All files
 /// relevant to the compiler should be in these directories.
 pub fn get_all_roots(config: &Config) -> Vec<PathBuf> {
@@ -91,6 +172,16 @@ fn get_source_roots(config: &Config) -> Vec<PathBuf> {
     config.sources.keys().cloned().collect()
 }
 
+/// Returns the artifact directories of the projects that don't write their
+/// artifacts next to the source files.
+fn get_artifact_directories(config: &Config) -> Vec<PathBuf> {
+    config
+        .projects
+        .values()
+        .filter_map(|project_config| project_config.artifact_directory().cloned())
+        .collect()
+}
+
 /// Returns all root directories of GraphQL schema extension files for the
 /// config.
 fn get_extension_roots(config: &Config) -> Vec<PathBuf> {
@@ -106,9 +197,9 @@ fn get_schema_file_paths(config: &Config) -> Vec<PathBuf> {
     config
         .projects
         .values()
-        .filter_map(|project_config| match &project_config.schema_location {
-            SchemaLocation::File(schema_file) => Some(schema_file.clone()),
-            SchemaLocation::Directory(_) => None,
+        .flat_map(|project_config| match &project_config.schema_location {
+            SchemaLocation::Files(schema_files) => schema_files.clone(),
+            SchemaLocation::Directory(_) => vec![],
         })
         .collect()
 }
@@ -119,7 +210,7 @@ fn get_schema_dir_paths(config: &Config) -> Vec<PathBuf> {
         .projects
         .values()
         .filter_map(|project_config| match &project_config.schema_location {
-            SchemaLocation::File(_) => None,
+            SchemaLocation::Files(_) => None,
             SchemaLocation::Directory(schema_dir) => Some(schema_dir.clone()),
         })
         .collect()
@@ -186,6 +277,80 @@ fn unify_roots(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_is_relevant_file() {
+        let config = Config::from_string_for_test(
+            r#"
+                {
+                    "sources": {
+                        "src": "public"
+                    },
+                    "blacklist": ["src/vendor/**"],
+                    "projects": {
+                        "public": {
+                            "schema": "graphql/public.graphql",
+                            "extensions": ["graphql/extensions"]
+                        }
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+        assert!(is_relevant_file(&config, Path::new("src/Foo.js")));
+        assert!(is_relevant_file(&config, Path::new("src/nested/Foo.js")));
+        assert!(!is_relevant_file(&config, Path::new("src/Foo.css")));
+        assert!(!is_relevant_file(&config, Path::new("src/vendor/Foo.js")));
+        assert!(!is_relevant_file(&config, Path::new("lib/Foo.js")));
+        assert!(!is_relevant_file(
+            &config,
+            Path::new("src/__generated__/Foo_user.graphql.js")
+        ));
+        assert!(is_relevant_file(&config, Path::new(".relayignore")));
+        assert!(!is_relevant_file(&config, Path::new("src/.relayignore")));
+        assert!(is_relevant_file(
+            &config,
+            Path::new("graphql/public.graphql")
+        ));
+        assert!(!is_relevant_file(
+            &config,
+            Path::new("graphql/other.graphql")
+        ));
+        assert!(is_relevant_file(
+            &config,
+            Path::new("graphql/extensions/client.graphql")
+        ));
+    }
+
+    #[test]
+    fn test_is_ignored_dir() {
+        let config = Config::from_string_for_test(
+            r#"
+                {
+                    "sources": {
+                        "src": "public"
+                    },
+                    "blacklist": ["**/node_modules/**", "src/vendor/**", "src/*.test.js"],
+                    "projects": {
+                        "public": {
+                            "schema": "graphql/public.graphql",
+                            "extensions": ["src/vendor/extensions"]
+                        }
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+        assert!(is_ignored_dir(&config, Path::new("src/node_modules")));
+        assert!(is_ignored_dir(&config, Path::new("src/lib/node_modules")));
+        assert!(is_ignored_dir(&config, Path::new("src/__generated__")));
+        assert!(is_ignored_dir(&config, Path::new("src/vendor/lib")));
+        assert!(!is_ignored_dir(&config, Path::new("src")));
+        assert!(!is_ignored_dir(&config, Path::new("src/components")));
+        // The extensions of the project are in the blacklisted directory
+        assert!(!is_ignored_dir(&config, Path::new("src/vendor")));
+        assert!(!is_ignored_dir(&config, Path::new("src/vendor/extensions")));
+    }
+
     #[test]
     fn test_unify_roots() {
         assert_eq!(unify_roots(vec![]).len(), 0);
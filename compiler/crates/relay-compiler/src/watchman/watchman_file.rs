@@ -7,6 +7,7 @@
 
 use crate::errors::{Error, Result};
 use serde::Deserialize;
+use std::path::{Path, PathBuf};
 use watchman_client::prelude::*;
 
 query_result_type! {
@@ -17,13 +18,28 @@ query_result_type! {
     }
 }
 
+/// A file reported by a `FileSource`. The name is relative to the root of the file source.
+#[derive(Clone, Debug)]
+pub struct File {
+    pub name: PathBuf,
+    pub exists: bool,
+}
+
+impl From<&WatchmanFile> for File {
+    fn from(file: &WatchmanFile) -> Self {
+        Self {
+            name: (*file.name).to_owned(),
+            exists: *file.exists,
+        }
+    }
+}
+
 /// Reads a file into a string.
-pub fn read_to_string(resolved_root: &ResolvedRoot, file: &WatchmanFile) -> Result<String> {
-    if !(*file.exists) {
-        unreachable!("Can't read from non-existent file: {:?}", *file.name);
+pub fn read_to_string(resolved_root: &Path, file: &File) -> Result<String> {
+    if !file.exists {
+        unreachable!("Can't read from non-existent file: {:?}", file.name);
     }
-    let mut absolute_path = resolved_root.path();
-    absolute_path.push(&*file.name);
+    let absolute_path = resolved_root.join(&file.name);
     std::fs::read_to_string(&absolute_path).map_err(|err| Error::FileRead {
         file: absolute_path.clone(),
         source: err,
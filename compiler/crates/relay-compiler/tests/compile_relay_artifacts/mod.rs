@@ -55,6 +55,9 @@ pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
         program,
         &Default::default(),
         &*OSS_CONNECTION_INTERFACE,
+        false,
+        false,
+        None,
         &ConsoleLogger,
     )
     .map_err(validation_errors_to_string)?;
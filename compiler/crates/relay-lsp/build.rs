@@ -0,0 +1,44 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::Path;
+use std::process::Command;
+
+/// Sets `RELAY_LSP_GIT_HASH` to the commit the server is built from, appended to the
+/// version reported by the server, or to `unknown` outside of a git checkout.
+fn main() {
+    let git_hash = git(&["rev-parse", "--short", "HEAD"]);
+    println!(
+        "cargo:rustc-env=RELAY_LSP_GIT_HASH={}",
+        git_hash.as_deref().unwrap_or("unknown")
+    );
+    println!("cargo:rerun-if-changed=build.rs");
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        let git_dir = Path::new(&git_dir);
+        // `HEAD` changes when switching branches, the branch it points to when
+        // committing, and the branches are moved to `packed-refs` by `git gc`
+        let mut paths = vec![git_dir.join("HEAD"), git_dir.join("packed-refs")];
+        if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            paths.push(git_dir.join(head_ref));
+        }
+        // A path that doesn't exist would run the script on each build
+        for path in paths.iter().filter(|path| path.exists()) {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+}
+
+/// The trimmed output of a successful git command
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|output| output.trim().to_string())
+}
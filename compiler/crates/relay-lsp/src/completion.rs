@@ -6,34 +6,117 @@
  */
 
 //! Utilities for providing the completion language feature
+use crate::hover::{get_custom_scalar_client_type, get_deprecation_reason};
 use crate::lsp::Position;
 use common::{FileKey, Span};
 use graphql_syntax::{parse, Document, GraphQLSource};
-use interner::StringKey;
-use log::info;
+use interner::{Intern, StringKey};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
+use graphql_ir::{Program, Value, VariableDefinition};
+use graphql_text_printer::print_value;
 use relay_compiler::Programs;
 
 use crate::lsp::{
-    CompletionItem, CompletionParams, CompletionResponse, Connection, Message, ServerRequestId,
-    ServerResponse, TextDocumentPositionParams, Url,
+    CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse, Connection,
+    Documentation, InsertTextFormat, MarkupContent, MarkupKind, Message, Range, ServerRequestId,
+    ServerResponse, TextDocumentPositionParams, TextEdit, Url,
 };
 use schema::{
-    Directive as SchemaDirective, DirectiveLocation, Schema, Type, TypeReference, TypeWithFields,
+    get_relay_directive_argument, Argument as SchemaArgument, ArgumentDefinitions,
+    Directive as SchemaDirective, DirectiveLocation, Object, RelayDirectiveArgument,
+    RelayDirectiveArgumentKind, Schema, Type, TypeReference, TypeWithFields,
 };
 
 use graphql_syntax::{
-    Directive, ExecutableDefinition, FragmentSpread, InlineFragment, LinkedField, List,
-    OperationDefinition, OperationKind, ScalarField, Selection,
+    Argument, ConstantValue, Directive, ExecutableDefinition, FragmentSpread, InlineFragment,
+    LinkedField, List, OperationDefinition, OperationKind, ScalarField, Selection, TypeAnnotation,
+    Value as SyntaxValue,
 };
 
-pub type GraphQLSourceCache = std::collections::HashMap<Url, Vec<GraphQLSource>>;
+/// The characters requesting a completion as they are typed: directive names after
+/// `@`, fragment spreads after `...`, argument names after `(`, argument values after
+/// `:`, variables after `$`, and the type conditions following `on ` after a space.
+pub const COMPLETION_TRIGGER_CHARACTERS: &[&str] = &["@", ".", "(", ":", "$", " "];
+
+const DIRECTIVE_NAME_PLACEHOLDER: &str = "__directive";
+const TYPE_CONDITION_PLACEHOLDER: &str = "__TypeCondition";
+const VARIABLE_NAME_PLACEHOLDER: &str = "__variable";
+const ARGUMENTS_DIRECTIVE_NAME: &str = "arguments";
+const ARGUMENT_DEFINITIONS_DIRECTIVE_NAME: &str = "argumentDefinitions";
+/// The `sortText` of the items of required fields and arguments until they are ranked
+/// by `rank_completion_items`
+const REQUIRED_SORT_TEXT: &str = "required";
+
+pub type GraphQLSourceCache = HashMap<Url, Vec<GraphQLSource>>;
+
+/// The `data` of completion items that have documentation, which is only sent once the
+/// client resolves the item. The coordinate identifies the schema member of the item,
+/// e.g. `User.name` for a field or enum value, or `@include` for a directive.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionItemData {
+    project_name: String,
+    coordinate: String,
+}
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum CompletionKind {
     FieldName,
     FragmentSpread,
-    DirectiveName { location: DirectiveLocation },
+    DirectiveName {
+        location: DirectiveLocation,
+    },
+    ArgumentName {
+        field_name: StringKey,
+    },
+    ArgumentValue {
+        field_name: StringKey,
+        argument_name: StringKey,
+    },
+    DirectiveArgumentName {
+        directive_name: StringKey,
+    },
+    DirectiveArgumentValue {
+        directive_name: StringKey,
+        argument_name: StringKey,
+    },
+    /// The name of a field of an input object in the value of a field argument, the
+    /// input object is at the `input_value_path` of the request
+    ArgumentInputFieldName {
+        field_name: StringKey,
+        argument_name: StringKey,
+    },
+    /// The name of a field of an input object in the value of a directive argument
+    DirectiveArgumentInputFieldName {
+        directive_name: StringKey,
+        argument_name: StringKey,
+    },
+    /// The name of an argument of `@arguments` on a spread of the fragment
+    FragmentArgumentName {
+        fragment_name: StringKey,
+    },
+    /// The name of a variable in the value of an argument, one of the `variables` of
+    /// the request
+    VariableName,
+    InlineFragmentTypeCondition,
+    /// The keyword starting a definition, at the top level of a GraphQL source
+    DefinitionKeyword,
+    /// The name of the operation or fragment after the keyword starting its definition,
+    /// suggested from the name of the module the GraphQL source is in
+    DefinitionName {
+        definition_kind: DefinitionKind,
+        module_name: Option<StringKey>,
+    },
+    FragmentTypeCondition,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum DefinitionKind {
+    Operation(OperationKind),
+    Fragment,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -43,6 +126,19 @@ pub struct CompletionRequest {
     /// A list of type metadata that we can use to resolve the leaf
     /// type the request is being made against
     type_path: Vec<TypePathItem>,
+    /// The names of the input object fields enclosing the position in the value of an
+    /// argument, e.g. `["name"]` for `(filter: { name: { | } })`
+    input_value_path: Vec<StringKey>,
+    /// The variables in scope at the position with their printed types: the variable
+    /// definitions of the operation, or the arguments the fragment declares with
+    /// `@argumentDefinitions`
+    variables: Vec<(StringKey, String)>,
+    /// The character whose typing triggered the request, one of
+    /// `COMPLETION_TRIGGER_CHARACTERS`, or `None` if it was invoked by the user
+    trigger_character: Option<String>,
+    /// The part of the name being completed typed before the position, e.g. `na` for
+    /// `{ na| }`, used to rank the items
+    typed_name: String,
 }
 
 impl Default for CompletionRequest {
@@ -50,6 +146,10 @@ impl Default for CompletionRequest {
         CompletionRequest {
             kind: CompletionKind::FieldName,
             type_path: vec![],
+            input_value_path: vec![],
+            variables: vec![],
+            trigger_character: None,
+            typed_name: String::new(),
         }
     }
 }
@@ -60,14 +160,18 @@ impl CompletionRequest {
     }
 
     /// Returns the leaf type, which is the type that the completion request is being made against.
-    fn resolve_leaf_type(self, schema: &Schema) -> Type {
+    /// The type is narrowed by the type conditions of the enclosing inline fragments and
+    /// fragment definition. Returns `None` if a type or field of the path isn't part of the
+    /// schema, e.g. while its name is being typed.
+    fn resolve_leaf_type(self, schema: &Schema) -> Option<Type> {
         let mut type_path = self.type_path;
         type_path.reverse();
-        let mut type_ = resolve_root_type(type_path.pop().expect("path must be non-empty"), schema);
+        let mut type_ =
+            resolve_root_type(type_path.pop().expect("path must be non-empty"), schema)?;
         while let Some(path_item) = type_path.pop() {
-            type_ = resolve_relative_type(type_, path_item, schema);
+            type_ = resolve_relative_type(type_, path_item, schema)?;
         }
-        type_
+        Some(type_)
     }
 }
 
@@ -97,10 +201,23 @@ pub fn create_completion_request(document: Document, position_span: Span) -> Com
                         operation.name
                     );
                     let OperationDefinition {
+                        variable_definitions,
                         selections,
                         directives,
                         ..
                     } = operation;
+                    if let Some(variable_definitions) = variable_definitions {
+                        completion_request.variables = variable_definitions
+                            .items
+                            .iter()
+                            .map(|variable_definition| {
+                                (
+                                    variable_definition.name.name,
+                                    print_type_annotation(&variable_definition.type_),
+                                )
+                            })
+                            .collect();
+                    }
 
                     let directive_location = match kind {
                         OperationKind::Query => DirectiveLocation::Query,
@@ -122,6 +239,8 @@ pub fn create_completion_request(document: Document, position_span: Span) -> Com
                 if fragment.location.contains(position_span) {
                     let type_name = fragment.type_condition.type_.value;
                     completion_request.add_type(TypePathItem::FragmentDefinition { type_name });
+                    completion_request.variables =
+                        fragment_argument_definitions(&fragment.directives);
                     build_request_from_selection_or_directives(
                         &fragment.selections,
                         &fragment.directives,
@@ -138,14 +257,14 @@ pub fn create_completion_request(document: Document, position_span: Span) -> Com
 }
 
 /// Resolves the root type of this completion path.
-fn resolve_root_type(root_path_item: TypePathItem, schema: &Schema) -> Type {
+fn resolve_root_type(root_path_item: TypePathItem, schema: &Schema) -> Option<Type> {
     match root_path_item {
         TypePathItem::Operation(kind) => match kind {
-            OperationKind::Query => schema.query_type().unwrap(),
-            OperationKind::Mutation => schema.mutation_type().unwrap(),
-            OperationKind::Subscription => schema.subscription_type().unwrap(),
+            OperationKind::Query => schema.query_type(),
+            OperationKind::Mutation => schema.mutation_type(),
+            OperationKind::Subscription => schema.subscription_type(),
         },
-        TypePathItem::FragmentDefinition { type_name } => schema.get_type(type_name).unwrap(),
+        TypePathItem::FragmentDefinition { type_name } => schema.get_type(type_name),
         _ => {
             // TODO(brandondail) fail silently and log here instead
             panic!("Completion paths must start with an operation or fragment")
@@ -153,7 +272,11 @@ fn resolve_root_type(root_path_item: TypePathItem, schema: &Schema) -> Type {
     }
 }
 
-fn resolve_relative_type(parent_type: Type, path_item: TypePathItem, schema: &Schema) -> Type {
+fn resolve_relative_type(
+    parent_type: Type,
+    path_item: TypePathItem,
+    schema: &Schema,
+) -> Option<Type> {
     match path_item {
         TypePathItem::Operation(_) => {
             // TODO(brandondail) fail silently and log here instead
@@ -164,19 +287,24 @@ fn resolve_relative_type(parent_type: Type, path_item: TypePathItem, schema: &Sc
             panic!("Fragments must only exist at the root of the completion path");
         }
         TypePathItem::LinkedField { name } => {
-            let field_id = schema.named_field(parent_type, name).unwrap();
+            let field_id = schema.named_field(parent_type, name)?;
             let field = schema.field(field_id);
             info!("resolved type for {:?} : {:?}", field.name, field.type_);
-            field.type_.inner()
+            Some(field.type_.inner())
         }
-        TypePathItem::ScalarField { .. } => parent_type,
-        TypePathItem::InlineFragment { type_name } => schema.get_type(type_name).unwrap(),
+        TypePathItem::ScalarField { .. } => Some(parent_type),
+        TypePathItem::InlineFragment { type_name } => schema.get_type(type_name),
     }
 }
 
+/// The detail of a field of a custom scalar type contains the type and its client
+/// type, e.g. `DateTime (string)`.
 fn resolve_completion_items_from_fields<T: TypeWithFields>(
     type_: &T,
+    type_name: StringKey,
     schema: &Schema,
+    project_name: StringKey,
+    custom_scalars: &HashMap<StringKey, String>,
 ) -> Vec<CompletionItem> {
     type_
         .fields()
@@ -184,20 +312,84 @@ fn resolve_completion_items_from_fields<T: TypeWithFields>(
         .map(|field_id| {
             let field = schema.field(*field_id);
             let name = field.name.to_string();
-            CompletionItem::new_simple(name, String::from(""))
+            let detail = match get_custom_scalar_client_type(&field.type_, schema, custom_scalars) {
+                Some(client_type) => {
+                    format!("{} ({})", schema.get_type_string(&field.type_), client_type)
+                }
+                None => String::from(""),
+            };
+            let mut item = CompletionItem::new_simple(name, detail);
+            item.data = completion_item_data(project_name, format!("{}.{}", type_name, field.name));
+            if field.type_.is_non_null() {
+                item.sort_text = Some(REQUIRED_SORT_TEXT.to_string());
+            }
+            if get_deprecation_reason(&field.directives).is_some() {
+                item.deprecated = Some(true);
+            }
+            if let Some(snippet) = arguments_snippet(&item.label, &field.arguments, schema) {
+                item.insert_text = Some(snippet);
+                item.insert_text_format = Some(InsertTextFormat::Snippet);
+            }
+            item
+        })
+        .collect()
+}
+
+/// Keeps one item per field name of an object, the first one built from the object's
+/// own definition, so that its documentation and deprecation are resolved from the
+/// object rather than from an interface. The interfaces of the object declaring the
+/// field are added to the detail, e.g. `from Node, Actor`.
+fn dedupe_object_field_items(
+    items: Vec<CompletionItem>,
+    object: &Object,
+    schema: &Schema,
+) -> Vec<CompletionItem> {
+    let mut seen_labels = HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen_labels.insert(item.label.clone()))
+        .map(|mut item| {
+            let field_name = item.label.as_str().intern();
+            let interface_names: Vec<&str> = object
+                .interfaces
+                .iter()
+                .map(|interface_id| schema.interface(*interface_id))
+                .filter(|interface| {
+                    interface
+                        .fields
+                        .iter()
+                        .any(|field_id| schema.field(*field_id).name == field_name)
+                })
+                .map(|interface| interface.name.lookup())
+                .collect();
+            if !interface_names.is_empty() {
+                let provenance = format!("from {}", interface_names.join(", "));
+                item.detail = Some(match item.detail.as_deref() {
+                    Some(detail) if !detail.is_empty() => format!("{}, {}", detail, provenance),
+                    _ => provenance,
+                });
+            }
+            item
         })
         .collect()
 }
 
-/// Finds all the valid fragment names for a given type. Used to complete fragment spreads
-fn get_valid_fragments_for_type(type_: Type, programs: &Programs<'_>) -> Vec<StringKey> {
+/// Finds all the valid fragment names for a given type, the fragments whose type condition
+/// overlaps with the type, e.g. fragments on an interface the type implements. Used to
+/// complete fragment spreads
+fn get_valid_fragments_for_type(type_: Type, program: &Program<'_>) -> Vec<StringKey> {
+    let schema = program.schema();
     let mut valid_fragment_names = vec![];
-    for fragment in programs.source.fragments() {
-        if fragment.type_condition == type_ {
+    for fragment in program.fragments() {
+        if schema.are_overlapping_types(fragment.type_condition, type_) {
             valid_fragment_names.push(fragment.name.item);
         }
     }
-    info!("get_valid_fragments_for_type {:#?}", valid_fragment_names);
+    valid_fragment_names.sort_by_key(|fragment_name| fragment_name.lookup());
+    debug!(
+        "Found {} fragments valid for the type",
+        valid_fragment_names.len()
+    );
     valid_fragment_names
 }
 
@@ -205,7 +397,7 @@ fn resolve_completion_items_for_fragment_spread(
     type_: Type,
     programs: &Programs<'_>,
 ) -> Vec<CompletionItem> {
-    get_valid_fragments_for_type(type_, programs)
+    get_valid_fragments_for_type(type_, &programs.source)
         .iter()
         .map(|fragment_name| {
             CompletionItem::new_simple(fragment_name.to_string(), String::from(""))
@@ -213,13 +405,72 @@ fn resolve_completion_items_for_fragment_spread(
         .collect()
 }
 
+/// Returns the completion items for the request, without their documentation, see
+/// `resolve_completion_item`. The items are resolved against the schema of `project_name`.
 pub fn completion_items_for_request(
     request: CompletionRequest,
     schema: &Schema,
     programs: Option<&Programs<'_>>,
+    project_name: StringKey,
+    custom_scalars: &HashMap<StringKey, String>,
+) -> Option<Vec<CompletionItem>> {
+    let typed_name = request.typed_name.clone();
+    let items = completion_items_for_kind(request, schema, programs, project_name, custom_scalars)?;
+    Some(rank_completion_items(items, &typed_name))
+}
+
+/// Sets the `sortText` of the items so that, after the items matching the typed name
+/// exactly and those starting with it, the required fields and arguments are listed
+/// first. The deprecated fields are listed last, and items of the same rank are sorted
+/// by label. The `filterText` is the label, e.g. without the arguments of a snippet.
+fn rank_completion_items(items: Vec<CompletionItem>, typed_name: &str) -> Vec<CompletionItem> {
+    let typed_name = typed_name.to_lowercase();
+    items
+        .into_iter()
+        .map(|mut item| {
+            let label = item.label.to_lowercase();
+            let deprecated_rank = if item.deprecated == Some(true) { 1 } else { 0 };
+            let match_rank = if label == typed_name {
+                0
+            } else if label.starts_with(&typed_name) {
+                1
+            } else {
+                2
+            };
+            let required_rank = if item.sort_text.as_deref() == Some(REQUIRED_SORT_TEXT) {
+                0
+            } else {
+                1
+            };
+            item.sort_text = Some(format!(
+                "{}{}{}_{}",
+                deprecated_rank, match_rank, required_rank, item.label
+            ));
+            item.filter_text = Some(item.label.clone());
+            item
+        })
+        .collect()
+}
+
+fn completion_items_for_kind(
+    request: CompletionRequest,
+    schema: &Schema,
+    programs: Option<&Programs<'_>>,
+    project_name: StringKey,
+    custom_scalars: &HashMap<StringKey, String>,
 ) -> Option<Vec<CompletionItem>> {
     let kind = request.kind;
-    let leaf_type = request.resolve_leaf_type(schema);
+    if !is_completed_after_trigger_character(request.trigger_character.as_deref(), kind) {
+        return Some(vec![]);
+    }
+    if let Some(items) = completion_items_for_definition(kind, schema) {
+        return Some(items);
+    }
+    if let CompletionKind::VariableName = kind {
+        return Some(completion_items_for_variables(&request.variables));
+    }
+    let input_value_path = request.input_value_path.clone();
+    let leaf_type = request.resolve_leaf_type(schema)?;
     info!("completion_items_for_request: {:?} - {:?}", leaf_type, kind);
     match kind {
         CompletionKind::FragmentSpread => {
@@ -233,13 +484,25 @@ pub fn completion_items_for_request(
         CompletionKind::FieldName => match leaf_type {
             Type::Interface(interface_id) => {
                 let interface = schema.interface(interface_id);
-                let items = resolve_completion_items_from_fields(interface, schema);
+                let items = resolve_completion_items_from_fields(
+                    interface,
+                    interface.name,
+                    schema,
+                    project_name,
+                    custom_scalars,
+                );
                 Some(items)
             }
             Type::Object(object_id) => {
                 let object = schema.object(object_id);
-                let items = resolve_completion_items_from_fields(object, schema);
-                Some(items)
+                let items = resolve_completion_items_from_fields(
+                    object,
+                    object.name,
+                    schema,
+                    project_name,
+                    custom_scalars,
+                );
+                Some(dedupe_object_field_items(items, object, schema))
             }
             Type::Enum(_) | Type::InputObject(_) | Type::Scalar(_) | Type::Union(_) => None,
         },
@@ -247,13 +510,424 @@ pub fn completion_items_for_request(
             let directives = schema.directives_for_location(location);
             let items = directives
                 .iter()
-                .map(|directive| completion_item_from_directive(directive, schema))
+                .map(|directive| completion_item_from_directive(directive, schema, project_name))
+                .collect();
+            Some(items)
+        }
+        CompletionKind::ArgumentName { field_name } => {
+            let field = schema.field(schema.named_field(leaf_type, field_name)?);
+            let items = field
+                .arguments
+                .iter()
+                .map(|argument| completion_item_from_argument(argument, schema))
+                .collect();
+            Some(items)
+        }
+        CompletionKind::ArgumentValue {
+            field_name,
+            argument_name,
+        } => {
+            let field = schema.field(schema.named_field(leaf_type, field_name)?);
+            let argument = field.arguments.named(argument_name)?;
+            let argument = resolve_input_value_argument(argument, &input_value_path, schema)?;
+            completion_items_for_argument_value(argument, schema, project_name)
+        }
+        CompletionKind::ArgumentInputFieldName {
+            field_name,
+            argument_name,
+        } => {
+            let field = schema.field(schema.named_field(leaf_type, field_name)?);
+            let argument = field.arguments.named(argument_name)?;
+            let argument = resolve_input_value_argument(argument, &input_value_path, schema)?;
+            completion_items_for_input_object_fields(argument, schema)
+        }
+        CompletionKind::DirectiveArgumentName { directive_name } => {
+            let directive = schema.get_directive(directive_name)?;
+            let items = directive
+                .arguments
+                .iter()
+                .map(|argument| completion_item_from_argument(argument, schema))
+                .collect();
+            Some(items)
+        }
+        CompletionKind::DirectiveArgumentValue {
+            directive_name,
+            argument_name,
+        } => {
+            let directive = schema.get_directive(directive_name)?;
+            let argument = directive.arguments.named(argument_name)?;
+            if !input_value_path.is_empty() {
+                let argument = resolve_input_value_argument(argument, &input_value_path, schema)?;
+                return completion_items_for_argument_value(argument, schema, project_name);
+            }
+            if let Some(relay_argument) =
+                get_relay_directive_argument(directive_name, argument_name)
+            {
+                return Some(completion_items_for_relay_directive_argument(
+                    relay_argument,
+                ));
+            }
+            completion_items_for_argument_value(argument, schema, project_name)
+        }
+        CompletionKind::DirectiveArgumentInputFieldName {
+            directive_name,
+            argument_name,
+        } => {
+            let directive = schema.get_directive(directive_name)?;
+            let argument = directive.arguments.named(argument_name)?;
+            let argument = resolve_input_value_argument(argument, &input_value_path, schema)?;
+            completion_items_for_input_object_fields(argument, schema)
+        }
+        CompletionKind::FragmentArgumentName { fragment_name } => {
+            // The fragment may not be part of the programs yet, e.g. before the first check
+            let fragment =
+                match programs.and_then(|programs| programs.source.fragment(fragment_name)) {
+                    Some(fragment) => fragment,
+                    None => return Some(vec![]),
+                };
+            let items = fragment
+                .variable_definitions
+                .iter()
+                .map(|argument_definition| {
+                    completion_item_from_argument_definition(argument_definition, schema)
+                })
+                .collect();
+            Some(items)
+        }
+        CompletionKind::InlineFragmentTypeCondition => {
+            Some(completion_items_for_type_condition(Some(leaf_type), schema))
+        }
+        // Completed by `completion_items_for_definition` and `completion_items_for_variables`
+        CompletionKind::DefinitionKeyword
+        | CompletionKind::DefinitionName { .. }
+        | CompletionKind::FragmentTypeCondition
+        | CompletionKind::VariableName => None,
+    }
+}
+
+/// Checks if a completion of `kind` is expected after typing `trigger_character`, e.g.
+/// `@` only triggers the completion of directive names and `$` the completion of
+/// variables, so that the editor doesn't list e.g. fields after each space. Requests
+/// invoked by the user without a trigger character are always completed.
+fn is_completed_after_trigger_character(
+    trigger_character: Option<&str>,
+    kind: CompletionKind,
+) -> bool {
+    match trigger_character {
+        None => true,
+        Some("@") => matches!(kind, CompletionKind::DirectiveName { .. }),
+        Some("$") => matches!(kind, CompletionKind::VariableName),
+        Some(".") => matches!(kind, CompletionKind::FragmentSpread),
+        Some("(") => matches!(
+            kind,
+            CompletionKind::ArgumentName { .. }
+                | CompletionKind::DirectiveArgumentName { .. }
+                | CompletionKind::FragmentArgumentName { .. }
+        ),
+        Some(":") => matches!(
+            kind,
+            CompletionKind::ArgumentValue { .. }
+                | CompletionKind::DirectiveArgumentValue { .. }
+                | CompletionKind::ArgumentInputFieldName { .. }
+                | CompletionKind::DirectiveArgumentInputFieldName { .. }
+        ),
+        Some(" ") => matches!(
+            kind,
+            CompletionKind::InlineFragmentTypeCondition
+                | CompletionKind::FragmentTypeCondition
+                | CompletionKind::DefinitionName { .. }
+        ),
+        Some(_) => true,
+    }
+}
+
+/// The variables are labeled without the `$` already typed, with their type as detail
+fn completion_items_for_variables(variables: &[(StringKey, String)]) -> Vec<CompletionItem> {
+    variables
+        .iter()
+        .map(|(name, type_)| {
+            let mut item = CompletionItem::new_simple(name.to_string(), type_.clone());
+            item.kind = Some(CompletionItemKind::Variable);
+            item
+        })
+        .collect()
+}
+
+fn print_type_annotation(type_: &TypeAnnotation) -> String {
+    match type_ {
+        TypeAnnotation::Named(name) => name.value.to_string(),
+        TypeAnnotation::List(list) => format!("[{}]", print_type_annotation(&list.type_)),
+        TypeAnnotation::NonNull(non_null) => {
+            format!("{}!", print_type_annotation(&non_null.type_))
+        }
+    }
+}
+
+/// The arguments declared by a fragment with `@argumentDefinitions`, e.g.
+/// `@argumentDefinitions(count: {type: "Int"})`, with the `type` of their definition.
+fn fragment_argument_definitions(directives: &[Directive]) -> Vec<(StringKey, String)> {
+    let arguments = match directives
+        .iter()
+        .find(|directive| directive.name.value.lookup() == ARGUMENT_DEFINITIONS_DIRECTIVE_NAME)
+        .and_then(|directive| directive.arguments.as_ref())
+    {
+        Some(arguments) => arguments,
+        None => return vec![],
+    };
+    arguments
+        .items
+        .iter()
+        .map(|argument| {
+            let type_ = match &argument.value {
+                SyntaxValue::Constant(ConstantValue::Object(object)) => object
+                    .items
+                    .iter()
+                    .find(|field| field.name.value.lookup() == "type")
+                    .and_then(|field| match &field.value {
+                        ConstantValue::String(type_) => Some(type_.value.to_string()),
+                        _ => None,
+                    }),
+                _ => None,
+            };
+            (argument.name.value, type_.unwrap_or_default())
+        })
+        .collect()
+}
+
+/// Completes the definitions at the top level of a GraphQL source, which don't have a
+/// parent type: the keywords of the operation types of the schema and `fragment`, the
+/// names of new definitions and the type conditions of fragments. Returns `None` for
+/// the other kinds of completions.
+fn completion_items_for_definition(
+    kind: CompletionKind,
+    schema: &Schema,
+) -> Option<Vec<CompletionItem>> {
+    match kind {
+        CompletionKind::DefinitionKeyword => {
+            let mut keywords = vec!["query"];
+            if schema.mutation_type().is_some() {
+                keywords.push("mutation");
+            }
+            if schema.subscription_type().is_some() {
+                keywords.push("subscription");
+            }
+            keywords.push("fragment");
+            let items = keywords
+                .into_iter()
+                .map(|keyword| {
+                    let mut item =
+                        CompletionItem::new_simple(keyword.to_string(), String::from(""));
+                    item.kind = Some(CompletionItemKind::Keyword);
+                    item
+                })
+                .collect();
+            Some(items)
+        }
+        CompletionKind::DefinitionName {
+            definition_kind,
+            module_name,
+        } => {
+            let module_name = match module_name {
+                Some(module_name) => module_name,
+                None => return Some(vec![]),
+            };
+            // Relay expects the names of operations to start with the module name and
+            // end with the operation type, and fragments to be named `<module>_<prop>`
+            let name = match definition_kind {
+                DefinitionKind::Operation(OperationKind::Query) => format!("{}Query", module_name),
+                DefinitionKind::Operation(OperationKind::Mutation) => {
+                    format!("{}Mutation", module_name)
+                }
+                DefinitionKind::Operation(OperationKind::Subscription) => {
+                    format!("{}Subscription", module_name)
+                }
+                DefinitionKind::Fragment => format!("{}_fragment", module_name),
+            };
+            Some(vec![CompletionItem::new_simple(
+                name,
+                String::from("Based on the file name"),
+            )])
+        }
+        CompletionKind::FragmentTypeCondition => {
+            Some(completion_items_for_type_condition(None, schema))
+        }
+        _ => None,
+    }
+}
+
+/// Like `completion_item_from_argument`, for the arguments a fragment declares with
+/// `@argumentDefinitions`.
+fn completion_item_from_argument_definition(
+    argument_definition: &VariableDefinition,
+    schema: &Schema,
+) -> CompletionItem {
+    let mut detail = schema.get_type_string(&argument_definition.type_);
+    match &argument_definition.default_value {
+        Some(default_value) => detail.push_str(&format!(
+            " = {}",
+            print_value(schema, &Value::Constant(default_value.clone()))
+        )),
+        None => {
+            if argument_definition.type_.is_non_null() {
+                detail.push_str(" (required)");
+            }
+        }
+    }
+    let mut item = CompletionItem::new_simple(argument_definition.name.item.to_string(), detail);
+    if argument_definition.default_value.is_none() && argument_definition.type_.is_non_null() {
+        item.sort_text = Some(REQUIRED_SORT_TEXT.to_string());
+    }
+    item
+}
+
+/// The valid type conditions of an inline fragment are the object, interface and union
+/// types that overlap with the parent type, e.g. the members of a union or the
+/// implementers of an interface. Fragment definitions, without a parent type, can have
+/// any of these types. The detail contains the kind of the type.
+fn completion_items_for_type_condition(
+    parent_type: Option<Type>,
+    schema: &Schema,
+) -> Vec<CompletionItem> {
+    let mut items: Vec<CompletionItem> = schema
+        .get_type_map()
+        .filter_map(|(type_name, type_)| {
+            let kind = match type_ {
+                Type::Object(_) => "object",
+                Type::Interface(_) => "interface",
+                Type::Union(_) => "union",
+                Type::Enum(_) | Type::InputObject(_) | Type::Scalar(_) => return None,
+            };
+            let is_valid = match parent_type {
+                Some(parent_type) => schema.are_overlapping_types(parent_type, *type_),
+                None => true,
+            };
+            if is_valid {
+                Some(CompletionItem::new_simple(
+                    type_name.to_string(),
+                    kind.to_string(),
+                ))
+            } else {
+                None
+            }
+        })
+        .collect();
+    items.sort_by(|a, b| a.label.cmp(&b.label));
+    items
+}
+
+/// Only the values of enum arguments can be completed
+fn completion_items_for_argument_value(
+    argument: &SchemaArgument,
+    schema: &Schema,
+    project_name: StringKey,
+) -> Option<Vec<CompletionItem>> {
+    match argument.type_.inner() {
+        Type::Enum(enum_id) => {
+            let enum_ = schema.enum_(enum_id);
+            let items = enum_
+                .values
+                .iter()
+                .map(|enum_value| {
+                    let mut item = CompletionItem::new_simple(
+                        enum_value.value.to_string(),
+                        enum_.name.to_string(),
+                    );
+                    item.data = completion_item_data(
+                        project_name,
+                        format!("{}.{}", enum_.name, enum_value.value),
+                    );
+                    item
+                })
                 .collect();
             Some(items)
         }
+        Type::InputObject(_)
+        | Type::Interface(_)
+        | Type::Object(_)
+        | Type::Scalar(_)
+        | Type::Union(_) => None,
     }
 }
 
+/// The arguments of the Relay directives read by the compiler only accept literals,
+/// e.g. `true` and `false` for `@relay(plural: ...)`, so variables aren't suggested.
+fn completion_items_for_relay_directive_argument(
+    relay_argument: &RelayDirectiveArgument,
+) -> Vec<CompletionItem> {
+    match relay_argument.kind {
+        RelayDirectiveArgumentKind::Boolean => vec!["true", "false"]
+            .into_iter()
+            .map(|value| {
+                let mut item = CompletionItem::new_simple(value.to_string(), "Boolean".to_string());
+                item.kind = Some(CompletionItemKind::Value);
+                item
+            })
+            .collect(),
+        RelayDirectiveArgumentKind::String => vec![],
+    }
+}
+
+/// Resolves the field of an input object at `input_value_path` in the value of
+/// `argument`, e.g. the `name` field of the `filter` argument for `["name"]`. The fields
+/// of a list of input objects are the fields of the input object.
+fn resolve_input_value_argument<'a>(
+    argument: &'a SchemaArgument,
+    input_value_path: &[StringKey],
+    schema: &'a Schema,
+) -> Option<&'a SchemaArgument> {
+    let mut argument = argument;
+    for field_name in input_value_path {
+        argument = match argument.type_.inner() {
+            Type::InputObject(input_object_id) => schema
+                .input_object(input_object_id)
+                .fields
+                .named(*field_name)?,
+            _ => return None,
+        };
+    }
+    Some(argument)
+}
+
+/// Completes the fields of the input object type of `argument`, with the same details
+/// as arguments, e.g. `String! (required)` or `Int = 10`.
+fn completion_items_for_input_object_fields(
+    argument: &SchemaArgument,
+    schema: &Schema,
+) -> Option<Vec<CompletionItem>> {
+    match argument.type_.inner() {
+        Type::InputObject(input_object_id) => {
+            let items = schema
+                .input_object(input_object_id)
+                .fields
+                .iter()
+                .map(|field| completion_item_from_argument(field, schema))
+                .collect();
+            Some(items)
+        }
+        _ => None,
+    }
+}
+
+/// The detail of an argument completion contains its type, default value and
+/// whether the argument is required, e.g. `ID! (required)` or `Int = 10`.
+fn completion_item_from_argument(argument: &SchemaArgument, schema: &Schema) -> CompletionItem {
+    let mut detail = schema.get_type_string(&argument.type_);
+    let is_required = argument.default_value.is_none() && argument.type_.is_non_null();
+    match &argument.default_value {
+        Some(default_value) => detail.push_str(&format!(" = {}", default_value)),
+        None => {
+            if is_required {
+                detail.push_str(" (required)");
+            }
+        }
+    }
+    let mut item = CompletionItem::new_simple(argument.name.to_string(), detail);
+    if is_required {
+        item.sort_text = Some(REQUIRED_SORT_TEXT.to_string());
+    }
+    item
+}
+
 fn build_request_from_selections(
     selections: &List<Selection>,
     position_span: Span,
@@ -263,21 +937,35 @@ fn build_request_from_selections(
         if item.span().contains(position_span) {
             match item {
                 Selection::LinkedField(node) => {
-                    completion_request.kind = CompletionKind::FieldName;
                     let LinkedField {
                         name,
+                        arguments,
                         selections,
                         directives,
                         ..
                     } = node;
-                    completion_request.add_type(TypePathItem::LinkedField { name: name.value });
-                    build_request_from_selection_or_directives(
-                        selections,
-                        directives,
-                        DirectiveLocation::Field,
-                        position_span,
-                        completion_request,
-                    );
+                    match arguments {
+                        Some(arguments) if arguments.span.contains(position_span) => {
+                            build_request_from_arguments(
+                                arguments,
+                                name.value,
+                                position_span,
+                                completion_request,
+                            );
+                        }
+                        _ => {
+                            completion_request.kind = CompletionKind::FieldName;
+                            completion_request
+                                .add_type(TypePathItem::LinkedField { name: name.value });
+                            build_request_from_selection_or_directives(
+                                selections,
+                                directives,
+                                DirectiveLocation::Field,
+                                position_span,
+                                completion_request,
+                            );
+                        }
+                    }
                 }
                 Selection::FragmentSpread(spread) => {
                     let FragmentSpread {
@@ -285,6 +973,11 @@ fn build_request_from_selections(
                     } = spread;
                     if name.span.contains(position_span) {
                         completion_request.kind = CompletionKind::FragmentSpread;
+                    } else if let Some(fragment_name) =
+                        fragment_argument_name_completion(directives, name.value, position_span)
+                    {
+                        completion_request.kind =
+                            CompletionKind::FragmentArgumentName { fragment_name };
                     } else {
                         build_request_from_directives(
                             directives,
@@ -301,49 +994,260 @@ fn build_request_from_selections(
                         type_condition,
                         ..
                     } = node;
-                    if let Some(type_condition) = type_condition {
-                        let type_name = type_condition.type_.value;
-                        completion_request.add_type(TypePathItem::InlineFragment { type_name });
-                        build_request_from_selection_or_directives(
-                            selections,
-                            directives,
-                            DirectiveLocation::InlineFragment,
-                            position_span,
-                            completion_request,
-                        )
+                    match type_condition {
+                        // The type condition is completed against the parent type
+                        Some(type_condition) if type_condition.span.contains(position_span) => {
+                            completion_request.kind = CompletionKind::InlineFragmentTypeCondition;
+                        }
+                        _ => {
+                            // Inline fragments without a type condition keep the parent type
+                            if let Some(type_condition) = type_condition {
+                                let type_name = type_condition.type_.value;
+                                completion_request
+                                    .add_type(TypePathItem::InlineFragment { type_name });
+                            }
+                            build_request_from_selection_or_directives(
+                                selections,
+                                directives,
+                                DirectiveLocation::InlineFragment,
+                                position_span,
+                                completion_request,
+                            )
+                        }
                     }
                 }
                 Selection::ScalarField(node) => {
                     let ScalarField {
-                        directives, name, ..
-                    } = node;
-                    completion_request.add_type(TypePathItem::ScalarField { name: name.value });
-                    build_request_from_directives(
                         directives,
-                        DirectiveLocation::Scalar,
-                        position_span,
-                        completion_request,
-                    );
+                        name,
+                        arguments,
+                        ..
+                    } = node;
+                    match arguments {
+                        Some(arguments) if arguments.span.contains(position_span) => {
+                            build_request_from_arguments(
+                                arguments,
+                                name.value,
+                                position_span,
+                                completion_request,
+                            );
+                        }
+                        _ => {
+                            completion_request
+                                .add_type(TypePathItem::ScalarField { name: name.value });
+                            build_request_from_directives(
+                                directives,
+                                DirectiveLocation::Field,
+                                position_span,
+                                completion_request,
+                            );
+                        }
+                    }
                 }
             }
         }
     }
 }
 
+/// The type path isn't extended with the field, so the leaf type is the parent type
+/// of the field the arguments belong to.
+fn build_request_from_arguments(
+    arguments: &List<Argument>,
+    field_name: StringKey,
+    position_span: Span,
+    completion_request: &mut CompletionRequest,
+) {
+    for argument in &arguments.items {
+        if argument.value.span().contains(position_span) {
+            let argument_name = argument.name.value;
+            completion_request.kind = match input_value_position(
+                &argument.value,
+                position_span,
+                &mut completion_request.input_value_path,
+            ) {
+                InputValuePosition::Value => CompletionKind::ArgumentValue {
+                    field_name,
+                    argument_name,
+                },
+                InputValuePosition::FieldName => CompletionKind::ArgumentInputFieldName {
+                    field_name,
+                    argument_name,
+                },
+                InputValuePosition::Variable => CompletionKind::VariableName,
+            };
+            return;
+        }
+    }
+    completion_request.kind = CompletionKind::ArgumentName { field_name };
+}
+
 fn build_request_from_directives(
     directives: &[Directive],
     location: DirectiveLocation,
     position_span: Span,
     completion_request: &mut CompletionRequest,
 ) {
-    for Directive { span, .. } in directives {
+    for Directive {
+        span,
+        name,
+        arguments,
+        ..
+    } in directives
+    {
         if span.contains(position_span) {
-            completion_request.kind = CompletionKind::DirectiveName { location };
+            completion_request.kind = match arguments {
+                Some(arguments) if arguments.span.contains(position_span) => {
+                    directive_argument_completion_kind(
+                        arguments,
+                        name.value,
+                        position_span,
+                        &mut completion_request.input_value_path,
+                    )
+                }
+                _ => CompletionKind::DirectiveName { location },
+            };
             break;
         }
     }
 }
 
+/// Returns the name of the spread fragment if the position is on the name of an
+/// argument of the `@arguments` directive of the spread.
+fn fragment_argument_name_completion(
+    directives: &[Directive],
+    fragment_name: StringKey,
+    position_span: Span,
+) -> Option<StringKey> {
+    let arguments = directives
+        .iter()
+        .find(|directive| directive.name.value.lookup() == ARGUMENTS_DIRECTIVE_NAME)?
+        .arguments
+        .as_ref()?;
+    if !arguments.span.contains(position_span)
+        || arguments
+            .items
+            .iter()
+            .any(|argument| argument.value.span().contains(position_span))
+    {
+        return None;
+    }
+    Some(fragment_name)
+}
+
+fn directive_argument_completion_kind(
+    arguments: &List<Argument>,
+    directive_name: StringKey,
+    position_span: Span,
+    input_value_path: &mut Vec<StringKey>,
+) -> CompletionKind {
+    for argument in &arguments.items {
+        if argument.value.span().contains(position_span) {
+            let argument_name = argument.name.value;
+            return match input_value_position(&argument.value, position_span, input_value_path) {
+                InputValuePosition::Value => CompletionKind::DirectiveArgumentValue {
+                    directive_name,
+                    argument_name,
+                },
+                InputValuePosition::FieldName => CompletionKind::DirectiveArgumentInputFieldName {
+                    directive_name,
+                    argument_name,
+                },
+                InputValuePosition::Variable => CompletionKind::VariableName,
+            };
+        }
+    }
+    CompletionKind::DirectiveArgumentName { directive_name }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputValuePosition {
+    /// The position is in a value, e.g. an enum value
+    Value,
+    /// The position is on the name of a field of an input object, outside of the values
+    /// of its fields
+    FieldName,
+    /// The position is on a variable, e.g. `$id`
+    Variable,
+}
+
+/// Finds the position in the value of an argument, descending into the input objects
+/// and lists containing it. The names of the enclosing fields of input objects are
+/// pushed to `input_value_path`.
+fn input_value_position(
+    value: &SyntaxValue,
+    position_span: Span,
+    input_value_path: &mut Vec<StringKey>,
+) -> InputValuePosition {
+    match value {
+        SyntaxValue::Constant(value) => {
+            constant_input_value_position(value, position_span, input_value_path)
+        }
+        SyntaxValue::List(list) => match list
+            .items
+            .iter()
+            .find(|item| item.span().contains(position_span))
+        {
+            Some(item) => input_value_position(item, position_span, input_value_path),
+            None => InputValuePosition::Value,
+        },
+        SyntaxValue::Object(object) if is_within_delimiters(object, position_span) => {
+            match object
+                .items
+                .iter()
+                .find(|field| field.value.span().contains(position_span))
+            {
+                Some(field) => {
+                    input_value_path.push(field.name.value);
+                    input_value_position(&field.value, position_span, input_value_path)
+                }
+                None => InputValuePosition::FieldName,
+            }
+        }
+        SyntaxValue::Object(_) => InputValuePosition::Value,
+        SyntaxValue::Variable(_) => InputValuePosition::Variable,
+    }
+}
+
+/// Same as `input_value_position`, for values without variables
+fn constant_input_value_position(
+    value: &ConstantValue,
+    position_span: Span,
+    input_value_path: &mut Vec<StringKey>,
+) -> InputValuePosition {
+    match value {
+        ConstantValue::List(list) => match list
+            .items
+            .iter()
+            .find(|item| item.span().contains(position_span))
+        {
+            Some(item) => constant_input_value_position(item, position_span, input_value_path),
+            None => InputValuePosition::Value,
+        },
+        ConstantValue::Object(object) if is_within_delimiters(object, position_span) => {
+            match object
+                .items
+                .iter()
+                .find(|field| field.value.span().contains(position_span))
+            {
+                Some(field) => {
+                    input_value_path.push(field.name.value);
+                    constant_input_value_position(&field.value, position_span, input_value_path)
+                }
+                None => InputValuePosition::FieldName,
+            }
+        }
+        _ => InputValuePosition::Value,
+    }
+}
+
+/// Checks if the position is between the delimiters of a list, e.g. the braces of an
+/// input object, rather than before or after them.
+fn is_within_delimiters<T>(list: &List<T>, position_span: Span) -> bool {
+    let start = list.start.span;
+    start.start + start.length <= position_span.start
+        && position_span.start + position_span.length <= list.end.span.start
+}
+
 fn build_request_from_selection_or_directives(
     selections: &List<Selection>,
     directives: &[Directive],
@@ -364,42 +1268,55 @@ fn build_request_from_selection_or_directives(
     }
 }
 
-fn completion_item_from_directive(directive: &SchemaDirective, schema: &Schema) -> CompletionItem {
+/// A snippet inserting the required arguments of a field or directive with a tab stop
+/// for each value, e.g. `friends(first: $1)` or `connection(key: "$1")`. Returns `None`
+/// if there are no required arguments.
+fn arguments_snippet(
+    label: &str,
+    arguments: &ArgumentDefinitions,
+    schema: &Schema,
+) -> Option<String> {
+    let args: Vec<String> = arguments
+        .iter()
+        .filter(|arg| arg.default_value.is_none())
+        .filter_map(|arg| match &arg.type_ {
+            TypeReference::NonNull(type_) => Some((arg.name, type_)),
+            _ => None,
+        })
+        .enumerate()
+        .map(|(index, (name, type_))| {
+            let cursor_location = index + 1;
+            let value_snippet = match type_.as_ref() {
+                t if t.is_list() => format!("[${}]", cursor_location),
+                t if schema.is_string(t.inner()) => format!("\"${}\"", cursor_location),
+                _ => format!("${}", cursor_location),
+            };
+            format!("{}: {}", name, value_snippet)
+        })
+        .collect();
+    if args.is_empty() {
+        None
+    } else {
+        Some(format!("{}({})", label, args.join(", ")))
+    }
+}
+
+fn completion_item_from_directive(
+    directive: &SchemaDirective,
+    schema: &Schema,
+    project_name: StringKey,
+) -> CompletionItem {
     let SchemaDirective {
         name, arguments, ..
     } = directive;
 
-    use crate::lsp::InsertTextFormat;
-
     // Always use the name of the directive as the label
     let label = name.to_string();
 
     // We can return a snippet with the expected arguments of the directive
-    let (insert_text, insert_text_format) = if arguments.is_empty() {
-        (label.clone(), InsertTextFormat::PlainText)
-    } else {
-        let mut cursor_location = 1;
-        let mut args = vec![];
-
-        for arg in arguments.iter() {
-            if let TypeReference::NonNull(type_) = &arg.type_ {
-                let value_snippet = match type_ {
-                    t if t.is_list() => format!("[${}]", cursor_location),
-                    t if schema.is_string(t.inner()) => format!("\"${}\"", cursor_location),
-                    _ => format!("${}", cursor_location),
-                };
-                let str = format!("{} : {}", arg.name, value_snippet);
-                args.push(str);
-                cursor_location += 1;
-            }
-        }
-
-        if args.is_empty() {
-            (label.clone(), InsertTextFormat::PlainText)
-        } else {
-            let insert_text = format!("{}({})", label, args.join(", "));
-            (insert_text, InsertTextFormat::Snippet)
-        }
+    let (insert_text, insert_text_format) = match arguments_snippet(&label, arguments, schema) {
+        Some(snippet) => (snippet, InsertTextFormat::Snippet),
+        None => (label.clone(), InsertTextFormat::PlainText),
     };
 
     CompletionItem {
@@ -416,41 +1333,176 @@ fn completion_item_from_directive(directive: &SchemaDirective, schema: &Schema)
         text_edit: None,
         additional_text_edits: None,
         command: None,
-        data: None,
+        data: completion_item_data(project_name, format!("@{}", name)),
         tags: None,
     }
 }
 
+fn completion_item_data(project_name: StringKey, coordinate: String) -> Option<serde_json::Value> {
+    serde_json::to_value(CompletionItemData {
+        project_name: project_name.to_string(),
+        coordinate,
+    })
+    .ok()
+}
+
+/// Returns the name of the project a completion item was created for, so the item can be
+/// resolved against the schema of that project.
+pub fn completion_item_project_name(item: &CompletionItem) -> Option<StringKey> {
+    let data: CompletionItemData = serde_json::from_value(item.data.clone()?).ok()?;
+    Some(data.project_name.intern())
+}
+
+/// Adds the documentation to a completion item: the description of a directive and the
+/// deprecation reason of a field or enum value. Items without documentation are returned
+/// unchanged.
+pub fn resolve_completion_item(mut item: CompletionItem, schema: &Schema) -> CompletionItem {
+    let data: CompletionItemData = match item
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value(data).ok())
+    {
+        Some(data) => data,
+        None => return item,
+    };
+
+    let mut lines = vec![];
+    if data.coordinate.starts_with('@') {
+        if let Some(description) = schema
+            .get_directive(data.coordinate[1..].intern())
+            .and_then(|directive| directive.description)
+        {
+            lines.push(description.to_string());
+        }
+    } else if let Some((type_name, member_name)) = split_coordinate(&data.coordinate) {
+        let member_name = member_name.intern();
+        let directives = match schema.get_type(type_name.intern()) {
+            Some(Type::Enum(enum_id)) => schema
+                .enum_(enum_id)
+                .values
+                .iter()
+                .find(|enum_value| enum_value.value == member_name)
+                .map(|enum_value| enum_value.directives.as_slice()),
+            Some(type_) => schema
+                .named_field(type_, member_name)
+                .map(|field_id| schema.field(field_id).directives.as_slice()),
+            None => None,
+        };
+        if let Some(reason) = directives.and_then(get_deprecation_reason) {
+            item.deprecated = Some(true);
+            lines.push(format!("**Deprecated**: {}", reason));
+        }
+    }
+
+    if !lines.is_empty() {
+        item.documentation = Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: lines.join("\n\n"),
+        }));
+    }
+    item
+}
+
+fn split_coordinate(coordinate: &str) -> Option<(&str, &str)> {
+    let separator = coordinate.find('.')?;
+    Some((&coordinate[..separator], &coordinate[separator + 1..]))
+}
+
 /// Maps the LSP `Position` type back to a relative span, so we can find out which syntax node(s)
-/// this completion request came from
+/// this completion request came from. The first line of the source starts at the
+/// `column_index` of the document, e.g. after the `graphql` tag of a template.
+/// Spans are byte offsets into the source and LSP characters are UTF-16 code units, as
+/// in `Span::to_range`. A position past the end of its line maps to the end of the line.
 pub fn position_to_span(position: Position, source: &GraphQLSource) -> Option<Span> {
-    let mut index_of_last_line = 0;
-    let mut line_index = source.line_index as u64;
-
-    let mut chars = source.text.chars().enumerate().peekable();
+    // Zero-indexed line and character in the document of the current character
+    let mut line = source.line_index as u64;
+    let mut character = source.column_index as u64;
+    if position.line == line && position.character < character {
+        return None;
+    }
+    let mut chars = source.text.char_indices().peekable();
 
     while let Some((index, chr)) = chars.next() {
-        let is_newline = match chr {
+        let is_line_terminator = match chr {
             // Line terminators: https://www.ecma-international.org/ecma-262/#sec-line-terminators
-            '\u{000A}' | '\u{000D}' | '\u{2028}' | '\u{2029}' => match (chr, chars.peek()) {
-                // <CLRF>
-                ('\u{000D}', Some((_, '\u{000D}'))) => false,
-                _ => true,
-            },
+            '\u{000A}' | '\u{000D}' | '\u{2028}' | '\u{2029}' => true,
             _ => false,
         };
-
-        if is_newline {
-            line_index += 1;
-            index_of_last_line = index as u64;
+        if line == position.line && (character >= position.character || is_line_terminator) {
+            return Some(Span::new(index as u32, 0));
         }
-
-        if line_index == position.line {
-            let start_offset = index_of_last_line + position.character;
-            return Some(Span::new(start_offset as u32, 0));
+        match (chr, chars.peek()) {
+            // <CRLF> is a single line terminator, the line is incremented at the <LF>
+            ('\u{000D}', Some((_, '\u{000A}'))) => {}
+            _ if is_line_terminator => {
+                line += 1;
+                character = 0;
+            }
+            _ => character += chr.len_utf16() as u64,
         }
     }
-    None
+
+    if line == position.line {
+        Some(Span::new(source.text.len() as u32, 0))
+    } else {
+        None
+    }
+}
+
+/// The range of the name being completed, from its start until the position of the
+/// request, which is replaced by the text edits of the completion items.
+pub fn get_completion_replace_range(
+    params: &CompletionParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> Option<Range> {
+    let TextDocumentPositionParams {
+        text_document,
+        position,
+    } = &params.text_document_position;
+    let graphql_source =
+        get_graphql_source_for_position(&text_document.uri, *position, graphql_source_cache)?;
+    let position_span = position_to_span(*position, graphql_source)?;
+    let text_before_position = graphql_source.text.get(..position_span.start as usize)?;
+    let name_length = text_before_position
+        .chars()
+        .rev()
+        .take_while(|chr| chr.is_ascii_alphanumeric() || *chr == '_')
+        .count() as u64;
+    Some(Range::new(
+        Position::new(
+            position.line,
+            position.character.saturating_sub(name_length),
+        ),
+        *position,
+    ))
+}
+
+/// Replaces the name being completed in `range` with the insert text of each item.
+/// Snippets are only inserted if the client supports them, otherwise the label of
+/// the item is inserted as plain text.
+pub fn add_completion_text_edits(
+    items: Vec<CompletionItem>,
+    range: Option<Range>,
+    snippet_support: bool,
+) -> Vec<CompletionItem> {
+    items
+        .into_iter()
+        .map(|mut item| {
+            let is_snippet = item.insert_text_format == Some(InsertTextFormat::Snippet);
+            let new_text = match item.insert_text.take() {
+                Some(insert_text) if snippet_support || !is_snippet => insert_text,
+                _ => item.label.clone(),
+            };
+            if is_snippet && !snippet_support {
+                item.insert_text_format = Some(InsertTextFormat::PlainText);
+            }
+            match range {
+                Some(range) => item.text_edit = Some(TextEdit::new(range, new_text)),
+                None => item.insert_text = Some(new_text),
+            }
+            item
+        })
+        .collect()
 }
 
 pub fn send_completion_response(
@@ -458,10 +1510,6 @@ pub fn send_completion_response(
     request_id: ServerRequestId,
     connection: &Connection,
 ) {
-    // If there are no items, don't send any response
-    if items.is_empty() {
-        return;
-    }
     let completion_response = CompletionResponse::Array(items);
     let result = serde_json::to_value(&completion_response).unwrap();
     let response = ServerResponse {
@@ -472,6 +1520,236 @@ pub fn send_completion_response(
     connection.sender.send(Message::Response(response)).unwrap();
 }
 
+pub fn send_resolve_completion_item_response(
+    item: CompletionItem,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&item).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+/// Returns the GraphQL source in the document at `url` whose range contains `position`,
+/// or `None` if the document isn't tracked or the position is outside of every GraphQL source.
+pub fn get_graphql_source_for_position<'a>(
+    url: &Url,
+    position: Position,
+    graphql_source_cache: &'a GraphQLSourceCache,
+) -> Option<&'a GraphQLSource> {
+    let graphql_sources = match graphql_source_cache.get(url) {
+        Some(sources) => sources,
+        // If we have no sources for this file, do nothing
+        None => return None,
+    };
+
+    debug!(
+        "Got request for file with {} GraphQL sources",
+        graphql_sources.len()
+    );
+
+    // We have GraphQL documents, now check if the position
+    // falls within the range of one of these documents.
+    graphql_sources.iter().find(|graphql_source| {
+        let range = graphql_source.to_range();
+        position >= range.start && position <= range.end
+    })
+}
+
+/// The name of a directive is missing right after typing `@`, which makes the document
+/// invalid. Returns the text with a placeholder name inserted after the `@` at the
+/// position, so the location of the directive can still be found.
+fn insert_directive_name_placeholder(text: &str, position_span: Span) -> Option<String> {
+    let offset = position_span.start as usize;
+    let at_index = (offset.saturating_sub(1)..=offset)
+        .rev()
+        .find(|index| text.as_bytes().get(*index) == Some(&b'@'))?;
+    let mut text = text.to_string();
+    text.insert_str(at_index + 1, DIRECTIVE_NAME_PLACEHOLDER);
+    Some(text)
+}
+
+/// While typing `$` in the value of an argument, the name of the variable is missing,
+/// which makes the document invalid. Returns the text with a placeholder name inserted
+/// after the `$`, so the argument at the position can still be found.
+fn insert_variable_name_placeholder(text: &str, position_span: Span) -> Option<String> {
+    let offset = position_span.start as usize;
+    if !text.get(..offset)?.ends_with('$') {
+        return None;
+    }
+    let mut text = text.to_string();
+    text.insert_str(offset, VARIABLE_NAME_PLACEHOLDER);
+    Some(text)
+}
+
+/// While typing `... on `, the type name and the selection set of the inline fragment
+/// are missing, which makes the document invalid. Returns the text with a placeholder
+/// type name and an empty selection inserted where they are missing, so the type
+/// condition at the position can still be found.
+fn insert_type_condition_placeholder(text: &str, position_span: Span) -> Option<String> {
+    let is_name_char = |chr: char| chr.is_ascii_alphanumeric() || chr == '_';
+    let offset = position_span.start as usize;
+    let (before, after) = (text.get(..offset)?, text.get(offset..)?);
+
+    let before_name = before.trim_end_matches(is_name_char);
+    let before_on = before_name.trim_end();
+    if before_on.len() == before_name.len() || !before_on.ends_with("on") {
+        return None;
+    }
+    if !before_on[..before_on.len() - 2].trim_end().ends_with("...") {
+        return None;
+    }
+
+    let name_end = offset + (after.len() - after.trim_start_matches(is_name_char).len());
+    let mut insertion = String::new();
+    if before_name.len() == before.len() && name_end == offset {
+        insertion.push_str(TYPE_CONDITION_PLACEHOLDER);
+    }
+    if !text[name_end..].trim_start().starts_with('{') {
+        insertion.push_str(" { __typename }");
+    }
+    if insertion.is_empty() {
+        return None;
+    }
+    let mut text = text.to_string();
+    text.insert_str(name_end, &insertion);
+    Some(text)
+}
+
+/// While typing the name of a field of an input object, e.g. `{ na| }`, or of an
+/// argument, the value is missing, which makes the document invalid. Returns the text
+/// with a placeholder value inserted after the name, so the name at the position can
+/// still be found.
+fn insert_input_field_value_placeholder(text: &str, position_span: Span) -> Option<String> {
+    let is_name_char = |chr: char| chr.is_ascii_alphanumeric() || chr == '_';
+    let offset = position_span.start as usize;
+    let (before, after) = (text.get(..offset)?, text.get(offset..)?);
+    if !before.ends_with(is_name_char) {
+        return None;
+    }
+    let name_end = offset + (after.len() - after.trim_start_matches(is_name_char).len());
+    if text[name_end..].trim_start().starts_with(':') {
+        return None;
+    }
+    let mut text = text.to_string();
+    text.insert_str(name_end, ": null");
+    Some(text)
+}
+
+/// Returns the kind of a completion at the top level of a GraphQL source, outside of the
+/// selections and variable definitions of any definition, given the text of the source
+/// before the position:
+/// - the keyword starting a definition, e.g. `que|`
+/// - the name of the definition after the keyword, e.g. `query |`
+/// - the type condition of a fragment definition, e.g. `fragment Foo on |`
+fn definition_completion_kind(
+    text_before_position: &str,
+    module_name: Option<StringKey>,
+) -> Option<CompletionKind> {
+    // Find the start of the definition at the position, after the end of the previous
+    // definition. Braces and parentheses within strings and comments are skipped.
+    let mut depth = 0;
+    let mut definition_start = 0;
+    let mut in_string = false;
+    let mut in_comment = false;
+    let mut is_escaped = false;
+    for (index, chr) in text_before_position.char_indices() {
+        if in_comment {
+            in_comment = chr != '\n';
+            continue;
+        }
+        if in_string {
+            if is_escaped {
+                is_escaped = false;
+            } else if chr == '\\' {
+                is_escaped = true;
+            } else if chr == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match chr {
+            '#' => in_comment = true,
+            '"' => in_string = true,
+            '{' | '(' => depth += 1,
+            '}' | ')' => {
+                if depth == 0 {
+                    return None;
+                }
+                depth -= 1;
+                if depth == 0 && chr == '}' {
+                    definition_start = index + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 || in_string || in_comment {
+        return None;
+    }
+
+    // The name being typed at the position is completed
+    let definition = &text_before_position[definition_start..];
+    let before_name =
+        definition.trim_end_matches(|chr: char| chr.is_ascii_alphanumeric() || chr == '_');
+    if !before_name.is_empty() && !before_name.ends_with(|chr: char| chr.is_whitespace()) {
+        return None;
+    }
+    match before_name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => Some(CompletionKind::DefinitionKeyword),
+        [keyword] => {
+            let definition_kind = match *keyword {
+                "query" => DefinitionKind::Operation(OperationKind::Query),
+                "mutation" => DefinitionKind::Operation(OperationKind::Mutation),
+                "subscription" => DefinitionKind::Operation(OperationKind::Subscription),
+                "fragment" => DefinitionKind::Fragment,
+                _ => return None,
+            };
+            Some(CompletionKind::DefinitionName {
+                definition_kind,
+                module_name,
+            })
+        }
+        ["fragment", _, "on"] => Some(CompletionKind::FragmentTypeCondition),
+        _ => None,
+    }
+}
+
+/// The name of the module of a document, used to name its definitions: the file name
+/// without extensions, in camel case, e.g. `UserProfile` for `UserProfile.react.js` and
+/// `userProfile` for `user-profile.js`.
+fn module_name(url: &Url) -> Option<StringKey> {
+    let file_name = url.path_segments()?.last()?;
+    let file_stem = file_name.split('.').next()?;
+    let mut module_name = String::new();
+    let mut capitalize_next = false;
+    for chr in file_stem.chars() {
+        if chr.is_ascii_alphanumeric() {
+            if capitalize_next {
+                module_name.push(chr.to_ascii_uppercase());
+            } else {
+                module_name.push(chr);
+            }
+            capitalize_next = false;
+        } else {
+            capitalize_next = !module_name.is_empty();
+        }
+    }
+    if module_name.is_empty() {
+        None
+    } else {
+        Some(module_name.intern())
+    }
+}
+
 /// Return a `CompletionPath` for this request, only if the completion request occurs
 // within a GraphQL document. Otherwise return `None`
 pub fn get_completion_request(
@@ -480,61 +1758,75 @@ pub fn get_completion_request(
 ) -> Option<CompletionRequest> {
     let CompletionParams {
         text_document_position,
+        context,
         ..
     } = params;
+    let trigger_character = context.and_then(|context| context.trigger_character);
     let TextDocumentPositionParams {
         text_document,
         position,
     } = text_document_position;
     let url = text_document.uri;
-    let graphql_sources = match graphql_source_cache.get(&url) {
-        Some(sources) => sources,
-        // If we have no sources for this file, do nothing
-        None => return None,
-    };
-
-    info!(
-        "Got completion request for file with sources: {:#?}",
-        *graphql_sources
-    );
 
     info!("position: {:?}", position);
 
-    // We have GraphQL documents, now check if the completion request
-    // falls within the range of one of these documents.
-    let mut target_graphql_source: Option<&GraphQLSource> = None;
-    for graphql_source in &*graphql_sources {
-        let range = graphql_source.to_range();
-        if position >= range.start && position <= range.end {
-            target_graphql_source = Some(graphql_source);
-            break;
-        }
-    }
-
-    let graphql_source = match target_graphql_source {
+    let graphql_source = match get_graphql_source_for_position(&url, position, graphql_source_cache)
+    {
         Some(source) => source,
         // Exit early if this completion request didn't fall within
         // the range of one of our GraphQL documents
         None => return None,
     };
 
-    match parse(&graphql_source.text, FileKey::new(&url.to_string())) {
+    // Now we need to take the `Position` and map that to an offset relative
+    // to this GraphQL document, as the `Span`s in the document are relative.
+    // Map the position to a zero-length span, relative to this GraphQL source.
+    let position_span = match position_to_span(position, &graphql_source) {
+        Some(span) => span,
+        // Exit early if we can't map the position for some reason
+        None => return None,
+    };
+
+    let text_before_position = graphql_source
+        .text
+        .get(..position_span.start as usize)
+        .unwrap_or(&graphql_source.text);
+    let typed_name = text_before_position[text_before_position
+        .trim_end_matches(|chr: char| chr.is_ascii_alphanumeric() || chr == '_')
+        .len()..]
+        .to_string();
+    if let Some(kind) = definition_completion_kind(text_before_position, module_name(&url)) {
+        return Some(CompletionRequest {
+            kind,
+            trigger_character,
+            typed_name,
+            ..Default::default()
+        });
+    }
+
+    let file_key = FileKey::new(&url.to_string());
+    let parse_result = match parse(&graphql_source.text, file_key) {
+        Err(err) => match insert_directive_name_placeholder(&graphql_source.text, position_span)
+            .or_else(|| insert_variable_name_placeholder(&graphql_source.text, position_span))
+            .or_else(|| insert_type_condition_placeholder(&graphql_source.text, position_span))
+            .or_else(|| insert_input_field_value_placeholder(&graphql_source.text, position_span))
+        {
+            Some(text) => parse(&text, file_key),
+            None => Err(err),
+        },
+        result => result,
+    };
+    match parse_result {
         Ok(document) => {
-            // Now we need to take the `Position` and map that to an offset relative
-            // to this GraphQL document, as the `Span`s in the document are relative.
             info!("Successfully parsed the definitions for a target GraphQL source");
-            // Map the position to a zero-length span, relative to this GraphQL source.
-            let position_span = match position_to_span(position, &graphql_source) {
-                Some(span) => span,
-                // Exit early if we can't map the position for some reason
-                None => return None,
-            };
             // Now we need to walk the Document, tracking our path along the way, until
             // we find the position within the document. Note that the GraphQLSource will
             // already be updated *with the characters that triggered the completion request*
             // since the change event fires before completion.
             info!("position_span: {:?}", position_span);
-            let completion_request = create_completion_request(document, position_span);
+            let mut completion_request = create_completion_request(document, position_span);
+            completion_request.trigger_character = trigger_character;
+            completion_request.typed_name = typed_name;
             info!("Completion path: {:#?}", completion_request);
             Some(completion_request)
         }
@@ -545,3 +1837,603 @@ pub fn get_completion_request(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{PartialResultParams, TextDocumentIdentifier, WorkDoneProgressParams};
+    use graphql_ir::build;
+    use schema::{build_schema, build_schema_with_extensions};
+
+    const NODE_SCHEMA: &str = "
+        type Query { node: Node }
+        interface Node { id: ID }
+        type User implements Node { id: ID, name: String }
+        type Page implements Node { id: ID, title: String }
+    ";
+
+    fn field_labels_at(text: &str, offset: usize, schema: &Schema) -> Vec<String> {
+        let document = parse(text, FileKey::new("test.graphql")).unwrap();
+        let request = create_completion_request(document, Span::new(offset as u32, 0));
+        completion_items_for_request(
+            request,
+            schema,
+            None,
+            "test_project".intern(),
+            &HashMap::new(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+    }
+
+    #[test]
+    fn test_position_to_span_is_a_byte_offset() {
+        // `é` is two bytes but a single UTF-16 code unit, `𝄞` is four bytes and two
+        // UTF-16 code units
+        let source = GraphQLSource::new("query { é𝄞: me }", 2, 4);
+        assert_eq!(
+            position_to_span(Position::new(2, 4), &source),
+            Some(Span::new(0, 0))
+        );
+        assert_eq!(
+            position_to_span(Position::new(2, 13), &source),
+            Some(Span::new(10, 0))
+        );
+        assert_eq!(
+            position_to_span(Position::new(2, 16), &source),
+            Some(Span::new(15, 0))
+        );
+        assert_eq!(position_to_span(Position::new(2, 3), &source), None);
+        assert_eq!(position_to_span(Position::new(1, 4), &source), None);
+        // Past the end of the source
+        assert_eq!(
+            position_to_span(Position::new(2, 40), &source),
+            Some(Span::new(source.text.len() as u32, 0))
+        );
+    }
+
+    #[test]
+    fn test_position_to_span_with_crlf_line_endings() {
+        let source = GraphQLSource::new("query {\r\n  me\r\n}", 0, 0);
+        assert_eq!(
+            position_to_span(Position::new(1, 2), &source),
+            Some(Span::new(11, 0))
+        );
+        assert_eq!(
+            position_to_span(Position::new(2, 0), &source),
+            Some(Span::new(15, 0))
+        );
+        // Past the end of the line maps to its line terminator
+        assert_eq!(
+            position_to_span(Position::new(0, 20), &source),
+            Some(Span::new(7, 0))
+        );
+        assert_eq!(position_to_span(Position::new(3, 0), &source), None);
+    }
+
+    #[test]
+    fn test_completes_fields_of_schema_extensions() {
+        let schema = build_schema_with_extensions(
+            &["type Query { me: User } type User { id: ID }"],
+            &["extend type Query { localState: String }"],
+        )
+        .unwrap();
+        let request = CompletionRequest {
+            kind: CompletionKind::FieldName,
+            type_path: vec![TypePathItem::Operation(OperationKind::Query)],
+            ..Default::default()
+        };
+        let items = completion_items_for_request(
+            request,
+            &schema,
+            None,
+            "test_project".intern(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert!(labels.contains(&"me"));
+        assert!(labels.contains(&"localState"));
+    }
+
+    #[test]
+    fn test_dedupes_fields_declared_by_several_interfaces() {
+        let schema = build_schema(
+            "
+            type Query { me: User }
+            interface Node { id: ID! }
+            interface Entity { id: ID! url: String }
+            type User implements Node & Entity { id: ID! url: String name: String }
+            ",
+        )
+        .unwrap();
+        let request = CompletionRequest {
+            kind: CompletionKind::FieldName,
+            type_path: vec![
+                TypePathItem::Operation(OperationKind::Query),
+                TypePathItem::LinkedField {
+                    name: "me".intern(),
+                },
+            ],
+            ..Default::default()
+        };
+        let items = completion_items_for_request(
+            request,
+            &schema,
+            None,
+            "test_project".intern(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let id_items: Vec<&CompletionItem> =
+            items.iter().filter(|item| item.label == "id").collect();
+        assert_eq!(id_items.len(), 1);
+        assert_eq!(id_items[0].detail.as_deref(), Some("from Node, Entity"));
+        assert_eq!(
+            id_items[0].data,
+            completion_item_data("test_project".intern(), "User.id".to_string())
+        );
+        let url_item = items.iter().find(|item| item.label == "url").unwrap();
+        assert_eq!(url_item.detail.as_deref(), Some("from Entity"));
+        let name_item = items.iter().find(|item| item.label == "name").unwrap();
+        assert_eq!(name_item.detail.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_completes_literals_of_relay_directive_arguments() {
+        let schema = build_schema_with_extensions(
+            &["type Query { me: User } type User { id: ID }"],
+            &[schema::RELAY_EXTENSIONS],
+        )
+        .unwrap();
+        let request = CompletionRequest {
+            kind: CompletionKind::DirectiveArgumentValue {
+                directive_name: "relay".intern(),
+                argument_name: "plural".intern(),
+            },
+            type_path: vec![TypePathItem::FragmentDefinition {
+                type_name: "User".intern(),
+            }],
+            ..Default::default()
+        };
+        let labels: Vec<String> = completion_items_for_request(
+            request,
+            &schema,
+            None,
+            "test_project".intern(),
+            &HashMap::new(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|item| item.label)
+        .collect();
+        assert_eq!(labels, vec!["true", "false"]);
+    }
+
+    #[test]
+    fn test_completes_required_arguments_of_fields_as_snippets() {
+        let schema = build_schema(
+            "type Query { user(id: ID!, name: String!, first: Int, after: ID! = 1): User, me: User }
+             type User { id: ID }",
+        )
+        .unwrap();
+        let request = CompletionRequest {
+            kind: CompletionKind::FieldName,
+            type_path: vec![TypePathItem::Operation(OperationKind::Query)],
+            ..Default::default()
+        };
+        let items = completion_items_for_request(
+            request,
+            &schema,
+            None,
+            "test_project".intern(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let user = items.iter().find(|item| item.label == "user").unwrap();
+        assert_eq!(
+            user.insert_text.as_deref(),
+            Some("user(id: $1, name: \"$2\")")
+        );
+        assert_eq!(user.insert_text_format, Some(InsertTextFormat::Snippet));
+        let me = items.iter().find(|item| item.label == "me").unwrap();
+        assert_eq!(me.insert_text, None);
+
+        let range = Range::new(Position::new(2, 4), Position::new(2, 6));
+        let edited_items = add_completion_text_edits(vec![user.clone()], Some(range), true);
+        assert_eq!(
+            edited_items[0].text_edit,
+            Some(TextEdit::new(
+                range,
+                "user(id: $1, name: \"$2\")".to_string()
+            ))
+        );
+        let edited_items = add_completion_text_edits(vec![user.clone()], Some(range), false);
+        assert_eq!(
+            edited_items[0].text_edit,
+            Some(TextEdit::new(range, "user".to_string()))
+        );
+        assert_eq!(
+            edited_items[0].insert_text_format,
+            Some(InsertTextFormat::PlainText)
+        );
+    }
+
+    #[test]
+    fn test_completes_fragment_arguments_in_arguments_directive() {
+        let text = "fragment Foo on User { ...Bar @arguments(first: 10, ) }";
+        let document = parse(text, FileKey::new("test.graphql")).unwrap();
+        let offset = text.find(", )").unwrap() + 2;
+        let request = create_completion_request(document, Span::new(offset as u32, 0));
+        assert_eq!(
+            request.kind,
+            CompletionKind::FragmentArgumentName {
+                fragment_name: "Bar".intern()
+            }
+        );
+    }
+
+    #[test]
+    fn test_completes_fields_of_nested_input_objects() {
+        let schema = build_schema(
+            "type Query { search(filter: SearchFilter): String }
+             input SearchFilter { name: NameFilter!, first: Int = 10 }
+             input NameFilter { startsWith: String!, exact: Boolean }",
+        )
+        .unwrap();
+        let text = "query { search(filter: { name: {  } }) }";
+        let completion_at = |offset: usize| {
+            let document = parse(text, FileKey::new("test.graphql")).unwrap();
+            let request = create_completion_request(document, Span::new(offset as u32, 0));
+            let items = completion_items_for_request(
+                request,
+                &schema,
+                None,
+                "test_project".intern(),
+                &HashMap::new(),
+            )
+            .unwrap();
+            items
+                .into_iter()
+                .map(|item| (item.label, item.detail.unwrap()))
+                .collect::<Vec<_>>()
+        };
+        let field_name_offset = text.find("{ name").unwrap() + 1;
+        assert_eq!(
+            completion_at(field_name_offset),
+            vec![
+                ("name".to_string(), "NameFilter! (required)".to_string()),
+                ("first".to_string(), "Int = 10".to_string()),
+            ]
+        );
+        let nested_field_name_offset = text.find("{  }").unwrap() + 2;
+        assert_eq!(
+            completion_at(nested_field_name_offset),
+            vec![
+                ("startsWith".to_string(), "String! (required)".to_string()),
+                ("exact".to_string(), "Boolean".to_string()),
+            ]
+        );
+
+        let text = "query { search(filter: { name: { sta } }) }";
+        let offset = text.find("sta").unwrap() + 3;
+        let text_with_placeholder =
+            insert_input_field_value_placeholder(text, Span::new(offset as u32, 0)).unwrap();
+        assert_eq!(
+            text_with_placeholder,
+            "query { search(filter: { name: { sta: null } }) }"
+        );
+        let document = parse(&text_with_placeholder, FileKey::new("test.graphql")).unwrap();
+        let request = create_completion_request(document, Span::new(offset as u32, 0));
+        assert_eq!(
+            request.kind,
+            CompletionKind::ArgumentInputFieldName {
+                field_name: "search".intern(),
+                argument_name: "filter".intern(),
+            }
+        );
+        assert_eq!(request.input_value_path, vec!["name".intern()]);
+    }
+
+    #[test]
+    fn test_completes_variables_in_scope_after_dollar() {
+        let schema = build_schema(NODE_SCHEMA).unwrap();
+        let completion_at = |text: &str| {
+            let offset = text.find('$').unwrap() + 1;
+            let text = insert_variable_name_placeholder(text, Span::new(offset as u32, 0))
+                .unwrap_or_else(|| text.to_string());
+            let document = parse(&text, FileKey::new("test.graphql")).unwrap();
+            let mut request = create_completion_request(document, Span::new(offset as u32, 0));
+            request.trigger_character = Some("$".to_string());
+            completion_items_for_request(
+                request,
+                &schema,
+                None,
+                "test_project".intern(),
+                &HashMap::new(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.label, item.detail.unwrap()))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            completion_at("query Foo($id: ID!, $ids: [ID]) { node @include(if: $) { id } }"),
+            vec![
+                ("id".to_string(), "ID!".to_string()),
+                ("ids".to_string(), "[ID]".to_string()),
+            ]
+        );
+        assert_eq!(
+            completion_at(
+                "fragment Foo on Node @argumentDefinitions(cond: {type: \"Boolean!\"}) {
+                  id @include(if: $)
+                }"
+            ),
+            vec![("cond".to_string(), "Boolean!".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_only_completes_the_kinds_expected_after_the_trigger_character() {
+        let schema = build_schema(NODE_SCHEMA).unwrap();
+        let labels = |kind, trigger_character: &str| -> Vec<String> {
+            let request = CompletionRequest {
+                kind,
+                type_path: vec![TypePathItem::FragmentDefinition {
+                    type_name: "Node".intern(),
+                }],
+                trigger_character: Some(trigger_character.to_string()),
+                ..Default::default()
+            };
+            completion_items_for_request(
+                request,
+                &schema,
+                None,
+                "test_project".intern(),
+                &HashMap::new(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+        };
+        let directive_name = CompletionKind::DirectiveName {
+            location: DirectiveLocation::Field,
+        };
+        assert!(labels(directive_name, "@").contains(&"include".to_string()));
+        assert!(labels(directive_name, " ").is_empty());
+        assert!(labels(CompletionKind::FieldName, " ").is_empty());
+        assert!(labels(CompletionKind::FieldName, "@").is_empty());
+        assert_eq!(
+            labels(CompletionKind::InlineFragmentTypeCondition, " "),
+            vec!["Node", "Page", "User"]
+        );
+    }
+
+    #[test]
+    fn test_ranks_the_items_matching_the_typed_name_first() {
+        let schema = build_schema(
+            "type Query { user: User }
+             type User {
+               address: String
+               id: ID!
+               name: String
+               nameRenderer: String
+               nickname: String
+               oldName: String @deprecated(reason: \"Use name\")
+               username: String!
+             }",
+        )
+        .unwrap();
+        let text = "const a = graphql`query { user { name } }`;";
+        let url = Url::parse("file:///test.js").unwrap();
+        let mut graphql_source_cache: GraphQLSourceCache = HashMap::new();
+        graphql_source_cache.insert(url.clone(), extract_graphql::parse_chunks(text).unwrap());
+        let request = get_completion_request(
+            CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: url },
+                    position: Position::new(0, text.find(" } }").unwrap() as u64),
+                },
+                work_done_progress_params: WorkDoneProgressParams {
+                    work_done_token: None,
+                },
+                partial_result_params: PartialResultParams {
+                    partial_result_token: None,
+                },
+                context: None,
+            },
+            &graphql_source_cache,
+        )
+        .unwrap();
+        assert_eq!(request.typed_name, "name");
+        let mut items = completion_items_for_request(
+            request,
+            &schema,
+            None,
+            "test_project".intern(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert!(items
+            .iter()
+            .all(|item| item.filter_text.as_ref() == Some(&item.label)));
+        items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "name",
+                "nameRenderer",
+                "id",
+                "username",
+                "address",
+                "nickname",
+                "oldName"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_completes_fields_of_inline_fragment_type_condition() {
+        let schema = build_schema(NODE_SCHEMA).unwrap();
+        let text = "fragment Foo on Node {  id ... on User {  } }";
+        let fragment_offset = text.find("{ ").unwrap() + 1;
+        assert_eq!(field_labels_at(text, fragment_offset, &schema), vec!["id"]);
+        let inline_fragment_offset = text.rfind("{ ").unwrap() + 1;
+        assert_eq!(
+            field_labels_at(text, inline_fragment_offset, &schema),
+            vec!["id", "name"]
+        );
+    }
+
+    #[test]
+    fn test_suggests_fragments_with_overlapping_type_conditions() {
+        let schema = build_schema(NODE_SCHEMA).unwrap();
+        let text = "
+            fragment NodeFragment on Node { id }
+            fragment UserFragment on User { name }
+            fragment PageFragment on Page { title }
+        ";
+        let document = parse(text, FileKey::new("test.graphql")).unwrap();
+        let program =
+            Program::from_definitions(&schema, build(&schema, &document.definitions).unwrap());
+        let user_type = schema.get_type("User".intern()).unwrap();
+        assert_eq!(
+            get_valid_fragments_for_type(user_type, &program),
+            vec!["NodeFragment".intern(), "UserFragment".intern()]
+        );
+    }
+
+    #[test]
+    fn test_completes_within_the_graphql_source_containing_the_position() {
+        let text = "const a = graphql`fragment Foo on User { id }`;
+const b = graphql`
+  fragment Bar on Page {
+    id
+    
+  }
+`;";
+        let url = Url::parse("file:///test.js").unwrap();
+        let mut graphql_source_cache: GraphQLSourceCache = HashMap::new();
+        graphql_source_cache.insert(url.clone(), extract_graphql::parse_chunks(text).unwrap());
+        let completion_at = |line, character| {
+            get_completion_request(
+                CompletionParams {
+                    text_document_position: TextDocumentPositionParams {
+                        text_document: TextDocumentIdentifier { uri: url.clone() },
+                        position: Position::new(line, character),
+                    },
+                    work_done_progress_params: WorkDoneProgressParams {
+                        work_done_token: None,
+                    },
+                    partial_result_params: PartialResultParams {
+                        partial_result_token: None,
+                    },
+                    context: None,
+                },
+                &graphql_source_cache,
+            )
+            .map(|request| request.type_path)
+        };
+
+        let first_fragment_column = text.find("id }").unwrap() as u64 + 3;
+        assert_eq!(
+            completion_at(0, first_fragment_column),
+            Some(vec![TypePathItem::FragmentDefinition {
+                type_name: "User".intern()
+            }])
+        );
+        assert_eq!(
+            completion_at(4, 4),
+            Some(vec![TypePathItem::FragmentDefinition {
+                type_name: "Page".intern()
+            }])
+        );
+        assert_eq!(completion_at(0, 2), None);
+    }
+
+    #[test]
+    fn test_completes_definitions_at_the_top_level() {
+        let module_name = Some("UserProfile".intern());
+        assert_eq!(
+            definition_completion_kind("", module_name),
+            Some(CompletionKind::DefinitionKeyword)
+        );
+        assert_eq!(
+            definition_completion_kind("query Foo { me { id } }\nfrag", module_name),
+            Some(CompletionKind::DefinitionKeyword)
+        );
+        assert_eq!(
+            definition_completion_kind("\n  mutation ", module_name),
+            Some(CompletionKind::DefinitionName {
+                definition_kind: DefinitionKind::Operation(OperationKind::Mutation),
+                module_name
+            })
+        );
+        assert_eq!(
+            definition_completion_kind("fragment Foo on Us", module_name),
+            Some(CompletionKind::FragmentTypeCondition)
+        );
+        assert_eq!(
+            definition_completion_kind("query Foo { ", module_name),
+            None
+        );
+        assert_eq!(
+            definition_completion_kind("query Foo($id: ID) @dir(arg: \"}\") ", module_name),
+            None
+        );
+
+        let schema = build_schema(NODE_SCHEMA).unwrap();
+        let labels = |kind| -> Vec<String> {
+            let request = CompletionRequest {
+                kind,
+                type_path: vec![],
+                ..Default::default()
+            };
+            completion_items_for_request(
+                request,
+                &schema,
+                None,
+                "test_project".intern(),
+                &HashMap::new(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+        };
+        assert_eq!(
+            labels(CompletionKind::DefinitionKeyword),
+            vec!["query", "fragment"]
+        );
+        assert_eq!(
+            labels(CompletionKind::DefinitionName {
+                definition_kind: DefinitionKind::Operation(OperationKind::Query),
+                module_name
+            }),
+            vec!["UserProfileQuery"]
+        );
+        assert_eq!(
+            labels(CompletionKind::FragmentTypeCondition),
+            vec!["Node", "Page", "Query", "User"]
+        );
+    }
+
+    #[test]
+    fn test_module_name() {
+        let module_name = |url| module_name(&Url::parse(url).unwrap());
+        assert_eq!(
+            module_name("file:///src/UserProfile.react.js"),
+            Some("UserProfile".intern())
+        );
+        assert_eq!(
+            module_name("file:///src/user-profile.js"),
+            Some("userProfile".intern())
+        );
+        assert_eq!(module_name("file:///"), None);
+    }
+}
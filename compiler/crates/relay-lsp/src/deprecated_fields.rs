@@ -0,0 +1,119 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for reporting the selections of deprecated fields
+use crate::error_reporting::DIAGNOSTIC_SOURCE;
+use crate::lsp::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString};
+use crate::rename::name_location;
+use common::Location;
+use graphql_syntax::GraphQLSource;
+use graphql_transforms::collect_deprecated_fields;
+use interner::StringKey;
+use relay_compiler::Programs;
+use std::collections::BTreeMap;
+
+/// The code of the diagnostics for selections of deprecated fields
+const DEPRECATED_FIELD_DIAGNOSTIC_CODE: &str = "relay-deprecated-field";
+
+/// A selection of a field that is `@deprecated` in the schema of its project
+pub struct DeprecatedField {
+    pub location: Location,
+    pub name: StringKey,
+    pub message: String,
+}
+
+/// Finds the selections of deprecated fields in the given `Programs`, each checked
+/// against the schema of its project.
+pub fn find_deprecated_fields<'a, 'schema: 'a>(
+    project_programs: impl Iterator<Item = &'a Programs<'schema>>,
+) -> Vec<DeprecatedField> {
+    // The documents of a base project are part of the programs of each project
+    // extending it, so they are deduped by location
+    let mut deprecated_fields = BTreeMap::new();
+    for programs in project_programs {
+        let schema = programs.source.schema();
+        for usage in collect_deprecated_fields(&programs.source) {
+            deprecated_fields
+                .entry(usage.location)
+                .or_insert_with(|| DeprecatedField {
+                    location: usage.location,
+                    name: schema.field(usage.field).name,
+                    message: usage.message(schema),
+                });
+        }
+    }
+    deprecated_fields
+        .into_iter()
+        .map(|(_, field)| field)
+        .collect()
+}
+
+/// Creates the warning for a selection of a deprecated field in `source`, the range of
+/// the diagnostic covers the name of the field.
+pub fn deprecated_field_diagnostic(
+    deprecated_field: &DeprecatedField,
+    source: &GraphQLSource,
+) -> Diagnostic {
+    let range = name_location(&deprecated_field.location, deprecated_field.name, source)
+        .span()
+        .to_range(&source.text, source.line_index, source.column_index);
+    Diagnostic {
+        code: Some(NumberOrString::String(
+            DEPRECATED_FIELD_DIAGNOSTIC_CODE.to_string(),
+        )),
+        message: deprecated_field.message.clone(),
+        range,
+        related_information: None,
+        severity: Some(DiagnosticSeverity::Warning),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        tags: Some(vec![DiagnosticTag::Deprecated]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{Position, Range};
+    use crate::references::tests::programs_for_documents;
+    use schema::build_schema;
+
+    #[test]
+    fn test_reports_selections_of_deprecated_fields() {
+        let documents: &[(&str, &str)] = &[(
+            "User.js",
+            "fragment User_user on User {\n  name\n  oldName\n}",
+        )];
+        let schema = build_schema(
+            r#"
+            directive @deprecated(reason: String) on FIELD_DEFINITION
+            type Query { me: User }
+            type User {
+                name: String
+                oldName: String @deprecated(reason: "Use `name` instead")
+            }
+            "#,
+        )
+        .unwrap();
+        let programs = programs_for_documents(&schema, documents);
+
+        let deprecated_fields = find_deprecated_fields(std::iter::once(&programs));
+        assert_eq!(deprecated_fields.len(), 1);
+        assert_eq!(deprecated_fields[0].name.lookup(), "oldName");
+
+        let source = GraphQLSource::new(documents[0].1, 0, 0);
+        let diagnostic = deprecated_field_diagnostic(&deprecated_fields[0], &source);
+        assert_eq!(
+            diagnostic.message,
+            "The field `oldName` is deprecated: Use `name` instead"
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::Warning));
+        assert_eq!(
+            diagnostic.range,
+            Range::new(Position::new(2, 2), Position::new(2, 9))
+        );
+    }
+}
@@ -0,0 +1,345 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for highlighting the occurrences of a variable or fragment in a document
+use crate::completion::{get_graphql_source_for_position, position_to_span, GraphQLSourceCache};
+use crate::lsp::{
+    Connection, DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams, Message,
+    ServerRequestId, ServerResponse,
+};
+use common::{FileKey, Span};
+use graphql_syntax::{
+    parse, Argument, Directive, ExecutableDefinition, GraphQLSource, List, Selection, Value,
+};
+use interner::{Intern, StringKey};
+use log::info;
+
+/// The directive declaring the arguments of a fragment
+const ARGUMENT_DEFINITIONS_DIRECTIVE_NAME: &str = "argumentDefinitions";
+
+/// What the occurrences refer to. Variables are scoped to the operation or fragment
+/// they are used in, fragments to the whole document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HighlightTarget {
+    Variable(StringKey),
+    Fragment(StringKey),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Occurrence {
+    target: HighlightTarget,
+    span: Span,
+    kind: DocumentHighlightKind,
+}
+
+/// Returns the occurrences of the variable or fragment at the requested position within
+/// the document. The definitions of variables (including the arguments of
+/// `@argumentDefinitions`) and fragments are writes, their usages are reads.
+pub fn get_document_highlights(
+    params: &DocumentHighlightParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> Vec<DocumentHighlight> {
+    let url = &params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+    let graphql_sources = match graphql_source_cache.get(url) {
+        Some(graphql_sources) => graphql_sources,
+        None => return vec![],
+    };
+    let target_source = match get_graphql_source_for_position(url, position, graphql_source_cache) {
+        Some(graphql_source) => graphql_source,
+        None => return vec![],
+    };
+    let position_span = match position_to_span(position, target_source) {
+        Some(position_span) => position_span,
+        None => return vec![],
+    };
+
+    // The occurrences of each definition of each GraphQL source of the document
+    let file_key = FileKey::new(&url.to_string());
+    let sources_occurrences: Vec<(&GraphQLSource, Vec<Vec<Occurrence>>)> = graphql_sources
+        .iter()
+        .map(|graphql_source| {
+            let definitions_occurrences = match parse(&graphql_source.text, file_key) {
+                Ok(document) => document
+                    .definitions
+                    .iter()
+                    .map(definition_occurrences)
+                    .collect(),
+                Err(err) => {
+                    info!(
+                        "Failed to parse a GraphQL source for document highlights: {:?}",
+                        err
+                    );
+                    vec![]
+                }
+            };
+            (graphql_source, definitions_occurrences)
+        })
+        .collect();
+
+    let target_definition = sources_occurrences
+        .iter()
+        .filter(|(graphql_source, _)| std::ptr::eq(*graphql_source, target_source))
+        .flat_map(|(_, definitions_occurrences)| definitions_occurrences)
+        .find(|occurrences| {
+            occurrences
+                .iter()
+                .any(|occurrence| occurrence.span.contains(position_span))
+        });
+    let target = match target_definition.and_then(|occurrences| {
+        occurrences
+            .iter()
+            .find(|occurrence| occurrence.span.contains(position_span))
+    }) {
+        Some(occurrence) => occurrence.target,
+        None => return vec![],
+    };
+
+    let mut highlights = vec![];
+    match target {
+        HighlightTarget::Variable(_) => {
+            // Variables are only highlighted within the definition they are used in
+            if let Some(occurrences) = target_definition {
+                push_highlights(&mut highlights, target_source, occurrences, target);
+            }
+        }
+        HighlightTarget::Fragment(_) => {
+            for (graphql_source, definitions_occurrences) in &sources_occurrences {
+                for occurrences in definitions_occurrences {
+                    push_highlights(&mut highlights, graphql_source, occurrences, target);
+                }
+            }
+        }
+    }
+    highlights
+}
+
+fn push_highlights(
+    highlights: &mut Vec<DocumentHighlight>,
+    graphql_source: &GraphQLSource,
+    occurrences: &[Occurrence],
+    target: HighlightTarget,
+) {
+    highlights.extend(
+        occurrences
+            .iter()
+            .filter(|occurrence| occurrence.target == target)
+            .map(|occurrence| DocumentHighlight {
+                range: occurrence.span.to_range(
+                    &graphql_source.text,
+                    graphql_source.line_index,
+                    graphql_source.column_index,
+                ),
+                kind: Some(occurrence.kind),
+            }),
+    );
+}
+
+fn definition_occurrences(definition: &ExecutableDefinition) -> Vec<Occurrence> {
+    let mut collector = OccurrenceCollector {
+        occurrences: vec![],
+    };
+    match definition {
+        ExecutableDefinition::Operation(operation) => {
+            if let Some(variable_definitions) = &operation.variable_definitions {
+                for variable_definition in &variable_definitions.items {
+                    collector.push(
+                        HighlightTarget::Variable(variable_definition.name.name),
+                        variable_definition.name.token.inner_span,
+                        DocumentHighlightKind::Write,
+                    );
+                    collector.collect_directives(&variable_definition.directives);
+                }
+            }
+            collector.collect_directives(&operation.directives);
+            collector.collect_selections(&operation.selections);
+        }
+        ExecutableDefinition::Fragment(fragment) => {
+            collector.push(
+                HighlightTarget::Fragment(fragment.name.value),
+                fragment.name.token.inner_span,
+                DocumentHighlightKind::Write,
+            );
+            let argument_definitions_name = ARGUMENT_DEFINITIONS_DIRECTIVE_NAME.intern();
+            for directive in &fragment.directives {
+                match &directive.arguments {
+                    Some(arguments) if directive.name.value == argument_definitions_name => {
+                        for argument in &arguments.items {
+                            collector.push(
+                                HighlightTarget::Variable(argument.name.value),
+                                argument.name.token.inner_span,
+                                DocumentHighlightKind::Write,
+                            );
+                        }
+                    }
+                    _ => collector.collect_arguments(&directive.arguments),
+                }
+            }
+            collector.collect_selections(&fragment.selections);
+        }
+    }
+    collector.occurrences
+}
+
+struct OccurrenceCollector {
+    occurrences: Vec<Occurrence>,
+}
+
+impl OccurrenceCollector {
+    fn collect_selections(&mut self, selections: &List<Selection>) {
+        for selection in &selections.items {
+            match selection {
+                Selection::LinkedField(field) => {
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                    self.collect_selections(&field.selections);
+                }
+                Selection::ScalarField(field) => {
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.collect_directives(&fragment.directives);
+                    self.collect_selections(&fragment.selections);
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.push(
+                        HighlightTarget::Fragment(spread.name.value),
+                        spread.name.token.inner_span,
+                        DocumentHighlightKind::Read,
+                    );
+                    self.collect_directives(&spread.directives);
+                }
+            }
+        }
+    }
+
+    fn collect_directives(&mut self, directives: &[Directive]) {
+        for directive in directives {
+            self.collect_arguments(&directive.arguments);
+        }
+    }
+
+    fn collect_arguments(&mut self, arguments: &Option<List<Argument>>) {
+        if let Some(arguments) = arguments {
+            for argument in &arguments.items {
+                self.collect_value(&argument.value);
+            }
+        }
+    }
+
+    fn collect_value(&mut self, value: &Value) {
+        match value {
+            Value::Variable(variable) => self.push(
+                HighlightTarget::Variable(variable.name),
+                variable.token.inner_span,
+                DocumentHighlightKind::Read,
+            ),
+            Value::List(list) => {
+                for item in &list.items {
+                    self.collect_value(item);
+                }
+            }
+            Value::Object(fields) => {
+                for field in &fields.items {
+                    self.collect_value(&field.value);
+                }
+            }
+            Value::Constant(_) => {}
+        }
+    }
+
+    fn push(&mut self, target: HighlightTarget, span: Span, kind: DocumentHighlightKind) {
+        self.occurrences.push(Occurrence { target, span, kind });
+    }
+}
+
+pub fn send_document_highlights_response(
+    highlights: Vec<DocumentHighlight>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&highlights).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{
+        PartialResultParams, Position, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+        WorkDoneProgressParams,
+    };
+    use std::collections::HashMap;
+
+    fn highlights_at(text: &str, offset: usize) -> Vec<(u64, u64, DocumentHighlightKind)> {
+        let url = Url::parse("file:///test.graphql").unwrap();
+        let mut graphql_source_cache: GraphQLSourceCache = HashMap::new();
+        graphql_source_cache.insert(url.clone(), vec![GraphQLSource::new(text, 0, 0)]);
+        let params = DocumentHighlightParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: url },
+                position: Position::new(0, offset as u64),
+            },
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        };
+        get_document_highlights(&params, &graphql_source_cache)
+            .into_iter()
+            .map(|highlight| {
+                (
+                    highlight.range.start.character,
+                    highlight.range.end.character,
+                    highlight.kind.unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_highlights_variables_within_their_definition() {
+        let text = "query Foo($id: ID) { node(id: $id) { id } } query Bar($id: ID) { node(id: $id) { id } }";
+        let use_offset = text.find("$id)").unwrap();
+        assert_eq!(
+            highlights_at(text, use_offset + 1),
+            vec![
+                (10, 13, DocumentHighlightKind::Write),
+                (
+                    use_offset as u64,
+                    use_offset as u64 + 3,
+                    DocumentHighlightKind::Read
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlights_fragments_across_definitions() {
+        let text = "fragment Foo on User { id } query Bar { me { ...Foo } }";
+        let spread_offset = text.find("...Foo").unwrap() + 3;
+        assert_eq!(
+            highlights_at(text, 10),
+            vec![
+                (9, 12, DocumentHighlightKind::Write),
+                (
+                    spread_offset as u64,
+                    spread_offset as u64 + 3,
+                    DocumentHighlightKind::Read
+                ),
+            ]
+        );
+    }
+}
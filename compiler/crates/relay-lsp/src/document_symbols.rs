@@ -0,0 +1,274 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the document symbols language feature
+use crate::completion::GraphQLSourceCache;
+use crate::lsp::{
+    Connection, DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, Message, Range,
+    ServerRequestId, ServerResponse, SymbolKind,
+};
+use common::{FileKey, Span};
+use graphql_syntax::{
+    parse, ExecutableDefinition, FragmentDefinition, GraphQLSource, List, OperationDefinition,
+    OperationKind, Selection,
+};
+use log::info;
+
+/// Returns the symbols of every operation and fragment in each GraphQL source of the
+/// document, with the selected fields nested as children.
+pub fn get_document_symbols(
+    params: DocumentSymbolParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> Vec<DocumentSymbol> {
+    let url = params.text_document.uri;
+    let graphql_sources = match graphql_source_cache.get(&url) {
+        Some(sources) => sources,
+        None => return vec![],
+    };
+
+    let mut symbols = vec![];
+    for graphql_source in graphql_sources {
+        let document = match parse(&graphql_source.text, FileKey::new(&url.to_string())) {
+            Ok(document) => document,
+            Err(err) => {
+                // Skip sources that can't be parsed, but keep the symbols of the others
+                info!(
+                    "Failed to parse a GraphQL source for document symbols: {:?}",
+                    err
+                );
+                continue;
+            }
+        };
+        for definition in document.definitions {
+            symbols.push(match definition {
+                ExecutableDefinition::Operation(operation) => {
+                    operation_symbol(&operation, graphql_source)
+                }
+                ExecutableDefinition::Fragment(fragment) => {
+                    fragment_symbol(&fragment, graphql_source)
+                }
+            });
+        }
+    }
+    symbols
+}
+
+fn operation_symbol(operation: &OperationDefinition, source: &GraphQLSource) -> DocumentSymbol {
+    let kind = match &operation.operation {
+        Some((_, kind)) => *kind,
+        None => OperationKind::Query,
+    };
+    let detail = match kind {
+        OperationKind::Query => "query",
+        OperationKind::Mutation => "mutation",
+        OperationKind::Subscription => "subscription",
+    };
+    let range = span_to_range(*operation.location.span(), source);
+    // Anonymous operations are selected by the operation keyword, if any
+    let selection_range = match (&operation.name, &operation.operation) {
+        (Some(name), _) => span_to_range(name.span, source),
+        (None, Some((token, _))) => span_to_range(token.span, source),
+        (None, None) => range,
+    };
+    let name = match &operation.name {
+        Some(name) => name.value.to_string(),
+        None => format!("Anonymous {}", detail),
+    };
+    new_symbol(
+        name,
+        Some(detail.to_string()),
+        SymbolKind::Function,
+        range,
+        selection_range,
+        selection_symbols(&operation.selections, source),
+    )
+}
+
+fn fragment_symbol(fragment: &FragmentDefinition, source: &GraphQLSource) -> DocumentSymbol {
+    new_symbol(
+        fragment.name.value.to_string(),
+        Some(format!("on {}", fragment.type_condition.type_.value)),
+        SymbolKind::Struct,
+        span_to_range(*fragment.location.span(), source),
+        span_to_range(fragment.name.span, source),
+        selection_symbols(&fragment.selections, source),
+    )
+}
+
+/// Returns a symbol for each field in the selections. The fields of inline fragments
+/// are flattened into the parent, fragment spreads don't contribute any symbols.
+fn selection_symbols(selections: &List<Selection>, source: &GraphQLSource) -> Vec<DocumentSymbol> {
+    let mut symbols = vec![];
+    for selection in &selections.items {
+        match selection {
+            Selection::LinkedField(node) => symbols.push(new_symbol(
+                node.name.value.to_string(),
+                node.alias
+                    .as_ref()
+                    .map(|alias| format!("alias: {}", alias.alias.value)),
+                SymbolKind::Field,
+                span_to_range(node.span, source),
+                span_to_range(node.name.span, source),
+                selection_symbols(&node.selections, source),
+            )),
+            Selection::ScalarField(node) => symbols.push(new_symbol(
+                node.name.value.to_string(),
+                node.alias
+                    .as_ref()
+                    .map(|alias| format!("alias: {}", alias.alias.value)),
+                SymbolKind::Field,
+                span_to_range(node.span, source),
+                span_to_range(node.name.span, source),
+                vec![],
+            )),
+            Selection::InlineFragment(node) => {
+                symbols.extend(selection_symbols(&node.selections, source))
+            }
+            Selection::FragmentSpread(_) => {}
+        }
+    }
+    symbols
+}
+
+fn new_symbol(
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        deprecated: None,
+        range,
+        selection_range,
+        children: if children.is_empty() {
+            None
+        } else {
+            Some(children)
+        },
+    }
+}
+
+/// Maps a span relative to the GraphQL source to a range within the document
+/// the source was extracted from. The spans of syntax nodes include the whitespace
+/// and comments around their tokens, which are left out of the range.
+fn span_to_range(span: Span, source: &GraphQLSource) -> Range {
+    trim_span(span, &source.text).to_range(&source.text, source.line_index, source.column_index)
+}
+
+fn trim_span(span: Span, text: &str) -> Span {
+    let (start, length) = span.as_usize();
+    let is_ignored = |c: char| c.is_whitespace() || c == ',';
+    let mut node_text = &text[start..start + length];
+    loop {
+        node_text = node_text.trim_start_matches(is_ignored);
+        if !node_text.starts_with('#') {
+            break;
+        }
+        // Skip the leading comment up to the end of its line
+        node_text = match node_text.find(|c| c == '\n' || c == '\r') {
+            Some(index) => &node_text[index..],
+            None => "",
+        };
+    }
+    let trimmed_start = start + length - node_text.len();
+    Span::from_usize(trimmed_start, node_text.trim_end_matches(is_ignored).len())
+}
+
+pub fn send_document_symbols_response(
+    symbols: Vec<DocumentSymbol>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&DocumentSymbolResponse::Nested(symbols)).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_symbols(source: &GraphQLSource) -> Vec<DocumentSymbol> {
+        parse(&source.text, FileKey::new("test.js"))
+            .unwrap()
+            .definitions
+            .iter()
+            .map(|definition| match definition {
+                ExecutableDefinition::Operation(operation) => operation_symbol(operation, source),
+                ExecutableDefinition::Fragment(fragment) => fragment_symbol(fragment, source),
+            })
+            .collect()
+    }
+
+    fn print_range(range: Range) -> String {
+        format!(
+            "{}:{}-{}:{}",
+            range.start.line, range.start.character, range.end.line, range.end.character
+        )
+    }
+
+    /// Prints each symbol with its range and selection range, and its children indented
+    fn print_symbols(symbols: &[DocumentSymbol], indentation: usize, printed: &mut Vec<String>) {
+        for symbol in symbols {
+            printed.push(format!(
+                "{}{}{} {} @ {}",
+                "  ".repeat(indentation),
+                symbol.name,
+                match &symbol.detail {
+                    Some(detail) => format!(" ({})", detail),
+                    None => "".to_string(),
+                },
+                print_range(symbol.range),
+                print_range(symbol.selection_range),
+            ));
+            if let Some(children) = &symbol.children {
+                print_symbols(children, indentation + 1, printed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_symbols_of_operation_and_fragment_with_nested_selections() {
+        let text = "query UserQuery {
+  me {
+    name
+    ... on User { id }
+  }
+}
+fragment User_user on User {
+  # The friends of the user
+  friends {
+    count: id
+  }
+}";
+        // The source starts on the 3rd line of the document
+        let source = GraphQLSource::new(text, 2, 0);
+        let mut printed = vec![];
+        print_symbols(&source_symbols(&source), 0, &mut printed);
+        assert_eq!(
+            printed,
+            vec![
+                "UserQuery (query) 2:0-7:1 @ 2:6-2:15",
+                "  me 3:2-6:3 @ 3:2-3:4",
+                "    name 4:4-4:8 @ 4:4-4:8",
+                "    id 5:18-5:20 @ 5:18-5:20",
+                "User_user (on User) 8:0-13:1 @ 8:9-8:18",
+                "  friends 10:2-12:3 @ 10:2-10:9",
+                "    id (alias: count) 11:4-11:13 @ 11:11-11:13",
+            ]
+        );
+    }
+}
@@ -6,77 +6,144 @@
  */
 
 //! Utilities for reporting errors to an LSP client
+use crate::completion::GraphQLSourceCache;
 use crate::lsp::{
-    publish_diagnostic, url_from_location, Diagnostic, DiagnosticSeverity, PublishDiagnosticsParams,
+    lsp_location_from_location, show_error_message, show_warning_message, url_from_location,
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, NumberOrString,
 };
-use crate::lsp::{Connection, Url};
+use crate::lsp::{Connection, Position, Range, Url};
 use crate::state::ServerState;
+use common::Location;
+use graphql_ir::ValidationMessage;
+use graphql_syntax::GraphQLSource;
 
+use relay_compiler::compiler_state::ProjectName;
+use relay_compiler::config::Config;
 use relay_compiler::errors::{
-    BuildProjectError, SyntaxErrorWithSource, ValidationError, ValidationErrorWithSources,
+    BuildProjectError, Error as CompilerError, SchemaValidationError, SyntaxErrorWithSource,
+    ValidationError, ValidationErrorWithSources,
 };
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-/// Report errors that occur during the `build_project` step
-pub fn report_build_project_errors(
+/// The `source` of all diagnostics reported by Relay, which editors show next to the
+/// message and allow filtering by.
+pub const DIAGNOSTIC_SOURCE: &str = "relay";
+
+/// Prefixes the code of a diagnostic with the project it was reported for, e.g.
+/// `my_project` or `my_project/unused-fragment`, so that diagnostics of configs with
+/// multiple projects can be told apart.
+pub fn add_project_code(diagnostic: &mut Diagnostic, project_name: ProjectName) {
+    diagnostic.code = Some(NumberOrString::String(match &diagnostic.code {
+        Some(NumberOrString::String(code)) => format!("{}/{}", project_name, code),
+        Some(NumberOrString::Number(code)) => format!("{}/{}", project_name, code),
+        None => project_name.to_string(),
+    }));
+}
+
+/// Whether the code of the diagnostic is already prefixed with the name of one of the
+/// projects of the config, see `add_project_code`.
+pub fn has_project_code(diagnostic: &Diagnostic, config: &Config) -> bool {
+    let code = match &diagnostic.code {
+        Some(NumberOrString::String(code)) => code,
+        _ => return false,
+    };
+    config.projects.keys().any(|project_name| {
+        let project_name = project_name.lookup();
+        code == project_name
+            || (code.starts_with(project_name) && code[project_name.len()..].starts_with('/'))
+    })
+}
+
+/// Diagnostics for the error of checking the project `project_name`, with the code of
+/// that project. A document can be shared by several projects, e.g. with a base
+/// project, and fail to check in only one of them, so the project of the document
+/// doesn't tell which check failed.
+pub fn project_error_diagnostics(
+    project_name: ProjectName,
+    error: BuildProjectError,
+    root_dir: &PathBuf,
+) -> HashMap<Url, Vec<Diagnostic>> {
+    let mut diagnostics = build_project_error_diagnostics(vec![error], root_dir);
+    for diagnostic in diagnostics.values_mut().flatten() {
+        add_project_code(diagnostic, project_name);
+    }
+    diagnostics
+}
+
+/// Diagnostics for errors that occur during the `build_project` step, grouped by the
+/// document they belong to. Unused operation variables are reported as a warning on
+/// each of the unused variable declarations. Other errors are reported on their first
+/// location, with the remaining locations, e.g. the other side of conflicting
+/// selections, as related information.
+pub fn build_project_error_diagnostics(
     errors: Vec<BuildProjectError>,
-    connection: &Connection,
-    server_state: &mut ServerState,
-) {
+    root_dir: &PathBuf,
+) -> HashMap<Url, Vec<Diagnostic>> {
+    let mut diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
     for error in errors {
         match error {
             BuildProjectError::ValidationErrors { errors } => {
                 for ValidationErrorWithSources { error, sources } in errors {
                     let ValidationError { message, locations } = error;
 
-                    let message = format!("{}", message);
-
-                    let (source, location) = match (sources.first(), locations.first()) {
-                        (Some(Some(source)), Some(location)) => (source, location),
-                        _ => {
-                            // If we can't get the source and location we can't report the error, so
-                            // exit early.
-                            // TODO(brandondail) we should always have at least one source and location, so log here when we don't
-                            return;
-                        }
+                    let (severity, locations_and_sources, related_information) = match message {
+                        ValidationMessage::UnusedVariables { .. } => (
+                            DiagnosticSeverity::Warning,
+                            locations.iter().zip(sources.iter()).collect::<Vec<_>>(),
+                            None,
+                        ),
+                        _ => (
+                            DiagnosticSeverity::Error,
+                            locations.iter().zip(sources.iter()).take(1).collect(),
+                            related_information_for_locations(
+                                &message,
+                                locations.get(1..).unwrap_or(&[]),
+                                sources.get(1..).unwrap_or(&[]),
+                                root_dir,
+                            ),
+                        ),
                     };
 
-                    let url = match url_from_location(location, &server_state.root_dir) {
-                        Some(url) => url,
-                        None => {
-                            // If we can't parse the location as a Url we can't report the error
-                            // TODO(brandondail) we should always be able to parse as a Url, so log here when we don't
-                            return;
-                        }
-                    };
+                    let message = format!("{}", message);
 
-                    server_state.register_url_with_diagnostics(url.clone());
-
-                    let range = location.span().to_range(
-                        &source.text,
-                        source.line_index,
-                        source.column_index,
-                    );
-
-                    let diagnostic = Diagnostic {
-                        code: None,
-                        message,
-                        range,
-                        related_information: None,
-                        severity: Some(DiagnosticSeverity::Error),
-                        source: None,
-                        tags: None,
-                    };
+                    for (location, source) in locations_and_sources {
+                        let source = match source {
+                            Some(source) => source,
+                            None => {
+                                // If we can't get the source we can't report the error
+                                // TODO(brandondail) we should always have a source for each location, so log here when we don't
+                                continue;
+                            }
+                        };
 
-                    let params = PublishDiagnosticsParams {
-                        diagnostics: vec![diagnostic],
-                        uri: url,
-                        version: None,
-                    };
+                        let url = match url_from_location(location, root_dir) {
+                            Some(url) => url,
+                            None => {
+                                // If we can't parse the location as a Url we can't report the error
+                                // TODO(brandondail) we should always be able to parse as a Url, so log here when we don't
+                                continue;
+                            }
+                        };
+
+                        let range = location.span().to_range(
+                            &source.text,
+                            source.line_index,
+                            source.column_index,
+                        );
 
-                    publish_diagnostic(params, &connection).unwrap();
+                        diagnostics.entry(url).or_default().push(Diagnostic {
+                            code: None,
+                            message: message.clone(),
+                            range,
+                            related_information: related_information.clone(),
+                            severity: Some(severity),
+                            source: Some(DIAGNOSTIC_SOURCE.to_string()),
+                            tags: None,
+                        });
+                    }
                 }
             }
             // We ignore persist/write errors for now. In the future we can potentially show a notification.
@@ -84,54 +151,301 @@ pub fn report_build_project_errors(
             BuildProjectError::WriteFileError { .. } => {}
         }
     }
-    // ...
+    diagnostics
+}
+
+/// Maps the secondary locations of an error to related information of its diagnostic, so
+/// editors can navigate between them. Locations without a source are skipped.
+fn related_information_for_locations(
+    message: &ValidationMessage,
+    locations: &[Location],
+    sources: &[Option<GraphQLSource>],
+    root_dir: &PathBuf,
+) -> Option<Vec<DiagnosticRelatedInformation>> {
+    let related_information: Vec<_> = locations
+        .iter()
+        .zip(sources.iter())
+        .filter_map(|(location, source)| {
+            let location = lsp_location_from_location(location, root_dir, source.as_ref()?)?;
+            Some(DiagnosticRelatedInformation {
+                location,
+                message: format!("{}", message),
+            })
+        })
+        .collect();
+    if related_information.is_empty() {
+        None
+    } else {
+        Some(related_information)
+    }
 }
 
-/// Report errors that occur during parsing
-pub fn report_syntax_errors(
+/// Diagnostics for errors that occur during parsing, grouped by the document they belong
+/// to. The spans of syntax errors are relative to the GraphQL source they occur in, so the
+/// ranges are offset by the position of the source in its document.
+pub fn syntax_error_diagnostics(
     errors: Vec<SyntaxErrorWithSource>,
-    connection: &Connection,
-    server_state: &mut ServerState,
-) {
+    graphql_source_cache: &GraphQLSourceCache,
+    root_dir: &PathBuf,
+) -> HashMap<Url, Vec<Diagnostic>> {
+    let mut diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
     for SyntaxErrorWithSource { error, source } in errors {
-        // Remove the index from the end of the path, resolve the absolute path
-        let file_path = {
-            let file_path_and_index = error.location.file().lookup();
-            let file_path_and_index: Vec<&str> = file_path_and_index.split(':').collect();
-            let file_path = PathBuf::from(file_path_and_index[0]);
-            fs::canonicalize(server_state.root_dir.join(file_path)).unwrap()
+        // Split the index of the source from the end of the path, resolve the absolute path
+        let file_path_and_index = error.location.file().lookup();
+        let (file_path, source_index) = match file_path_and_index.rfind(':') {
+            Some(separator) => (
+                &file_path_and_index[..separator],
+                file_path_and_index[separator + 1..].parse::<usize>().ok(),
+            ),
+            None => (file_path_and_index, None),
+        };
+        let url = match fs::canonicalize(root_dir.join(file_path))
+            .ok()
+            .and_then(|file_path| Url::from_file_path(file_path).ok())
+        {
+            Some(url) => url,
+            None => continue,
         };
 
-        let url = Url::from_file_path(file_path).unwrap();
-
-        // Track the url we're reporting diagnostics for so we can
-        // clear them out later.
-        server_state.register_url_with_diagnostics(url.clone());
-
-        let message = format!("{}", error.kind);
-
+        let source = synced_graphql_source(&url, source_index, &source, graphql_source_cache)
+            .unwrap_or(&source);
         let range =
             error
                 .location
                 .span()
                 .to_range(&source.text, source.line_index, source.column_index);
 
-        let diagnostic = Diagnostic {
+        diagnostics.entry(url).or_default().push(Diagnostic {
             code: None,
-            message,
+            message: format!("{}", error.kind),
             range,
             related_information: None,
             severity: Some(DiagnosticSeverity::Error),
-            source: Some(source.text),
+            source: Some(DIAGNOSTIC_SOURCE.to_string()),
             tags: None,
+        });
+    }
+    diagnostics
+}
+
+/// Diagnostics for the errors of a schema that failed to build, grouped by the schema
+/// file they belong to. The schema errors don't have a position, so they are reported
+/// at the start of the file. Errors that aren't specific to a file are reported on each
+/// of the `schema_files` of the project.
+pub fn schema_error_diagnostics(
+    error: &CompilerError,
+    schema_files: &[&PathBuf],
+    root_dir: &PathBuf,
+) -> HashMap<Url, Vec<Diagnostic>> {
+    let mut diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
+    let validation_errors = match error {
+        CompilerError::SchemaValidation {
+            validation_errors, ..
+        } => validation_errors,
+        _ => return diagnostics,
+    };
+    for validation_error in validation_errors {
+        let files: Vec<&PathBuf> = match validation_error {
+            SchemaValidationError::ConflictingTypeDefinitions { files, .. } => {
+                files.iter().collect()
+            }
+            SchemaValidationError::InvalidSchemaFile { file, .. } => vec![file],
+            SchemaValidationError::InvalidSchema { .. } => schema_files.to_vec(),
+        };
+        for file in files {
+            let url = match fs::canonicalize(root_dir.join(file))
+                .ok()
+                .and_then(|file_path| Url::from_file_path(file_path).ok())
+            {
+                Some(url) => url,
+                None => continue,
+            };
+            let position = Position::new(0, 0);
+            diagnostics.entry(url).or_default().push(Diagnostic {
+                code: None,
+                message: format!("{}", validation_error),
+                range: Range::new(position, position),
+                related_information: None,
+                severity: Some(DiagnosticSeverity::Error),
+                source: Some(DIAGNOSTIC_SOURCE.to_string()),
+                tags: None,
+            });
+        }
+    }
+    diagnostics
+}
+
+/// The document open in the editor can differ from the file the compiler parsed, e.g.
+/// when lines were added above the GraphQL source without saving. The synced source is
+/// used when it has the same text, so the range is offset by its current position.
+fn synced_graphql_source<'a>(
+    url: &Url,
+    source_index: Option<usize>,
+    source: &GraphQLSource,
+    graphql_source_cache: &'a GraphQLSourceCache,
+) -> Option<&'a GraphQLSource> {
+    let synced_source = graphql_source_cache.get(url)?.get(source_index?)?;
+    if synced_source.text == source.text {
+        Some(synced_source)
+    } else {
+        None
+    }
+}
+
+/// The file source found no files to compile, e.g. because the `sources` of the config
+/// don't match the files of the root directory. Shown as a warning, since nothing is
+/// checked until the sources are fixed.
+pub fn report_empty_query_result(config: &Config, connection: &Connection) {
+    show_warning_message(empty_query_result_message(config), connection).unwrap();
+}
+
+fn empty_query_result_message(config: &Config) -> String {
+    let mut source_dirs: Vec<String> = config
+        .sources
+        .keys()
+        .map(|source_dir| format!("`{}`", source_dir.display()))
+        .collect();
+    source_dirs.sort();
+    if source_dirs.is_empty() {
+        "Relay: No GraphQL sources were found, the config doesn't have any `sources`.".to_string()
+    } else {
+        format!(
+            "Relay: No GraphQL sources were found in the source directories of the config: {}. Check that the `sources` of the config match files of the root directory `{}`.",
+            source_dirs.join(", "),
+            config.root_dir.display()
+        )
+    }
+}
+
+/// Report errors that occur while loading the config file. These are shown as a notification,
+/// and additionally as diagnostics on the config file if it exists: at the position of a
+/// parse error, and at the start of the file for each validation error.
+pub fn report_config_error(
+    error: &CompilerError,
+    connection: &Connection,
+    server_state: &mut ServerState,
+) {
+    show_error_message(format!("Relay: {}", error), connection).unwrap();
+
+    let (config_path, diagnostics) = match error {
+        CompilerError::ConfigFileParse {
+            config_path,
+            source,
+        } => {
+            // serde_json positions are one-based, LSP positions are zero-based
+            let position = Position::new(
+                source.line().saturating_sub(1) as u64,
+                source.column().saturating_sub(1) as u64,
+            );
+            (
+                config_path,
+                vec![config_error_diagnostic(format!("{}", source), position)],
+            )
+        }
+        CompilerError::ConfigFileValidation {
+            config_path,
+            validation_errors,
+        } => (
+            config_path,
+            validation_errors
+                .iter()
+                .map(|error| config_error_diagnostic(format!("{}", error), Position::new(0, 0)))
+                .collect(),
+        ),
+        _ => return,
+    };
+    let url = match fs::canonicalize(config_path)
+        .ok()
+        .and_then(|path| Url::from_file_path(path).ok())
+    {
+        Some(url) => url,
+        None => return,
+    };
+
+    let mut diagnostics_by_url = HashMap::new();
+    diagnostics_by_url.insert(url, diagnostics);
+    server_state.publish_diagnostics(None, diagnostics_by_url, connection);
+}
+
+fn config_error_diagnostic(message: String, position: Position) -> Diagnostic {
+    Diagnostic {
+        code: None,
+        message,
+        range: Range::new(position, position),
+        related_information: None,
+        severity: Some(DiagnosticSeverity::Error),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        tags: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{Message, PublishDiagnosticsParams};
+    use crate::state::NegotiatedCapabilities;
+    use common::{FileKey, Span};
+    use interner::Intern;
+    use relay_compiler::errors::ConfigValidationError;
+
+    #[test]
+    fn test_project_error_diagnostics_have_the_code_of_the_project() {
+        let root_dir = std::env::temp_dir().join("relay_lsp_project_error_diagnostics");
+        fs::create_dir_all(&root_dir).unwrap();
+        let text = "fragment Foo on User { ...Bar }";
+        fs::write(root_dir.join("Foo.graphql"), text).unwrap();
+        let error = BuildProjectError::ValidationErrors {
+            errors: vec![ValidationErrorWithSources {
+                error: ValidationError::new(
+                    ValidationMessage::UndefinedFragment("Bar".intern()),
+                    vec![Location::new(FileKey::new("Foo.graphql"), Span::new(23, 6))],
+                ),
+                sources: vec![Some(GraphQLSource::new(text, 0, 0))],
+            }],
         };
 
-        let params = PublishDiagnosticsParams {
-            diagnostics: vec![diagnostic],
-            uri: url,
-            version: None,
+        let diagnostics = project_error_diagnostics("shared".intern(), error, &root_dir);
+        let diagnostics: Vec<&Diagnostic> = diagnostics.values().flatten().collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("shared".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_config_validation_errors_are_reported_on_the_config_file() {
+        let root_dir = std::env::temp_dir().join("relay_lsp_config_validation_errors");
+        fs::create_dir_all(&root_dir).unwrap();
+        let config_path = root_dir.join("relay.config.json");
+        fs::write(&config_path, "{}").unwrap();
+        let error = CompilerError::ConfigFileValidation {
+            config_path: config_path.clone(),
+            validation_errors: vec![ConfigValidationError::ProjectSchemaMissing {
+                project_name: "public".intern(),
+            }],
         };
+        let (connection, client) = Connection::memory();
+        let mut server_state = ServerState::new(root_dir, NegotiatedCapabilities::default());
+
+        report_config_error(&error, &connection, &mut server_state);
 
-        publish_diagnostic(params, &connection).unwrap();
+        let params: PublishDiagnosticsParams = client
+            .receiver
+            .try_iter()
+            .filter_map(|message| match message {
+                Message::Notification(notification) => {
+                    serde_json::from_value(notification.params).ok()
+                }
+                _ => None,
+            })
+            .last()
+            .expect("Expected diagnostics to be published");
+        assert_eq!(
+            params.uri,
+            Url::from_file_path(fs::canonicalize(&config_path).unwrap()).unwrap()
+        );
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(params.diagnostics[0].range.start, Position::new(0, 0));
     }
 }
@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for finding the field selection at a position of a GraphQL document
+use common::Span;
+use graphql_syntax::{
+    Argument, Document, ExecutableDefinition, Identifier, List, OperationKind, Selection,
+};
+use interner::StringKey;
+use schema::{FieldID, Schema, Type};
+
+/// A field selection of a document, with the type it is selected on
+pub struct FieldAtPosition<'a> {
+    pub parent_type: Type,
+    pub name: &'a Identifier,
+    pub arguments: Option<&'a List<Argument>>,
+}
+
+/// Walks the document until it finds the innermost field selection containing the
+/// position, resolving the parent type of each selection along the way. The field
+/// doesn't need to exist on its parent type, but the fields it is nested in do.
+pub fn find_field_at_position<'a>(
+    document: &'a Document,
+    position_span: Span,
+    schema: &Schema,
+) -> Option<FieldAtPosition<'a>> {
+    for definition in &document.definitions {
+        match definition {
+            ExecutableDefinition::Operation(operation) => {
+                if operation.location.contains(position_span) {
+                    let root_type = match operation.operation {
+                        Some((_, OperationKind::Mutation)) => schema.mutation_type(),
+                        Some((_, OperationKind::Subscription)) => schema.subscription_type(),
+                        Some((_, OperationKind::Query)) | None => schema.query_type(),
+                    }?;
+                    return find_field_in_selections(
+                        &operation.selections,
+                        root_type,
+                        position_span,
+                        schema,
+                    );
+                }
+            }
+            ExecutableDefinition::Fragment(fragment) => {
+                if fragment.location.contains(position_span) {
+                    let type_ = schema.get_type(fragment.type_condition.type_.value)?;
+                    return find_field_in_selections(
+                        &fragment.selections,
+                        type_,
+                        position_span,
+                        schema,
+                    );
+                }
+            }
+        }
+    }
+    None
+}
+
+fn find_field_in_selections<'a>(
+    selections: &'a List<Selection>,
+    parent_type: Type,
+    position_span: Span,
+    schema: &Schema,
+) -> Option<FieldAtPosition<'a>> {
+    let selection = selections
+        .items
+        .iter()
+        .find(|item| item.span().contains(position_span))?;
+    match selection {
+        Selection::LinkedField(node) => {
+            if node.selections.span.contains(position_span) {
+                let field_id = named_field(parent_type, node.name.value, schema)?;
+                let field_type = schema.field(field_id).type_.inner();
+                find_field_in_selections(&node.selections, field_type, position_span, schema)
+            } else {
+                Some(FieldAtPosition {
+                    parent_type,
+                    name: &node.name,
+                    arguments: node.arguments.as_ref(),
+                })
+            }
+        }
+        Selection::ScalarField(node) => Some(FieldAtPosition {
+            parent_type,
+            name: &node.name,
+            arguments: node.arguments.as_ref(),
+        }),
+        Selection::InlineFragment(node) => {
+            let type_ = match &node.type_condition {
+                Some(type_condition) => schema.get_type(type_condition.type_.value)?,
+                None => parent_type,
+            };
+            find_field_in_selections(&node.selections, type_, position_span, schema)
+        }
+        Selection::FragmentSpread(_) => None,
+    }
+}
+
+/// Like `Schema::named_field`, but returns `None` instead of panicking
+/// for types that can't have fields.
+pub fn named_field(parent_type: Type, name: StringKey, schema: &Schema) -> Option<FieldID> {
+    match parent_type {
+        Type::Object(_) | Type::Interface(_) | Type::Union(_) => {
+            schema.named_field(parent_type, name)
+        }
+        Type::Enum(_) | Type::InputObject(_) | Type::Scalar(_) => None,
+    }
+}
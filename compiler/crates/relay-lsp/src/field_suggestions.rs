@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for the "Did you mean" code actions replacing unknown field names
+use crate::completion::{get_graphql_source_for_position, position_to_span, GraphQLSourceCache};
+use crate::field_at_position::{find_field_at_position, named_field};
+use crate::lsp::{
+    CodeAction, CodeActionOrCommand, CodeActionParams, Range, TextEdit, Url, WorkspaceEdit,
+};
+use common::{suggested_names, FileKey};
+use graphql_ir::ValidationMessage;
+use graphql_syntax::parse;
+use interner::StringKey;
+use schema::{FieldID, Schema, Type, TypeWithFields};
+use std::collections::HashMap;
+
+/// The maximum number of suggested field names
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns a code action for each of the field names closest to the unknown field at
+/// the start of the requested range, replacing the name of the field. The unknown
+/// field diagnostics of the field are attached to the code actions.
+pub fn get_field_suggestion_code_actions(
+    params: &CodeActionParams,
+    graphql_source_cache: &GraphQLSourceCache,
+    schema: &Schema,
+) -> Vec<CodeActionOrCommand> {
+    let uri = &params.text_document.uri;
+    let position = params.range.start;
+    let graphql_source = match get_graphql_source_for_position(uri, position, graphql_source_cache)
+    {
+        Some(graphql_source) => graphql_source,
+        None => return vec![],
+    };
+    let document = match parse(&graphql_source.text, FileKey::new(&uri.to_string())) {
+        Ok(document) => document,
+        Err(_) => return vec![],
+    };
+    let field = match position_to_span(position, graphql_source)
+        .and_then(|position_span| find_field_at_position(&document, position_span, schema))
+    {
+        Some(field) => field,
+        None => return vec![],
+    };
+    if named_field(field.parent_type, field.name.value, schema).is_some() {
+        return vec![];
+    }
+
+    let message = format!(
+        "{}",
+        ValidationMessage::UnknownField {
+            type_: schema.get_type_name(field.parent_type),
+            field: field.name.value,
+        }
+    );
+    let diagnostics: Vec<_> = params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.message == message)
+        .cloned()
+        .collect();
+    let range = field.name.span.to_range(
+        &graphql_source.text,
+        graphql_source.line_index,
+        graphql_source.column_index,
+    );
+
+    let field_names = field_names(field.parent_type, schema);
+    suggested_field_names(field.name.value, &field_names)
+        .into_iter()
+        .enumerate()
+        .map(|(index, suggestion)| {
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Did you mean `{}`?", suggestion),
+                kind: Some("quickfix".to_string()),
+                diagnostics: if diagnostics.is_empty() {
+                    None
+                } else {
+                    Some(diagnostics.clone())
+                },
+                edit: Some(replace_edit(uri, range, suggestion)),
+                command: None,
+                is_preferred: Some(index == 0),
+            })
+        })
+        .collect()
+}
+
+fn replace_edit(uri: &Url, range: Range, new_name: StringKey) -> WorkspaceEdit {
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range,
+            new_text: new_name.to_string(),
+        }],
+    );
+    WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+    }
+}
+
+fn field_names(parent_type: Type, schema: &Schema) -> Vec<StringKey> {
+    let field_ids: &[FieldID] = match parent_type {
+        Type::Object(object_id) => schema.object(object_id).fields(),
+        Type::Interface(interface_id) => schema.interface(interface_id).fields(),
+        Type::Enum(_) | Type::InputObject(_) | Type::Scalar(_) | Type::Union(_) => &[],
+    };
+    field_ids
+        .iter()
+        .map(|field_id| schema.field(*field_id).name)
+        .collect()
+}
+
+/// Returns up to `MAX_SUGGESTIONS` of the `candidates` closest to `name`, see
+/// `common::suggested_names`.
+fn suggested_field_names(name: StringKey, candidates: &[StringKey]) -> Vec<StringKey> {
+    suggested_names(name, candidates.iter().copied(), MAX_SUGGESTIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interner::Intern;
+
+    #[test]
+    fn test_suggested_field_names() {
+        let candidates: Vec<StringKey> = vec![
+            "id".intern(),
+            "name".intern(),
+            "names".intern(),
+            "firstName".intern(),
+            "lastName".intern(),
+            "address".intern(),
+        ];
+        assert_eq!(
+            suggested_field_names("nam".intern(), &candidates),
+            vec!["name".intern(), "names".intern()]
+        );
+        assert_eq!(
+            suggested_field_names("firstname".intern(), &candidates),
+            vec!["firstName".intern(), "lastName".intern()]
+        );
+        assert!(suggested_field_names("xyzzy".intern(), &candidates).is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the folding ranges of selection sets and argument lists
+use crate::completion::GraphQLSourceCache;
+use crate::lsp::{
+    Connection, FoldingRange, FoldingRangeParams, Message, ServerRequestId, ServerResponse,
+};
+use common::FileKey;
+use graphql_syntax::{
+    parse, Argument, Directive, ExecutableDefinition, GraphQLSource, List, Selection,
+};
+use log::info;
+
+/// Returns the folding ranges of the GraphQL sources of the document: a range for each
+/// selection set, and for each list of arguments or variable definitions, spanning
+/// from the line of the opening brace or parenthesis to the line of the closing one.
+/// Lists on a single line can't be folded and are skipped.
+pub fn get_folding_ranges(
+    params: &FoldingRangeParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> Vec<FoldingRange> {
+    let url = &params.text_document.uri;
+    let mut ranges = vec![];
+    if let Some(graphql_sources) = graphql_source_cache.get(url) {
+        for graphql_source in graphql_sources {
+            ranges.extend(folding_ranges_for_source(
+                graphql_source,
+                FileKey::new(&url.to_string()),
+            ));
+        }
+    }
+    ranges
+}
+
+fn folding_ranges_for_source(source: &GraphQLSource, file_key: FileKey) -> Vec<FoldingRange> {
+    let document = match parse(&source.text, file_key) {
+        Ok(document) => document,
+        Err(err) => {
+            // Skip sources that can't be parsed, but keep the ranges of the others
+            info!(
+                "Failed to parse a GraphQL source for folding ranges: {:?}",
+                err
+            );
+            return vec![];
+        }
+    };
+    let mut collector = FoldingRangeCollector {
+        source,
+        ranges: vec![],
+    };
+    for definition in &document.definitions {
+        match definition {
+            ExecutableDefinition::Operation(operation) => {
+                if let Some(variable_definitions) = &operation.variable_definitions {
+                    collector.push(variable_definitions);
+                }
+                collector.collect_directives(&operation.directives);
+                collector.collect_selections(&operation.selections);
+            }
+            ExecutableDefinition::Fragment(fragment) => {
+                collector.collect_directives(&fragment.directives);
+                collector.collect_selections(&fragment.selections);
+            }
+        }
+    }
+    collector.ranges
+}
+
+struct FoldingRangeCollector<'a> {
+    source: &'a GraphQLSource,
+    ranges: Vec<FoldingRange>,
+}
+
+impl<'a> FoldingRangeCollector<'a> {
+    /// Adds the range of `selections`, followed by the ranges nested in it
+    fn collect_selections(&mut self, selections: &List<Selection>) {
+        self.push(selections);
+        for selection in &selections.items {
+            match selection {
+                Selection::LinkedField(field) => {
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                    self.collect_selections(&field.selections);
+                }
+                Selection::ScalarField(field) => {
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.collect_directives(&fragment.directives);
+                    self.collect_selections(&fragment.selections);
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.collect_directives(&spread.directives);
+                }
+            }
+        }
+    }
+
+    fn collect_directives(&mut self, directives: &[Directive]) {
+        for directive in directives {
+            self.collect_arguments(&directive.arguments);
+        }
+    }
+
+    fn collect_arguments(&mut self, arguments: &Option<List<Argument>>) {
+        if let Some(arguments) = arguments {
+            self.push(arguments);
+        }
+    }
+
+    /// Adds a range from the opening to the closing token of `list`, if they are on
+    /// different lines
+    fn push<T>(&mut self, list: &List<T>) {
+        let source = self.source;
+        let start = list
+            .start
+            .inner_span
+            .to_range(&source.text, source.line_index, source.column_index)
+            .start;
+        let end = list
+            .end
+            .inner_span
+            .to_range(&source.text, source.line_index, source.column_index)
+            .start;
+        if end.line > start.line {
+            self.ranges.push(FoldingRange {
+                start_line: start.line,
+                start_character: Some(start.character),
+                end_line: end.line,
+                end_character: Some(end.character),
+                kind: None,
+            });
+        }
+    }
+}
+
+pub fn send_folding_ranges_response(
+    ranges: Vec<FoldingRange>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&ranges).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_ranges(text: &str, line_index: usize) -> Vec<(u64, u64)> {
+        let source = GraphQLSource::new(text, line_index, 0);
+        folding_ranges_for_source(&source, FileKey::new("test.graphql"))
+            .into_iter()
+            .map(|range| (range.start_line, range.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_folding_ranges_of_nested_selection_sets() {
+        let text = "query Foo(
+  $id: ID
+) {
+  node(id: $id) {
+    ... on User {
+      friends(
+        first: 10
+      ) { count }
+    }
+  }
+}
+fragment Bar on User { id }";
+        assert_eq!(
+            line_ranges(text, 0),
+            vec![(0, 2), (2, 10), (3, 9), (4, 8), (5, 7)]
+        );
+    }
+
+    #[test]
+    fn test_folding_ranges_are_in_document_coordinates() {
+        let text = "fragment Foo on User {
+  id
+}";
+        assert_eq!(line_ranges(text, 5), vec![(5, 7)]);
+    }
+}
@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the hover language feature
+use crate::completion::{get_graphql_source_for_position, position_to_span, GraphQLSourceCache};
+use crate::field_at_position::{find_field_at_position, named_field};
+use crate::lsp::{
+    Connection, Hover, HoverContents, HoverParams, MarkupContent, MarkupKind, Message,
+    ServerRequestId, ServerResponse, TextDocumentPositionParams,
+};
+use common::FileKey;
+use graphql_syntax::parse;
+use interner::{Intern, StringKey};
+use log::info;
+use schema::{AstValue, DirectiveValue, Field, Schema, Type, TypeReference};
+use std::collections::HashMap;
+
+/// Resolves the hover contents for the field under the cursor, if the hover request
+/// occurs on the name of a field within a GraphQL document. The client types of the
+/// `custom_scalars` are shown next to the type of the field.
+pub fn get_hover_response_contents(
+    params: HoverParams,
+    graphql_source_cache: &GraphQLSourceCache,
+    schema: &Schema,
+    custom_scalars: &HashMap<StringKey, String>,
+) -> Option<HoverContents> {
+    let HoverParams {
+        text_document_position_params,
+        ..
+    } = params;
+    let TextDocumentPositionParams {
+        text_document,
+        position,
+    } = text_document_position_params;
+    let url = text_document.uri;
+
+    let graphql_source = get_graphql_source_for_position(&url, position, graphql_source_cache)?;
+    let document = match parse(&graphql_source.text, FileKey::new(&url.to_string())) {
+        Ok(document) => document,
+        Err(err) => {
+            info!(
+                "Failed to parse the target GraphQL source for hover: {:?}",
+                err
+            );
+            return None;
+        }
+    };
+    let position_span = position_to_span(position, &graphql_source)?;
+    let field = find_field_at_position(&document, position_span, schema)?;
+    if !field.name.span.contains(position_span) {
+        return None;
+    }
+    let field_id = named_field(field.parent_type, field.name.value, schema)?;
+    Some(HoverContents::Markup(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: hover_markdown_for_field(schema.field(field_id), schema, custom_scalars),
+    }))
+}
+
+fn hover_markdown_for_field(
+    field: &Field,
+    schema: &Schema,
+    custom_scalars: &HashMap<StringKey, String>,
+) -> String {
+    let mut type_line = format!(
+        "**{}**: `{}`",
+        field.name,
+        schema.get_type_string(&field.type_)
+    );
+    if let Some(client_type) = get_custom_scalar_client_type(&field.type_, schema, custom_scalars) {
+        type_line.push_str(&format!(" (`{}`)", client_type));
+    }
+    let mut lines = vec![type_line];
+
+    lines.push(if field.type_.is_non_null() {
+        "Non-null".to_string()
+    } else {
+        "Nullable".to_string()
+    });
+
+    if !field.arguments.is_empty() {
+        let arguments = field
+            .arguments
+            .iter()
+            .map(|argument| {
+                format!(
+                    "- `{}: {}`",
+                    argument.name,
+                    schema.get_type_string(&argument.type_)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        lines.push(format!("Arguments:\n{}", arguments));
+    }
+
+    if let Some(reason) = get_deprecation_reason(&field.directives) {
+        lines.push(format!("**Deprecated**: {}", reason));
+    }
+
+    lines.join("\n\n")
+}
+
+/// Returns the reason of a `@deprecated` field or enum value, given its directives.
+/// Deprecations without an explicit reason get the default reason from the GraphQL spec.
+pub fn get_deprecation_reason(directives: &[DirectiveValue]) -> Option<String> {
+    let deprecated_directive = directives
+        .iter()
+        .find(|directive| directive.name == "deprecated".intern())?;
+    let reason = deprecated_directive
+        .arguments
+        .iter()
+        .find(|argument| argument.name == "reason".intern())
+        .and_then(|argument| match &argument.value {
+            AstValue::String(reason) => Some(reason.clone()),
+            _ => None,
+        });
+    Some(reason.unwrap_or_else(|| "No longer supported".to_string()))
+}
+
+/// Returns the client type of a custom scalar type, or of the items of a list of a
+/// custom scalar type, if it's mapped in `custom_scalars`.
+pub fn get_custom_scalar_client_type<'a>(
+    type_: &TypeReference,
+    schema: &Schema,
+    custom_scalars: &'a HashMap<StringKey, String>,
+) -> Option<&'a str> {
+    match type_.inner() {
+        Type::Scalar(scalar_id) => custom_scalars
+            .get(&schema.scalar(scalar_id).name)
+            .map(String::as_str),
+        _ => None,
+    }
+}
+
+pub fn send_hover_response(
+    contents: Option<HoverContents>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let hover = contents.map(|contents| Hover {
+        contents,
+        range: None,
+    });
+    let result = serde_json::to_value(&hover).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{Position, TextDocumentIdentifier, Url, WorkDoneProgressParams};
+    use graphql_syntax::GraphQLSource;
+    use schema::build_schema;
+
+    #[test]
+    fn test_hover_shows_client_type_of_custom_scalars() {
+        let schema = build_schema(
+            "scalar DateTime type Query { me: User } type User { createdAt: DateTime!, name: String }",
+        )
+        .unwrap();
+        let text = "query Foo { me { createdAt name } }";
+        let url = Url::parse("file:///test.graphql").unwrap();
+        let mut graphql_source_cache: GraphQLSourceCache = HashMap::new();
+        graphql_source_cache.insert(url.clone(), vec![GraphQLSource::new(text, 0, 0)]);
+        let mut custom_scalars = HashMap::new();
+        custom_scalars.insert("DateTime".intern(), "string".to_string());
+        let hover_at = |field_name| {
+            let params = HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: url.clone() },
+                    position: Position::new(0, text.find(field_name).unwrap() as u64 + 1),
+                },
+                work_done_progress_params: WorkDoneProgressParams {
+                    work_done_token: None,
+                },
+            };
+            match get_hover_response_contents(
+                params,
+                &graphql_source_cache,
+                &schema,
+                &custom_scalars,
+            ) {
+                Some(HoverContents::Markup(markup)) => markup.value,
+                contents => panic!("Expected markup contents, got {:?}", contents),
+            }
+        };
+
+        assert_eq!(
+            hover_at("createdAt"),
+            "**createdAt**: `DateTime!` (`string`)\n\nNon-null"
+        );
+        assert_eq!(hover_at("name"), "**name**: `String`\n\nNullable");
+    }
+}
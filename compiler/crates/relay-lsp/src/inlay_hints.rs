@@ -0,0 +1,301 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the inlay hints language feature
+use crate::completion::GraphQLSourceCache;
+use crate::lsp::{
+    Connection, InlayHint, InlayHintParams, Message, Position, Range, ServerRequestId,
+    ServerResponse, INLAY_HINT_KIND_TYPE,
+};
+use common::{FileKey, NamedItem, Span};
+use graphql_ir::{Program, VariableDefinition};
+use graphql_syntax::{
+    parse, Argument, ConstantValue, Directive, ExecutableDefinition, GraphQLSource, List,
+    Selection, Value,
+};
+use interner::{Intern, StringKey};
+use log::info;
+
+/// The directive passing the arguments of a fragment spread
+const ARGUMENTS_DIRECTIVE_NAME: &str = "arguments";
+
+/// Returns the inlay hints of the GraphQL sources of the document within the requested
+/// range, using the fragments of `program`:
+/// - The global variables used by a fragment, which aren't declared in the fragment,
+///   are annotated with the type inferred from their usages, e.g. `$count: Int`.
+/// - The values passed to `@arguments` are annotated with the type of the argument
+///   definition of the spread fragment, e.g. `@arguments(count: 10: Int)`.
+pub fn get_inlay_hints(
+    params: &InlayHintParams,
+    graphql_source_cache: &GraphQLSourceCache,
+    program: &Program<'_>,
+) -> Vec<InlayHint> {
+    let url = &params.text_document.uri;
+    let mut hints = vec![];
+    if let Some(graphql_sources) = graphql_source_cache.get(url) {
+        for graphql_source in graphql_sources {
+            hints.extend(
+                inlay_hints_for_source(graphql_source, FileKey::new(&url.to_string()), program)
+                    .into_iter()
+                    .filter(|hint| is_in_range(hint.position, params.range)),
+            );
+        }
+    }
+    hints
+}
+
+fn inlay_hints_for_source(
+    source: &GraphQLSource,
+    file_key: FileKey,
+    program: &Program<'_>,
+) -> Vec<InlayHint> {
+    let document = match parse(&source.text, file_key) {
+        Ok(document) => document,
+        Err(err) => {
+            // Skip sources that can't be parsed, but keep the hints of the others
+            info!(
+                "Failed to parse a GraphQL source for inlay hints: {:?}",
+                err
+            );
+            return vec![];
+        }
+    };
+    let mut hints = vec![];
+    for definition in &document.definitions {
+        let mut collector = HintCollector {
+            source,
+            program,
+            global_variables: &[],
+            hints: &mut hints,
+        };
+        match definition {
+            ExecutableDefinition::Operation(operation) => {
+                collector.collect_directives(&operation.directives);
+                collector.collect_selections(&operation.selections);
+            }
+            ExecutableDefinition::Fragment(fragment) => {
+                if let Some(fragment_definition) = program.fragment(fragment.name.value) {
+                    collector.global_variables = &fragment_definition.used_global_variables;
+                }
+                collector.collect_directives(&fragment.directives);
+                collector.collect_selections(&fragment.selections);
+            }
+        }
+    }
+    hints
+}
+
+struct HintCollector<'a, 'schema> {
+    source: &'a GraphQLSource,
+    program: &'a Program<'schema>,
+    /// The global variables used by the fragment being visited, with their inferred types
+    global_variables: &'a [VariableDefinition],
+    hints: &'a mut Vec<InlayHint>,
+}
+
+impl<'a, 'schema> HintCollector<'a, 'schema> {
+    fn collect_selections(&mut self, selections: &List<Selection>) {
+        for selection in &selections.items {
+            match selection {
+                Selection::LinkedField(field) => {
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                    self.collect_selections(&field.selections);
+                }
+                Selection::ScalarField(field) => {
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.collect_directives(&fragment.directives);
+                    self.collect_selections(&fragment.selections);
+                }
+                Selection::FragmentSpread(spread) => {
+                    let arguments_directive_name = ARGUMENTS_DIRECTIVE_NAME.intern();
+                    for directive in &spread.directives {
+                        if directive.name.value == arguments_directive_name {
+                            self.collect_fragment_arguments(spread.name.value, directive);
+                        } else {
+                            self.collect_arguments(&directive.arguments);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn collect_directives(&mut self, directives: &[Directive]) {
+        for directive in directives {
+            self.collect_arguments(&directive.arguments);
+        }
+    }
+
+    fn collect_arguments(&mut self, arguments: &Option<List<Argument>>) {
+        if let Some(arguments) = arguments {
+            for argument in &arguments.items {
+                self.collect_value(&argument.value);
+            }
+        }
+    }
+
+    /// Annotates the values passed to the argument definitions of the spread fragment
+    /// with the type of the argument definition.
+    fn collect_fragment_arguments(
+        &mut self,
+        fragment_name: StringKey,
+        arguments_directive: &Directive,
+    ) {
+        let arguments = match &arguments_directive.arguments {
+            Some(arguments) => arguments,
+            None => return,
+        };
+        let program = self.program;
+        let fragment = program.fragment(fragment_name);
+        for argument in &arguments.items {
+            let argument_definition = fragment
+                .and_then(|fragment| fragment.variable_definitions.named(argument.name.value));
+            match argument_definition {
+                Some(argument_definition) => {
+                    let type_string = program.schema().get_type_string(&argument_definition.type_);
+                    self.push_hint(value_span(&argument.value), type_string);
+                    match argument.value {
+                        // The expected type is enough for a variable passed as is
+                        Value::Variable(_) => {}
+                        _ => self.collect_value(&argument.value),
+                    }
+                }
+                None => self.collect_value(&argument.value),
+            }
+        }
+    }
+
+    fn collect_value(&mut self, value: &Value) {
+        match value {
+            Value::Variable(variable) => {
+                if let Some(global_variable) = self.global_variables.named(variable.name) {
+                    let type_string = self
+                        .program
+                        .schema()
+                        .get_type_string(&global_variable.type_);
+                    self.push_hint(variable.token.inner_span, type_string);
+                }
+            }
+            Value::List(list) => {
+                for item in &list.items {
+                    self.collect_value(item);
+                }
+            }
+            Value::Object(fields) => {
+                for field in &fields.items {
+                    self.collect_value(&field.value);
+                }
+            }
+            Value::Constant(_) => {}
+        }
+    }
+
+    /// Adds a hint with the type at the end of `span`
+    fn push_hint(&mut self, span: Span, type_string: String) {
+        let position = Span::new(span.start + span.length, 0)
+            .to_range(
+                &self.source.text,
+                self.source.line_index,
+                self.source.column_index,
+            )
+            .start;
+        self.hints.push(InlayHint {
+            position,
+            label: format!(": {}", type_string),
+            kind: Some(INLAY_HINT_KIND_TYPE),
+        });
+    }
+}
+
+/// The span of the last token of a value, without the whitespace that follows it
+fn value_span(value: &Value) -> Span {
+    match value {
+        Value::Constant(value) => constant_value_span(value),
+        Value::Variable(variable) => variable.token.inner_span,
+        Value::List(list) => list.end.inner_span,
+        Value::Object(fields) => fields.end.inner_span,
+    }
+}
+
+fn constant_value_span(value: &ConstantValue) -> Span {
+    match value {
+        ConstantValue::Int(value) => value.token.inner_span,
+        ConstantValue::Float(value) => value.token.inner_span,
+        ConstantValue::String(value) => value.token.inner_span,
+        ConstantValue::Boolean(value) => value.token.inner_span,
+        ConstantValue::Null(token) => token.inner_span,
+        ConstantValue::Enum(value) => value.token.inner_span,
+        ConstantValue::List(list) => list.end.inner_span,
+        ConstantValue::Object(fields) => fields.end.inner_span,
+    }
+}
+
+fn is_in_range(position: Position, range: Range) -> bool {
+    (position.line, position.character) >= (range.start.line, range.start.character)
+        && (position.line, position.character) <= (range.end.line, range.end.character)
+}
+
+pub fn send_inlay_hints_response(
+    hints: Vec<InlayHint>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&hints).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_ir::build;
+    use schema::build_schema;
+
+    #[test]
+    fn test_inlay_hints_for_source() {
+        let schema = build_schema(
+            "type Query { me: User } type User { id: ID, friends(first: Int): [User], picture(size: Int): String }",
+        )
+        .unwrap();
+        let text = "fragment Foo on User { friends(first: $count) { ...Bar @arguments(size: 32) } }
+fragment Bar on User @argumentDefinitions(size: {type: \"Int\"}) { picture(size: $size) }";
+        let file_key = FileKey::new("test.graphql");
+        let document = parse(text, file_key).unwrap();
+        let program =
+            Program::from_definitions(&schema, build(&schema, &document.definitions).unwrap());
+        let source = GraphQLSource::new(text, 0, 0);
+
+        let hints = inlay_hints_for_source(&source, file_key, &program);
+        let labels: Vec<(u64, u64, &str)> = hints
+            .iter()
+            .map(|hint| {
+                (
+                    hint.position.line,
+                    hint.position.character,
+                    hint.label.as_str(),
+                )
+            })
+            .collect();
+        let count_end = text.find("$count").unwrap() + "$count".len();
+        let size_end = text.find("32").unwrap() + "32".len();
+        assert_eq!(
+            labels,
+            vec![
+                (0, count_end as u64, ": Int"),
+                (0, size_end as u64, ": Int"),
+            ]
+        );
+    }
+}
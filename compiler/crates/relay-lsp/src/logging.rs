@@ -0,0 +1,237 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for mirroring the server's logs to the client with `window/logMessage`
+//! notifications, so they can be inspected without attaching to the server's console.
+
+use crate::lsp::{
+    InitializeParams, LogMessage, LogMessageParams, Message, MessageType, Notification,
+    ServerNotification,
+};
+use common::{ConsoleLogEvent, ConsoleLogger, PerfLogEvent, PerfLogger};
+use crossbeam_channel::Sender;
+use env_logger::Env;
+use log::{kv, Level, LevelFilter, Log, Metadata, Record};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// The level of the logs sent to the client, unless the client configures it with the
+/// `logLevel` initialization option.
+pub const DEFAULT_CLIENT_LOG_LEVEL: LevelFilter = LevelFilter::Warn;
+
+/// Returns the level of the logs sent to the client, from the `logLevel` initialization
+/// option, e.g. `"info"` or `"off"`.
+pub fn client_log_level(params: &InitializeParams) -> LevelFilter {
+    params
+        .initialization_options
+        .as_ref()
+        .and_then(|options| options.get("logLevel"))
+        .and_then(|level| level.as_str())
+        .and_then(|level| LevelFilter::from_str(level).ok())
+        .unwrap_or(DEFAULT_CLIENT_LOG_LEVEL)
+}
+
+/// Whether the timings of perf log events are sent to the client, enabled with the
+/// `forwardPerfLogs` initialization option.
+pub fn forward_perf_logs(params: &InitializeParams) -> bool {
+    params
+        .initialization_options
+        .as_ref()
+        .and_then(|options| options.get("forwardPerfLogs"))
+        .and_then(|forward| forward.as_bool())
+        .unwrap_or(false)
+}
+
+struct ClientLogTarget {
+    sender: Sender<Message>,
+    level: LevelFilter,
+}
+
+/// Installs a logger that writes to the console, like `env_logger`. The returned
+/// `ClientLogForwarder` starts sending the logs to the client once it is connected.
+pub fn init_logger() -> ClientLogForwarder {
+    let console_logger =
+        env_logger::Builder::from_env(Env::default().default_filter_or("info, warn, error, debug"))
+            .build();
+    let client_target = Arc::new(Mutex::new(None));
+    let console_level = console_logger.filter();
+    log::set_boxed_logger(Box::new(LSPLogger {
+        console_logger,
+        client_target: client_target.clone(),
+    }))
+    .expect("Expected the logger to be installed once");
+    log::set_max_level(console_level);
+    ClientLogForwarder {
+        client_target,
+        console_level,
+    }
+}
+
+pub struct ClientLogForwarder {
+    client_target: Arc<Mutex<Option<ClientLogTarget>>>,
+    console_level: LevelFilter,
+}
+
+impl ClientLogForwarder {
+    /// Sends the logs up to `level` to the client from now on
+    pub fn forward_to_client(&self, sender: Sender<Message>, level: LevelFilter) {
+        *self.client_target.lock().unwrap() = Some(ClientLogTarget { sender, level });
+        log::set_max_level(std::cmp::max(self.console_level, level));
+    }
+}
+
+struct LSPLogger {
+    console_logger: env_logger::Logger,
+    client_target: Arc<Mutex<Option<ClientLogTarget>>>,
+}
+
+impl Log for LSPLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console_logger.enabled(metadata)
+            || self
+                .client_target
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map_or(false, |target| metadata.level() <= target.level)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.console_logger.matches(record) {
+            self.console_logger.log(record);
+        }
+        if let Some(target) = self.client_target.lock().unwrap().as_ref() {
+            if record.level() <= target.level {
+                send_log_message(
+                    &target.sender,
+                    message_type(record.level()),
+                    format_record(record),
+                );
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.console_logger.flush();
+    }
+}
+
+fn message_type(level: Level) -> MessageType {
+    match level {
+        Level::Error => MessageType::Error,
+        Level::Warn => MessageType::Warning,
+        Level::Info => MessageType::Info,
+        Level::Debug | Level::Trace => MessageType::Log,
+    }
+}
+
+/// Formats a record as `[LEVEL target] message key=value ...`, including the
+/// structured key-values of the record.
+fn format_record(record: &Record) -> String {
+    let mut message = format!("[{} {}] {}", record.level(), record.target(), record.args());
+    let mut visitor = KeyValueFormatter {
+        message: &mut message,
+    };
+    record.key_values().visit(&mut visitor).ok();
+    message
+}
+
+struct KeyValueFormatter<'a> {
+    message: &'a mut String,
+}
+
+impl<'a, 'kvs> kv::Visitor<'kvs> for KeyValueFormatter<'a> {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        self.message.push_str(&format!(" {}={}", key, value));
+        Ok(())
+    }
+}
+
+fn send_log_message(sender: &Sender<Message>, typ: MessageType, message: String) {
+    let notif =
+        ServerNotification::new(LogMessage::METHOD.into(), LogMessageParams { typ, message });
+    // The client may have disconnected, in which case there's nobody to show the log to
+    sender.send(Message::Notification(notif)).ok();
+}
+
+/// A `PerfLogger` that logs its events with an inner `PerfLogger`, e.g. one sending
+/// them to a telemetry backend, and sends the timings of its events to the client if
+/// `forwardPerfLogs` is enabled. The `ConsoleLogger` is used by default.
+pub struct LSPPerfLogger<P: PerfLogger = ConsoleLogger> {
+    perf_logger: P,
+    client_sender: Option<Sender<Message>>,
+}
+
+impl<P: PerfLogger> LSPPerfLogger<P> {
+    pub fn new(perf_logger: P, sender: &Sender<Message>, forward_perf_logs: bool) -> Self {
+        LSPPerfLogger {
+            perf_logger,
+            client_sender: if forward_perf_logs {
+                Some(sender.clone())
+            } else {
+                None
+            },
+        }
+    }
+}
+
+impl<P: PerfLogger> PerfLogger for LSPPerfLogger<P> {
+    type PerfLogEvent = LSPPerfLogEvent<P::PerfLogEvent>;
+    fn create_event(&self, name: impl Copy + Into<String>) -> Self::PerfLogEvent {
+        LSPPerfLogEvent {
+            event: self.perf_logger.create_event(name),
+            event_name: name.into(),
+            client_sender: self.client_sender.clone(),
+        }
+    }
+    fn complete_event(&self, event: Self::PerfLogEvent) {
+        self.perf_logger.complete_event(event.event);
+    }
+    fn flush(&self) {
+        self.perf_logger.flush();
+    }
+}
+
+pub struct LSPPerfLogEvent<E: PerfLogEvent = ConsoleLogEvent> {
+    event: E,
+    event_name: String,
+    client_sender: Option<Sender<Message>>,
+}
+
+impl<E: PerfLogEvent> LSPPerfLogEvent<E> {
+    fn log_to_client(&self, name: String, value: impl std::fmt::Display) {
+        if let Some(sender) = &self.client_sender {
+            send_log_message(
+                sender,
+                MessageType::Log,
+                format!("[perf {}] {}: {}", self.event_name, name, value),
+            );
+        }
+    }
+}
+
+impl<E: PerfLogEvent> PerfLogEvent for LSPPerfLogEvent<E> {
+    /// The name and start of the timer for the client, and the timer of the inner event
+    type Timer = (String, Instant, E::Timer);
+    fn number(&self, name: impl Copy + Into<String>, number: usize) {
+        self.event.number(name, number);
+        self.log_to_client(name.into(), number);
+    }
+    fn string(&self, name: impl Copy + Into<String>, value: String) {
+        self.log_to_client(name.into(), &value);
+        self.event.string(name, value);
+    }
+    fn start(&self, name: impl Copy + Into<String>) -> Self::Timer {
+        (name.into(), Instant::now(), self.event.start(name))
+    }
+    fn stop(&self, timer: Self::Timer) {
+        let (name, start, timer) = timer;
+        self.log_to_client(name, format!("{}ms", start.elapsed().as_millis()));
+        self.event.stop(timer);
+    }
+}
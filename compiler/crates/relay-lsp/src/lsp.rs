@@ -13,22 +13,186 @@ pub use lsp_server::{Connection, Message};
 pub use lsp_types::{notification::*, request::*, *};
 
 use common::Location;
+use graphql_syntax::GraphQLSource;
 pub use lsp_server::{
-    Notification as ServerNotification, Request as ServerRequest, RequestId as ServerRequestId,
-    Response as ServerResponse,
+    ErrorCode, Notification as ServerNotification, Request as ServerRequest,
+    RequestId as ServerRequestId, Response as ServerResponse, ResponseError,
 };
+// `Location` refers to `common::Location` in this crate, the LSP one is re-exported separately.
+pub use lsp_types::Location as LSPLocation;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+// The semantic tokens are only a proposal in the version of the protocol implemented by
+// lsp_types, so the types needed for the `textDocument/semanticTokens/full` request
+// are defined here.
+
+/// Request for the semantic tokens of a whole document
+pub enum SemanticTokensRequest {}
+
+impl Request for SemanticTokensRequest {
+    type Params = SemanticTokensParams;
+    type Result = Option<SemanticTokens>;
+    const METHOD: &'static str = "textDocument/semanticTokens/full";
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// The tokens of a document, encoded as described by `SemanticTokensLegend`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokens {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+}
+
+/// The token types and modifiers, the index of a type in `token_types` identifies
+/// the type in the encoded tokens
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensLegend {
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+}
+
+// Inlay hints aren't part of the version of the protocol implemented by lsp_types either,
+// so the types of the `textDocument/inlayHint` request are defined here as well.
+
+/// Request for the inlay hints of a range of a document
+pub enum InlayHintRequest {}
+
+impl Request for InlayHintRequest {
+    type Params = InlayHintParams;
+    type Result = Option<Vec<InlayHint>>;
+    const METHOD: &'static str = "textDocument/inlayHint";
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+}
+
+/// The kind of inlay hints annotating a type
+pub const INLAY_HINT_KIND_TYPE: u32 = 1;
+
+/// A label shown inline at a position of a document
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<u32>,
+}
+
 #[derive(Debug, Clone)]
 pub enum LSPBridgeMessage {
     CompletionRequest {
         request_id: ServerRequestId,
         params: CompletionParams,
     },
+    ResolveCompletionItem {
+        request_id: ServerRequestId,
+        params: CompletionItem,
+    },
+    HoverRequest {
+        request_id: ServerRequestId,
+        params: HoverParams,
+    },
     DidOpenTextDocument(DidOpenTextDocumentParams),
     DidChangeTextDocument(DidChangeTextDocumentParams),
+    DidSaveTextDocument(DidSaveTextDocumentParams),
+    DidChangeWatchedFiles(DidChangeWatchedFilesParams),
+    CancelRequest {
+        id: ServerRequestId,
+    },
+    WorkspaceSymbolRequest {
+        request_id: ServerRequestId,
+        params: WorkspaceSymbolParams,
+    },
     DidCloseTextDocument(DidCloseTextDocumentParams),
+    ReferencesRequest {
+        request_id: ServerRequestId,
+        params: ReferenceParams,
+    },
+    DocumentSymbolRequest {
+        request_id: ServerRequestId,
+        params: DocumentSymbolParams,
+    },
+    DocumentHighlightRequest {
+        request_id: ServerRequestId,
+        params: DocumentHighlightParams,
+    },
+    FoldingRangeRequest {
+        request_id: ServerRequestId,
+        params: FoldingRangeParams,
+    },
+    SelectionRangeRequest {
+        request_id: ServerRequestId,
+        params: SelectionRangeParams,
+    },
+    CodeActionRequest {
+        request_id: ServerRequestId,
+        params: CodeActionParams,
+    },
+    RenameRequest {
+        request_id: ServerRequestId,
+        params: RenameParams,
+    },
+    SemanticTokensRequest {
+        request_id: ServerRequestId,
+        params: SemanticTokensParams,
+    },
+    ExecuteCommandRequest {
+        request_id: ServerRequestId,
+        params: ExecuteCommandParams,
+    },
+    InlayHintRequest {
+        request_id: ServerRequestId,
+        params: InlayHintParams,
+    },
+    ShutdownRequest {
+        request_id: ServerRequestId,
+    },
+    Exit,
+}
+
+impl LSPBridgeMessage {
+    /// The id of the request, `None` for notifications
+    pub fn request_id(&self) -> Option<&ServerRequestId> {
+        match self {
+            LSPBridgeMessage::CompletionRequest { request_id, .. }
+            | LSPBridgeMessage::ResolveCompletionItem { request_id, .. }
+            | LSPBridgeMessage::HoverRequest { request_id, .. }
+            | LSPBridgeMessage::WorkspaceSymbolRequest { request_id, .. }
+            | LSPBridgeMessage::ReferencesRequest { request_id, .. }
+            | LSPBridgeMessage::DocumentSymbolRequest { request_id, .. }
+            | LSPBridgeMessage::DocumentHighlightRequest { request_id, .. }
+            | LSPBridgeMessage::FoldingRangeRequest { request_id, .. }
+            | LSPBridgeMessage::SelectionRangeRequest { request_id, .. }
+            | LSPBridgeMessage::CodeActionRequest { request_id, .. }
+            | LSPBridgeMessage::RenameRequest { request_id, .. }
+            | LSPBridgeMessage::SemanticTokensRequest { request_id, .. }
+            | LSPBridgeMessage::ExecuteCommandRequest { request_id, .. }
+            | LSPBridgeMessage::InlayHintRequest { request_id, .. }
+            | LSPBridgeMessage::ShutdownRequest { request_id } => Some(request_id),
+            LSPBridgeMessage::DidOpenTextDocument(_)
+            | LSPBridgeMessage::DidChangeTextDocument(_)
+            | LSPBridgeMessage::DidSaveTextDocument(_)
+            | LSPBridgeMessage::DidChangeWatchedFiles(_)
+            | LSPBridgeMessage::CancelRequest { .. }
+            | LSPBridgeMessage::DidCloseTextDocument(_)
+            | LSPBridgeMessage::Exit => None,
+        }
+    }
 }
 
 /// Converts a Location to a Url pointing to the canonical path based on the root_dir provided.
@@ -45,6 +209,21 @@ pub fn url_from_location(location: &Location, root_dir: &PathBuf) -> Option<Url>
     }
 }
 
+/// Converts a Location to an LSP Location. The span of the location is relative to
+/// the GraphQL `source` it was parsed from, which is used to compute the absolute range.
+/// Returns None if we are unable to do the conversion
+pub fn lsp_location_from_location(
+    location: &Location,
+    root_dir: &PathBuf,
+    source: &GraphQLSource,
+) -> Option<LSPLocation> {
+    let uri = url_from_location(location, root_dir)?;
+    let range = location
+        .span()
+        .to_range(&source.text, source.line_index, source.column_index);
+    Some(LSPLocation::new(uri, range))
+}
+
 /// Show a notification in the client
 pub fn show_info_message(
     message: impl Into<String>,
@@ -61,6 +240,72 @@ pub fn show_info_message(
     Ok(())
 }
 
+/// Show a warning notification in the client
+pub fn show_warning_message(
+    message: impl Into<String>,
+    connection: &Connection,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let notif = ServerNotification::new(
+        ShowMessage::METHOD.into(),
+        ShowMessageParams {
+            typ: MessageType::Warning,
+            message: message.into(),
+        },
+    );
+    connection.sender.send(Message::Notification(notif))?;
+    Ok(())
+}
+
+/// Show an error notification in the client
+pub fn show_error_message(
+    message: impl Into<String>,
+    connection: &Connection,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let notif = ServerNotification::new(
+        ShowMessage::METHOD.into(),
+        ShowMessageParams {
+            typ: MessageType::Error,
+            message: message.into(),
+        },
+    );
+    connection.sender.send(Message::Notification(notif))?;
+    Ok(())
+}
+
+/// Ask the client to create a work done progress with the given token, which
+/// has to happen before any progress with this token is reported
+pub fn create_work_done_progress(
+    token: &str,
+    connection: &Connection,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let request = ServerRequest::new(
+        ServerRequestId::from(format!("{}/create", token)),
+        WorkDoneProgressCreate::METHOD.into(),
+        WorkDoneProgressCreateParams {
+            token: NumberOrString::String(token.to_string()),
+        },
+    );
+    connection.sender.send(Message::Request(request))?;
+    Ok(())
+}
+
+/// Report the begin, progress or end of a work done progress to the client
+pub fn send_work_done_progress(
+    token: &str,
+    progress: WorkDoneProgress,
+    connection: &Connection,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let notif = ServerNotification::new(
+        Progress::METHOD.into(),
+        ProgressParams {
+            token: NumberOrString::String(token.to_string()),
+            value: ProgressParamsValue::WorkDone(progress),
+        },
+    );
+    connection.sender.send(Message::Notification(notif))?;
+    Ok(())
+}
+
 /// Publish diagnostics to the client
 pub fn publish_diagnostic(
     diagnostic_params: PublishDiagnosticsParams,
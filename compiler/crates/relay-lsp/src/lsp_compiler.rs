@@ -7,175 +7,873 @@
 
 //! An LSP-specific Compiler interface
 
-use crate::lsp::{Connection, LSPBridgeMessage};
+use crate::lsp::{
+    create_work_done_progress, lsp_location_from_location, send_work_done_progress,
+    show_error_message, show_info_message, show_warning_message, url_from_location,
+    CodeActionOrCommand, Connection, Diagnostic, DidChangeWatchedFilesParams,
+    DidSaveTextDocumentParams, FileChangeType, LSPBridgeMessage, Message, ServerRequestId,
+    ServerResponse, SymbolInformation, Url, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressEnd, WorkDoneProgressReport,
+};
 
-use relay_compiler::compiler_state::{CompilerState, ProjectName};
-use relay_compiler::config::Config;
+use relay_compiler::compiler_state::{
+    get_projects_with_schema_changes, CompilerState, ProjectName,
+};
+use relay_compiler::config::{Config, ProjectConfig};
 use relay_compiler::errors::Error as CompilerError;
 use relay_compiler::errors::Result as CompilerResult;
-use relay_compiler::FileSourceSubscription;
-use relay_compiler::{build_schema, check_project, parse_sources, Programs};
+use relay_compiler::relay_ignore::relay_ignore_changed;
+use relay_compiler::{
+    check_project, parse_sources_isolating_syntax_errors, try_build_schema, try_build_schemas,
+    Programs,
+};
+use relay_compiler::{
+    is_relevant_file, source_file_projects, File, FileSourceResult, FileSourceSubscription,
+};
 use schema::Schema;
 
-use common::{PerfLogEvent, PerfLogger};
-use interner::{Intern, StringKey};
+use common::{ConsoleLogger, Location, PerfLogEvent, PerfLogger};
+use graphql_ir::{FragmentDefinition, OperationDefinition, Program};
+use graphql_syntax::GraphQLSource;
+use graphql_transforms::OperationIds;
+use interner::StringKey;
 
 use crate::completion::{
-    completion_items_for_request, get_completion_request, send_completion_response,
-    GraphQLSourceCache,
+    add_completion_text_edits, completion_item_project_name, completion_items_for_request,
+    get_completion_replace_range, get_completion_request, resolve_completion_item,
+    send_completion_response, send_resolve_completion_item_response, GraphQLSourceCache,
 };
 
-use crate::error_reporting::{report_build_project_errors, report_syntax_errors};
-use crate::state::ServerState;
+use crate::document_highlights::{get_document_highlights, send_document_highlights_response};
+
+use crate::folding_ranges::{get_folding_ranges, send_folding_ranges_response};
+
+use crate::inlay_hints::{get_inlay_hints, send_inlay_hints_response};
+
+use crate::references::{
+    find_fragment_references, get_fragment_name_for_references_request, send_references_response,
+};
+
+use crate::document_symbols::{get_document_symbols, send_document_symbols_response};
+
+use crate::print_operation::{
+    get_print_operation_name, print_operation, send_print_operation_response,
+    send_unknown_command_response,
+};
+
+use crate::selection_ranges::{get_selection_ranges, send_selection_ranges_response};
+
+use crate::semantic_tokens::{get_semantic_tokens, send_semantic_tokens_response};
+
+use crate::stats::{is_stats_command, send_stats_response, to_milliseconds, CheckStats};
+use crate::version::{is_version_command, send_version_response};
+
+use crate::rename::{
+    create_rename_edit, find_rename_locations, get_rename_target, is_valid_graphql_name,
+    name_location, send_invalid_rename_response, send_rename_response,
+};
+
+use crate::deprecated_fields::{deprecated_field_diagnostic, find_deprecated_fields};
+
+use crate::unused_fragments::{
+    find_unused_fragments, get_code_actions, send_code_action_response, unused_fragment_diagnostic,
+};
+
+use crate::hover::{get_hover_response_contents, send_hover_response};
+
+use crate::workspace_symbols::{find_workspace_symbols, send_workspace_symbols_response};
+
+use crate::error_reporting::{
+    add_project_code, build_project_error_diagnostics, has_project_code, project_error_diagnostics,
+    report_config_error, report_empty_query_result, schema_error_diagnostics,
+    syntax_error_diagnostics,
+};
+use crate::field_suggestions::get_field_suggestion_code_actions;
+use crate::logging::LSPPerfLogger;
+use crate::missing_arguments::get_missing_arguments_code_action;
+use crate::restart::{is_restart_command, send_restart_response};
+use crate::saved_state::persist_compiler_state;
+use crate::state::{NegotiatedCapabilities, ServerState};
 use crate::text_documents::{
     on_did_change_text_document, on_did_close_text_document, on_did_open_text_document,
 };
 
-use common::ConsoleLogger;
+use crossbeam_channel::unbounded;
 use log::info;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::Receiver;
 
 use tokio::select;
+use tokio::time::{delay_until, Instant};
+
+/// The result of building the schema of each active project, see `Config::schema_projects`
+pub type SchemaMap = HashMap<ProjectName, CompilerResult<Schema>>;
+
+/// The default time to wait for more file source changes before checking the projects
+pub const DEFAULT_CHANGES_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How many debounce intervals the pending changes wait at most after the first of them
+const MAX_DEBOUNCE_INTERVALS: u32 = 10;
+
+/// The file source changes that were merged into the compiler state, but not checked yet
+#[derive(Default)]
+struct PendingChanges {
+    had_new_changes: bool,
+    schema_change_projects: HashSet<ProjectName>,
+    /// The compiler state has to be rebuilt from all files, e.g. since the `.relayignore`
+    /// file changed or the file source reconnected
+    rebuild_compiler_state: bool,
+    /// When the first of the changes arrived
+    first_change_at: Option<Instant>,
+}
+
+impl PendingChanges {
+    /// When to check the changes after a change arriving at `now`: once no other change
+    /// arrives within the debounce interval, but no later than `MAX_DEBOUNCE_INTERVALS`
+    /// intervals after the first change, so that a steady stream of changes doesn't
+    /// postpone the check indefinitely.
+    fn check_deadline(&mut self, now: Instant, debounce_interval: Duration) -> Instant {
+        let first_change_at = *self.first_change_at.get_or_insert(now);
+        (now + debounce_interval).min(first_change_at + debounce_interval * MAX_DEBOUNCE_INTERVALS)
+    }
+}
+
+/// The definitions of a program, without the schema it was built with
+struct ProgramDefinitions {
+    fragments: Vec<Arc<FragmentDefinition>>,
+    operations: Vec<Arc<OperationDefinition>>,
+}
 
-type SchemaMap = HashMap<ProjectName, Schema>;
+impl ProgramDefinitions {
+    fn new(program: &Program<'_>) -> Self {
+        Self {
+            fragments: program.fragments().cloned().collect(),
+            operations: program.operations().cloned().collect(),
+        }
+    }
 
-pub struct LSPCompiler<'schema, 'config> {
+    fn program<'schema>(&self, schema: &'schema Schema) -> Program<'schema> {
+        let mut program = Program::new(schema);
+        for fragment in &self.fragments {
+            program.insert_fragment(Arc::clone(fragment));
+        }
+        for operation in &self.operations {
+            program.insert_operation(Arc::clone(operation));
+        }
+        program
+    }
+}
+
+/// The programs of a checked project along with the schema they were built with. The
+/// definitions are shared with the programs, which are rebuilt borrowing the schema
+/// when they are used, so the schema outlives them even if the schema of the project
+/// is rebuilt in the meantime.
+struct ProjectPrograms {
+    schema: Arc<Schema>,
+    source: ProgramDefinitions,
+    reader: ProgramDefinitions,
+    normalization: ProgramDefinitions,
+    operation_text: ProgramDefinitions,
+    typegen: ProgramDefinitions,
+    operation_ids: OperationIds,
+}
+
+impl ProjectPrograms {
+    fn new(schema: Arc<Schema>, programs: Programs<'_>) -> Self {
+        Self {
+            source: ProgramDefinitions::new(&programs.source),
+            reader: ProgramDefinitions::new(&programs.reader),
+            normalization: ProgramDefinitions::new(&programs.normalization),
+            operation_text: ProgramDefinitions::new(&programs.operation_text),
+            typegen: ProgramDefinitions::new(&programs.typegen),
+            operation_ids: programs.operation_ids,
+            schema,
+        }
+    }
+
+    fn programs(&self) -> Programs<'_> {
+        Programs {
+            source: self.source.program(&self.schema),
+            reader: self.reader.program(&self.schema),
+            normalization: self.normalization.program(&self.schema),
+            operation_text: self.operation_text.program(&self.schema),
+            typegen: self.typegen.program(&self.schema),
+            operation_ids: self.operation_ids.clone(),
+        }
+    }
+}
+
+pub struct LSPCompiler<'config, P: PerfLogger = ConsoleLogger> {
     lsp_rx: Receiver<LSPBridgeMessage>,
-    schemas: &'schema SchemaMap,
+    /// The schema of each active project, replaced when its schema files change.
+    /// Projects whose schema fails to build don't have a schema until it is fixed.
+    schemas: HashMap<ProjectName, Arc<Schema>>,
     config: &'config Config,
     subscription: FileSourceSubscription<'config>,
     compiler_state: CompilerState,
     connection: Connection,
     synced_graphql_documents: GraphQLSourceCache,
+    /// Extensions of the documents that are a single GraphQL source
+    graphql_file_extensions: Vec<String>,
     server_state: ServerState,
-    project_programs: HashMap<StringKey, Programs<'schema>>,
+    project_programs: HashMap<StringKey, ProjectPrograms>,
+    /// Number of times the projects have been checked, used for unique progress tokens
+    check_count: usize,
+    /// How long to wait for more file source changes before checking the projects
+    changes_debounce_interval: Duration,
+    /// Whether the client requested a shutdown, after which it tells the server to exit
+    shutdown_requested: bool,
+    /// Whether the connection to Watchman was lost and hasn't been re-established yet
+    file_source_disconnected: bool,
+    /// The file the compiler state is persisted to when the server shuts down, see
+    /// `saved_state_path`
+    saved_state_path: Option<PathBuf>,
+    /// Logs the events of the compiler, with the `PerfLogger` the server was started with
+    perf_logger: LSPPerfLogger<P>,
 }
 
-impl<'schema, 'config> LSPCompiler<'schema, 'config> {
+impl<'config, P: PerfLogger> LSPCompiler<'config, P> {
     pub fn new(
-        schemas: &'schema SchemaMap,
+        schemas: CompilerResult<SchemaMap>,
         config: &'config Config,
         subscription: FileSourceSubscription<'config>,
         compiler_state: CompilerState,
         lsp_rx: Receiver<LSPBridgeMessage>,
         connection: Connection,
+        capabilities: NegotiatedCapabilities,
+        changes_debounce_interval: Duration,
+        graphql_file_extensions: Vec<String>,
+        saved_state_path: Option<PathBuf>,
+        perf_logger: LSPPerfLogger<P>,
     ) -> Self {
         let root_dir = config.root_dir.clone();
-        let server_state = ServerState::new(root_dir);
-        LSPCompiler {
+        let server_state = ServerState::new(root_dir, capabilities);
+        let mut lsp_compiler = LSPCompiler {
             lsp_rx,
             config,
-            schemas,
+            schemas: HashMap::new(),
             subscription,
             compiler_state,
             connection,
             synced_graphql_documents: HashMap::new(),
+            graphql_file_extensions,
             server_state,
             project_programs: HashMap::new(),
-        }
+            check_count: 0,
+            changes_debounce_interval,
+            shutdown_requested: false,
+            file_source_disconnected: false,
+            saved_state_path,
+            perf_logger,
+        };
+        lsp_compiler.replace_schemas(schemas);
+        lsp_compiler
     }
 
-    async fn check_projects_and_report_errors(&mut self, event: &impl PerfLogEvent) {
-        match self.check_projects(event).await {
-            Ok(_) => {
-                // Clear out any existing diagnostics
-                self.server_state.clear_diagnostics(&self.connection);
+    /// Replaces the schemas of all projects, the errors of the schemas that failed to
+    /// build are reported on their schema files. Config errors, e.g. a project without
+    /// any schema file, are shown as a message and leave all projects without a schema.
+    fn replace_schemas(&mut self, schemas: CompilerResult<SchemaMap>) {
+        self.schemas.clear();
+        let schemas = match schemas {
+            Ok(schemas) => schemas,
+            Err(error) => {
+                report_config_error(&error, &self.connection, &mut self.server_state);
+                return;
             }
-            Err(err) => {
-                match err {
-                    CompilerError::SyntaxErrors { errors } => {
-                        report_syntax_errors(errors, &self.connection, &mut self.server_state)
-                    }
-                    CompilerError::BuildProjectsErrors { errors } => report_build_project_errors(
-                        errors,
-                        &self.connection,
-                        &mut self.server_state,
-                    ),
-                    // Ignore the rest of these errors for now
-                    CompilerError::ConfigFileRead { .. } => {}
-                    CompilerError::ConfigFileParse { .. } => {}
-                    CompilerError::ConfigFileValidation { .. } => {}
-                    CompilerError::ReadFileError { .. } => {}
-                    CompilerError::WriteFileError { .. } => {}
-                    CompilerError::SerializationError { .. } => {}
-                    CompilerError::DeserializationError { .. } => {}
-                    CompilerError::CanonicalizeRoot { .. } => {}
-                    CompilerError::Watchman { .. } => {}
-                    CompilerError::EmptyQueryResult => {}
-                    CompilerError::FileRead { .. } => {}
-                    CompilerError::Syntax { .. } => {}
+        };
+        for (project_name, schema) in schemas {
+            match schema {
+                Ok(schema) => {
+                    self.schemas.insert(project_name, Arc::new(schema));
                 }
+                Err(error) => self.report_schema_error(project_name, &error),
             }
         }
     }
 
+    async fn check_projects_and_report_errors(
+        &mut self,
+        event: &impl PerfLogEvent,
+        additional_projects: &HashSet<ProjectName>,
+    ) {
+        self.check_count += 1;
+        let progress_token = if self.server_state.capabilities.work_done_progress {
+            let token = format!("relay/check_projects/{}", self.check_count);
+            create_work_done_progress(&token, &self.connection).ok();
+            send_work_done_progress(
+                &token,
+                WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                    title: "Relay".to_string(),
+                    cancellable: Some(false),
+                    message: Some("Checking projects".to_string()),
+                    percentage: None,
+                }),
+                &self.connection,
+            )
+            .ok();
+            Some(token)
+        } else {
+            None
+        };
+
+        let mut project_names = self.projects_to_check(additional_projects);
+        // The errors of the projects without a schema are reported on their schema files
+        project_names.retain(|project_name| self.schemas.contains_key(project_name));
+        let all_projects_valid = self
+            .check_projects(event, &project_names, progress_token.as_deref())
+            .await;
+
+        if let Some(token) = progress_token {
+            send_work_done_progress(
+                &token,
+                WorkDoneProgress::End(WorkDoneProgressEnd { message: None }),
+                &self.connection,
+            )
+            .ok();
+        }
+
+        // The diagnostics of each checked project were published once it was checked
+        if all_projects_valid {
+            self.report_warnings();
+        }
+    }
+
     pub async fn watch(&mut self) -> CompilerResult<()> {
+        let mut pending_changes: Option<PendingChanges> = None;
+        let mut check_deadline = Instant::now();
         loop {
             select! {
-                changes = self.subscription.next_change() => {
-                    if let Ok(file_source_changes) = changes {
-                        let file_source_changes = file_source_changes.unwrap();
-                        let incremental_check_event =
-                        ConsoleLogger.create_event("incremental_check_event");
-                    let incremental_check_time =
-                        incremental_check_event.start("incremental_check_time");
-                    let had_new_changes = self.compiler_state.add_pending_file_source_changes(
-                        &self.config,
-                        &file_source_changes,
-                        &incremental_check_event,
-                        &ConsoleLogger,
-                    )?;
-
-                    if had_new_changes {
-                        self.check_projects_and_report_errors(&incremental_check_event).await;
+                changes = self.subscription.next_change(&self.perf_logger) => {
+                    if changes.is_ok() && self.file_source_disconnected {
+                        self.file_source_disconnected = false;
+                        show_info_message("Relay reconnected to Watchman", &self.connection).ok();
                     }
-
-                    incremental_check_event.stop(incremental_check_time);
-                    ConsoleLogger.complete_event(incremental_check_event);
-                    // We probably don't want the messages queue to grow indefinitely
-                    // and we need to flush then, as the check/build is completed
-                    ConsoleLogger.flush();
-
+                    if let Err(CompilerError::Watchman { source }) = &changes {
+                        // The subscription reconnects on the following changes, and
+                        // provides all files once reconnected so they are checked again
+                        self.file_source_disconnected = true;
+                        let message = format!(
+                            "Relay lost the connection to Watchman, reconnecting: {}",
+                            source
+                        );
+                        show_warning_message(message, &self.connection).ok();
+                    }
+                    if let Err(CompilerError::EmptyQueryResult) = &changes {
+                        report_empty_query_result(&self.config, &self.connection);
+                    }
+                    if let Ok(Some(file_source_changes)) = changes {
+                        self.add_pending_changes(&mut pending_changes, &file_source_changes)?;
+                        // Newer changes supersede the pending check, so that changes arriving
+                        // within the debounce interval of each other are checked once
+                        if let Some(pending) = &mut pending_changes {
+                            check_deadline = pending
+                                .check_deadline(Instant::now(), self.changes_debounce_interval);
+                        }
+                    }
+                }
+                _ = delay_until(check_deadline), if pending_changes.is_some() => {
+                    if let Some(pending) = pending_changes.take() {
+                        self.check_pending_changes(pending).await;
                     }
                 }
                 message = self.lsp_rx.recv() => {
-                    if let Some(message) = message {
-                      self.on_lsp_bridge_message(message);
+                    let messages = match message {
+                        Some(message) => self.drain_lsp_messages(message),
+                        None => vec![],
+                    };
+                    for message in messages {
+                        match message {
+                            LSPBridgeMessage::DidSaveTextDocument(params) => {
+                                self.on_did_save_text_document(params).await;
+                            }
+                            LSPBridgeMessage::DidChangeWatchedFiles(params) => {
+                                let file_source_changes =
+                                    file_source_changes_from_watched_files(&self.config, &params);
+                                if file_source_changes.files.is_empty() {
+                                    continue;
+                                }
+                                if let Err(err) = self
+                                    .add_pending_changes(&mut pending_changes, &file_source_changes)
+                                {
+                                    info!("Failed to read the changed watched files: {:?}", err);
+                                }
+                                if let Some(pending) = &mut pending_changes {
+                                    check_deadline = pending.check_deadline(
+                                        Instant::now(),
+                                        self.changes_debounce_interval,
+                                    );
+                                }
+                            }
+                            LSPBridgeMessage::ExecuteCommandRequest { params, request_id }
+                                if is_restart_command(&params) =>
+                            {
+                                self.restart(request_id).await;
+                            }
+                            LSPBridgeMessage::Exit => {
+                                if !self.shutdown_requested {
+                                    info!("Exiting without a shutdown request");
+                                    self.persist_compiler_state();
+                                }
+                                self.perf_logger.flush();
+                                return Ok(());
+                            }
+                            message => self.on_lsp_bridge_message(message),
+                        }
                     }
                 }
             }
         }
     }
 
+    /// Adds the changes of the file source, or of the files watched by the client, to
+    /// the pending changes, which are checked once no other changes arrive within the
+    /// debounce interval.
+    fn add_pending_changes(
+        &mut self,
+        pending_changes: &mut Option<PendingChanges>,
+        file_source_changes: &FileSourceResult,
+    ) -> CompilerResult<()> {
+        let pending = pending_changes.get_or_insert_with(Default::default);
+        if file_source_changes.is_fresh_instance {
+            // The files deleted since the previous changes aren't part of the changes
+            pending.rebuild_compiler_state = true;
+            return Ok(());
+        }
+        let changes_event = self.perf_logger.create_event("file_source_changes_event");
+        pending.rebuild_compiler_state |= relay_ignore_changed(file_source_changes);
+        pending
+            .schema_change_projects
+            .extend(get_projects_with_schema_changes(
+                &self.config,
+                file_source_changes,
+            ));
+        let result = self.compiler_state.add_pending_file_source_changes(
+            &self.config,
+            file_source_changes,
+            &changes_event,
+            &self.perf_logger,
+        );
+        self.perf_logger.complete_event(changes_event);
+        pending.had_new_changes |= result?;
+        Ok(())
+    }
+
+    /// Returns `message` followed by the messages already queued after it. Completion
+    /// requests superseded by a later completion request for the same document are
+    /// answered with no items instead of being processed, since their positions are
+    /// obsolete by the time the queue catches up. Completion requests are tracked until
+    /// they are responded to, so that the queued cancellations apply to them.
+    fn drain_lsp_messages(&mut self, message: LSPBridgeMessage) -> Vec<LSPBridgeMessage> {
+        let mut messages = vec![message];
+        while let Ok(message) = self.lsp_rx.try_recv() {
+            messages.push(message);
+        }
+
+        let mut latest_completion_requests = HashMap::new();
+        for (index, message) in messages.iter().enumerate() {
+            match message {
+                LSPBridgeMessage::CompletionRequest { params, request_id } => {
+                    let uri = &params.text_document_position.text_document.uri;
+                    latest_completion_requests.insert(uri.clone(), index);
+                    self.server_state.start_request(request_id.clone());
+                }
+                LSPBridgeMessage::CancelRequest { id } => self.server_state.cancel_request(id),
+                _ => {}
+            }
+        }
+        let mut current_messages = Vec::with_capacity(messages.len());
+        for (index, message) in messages.into_iter().enumerate() {
+            match message {
+                LSPBridgeMessage::CompletionRequest { params, request_id }
+                    if latest_completion_requests
+                        .get(&params.text_document_position.text_document.uri)
+                        != Some(&index) =>
+                {
+                    info!("Skipping the stale completion request {:?}", request_id);
+                    if self.server_state.finish_request(&request_id) {
+                        send_completion_response(vec![], request_id, &self.connection);
+                    }
+                }
+                LSPBridgeMessage::CancelRequest { .. } => {}
+                message => current_messages.push(message),
+            }
+        }
+        current_messages
+    }
+
+    /// Rebuilds the schemas affected by the pending changes and checks the projects
+    /// with changes, if any.
+    async fn check_pending_changes(&mut self, pending_changes: PendingChanges) {
+        let incremental_check_event = self.perf_logger.create_event("incremental_check_event");
+        let incremental_check_time = incremental_check_event.start("incremental_check_time");
+        if pending_changes.rebuild_compiler_state {
+            // E.g. previously ignored files have to be read, and newly ignored ones dropped
+            if let Err(err) = self.rebuild_compiler_state(&incremental_check_event).await {
+                let message = format!("Relay was unable to rebuild the compiler state: {}", err);
+                show_error_message(message, &self.connection).ok();
+            }
+        } else {
+            let rebuilt_schema_projects = self.rebuild_schemas(
+                &pending_changes.schema_change_projects,
+                &incremental_check_event,
+            );
+
+            if pending_changes.had_new_changes || !rebuilt_schema_projects.is_empty() {
+                self.check_projects_and_report_errors(
+                    &incremental_check_event,
+                    &rebuilt_schema_projects,
+                )
+                .await;
+            }
+        }
+
+        incremental_check_event.stop(incremental_check_time);
+        self.perf_logger.complete_event(incremental_check_event);
+        // We probably don't want the messages queue to grow indefinitely
+        // and we need to flush then, as the check/build is completed
+        self.perf_logger.flush();
+    }
+
+    /// Rebuilds the compiler state from all files of the file source, rebuilds the
+    /// schemas and checks all projects again, after clearing all diagnostics. The
+    /// subscription to the file source and the synced documents are kept.
+    async fn restart(&mut self, request_id: ServerRequestId) {
+        let restart_event = self.perf_logger.create_event("restart_event");
+        let restart_time = restart_event.start("restart_time");
+        let result = self.rebuild_compiler_state(&restart_event).await;
+        send_restart_response(result, request_id, &self.connection);
+        restart_event.stop(restart_time);
+        self.perf_logger.complete_event(restart_event);
+        self.perf_logger.flush();
+    }
+
+    /// Clears all diagnostics, rebuilds the compiler state from all files of the file
+    /// source and checks all projects with the rebuilt schemas.
+    async fn rebuild_compiler_state(&mut self, event: &impl PerfLogEvent) -> CompilerResult<()> {
+        self.server_state.clear_diagnostics(&self.connection);
+        let file_source_result = self.subscription.query_all_files(event).await?;
+        self.compiler_state = CompilerState::from_file_source_changes(
+            self.config,
+            &file_source_result,
+            event,
+            &self.perf_logger,
+        )?;
+        self.project_programs.clear();
+        let schemas = Self::build_schemas(self.config, &self.compiler_state, event);
+        self.replace_schemas(schemas);
+        let project_names = self.config.projects.keys().copied().collect();
+        self.check_projects_and_report_errors(event, &project_names)
+            .await;
+        Ok(())
+    }
+
+    /// Some editors save files without the file source reporting the change, e.g. on
+    /// network file systems. The saved document is read again and its project is checked,
+    /// whether or not the file source reported the change.
+    async fn on_did_save_text_document(&mut self, params: DidSaveTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let project_names = projects_for_uri(self.config, &uri);
+        if project_names.is_empty() {
+            // The document isn't part of any project, so there is nothing to check
+            return;
+        }
+        let file_path = match uri.to_file_path() {
+            Ok(file_path) => file_path,
+            Err(_) => return,
+        };
+        let file_name = match file_path.strip_prefix(&self.config.root_dir) {
+            Ok(file_name) => file_name.to_owned(),
+            Err(_) => return,
+        };
+        let file_source_changes = FileSourceResult {
+            files: vec![File {
+                name: file_name,
+                exists: file_path.exists(),
+            }],
+            resolved_root: self.config.root_dir.clone(),
+            clock: None,
+            is_fresh_instance: false,
+        };
+
+        let did_save_event = self
+            .perf_logger
+            .create_event("did_save_text_document_event");
+        let did_save_time = did_save_event.start("did_save_check_time");
+        if let Err(err) = self.compiler_state.add_pending_file_source_changes(
+            &self.config,
+            &file_source_changes,
+            &did_save_event,
+            &self.perf_logger,
+        ) {
+            info!("Failed to read the saved document {}: {:?}", uri, err);
+        }
+        let projects = project_names.into_iter().collect();
+        self.check_projects_and_report_errors(&did_save_event, &projects)
+            .await;
+        did_save_event.stop(did_save_time);
+        self.perf_logger.complete_event(did_save_event);
+        self.perf_logger.flush();
+    }
+
     fn on_lsp_bridge_message(&mut self, message: LSPBridgeMessage) {
         match message {
             // Completion request
             LSPBridgeMessage::CompletionRequest { params, request_id } => {
+                if !self.server_state.finish_request(&request_id) {
+                    info!("Skipping the cancelled completion request {:?}", request_id);
+                    return;
+                }
+                let uri = &params.text_document_position.text_document.uri;
+                let (project_name, schema) = match self.project_schema_for_uri(uri) {
+                    Some(project_schema) => project_schema,
+                    None => {
+                        // The document isn't part of any project, so there is nothing to complete
+                        send_completion_response(vec![], request_id, &self.connection);
+                        return;
+                    }
+                };
+                let replace_range =
+                    get_completion_replace_range(&params, &self.synced_graphql_documents);
                 if let Some(completion_request) =
                     get_completion_request(params, &self.synced_graphql_documents)
                 {
                     info!("completion_request {:#?}", self.project_programs.keys());
-                    // TODO(brandondail) don't hardcode schema here
-                    let project_key = "facebook-test".intern();
-                    let schema = self.schemas.get(&project_key).unwrap();
-
-                    let programs = self.project_programs.get(&project_key);
+                    let programs = self
+                        .project_programs
+                        .get(&project_name)
+                        .map(ProjectPrograms::programs);
 
                     info!("programs? {:?}", programs.is_some());
 
-                    if let Some(items) =
-                        completion_items_for_request(completion_request, schema, programs)
-                    {
+                    if let Some(items) = completion_items_for_request(
+                        completion_request,
+                        schema,
+                        programs.as_ref(),
+                        project_name,
+                        &self.config.custom_scalars,
+                    ) {
+                        let items = add_completion_text_edits(
+                            items,
+                            replace_range,
+                            self.server_state.capabilities.completion_snippets,
+                        );
                         send_completion_response(items, request_id, &self.connection);
                     }
                 }
             }
+            LSPBridgeMessage::ResolveCompletionItem { params, request_id } => {
+                let schema = completion_item_project_name(&params)
+                    .and_then(|project_name| self.schemas.get(&project_name));
+                let item = match schema {
+                    Some(schema) => resolve_completion_item(params, schema),
+                    // The project was removed since the item was created, it can't be resolved
+                    None => params,
+                };
+                send_resolve_completion_item_response(item, request_id, &self.connection);
+            }
+            LSPBridgeMessage::HoverRequest { params, request_id } => {
+                let uri = &params.text_document_position_params.text_document.uri;
+                let contents = match self.project_schema_for_uri(uri) {
+                    Some((_, schema)) => get_hover_response_contents(
+                        params,
+                        &self.synced_graphql_documents,
+                        schema,
+                        &self.config.custom_scalars,
+                    ),
+                    None => None,
+                };
+                send_hover_response(contents, request_id, &self.connection);
+            }
+            LSPBridgeMessage::ReferencesRequest { params, request_id } => {
+                let locations = match get_fragment_name_for_references_request(
+                    &params,
+                    &self.synced_graphql_documents,
+                ) {
+                    Some(fragment_name) => find_fragment_references(
+                        fragment_name,
+                        self.programs().iter(),
+                        params.context.include_declaration,
+                    )
+                    .iter()
+                    .filter_map(|location| {
+                        let source = self.graphql_source_for_location(location)?;
+                        lsp_location_from_location(location, &self.config.root_dir, source)
+                    })
+                    .collect(),
+                    None => vec![],
+                };
+                send_references_response(locations, request_id, &self.connection);
+            }
+            LSPBridgeMessage::DocumentSymbolRequest { params, request_id } => {
+                let symbols = get_document_symbols(params, &self.synced_graphql_documents);
+                send_document_symbols_response(symbols, request_id, &self.connection);
+            }
+            LSPBridgeMessage::DocumentHighlightRequest { params, request_id } => {
+                let highlights = get_document_highlights(&params, &self.synced_graphql_documents);
+                send_document_highlights_response(highlights, request_id, &self.connection);
+            }
+            LSPBridgeMessage::FoldingRangeRequest { params, request_id } => {
+                let ranges = get_folding_ranges(&params, &self.synced_graphql_documents);
+                send_folding_ranges_response(ranges, request_id, &self.connection);
+            }
+            LSPBridgeMessage::SelectionRangeRequest { params, request_id } => {
+                let selection_ranges =
+                    get_selection_ranges(&params, &self.synced_graphql_documents);
+                send_selection_ranges_response(selection_ranges, request_id, &self.connection);
+            }
+            LSPBridgeMessage::WorkspaceSymbolRequest { params, request_id } => {
+                let symbols = find_workspace_symbols(&params.query, self.programs().iter())
+                    .into_iter()
+                    .filter_map(|symbol| {
+                        let source = self.graphql_source_for_location(&symbol.location)?;
+                        let location = lsp_location_from_location(
+                            &symbol.location,
+                            &self.config.root_dir,
+                            source,
+                        )?;
+                        Some(SymbolInformation {
+                            name: symbol.name.to_string(),
+                            kind: symbol.kind,
+                            deprecated: None,
+                            location,
+                            container_name: None,
+                        })
+                    })
+                    .collect();
+                send_workspace_symbols_response(symbols, request_id, &self.connection);
+            }
+            LSPBridgeMessage::CodeActionRequest { params, request_id } => {
+                let mut field_code_actions = vec![];
+                if let Some((_, schema)) = self.project_schema_for_uri(&params.text_document.uri) {
+                    field_code_actions.extend(get_missing_arguments_code_action(
+                        &params,
+                        &self.synced_graphql_documents,
+                        schema,
+                    ));
+                    field_code_actions.extend(get_field_suggestion_code_actions(
+                        &params,
+                        &self.synced_graphql_documents,
+                        schema,
+                    ));
+                }
+                let mut code_actions = get_code_actions(params);
+                code_actions.extend(field_code_actions);
+                if !self.server_state.capabilities.code_action_literals {
+                    // Clients without code action literal support only accept commands
+                    code_actions.retain(|code_action| match code_action {
+                        CodeActionOrCommand::Command(_) => true,
+                        CodeActionOrCommand::CodeAction(_) => false,
+                    });
+                }
+                send_code_action_response(code_actions, request_id, &self.connection);
+            }
+            LSPBridgeMessage::RenameRequest { params, request_id } => {
+                if !is_valid_graphql_name(&params.new_name) {
+                    send_invalid_rename_response(&params.new_name, request_id, &self.connection);
+                    return;
+                }
+                let edit =
+                    get_rename_target(&params, &self.synced_graphql_documents).map(|target| {
+                        let locations = find_rename_locations(target, self.programs().iter())
+                            .iter()
+                            .filter_map(|location| {
+                                let source = self.graphql_source_for_location(location)?;
+                                let location = name_location(location, target.name(), source);
+                                lsp_location_from_location(&location, &self.config.root_dir, source)
+                            })
+                            .collect();
+                        create_rename_edit(locations, &params.new_name)
+                    });
+                send_rename_response(edit, request_id, &self.connection);
+            }
+            LSPBridgeMessage::SemanticTokensRequest { params, request_id } => {
+                let tokens = if self.server_state.capabilities.semantic_tokens {
+                    Some(get_semantic_tokens(params, &self.synced_graphql_documents))
+                } else {
+                    None
+                };
+                send_semantic_tokens_response(tokens, request_id, &self.connection);
+            }
+            LSPBridgeMessage::InlayHintRequest { params, request_id } => {
+                let programs = if self.server_state.capabilities.inlay_hints {
+                    projects_for_uri(self.config, &params.text_document.uri)
+                        .iter()
+                        .find_map(|project_name| self.project_programs.get(project_name))
+                        .map(ProjectPrograms::programs)
+                } else {
+                    None
+                };
+                let hints = match programs {
+                    Some(programs) => {
+                        get_inlay_hints(&params, &self.synced_graphql_documents, &programs.source)
+                    }
+                    None => vec![],
+                };
+                send_inlay_hints_response(hints, request_id, &self.connection);
+            }
+            LSPBridgeMessage::ExecuteCommandRequest { params, request_id }
+                if is_stats_command(&params) =>
+            {
+                send_stats_response(
+                    self.server_state.last_check_stats.as_ref(),
+                    request_id,
+                    &self.connection,
+                );
+            }
+            LSPBridgeMessage::ExecuteCommandRequest { params, request_id }
+                if is_version_command(&params) =>
+            {
+                send_version_response(&self.server_state.server_info, request_id, &self.connection);
+            }
+            LSPBridgeMessage::ExecuteCommandRequest { params, request_id } => {
+                match get_print_operation_name(&params) {
+                    Some(operation_name) => {
+                        let text = print_operation(operation_name, self.programs().iter());
+                        send_print_operation_response(text, request_id, &self.connection);
+                    }
+                    None => send_unknown_command_response(&params, request_id, &self.connection),
+                }
+            }
+            LSPBridgeMessage::ShutdownRequest { request_id } => {
+                self.shutdown_requested = true;
+                self.persist_compiler_state();
+                let response = ServerResponse {
+                    id: request_id,
+                    error: None,
+                    result: Some(serde_json::Value::Null),
+                };
+                self.connection
+                    .sender
+                    .send(Message::Response(response))
+                    .unwrap();
+            }
+            LSPBridgeMessage::Exit => {
+                // Handled by `watch`, which stops when the client tells the server to exit
+            }
+            LSPBridgeMessage::CancelRequest { .. } => {
+                // Handled when draining the queued messages, see `drain_lsp_messages`
+            }
+            LSPBridgeMessage::DidSaveTextDocument(_)
+            | LSPBridgeMessage::DidChangeWatchedFiles(_) => {
+                // Handled by `watch`, since checking the projects is asynchronous
+            }
             LSPBridgeMessage::DidOpenTextDocument(params) => {
-                on_did_open_text_document(params, &mut self.synced_graphql_documents);
+                on_did_open_text_document(
+                    params,
+                    &self.graphql_file_extensions,
+                    &mut self.synced_graphql_documents,
+                );
             }
             LSPBridgeMessage::DidChangeTextDocument(params) => {
-                on_did_change_text_document(params, &mut self.synced_graphql_documents);
+                on_did_change_text_document(
+                    params,
+                    &self.graphql_file_extensions,
+                    &mut self.synced_graphql_documents,
+                );
             }
             LSPBridgeMessage::DidCloseTextDocument(params) => {
                 on_did_close_text_document(params, &mut self.synced_graphql_documents);
@@ -183,83 +881,608 @@ impl<'schema, 'config> LSPCompiler<'schema, 'config> {
         }
     }
 
+    /// Writes the compiler state, including the changes since the server started, to
+    /// the saved state file, so that the next server only queries the later changes
+    fn persist_compiler_state(&self) {
+        if let Some(saved_state_path) = &self.saved_state_path {
+            persist_compiler_state(&self.compiler_state, saved_state_path);
+        }
+    }
+
+    /// Reports a warning for each fragment that isn't spread in any of the checked
+    /// projects, and for each selection of a deprecated field.
+    fn report_warnings(&mut self) {
+        let mut diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
+        let programs = self.programs();
+        for fragment_name in find_unused_fragments(programs.iter()) {
+            let diagnostic = self
+                .graphql_source_for_location(&fragment_name.location)
+                .and_then(|source| unused_fragment_diagnostic(&fragment_name, source));
+            let url = url_from_location(&fragment_name.location, &self.config.root_dir);
+            if let (Some(diagnostic), Some(url)) = (diagnostic, url) {
+                diagnostics.entry(url).or_default().push(diagnostic);
+            }
+        }
+        for deprecated_field in find_deprecated_fields(programs.iter()) {
+            let diagnostic = self
+                .graphql_source_for_location(&deprecated_field.location)
+                .map(|source| deprecated_field_diagnostic(&deprecated_field, source));
+            let url = url_from_location(&deprecated_field.location, &self.config.root_dir);
+            if let (Some(diagnostic), Some(url)) = (diagnostic, url) {
+                diagnostics.entry(url).or_default().push(diagnostic);
+            }
+        }
+        // The programs borrow the compiler, which publishes the diagnostics
+        drop(programs);
+        self.server_state
+            .publish_diagnostics(None, diagnostics, &self.connection);
+    }
+
+    /// Returns the GraphQL source a location (e.g. from the IR) is relative to. The file
+    /// of these locations is a path relative to the root with the index of the GraphQL
+    /// source in the file appended, see `parse_sources`.
+    fn graphql_source_for_location(&self, location: &Location) -> Option<&GraphQLSource> {
+        let mut file_path_and_index = location.file().lookup().rsplitn(2, ':');
+        let index = file_path_and_index.next()?.parse::<usize>().ok()?;
+        let file_path = PathBuf::from(file_path_and_index.next()?);
+        let graphql_sources = &self.compiler_state.graphql_sources;
+        // Prefer pending sources, which contain the latest state of the file
+        graphql_sources
+            .pending_sources()
+            .chain(graphql_sources.processed_sources())
+            .find_map(|(_, source_set)| source_set.get(&file_path))
+            .and_then(|file_state| file_state.graphql_sources.get(index))
+    }
+
+    /// The first project of the document at `uri` with a schema, in the order of
+    /// `projects_for_uri`
+    fn project_schema_for_uri(&self, uri: &Url) -> Option<(ProjectName, &Schema)> {
+        // Only active projects and their bases have a schema, see `Config::schema_projects`
+        projects_for_uri(self.config, uri)
+            .into_iter()
+            .find_map(|project_name| {
+                let schema = self.schemas.get(&project_name)?;
+                Some((project_name, schema.as_ref()))
+            })
+    }
+
+    /// The programs of the checked projects
+    fn programs(&self) -> Vec<Programs<'_>> {
+        self.project_programs
+            .values()
+            .map(ProjectPrograms::programs)
+            .collect()
+    }
+
     pub fn build_schemas(
         config: &Config,
         compiler_state: &CompilerState,
         setup_event: &impl PerfLogEvent,
-    ) -> SchemaMap {
+    ) -> CompilerResult<SchemaMap> {
         let timer = setup_event.start("build_schemas");
-        let mut schemas = HashMap::new();
-        config.for_each_project(|project_config| {
-            let schema = build_schema(compiler_state, project_config);
-            schemas.insert(project_config.name, schema);
-        });
+        let schemas = try_build_schemas(config, compiler_state);
         setup_event.stop(timer);
         schemas
     }
 
-    async fn check_projects(&mut self, setup_event: &impl PerfLogEvent) -> CompilerResult<()> {
-        let graphql_asts =
-            setup_event.time("parse_sources_time", || parse_sources(&self.compiler_state))?;
-        let mut check_project_errors = vec![];
-        let mut project_programs = HashMap::new();
+    /// Rebuilds the schemas of the active projects that are affected by changes to
+    /// the schema or extension files of `schema_change_projects`, including the
+    /// projects extending one of them as their base. Returns the rebuilt projects.
+    /// A project whose schema fails to build isn't checked until its schema is fixed,
+    /// the errors of the schema are reported on its schema files instead.
+    fn rebuild_schemas(
+        &mut self,
+        schema_change_projects: &HashSet<ProjectName>,
+        event: &impl PerfLogEvent,
+    ) -> HashSet<ProjectName> {
+        let mut rebuilt_projects = HashSet::new();
+        if schema_change_projects.is_empty() {
+            return rebuilt_projects;
+        }
+        let timer = event.start("rebuild_schemas");
+        let config = self.config;
+        for project_config in config.schema_projects() {
+            let is_affected = schema_change_projects.contains(&project_config.name)
+                || project_config
+                    .base
+                    .map_or(false, |base| schema_change_projects.contains(&base));
+            if !is_affected {
+                continue;
+            }
+            match try_build_schema(&self.compiler_state, project_config) {
+                Ok(schema) => {
+                    // The programs checked with the previous schema keep it alive until
+                    // the project is checked again
+                    self.schemas.insert(project_config.name, Arc::new(schema));
+                    rebuilt_projects.insert(project_config.name);
+                }
+                Err(err) => {
+                    info!(
+                        "Failed to rebuild the schema of {}: {:?}",
+                        project_config.name, err
+                    );
+                    self.schemas.remove(&project_config.name);
+                    self.report_schema_error(project_config.name, &err);
+                }
+            }
+        }
+        event.stop(timer);
+        rebuilt_projects
+    }
+
+    /// Replaces the diagnostics of a project with the errors of its schema. They are
+    /// cleared by the next check of the project, once its schema builds again.
+    fn report_schema_error(&mut self, project_name: ProjectName, error: &CompilerError) {
+        let schema_files: Vec<&PathBuf> = self
+            .compiler_state
+            .schemas
+            .get(&project_name)
+            .map(|schema_sources| schema_sources.keys().collect())
+            .unwrap_or_default();
+        let mut diagnostics = schema_error_diagnostics(error, &schema_files, &self.config.root_dir);
+        if self.config.projects.len() > 1 {
+            for diagnostic in diagnostics.values_mut().flatten() {
+                add_project_code(diagnostic, project_name);
+            }
+        }
+        self.server_state
+            .publish_diagnostics(Some(project_name), diagnostics, &self.connection);
+    }
+
+    /// Returns the projects with pending changes and the `additional_projects`, or only
+    /// the project passed on the command line.
+    fn projects_to_check(&self, additional_projects: &HashSet<ProjectName>) -> Vec<ProjectName> {
         match self.config.only_project {
             Some(project_key) => {
-                let project_config =
-                    self.config.projects.get(&project_key).unwrap_or_else(|| {
-                        panic!("Expected the project {} to exist", &project_key)
-                    });
-                let schema = self.schemas.get(&project_config.name).unwrap();
-                let programs = check_project(
-                    project_config,
-                    &self.compiler_state,
-                    &graphql_asts,
-                    schema,
-                    &ConsoleLogger,
-                )
-                .await
-                .map_err(|err| {
-                    check_project_errors.push(err);
-                });
-                if let Ok(programs) = programs {
-                    project_programs.insert(project_key, programs);
+                if !self.config.projects.contains_key(&project_key) {
+                    panic!("Expected the project {} to exist", &project_key);
                 }
+                vec![project_key]
             }
             None => {
-                for project_config in self.config.projects.values() {
-                    if self
-                        .compiler_state
-                        .project_has_pending_changes(project_config.name)
-                    {
-                        let schema = self.schemas.get(&project_config.name).unwrap();
-                        // TODO: consider running all projects in parallel
-                        let programs = check_project(
-                            project_config,
-                            &self.compiler_state,
-                            &graphql_asts,
-                            schema,
-                            &ConsoleLogger,
-                        )
-                        .await
-                        .map_err(|err| {
-                            check_project_errors.push(err);
-                        })
+                let mut project_names: Vec<ProjectName> = self
+                    .config
+                    .projects
+                    .keys()
+                    .filter(|project_name| {
+                        self.compiler_state
+                            .project_has_pending_changes(**project_name)
+                            || additional_projects.contains(project_name)
+                    })
+                    .copied()
+                    .collect();
+                project_names.sort_by_key(|project_name| project_name.lookup());
+                project_names
+            }
+        }
+    }
+
+    /// Checks the `project_names`, e.g. the projects with pending changes and the
+    /// projects with a rebuilt schema. If a `progress_token` is given, the project
+    /// currently being checked is reported to the client. The diagnostics of each
+    /// project are published as soon as it is checked, replacing its previous ones,
+    /// so that large repos give feedback before all projects are checked. The timings
+    /// of the check are kept for the `relay/stats` command.
+    /// The GraphQL sources that can't be parsed are skipped, their syntax errors are
+    /// published with the diagnostics of the project of their document, so that the
+    /// rest of the projects is still checked while a file is being edited.
+    /// Returns whether all projects were checked without errors.
+    async fn check_projects(
+        &mut self,
+        setup_event: &impl PerfLogEvent,
+        project_names: &[ProjectName],
+        progress_token: Option<&str>,
+    ) -> bool {
+        let check_start = std::time::Instant::now();
+        let mut stats = CheckStats::default();
+        let mut graphql_asts = setup_event.time("parse_sources_time", || {
+            parse_sources_isolating_syntax_errors(&self.compiler_state)
+        });
+        stats.parse_sources_ms = to_milliseconds(check_start.elapsed());
+        let syntax_errors = graphql_asts.take_syntax_errors();
+        let has_syntax_errors = !syntax_errors.is_empty();
+        let mut syntax_diagnostics = syntax_error_diagnostics(
+            syntax_errors,
+            &self.synced_graphql_documents,
+            &self.config.root_dir,
+        );
+        let mut has_errors = false;
+        let mut project_programs = HashMap::new();
+        let config = self.config;
+        let mut project_schemas: Vec<(&ProjectConfig, Arc<Schema>)> = Vec::new();
+        for project_name in project_names {
+            let project_config = &config.projects[project_name];
+            let schema_lookup_start = std::time::Instant::now();
+            let schema = self.schemas.get(&project_config.name).map(Arc::clone);
+            stats.schema_lookup_ms += to_milliseconds(schema_lookup_start.elapsed());
+            if let Some(schema) = schema {
+                project_schemas.push((project_config, schema));
+            }
+        }
+
+        // Checking a project only reads the compiler state, the parsed sources and
+        // its own schema, so the projects are checked in parallel, at most
+        // `Config::max_concurrent_projects` at a time. The diagnostics of each project
+        // are published as soon as it is checked.
+        let compiler_state = &self.compiler_state;
+        let graphql_asts = &graphql_asts;
+        let perf_logger = &self.perf_logger;
+        let server_state = &mut self.server_state;
+        let connection = &self.connection;
+        let project_count = project_schemas.len();
+        let (result_sender, result_receiver) = unbounded();
+        config.project_thread_pool_scope(|scope| {
+            for (project_config, schema) in project_schemas {
+                let result_sender = result_sender.clone();
+                scope.spawn(move |_| {
+                    let project_check_start = std::time::Instant::now();
+                    let result = check_project(
+                        project_config,
+                        compiler_state,
+                        graphql_asts,
+                        &schema,
+                        perf_logger,
+                    )
+                    .map(|programs| ProjectPrograms::new(Arc::clone(&schema), programs));
+                    // The results are received until every project is checked
+                    result_sender
+                        .send((project_config, result, project_check_start.elapsed()))
                         .ok();
-                        if let Some(programs) = programs {
-                            project_programs.insert(project_config.name, programs);
-                        }
+                });
+            }
+            drop(result_sender);
+
+            for (index, (project_config, result, check_duration)) in
+                result_receiver.iter().enumerate()
+            {
+                if let Some(token) = progress_token {
+                    send_work_done_progress(
+                        token,
+                        WorkDoneProgress::Report(WorkDoneProgressReport {
+                            cancellable: Some(false),
+                            message: Some(format!(
+                                "Checked {} ({} remaining)",
+                                project_config.name,
+                                project_count - index - 1
+                            )),
+                            percentage: None,
+                        }),
+                        connection,
+                    )
+                    .ok();
+                }
+                let (mut diagnostics, project_has_errors) = match result {
+                    Ok(programs) => {
+                        project_programs.insert(project_config.name, programs);
+                        (HashMap::new(), false)
+                    }
+                    Err(err) if config.projects.len() > 1 => (
+                        project_error_diagnostics(project_config.name, err, &config.root_dir),
+                        true,
+                    ),
+                    Err(err) => (
+                        build_project_error_diagnostics(vec![err], &config.root_dir),
+                        true,
+                    ),
+                };
+                has_errors |= project_has_errors;
+                stats.add_project(project_config.name, check_duration, project_has_errors);
+                let project_syntax_urls: Vec<Url> = syntax_diagnostics
+                    .keys()
+                    .filter(|url| projects_for_uri(config, url).contains(&project_config.name))
+                    .cloned()
+                    .collect();
+                for url in project_syntax_urls {
+                    if let Some(url_diagnostics) = syntax_diagnostics.remove(&url) {
+                        diagnostics.entry(url).or_default().extend(url_diagnostics);
                     }
                 }
+                // Only the previous diagnostics of this project are replaced, the other
+                // projects keep theirs until they are checked
+                publish_project_diagnostics(
+                    config,
+                    server_state,
+                    connection,
+                    project_config.name,
+                    diagnostics,
+                );
             }
+        });
+        // The syntax errors of documents outside of the checked projects
+        publish_unchecked_syntax_diagnostics(
+            config,
+            &mut self.server_state,
+            &self.connection,
+            syntax_diagnostics,
+        );
+        stats.total_ms = to_milliseconds(check_start.elapsed());
+        self.server_state.last_check_stats = Some(stats);
+
+        if !has_errors {
+            // Keep the programs of the projects without changes, the unused fragments
+            // are found across the programs of all projects
+            self.project_programs.extend(project_programs);
         }
+        // The definitions of the sources with syntax errors are missing, which would make
+        // the fragments they spread look unused
+        !has_errors && !has_syntax_errors
+    }
+}
 
-        if check_project_errors.is_empty() {
-            self.project_programs = project_programs;
-            Ok(())
-        } else {
-            Err(CompilerError::BuildProjectsErrors {
-                errors: check_project_errors,
+/// Some clients report the changes of the files they watch with
+/// `workspace/didChangeWatchedFiles`, e.g. when Watchman isn't available. Returns the
+/// changed files relevant to the compiler, relative to the root, in the format of the
+/// changes of the file source.
+fn file_source_changes_from_watched_files(
+    config: &Config,
+    params: &DidChangeWatchedFilesParams,
+) -> FileSourceResult {
+    let files = params
+        .changes
+        .iter()
+        .filter_map(|change| {
+            let file_path = change.uri.to_file_path().ok()?;
+            let file_name = file_path.strip_prefix(&config.root_dir).ok()?;
+            if !is_relevant_file(config, file_name) {
+                return None;
+            }
+            Some(File {
+                name: file_name.to_owned(),
+                exists: change.typ != FileChangeType::Deleted && file_path.exists(),
             })
+        })
+        .collect();
+    FileSourceResult {
+        files,
+        resolved_root: config.root_dir.clone(),
+        clock: None,
+        is_fresh_instance: false,
+    }
+}
+
+/// Publishes the diagnostics of a check of `project_name`, replacing all diagnostics
+/// previously reported for that project. The diagnostics are kept by the checked
+/// project, also in documents shared with other projects, so that they are cleared
+/// once that project is checked again.
+fn publish_project_diagnostics(
+    config: &Config,
+    server_state: &mut ServerState,
+    connection: &Connection,
+    project_name: ProjectName,
+    mut diagnostics: HashMap<Url, Vec<Diagnostic>>,
+) {
+    if config.projects.len() > 1 {
+        // The errors of checking a project already have the code of that project
+        for diagnostic in diagnostics.values_mut().flatten() {
+            if !has_project_code(diagnostic, config) {
+                add_project_code(diagnostic, project_name);
+            }
         }
     }
+    server_state.publish_diagnostics(Some(project_name), diagnostics, connection);
+}
+
+/// Publishes the syntax errors of documents of projects that weren't checked, for the
+/// project of each document. Only the diagnostics of these documents are replaced, the
+/// other documents of the projects keep theirs until the projects are checked.
+/// Documents outside of every project are reported without a project.
+fn publish_unchecked_syntax_diagnostics(
+    config: &Config,
+    server_state: &mut ServerState,
+    connection: &Connection,
+    diagnostics: HashMap<Url, Vec<Diagnostic>>,
+) {
+    let mut project_diagnostics: HashMap<Option<ProjectName>, HashMap<Url, Vec<Diagnostic>>> =
+        HashMap::new();
+    for (url, diagnostics) in diagnostics {
+        let project_names = projects_for_uri(config, &url);
+        if project_names.is_empty() {
+            project_diagnostics
+                .entry(None)
+                .or_default()
+                .insert(url, diagnostics);
+            continue;
+        }
+        for project_name in project_names {
+            project_diagnostics
+                .entry(Some(project_name))
+                .or_default()
+                .insert(url.clone(), diagnostics.clone());
+        }
+    }
+    let has_multiple_projects = config.projects.len() > 1;
+    for (project_name, mut diagnostics) in project_diagnostics {
+        if let (Some(project_name), true) = (project_name, has_multiple_projects) {
+            for diagnostic in diagnostics.values_mut().flatten() {
+                add_project_code(diagnostic, project_name);
+            }
+        }
+        server_state.publish_document_diagnostics(project_name, diagnostics, connection);
+    }
+}
+
+/// Returns the projects the document at `uri` belongs to, sorted by name, with the
+/// same `sources` and `excludes` of the config as the compiler, see
+/// `source_file_projects`. A document of a base project belongs to the projects using it.
+fn projects_for_uri(config: &Config, uri: &Url) -> Vec<ProjectName> {
+    let file_path = match uri.to_file_path() {
+        Ok(file_path) => file_path,
+        Err(_) => return vec![],
+    };
+    match file_path.strip_prefix(&config.root_dir) {
+        Ok(relative_path) => source_file_projects(config, relative_path),
+        Err(_) => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{Position, PublishDiagnosticsParams, Range};
+    use interner::Intern;
+    use std::fs;
+
+    /// Loads a config from a temporary root directory, which has a `schema.graphql`
+    /// file and a directory for each of the `sources` of the config
+    fn load_test_config(test_name: &str, config: serde_json::Value) -> Config {
+        let root_dir = std::env::temp_dir().join(test_name);
+        for source_dir in config["sources"].as_object().unwrap().keys() {
+            fs::create_dir_all(root_dir.join(source_dir)).unwrap();
+        }
+        fs::write(root_dir.join("schema.graphql"), "type Query { id: ID }").unwrap();
+        let config_path = root_dir.join("relay.config.json");
+        fs::write(&config_path, config.to_string()).unwrap();
+        Config::load(root_dir, config_path).unwrap()
+    }
+
+    /// A config of the projects `a` and `b`, which share the sources of their base
+    /// project `shared`
+    fn shared_base_config(test_name: &str) -> Config {
+        load_test_config(
+            test_name,
+            serde_json::json!({
+                "sources": { "shared": "shared", "a": "a", "b": "b" },
+                "projects": {
+                    "shared": { "schema": "schema.graphql" },
+                    "a": { "schema": "schema.graphql", "base": "shared" },
+                    "b": { "schema": "schema.graphql", "base": "shared" }
+                }
+            }),
+        )
+    }
+
+    /// The diagnostics last published for `url`
+    fn last_published_diagnostics(client: &Connection, url: &Url) -> Option<Vec<Diagnostic>> {
+        client
+            .receiver
+            .try_iter()
+            .filter_map(|message| match message {
+                Message::Notification(notification) => {
+                    serde_json::from_value::<PublishDiagnosticsParams>(notification.params).ok()
+                }
+                _ => None,
+            })
+            .filter(|params| &params.uri == url)
+            .last()
+            .map(|params| params.diagnostics)
+    }
+
+    #[test]
+    fn test_check_deadline_of_pending_changes_is_capped() {
+        let interval = Duration::from_millis(100);
+        let first_change_at = Instant::now();
+        let mut pending = PendingChanges::default();
+        assert_eq!(
+            pending.check_deadline(first_change_at, interval),
+            first_change_at + interval
+        );
+        // Each change postpones the check by the debounce interval
+        let next_change_at = first_change_at + interval / 2;
+        assert_eq!(
+            pending.check_deadline(next_change_at, interval),
+            next_change_at + interval
+        );
+        // Until the maximum wait after the first change
+        let last_change_at =
+            first_change_at + interval * (MAX_DEBOUNCE_INTERVALS - 1) + interval / 2;
+        assert_eq!(
+            pending.check_deadline(last_change_at, interval),
+            first_change_at + interval * MAX_DEBOUNCE_INTERVALS
+        );
+        // The changes checked afterwards wait for their own first change
+        let mut next_pending = PendingChanges::default();
+        assert_eq!(
+            next_pending.check_deadline(last_change_at, interval),
+            last_change_at + interval
+        );
+    }
+
+    #[test]
+    fn test_projects_for_uri() {
+        let config = load_test_config(
+            "relay_lsp_projects_for_uri",
+            serde_json::json!({
+                "sources": { "shared": "shared", "a": "a", "b": "b" },
+                "projects": {
+                    "shared": { "schema": "schema.graphql" },
+                    "b": {
+                        "schema": "schema.graphql",
+                        "base": "shared",
+                        "excludes": ["**/node_modules/**"]
+                    },
+                    "a": {
+                        "schema": "schema.graphql",
+                        "base": "shared",
+                        "excludes": ["shared/legacy/**"]
+                    }
+                }
+            }),
+        );
+        let projects = |path: &str| -> Vec<String> {
+            let url = Url::from_file_path(config.root_dir.join(path)).unwrap();
+            projects_for_uri(&config, &url)
+                .into_iter()
+                .map(|project_name| project_name.to_string())
+                .collect()
+        };
+
+        assert_eq!(projects("a/Query.js"), vec!["a"]);
+        // A base source belongs to the projects using it, sorted by name
+        assert_eq!(projects("shared/User.js"), vec!["a", "b", "shared"]);
+        // Excluded paths don't belong to the projects excluding them
+        assert_eq!(projects("shared/legacy/User.js"), vec!["b", "shared"]);
+        assert_eq!(
+            projects("b/node_modules/pkg/Query.js"),
+            Vec::<String>::new()
+        );
+        assert_eq!(projects("other/Query.js"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_diagnostics_of_shared_document_are_cleared_when_project_is_checked_again() {
+        let config = shared_base_config("relay_lsp_shared_document_diagnostics");
+        let (connection, client) = Connection::memory();
+        let mut server_state =
+            ServerState::new(config.root_dir.clone(), NegotiatedCapabilities::default());
+        let url = Url::from_file_path(config.root_dir.join("shared/UserFragment.js")).unwrap();
+
+        let mut diagnostics = HashMap::new();
+        diagnostics.insert(
+            url.clone(),
+            vec![Diagnostic::new_simple(
+                Range::new(Position::new(0, 0), Position::new(0, 1)),
+                "Unknown field 'nam'".to_string(),
+            )],
+        );
+        publish_project_diagnostics(
+            &config,
+            &mut server_state,
+            &connection,
+            "a".intern(),
+            diagnostics,
+        );
+        assert_eq!(
+            last_published_diagnostics(&client, &url).map(|diagnostics| diagnostics.len()),
+            Some(1)
+        );
+
+        // Checking the other project doesn't change the document
+        publish_project_diagnostics(
+            &config,
+            &mut server_state,
+            &connection,
+            "b".intern(),
+            HashMap::new(),
+        );
+        assert_eq!(last_published_diagnostics(&client, &url), None);
+
+        publish_project_diagnostics(
+            &config,
+            &mut server_state,
+            &connection,
+            "a".intern(),
+            HashMap::new(),
+        );
+        assert_eq!(last_published_diagnostics(&client, &url), Some(vec![]));
+    }
 }
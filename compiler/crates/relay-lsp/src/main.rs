@@ -9,26 +9,51 @@
 
 mod client;
 mod completion;
+mod deprecated_fields;
+mod document_highlights;
+mod document_symbols;
 mod error_reporting;
+mod field_at_position;
+mod field_suggestions;
+mod folding_ranges;
+mod hover;
+mod inlay_hints;
+mod logging;
 mod lsp;
 mod lsp_compiler;
+mod missing_arguments;
+mod print_operation;
+mod references;
+mod rename;
+mod restart;
+mod saved_state;
+mod selection_ranges;
+mod semantic_tokens;
 mod server;
 mod state;
+mod stats;
 mod text_documents;
+mod unused_fragments;
+mod version;
+mod workspace_symbols;
+use common::ConsoleLogger;
 use lsp_server::Connection;
 use std::error::Error;
 
-use env_logger::Env;
 use log::info;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
-    env_logger::from_env(Env::default().default_filter_or("info, warn, error, debug")).init();
+    let client_log_forwarder = logging::init_logger();
     let (connection, io_handles) = Connection::stdio();
     info!("Initialized stdio transport layer");
-    let params = server::initialize(&connection)?;
+    let (params, capabilities) = server::initialize(&connection)?;
+    client_log_forwarder.forward_to_client(
+        connection.sender.clone(),
+        logging::client_log_level(&params),
+    );
     info!("JSON-RPC handshake completed");
-    server::run(connection, params).await?;
+    server::run(connection, params, capabilities, ConsoleLogger).await?;
     io_handles.join()?;
     Ok(())
 }
@@ -37,7 +62,9 @@ async fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
 mod tests {
     use super::client;
     use super::server;
-    use lsp_server::Connection;
+    use super::state::NegotiatedCapabilities;
+    use super::version::ServerInfo;
+    use lsp_server::{Connection, Message};
     use lsp_types::{ClientCapabilities, InitializeParams};
     use std::error::Error;
     #[test]
@@ -58,8 +85,30 @@ mod tests {
             client_info: None,
         };
         client::initialize(&client, &init_params, 0);
-        let params = server::initialize(&connection)?;
+        let (params, capabilities) = server::initialize(&connection)?;
         assert_eq!(params, init_params);
+        assert_eq!(capabilities, NegotiatedCapabilities::default());
+        // The version of the server is reported with its capabilities
+        match client.receiver.recv()? {
+            Message::Response(response) => {
+                let result = response.result.unwrap();
+                assert_eq!(result["serverInfo"]["name"], "relay-lsp");
+                assert_eq!(
+                    result["serverInfo"]["version"],
+                    ServerInfo::current().version.as_str()
+                );
+                assert!(result["serverInfo"]["version"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with(env!("CARGO_PKG_VERSION")));
+                assert!(result["serverInfo"]["gitHash"].is_null());
+                assert!(result["capabilities"].is_object());
+                // The client doesn't support semantic tokens or inlay hints
+                assert!(result["capabilities"]["semanticTokensProvider"].is_null());
+                assert!(result["capabilities"]["inlayHintProvider"].is_null());
+            }
+            message => panic!("Expected the initialize response, got {:?}", message),
+        }
         Ok(())
     }
 }
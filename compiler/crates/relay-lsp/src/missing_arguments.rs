@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for the code action adding the missing required arguments of a field
+use crate::completion::{get_graphql_source_for_position, position_to_span, GraphQLSourceCache};
+use crate::field_at_position::{find_field_at_position, named_field};
+use crate::lsp::{CodeAction, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit};
+use common::{FileKey, Span};
+use graphql_syntax::parse;
+use interner::StringKey;
+use schema::{Field, Schema};
+use std::collections::HashMap;
+
+/// Returns an "Add missing required arguments" code action if the field at the start of
+/// the requested range doesn't pass all of its required arguments. The missing arguments
+/// are passed a variable named after the argument, e.g. `id: $id`.
+pub fn get_missing_arguments_code_action(
+    params: &CodeActionParams,
+    graphql_source_cache: &GraphQLSourceCache,
+    schema: &Schema,
+) -> Option<CodeActionOrCommand> {
+    let uri = &params.text_document.uri;
+    let position = params.range.start;
+    let graphql_source = get_graphql_source_for_position(uri, position, graphql_source_cache)?;
+    let document = parse(&graphql_source.text, FileKey::new(&uri.to_string())).ok()?;
+    let position_span = position_to_span(position, graphql_source)?;
+    let field = find_field_at_position(&document, position_span, schema)?;
+    let field_id = named_field(field.parent_type, field.name.value, schema)?;
+
+    let provided_arguments: Vec<StringKey> = field
+        .arguments
+        .map(|arguments| {
+            arguments
+                .items
+                .iter()
+                .map(|argument| argument.name.value)
+                .collect()
+        })
+        .unwrap_or_default();
+    let missing_arguments = missing_required_arguments(schema.field(field_id), &provided_arguments);
+    if missing_arguments.is_empty() {
+        return None;
+    }
+
+    // The arguments are added before the closing parenthesis of the existing arguments,
+    // or in a new argument list after the name of the field
+    let (insert_offset, has_arguments) = match field.arguments {
+        Some(arguments) => (arguments.end.inner_span.start, !arguments.items.is_empty()),
+        None => (field.name.span.start + field.name.span.length, false),
+    };
+    let range = Span::new(insert_offset, 0).to_range(
+        &graphql_source.text,
+        graphql_source.line_index,
+        graphql_source.column_index,
+    );
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![TextEdit {
+            range,
+            new_text: missing_arguments_text(&missing_arguments, has_arguments),
+        }],
+    );
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Add missing required arguments to `{}`", field.name.value),
+        kind: Some("quickfix".to_string()),
+        diagnostics: None,
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+        }),
+        command: None,
+        is_preferred: Some(true),
+    }))
+}
+
+/// The non-null arguments without a default value that aren't provided
+fn missing_required_arguments(field: &Field, provided_arguments: &[StringKey]) -> Vec<StringKey> {
+    field
+        .arguments
+        .iter()
+        .filter(|argument| {
+            argument.type_.is_non_null()
+                && argument.default_value.is_none()
+                && !provided_arguments.contains(&argument.name)
+        })
+        .map(|argument| argument.name)
+        .collect()
+}
+
+/// The text inserted for the missing arguments, either appended to the existing
+/// arguments of the field or as a new argument list.
+fn missing_arguments_text(missing_arguments: &[StringKey], has_arguments: bool) -> String {
+    let arguments = missing_arguments
+        .iter()
+        .map(|argument_name| format!("{}: ${}", argument_name, argument_name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if has_arguments {
+        format!(", {}", arguments)
+    } else {
+        format!("({})", arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interner::Intern;
+
+    #[test]
+    fn test_missing_arguments_text() {
+        let missing_arguments = vec!["first".intern(), "after".intern()];
+        assert_eq!(
+            missing_arguments_text(&missing_arguments, false),
+            "(first: $first, after: $after)"
+        );
+        assert_eq!(
+            missing_arguments_text(&missing_arguments, true),
+            ", first: $first, after: $after"
+        );
+    }
+}
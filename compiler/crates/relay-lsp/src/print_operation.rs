@@ -0,0 +1,126 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for the `relay/printOperation` command, printing the text of an operation
+//! as it is sent to the server.
+use crate::lsp::{
+    Connection, ErrorCode, ExecuteCommandParams, Message, ResponseError, ServerRequestId,
+    ServerResponse,
+};
+use crate::restart::RESTART_COMMAND;
+use crate::stats::STATS_COMMAND;
+use crate::version::VERSION_COMMAND;
+use graphql_text_printer::print_full_operation;
+use interner::Intern;
+use relay_compiler::Programs;
+
+/// The name of the command printing the text of an operation
+pub const PRINT_OPERATION_COMMAND: &str = "relay/printOperation";
+
+/// Returns the operation name of a `relay/printOperation` command, which is passed as
+/// the only argument of the command.
+pub fn get_print_operation_name(params: &ExecuteCommandParams) -> Option<&str> {
+    if params.command != PRINT_OPERATION_COMMAND {
+        return None;
+    }
+    match params.arguments.as_slice() {
+        [argument] => argument.as_str(),
+        _ => None,
+    }
+}
+
+/// Prints the query text of the operation named `operation_name`, after the transforms
+/// applied to the operations sent to the server, along with the fragments it spreads.
+pub fn print_operation<'a, 'schema: 'a>(
+    operation_name: &str,
+    project_programs: impl Iterator<Item = &'a Programs<'schema>>,
+) -> Option<String> {
+    let name = operation_name.intern();
+    for programs in project_programs {
+        if let Some(operation) = programs.operation_text.operation(name) {
+            return Some(print_full_operation(&programs.operation_text, operation));
+        }
+    }
+    None
+}
+
+pub fn send_print_operation_response(
+    text: Option<String>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&text).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+pub fn send_unknown_command_response(
+    params: &ExecuteCommandParams,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let response = ServerResponse {
+        id: request_id,
+        error: Some(ResponseError {
+            code: ErrorCode::InvalidParams as i32,
+            message: format!(
+                "Expected `{}` with an operation name, `{}`, `{}` or `{}`, got `{}`",
+                PRINT_OPERATION_COMMAND,
+                RESTART_COMMAND,
+                STATS_COMMAND,
+                VERSION_COMMAND,
+                params.command
+            ),
+            data: None,
+        }),
+        result: None,
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::WorkDoneProgressParams;
+    use serde_json::json;
+
+    fn command_params(command: &str, arguments: Vec<serde_json::Value>) -> ExecuteCommandParams {
+        ExecuteCommandParams {
+            command: command.to_string(),
+            arguments,
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_get_print_operation_name() {
+        assert_eq!(
+            get_print_operation_name(&command_params(
+                PRINT_OPERATION_COMMAND,
+                vec![json!("UserQuery")]
+            )),
+            Some("UserQuery")
+        );
+        assert_eq!(
+            get_print_operation_name(&command_params(PRINT_OPERATION_COMMAND, vec![])),
+            None
+        );
+        assert_eq!(
+            get_print_operation_name(&command_params(
+                "relay/unknownCommand",
+                vec![json!("UserQuery")]
+            )),
+            None
+        );
+    }
+}
@@ -0,0 +1,248 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the find references language feature
+use crate::completion::{get_graphql_source_for_position, position_to_span, GraphQLSourceCache};
+use crate::lsp::{
+    Connection, LSPLocation, Message, ReferenceParams, ServerRequestId, ServerResponse,
+    TextDocumentPositionParams,
+};
+use common::{FileKey, Location, Span};
+use graphql_ir::{FragmentSpread, Visitor};
+use graphql_syntax::{parse, Document, ExecutableDefinition, List, Selection};
+use interner::StringKey;
+use log::info;
+use relay_compiler::Programs;
+use std::collections::BTreeSet;
+
+/// Returns the name of the fragment under the cursor, if the references request occurs
+/// on the name of a fragment definition or fragment spread.
+pub fn get_fragment_name_for_references_request(
+    params: &ReferenceParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> Option<StringKey> {
+    let TextDocumentPositionParams {
+        text_document,
+        position,
+    } = &params.text_document_position;
+    let url = &text_document.uri;
+
+    let graphql_source = get_graphql_source_for_position(url, *position, graphql_source_cache)?;
+    let document = match parse(&graphql_source.text, FileKey::new(&url.to_string())) {
+        Ok(document) => document,
+        Err(err) => {
+            info!(
+                "Failed to parse the target GraphQL source for references: {:?}",
+                err
+            );
+            return None;
+        }
+    };
+    let position_span = position_to_span(*position, &graphql_source)?;
+    find_fragment_name_at_position(document, position_span)
+}
+
+/// Returns the name of the fragment whose definition name or spread contains the position.
+pub fn find_fragment_name_at_position(
+    document: Document,
+    position_span: Span,
+) -> Option<StringKey> {
+    for definition in document.definitions {
+        match definition {
+            ExecutableDefinition::Operation(operation) => {
+                if operation.location.contains(position_span) {
+                    return find_fragment_name_in_selections(&operation.selections, position_span);
+                }
+            }
+            ExecutableDefinition::Fragment(fragment) => {
+                if fragment.name.span.contains(position_span) {
+                    return Some(fragment.name.value);
+                }
+                if fragment.location.contains(position_span) {
+                    return find_fragment_name_in_selections(&fragment.selections, position_span);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn find_fragment_name_in_selections(
+    selections: &List<Selection>,
+    position_span: Span,
+) -> Option<StringKey> {
+    let selection = selections
+        .items
+        .iter()
+        .find(|item| item.span().contains(position_span))?;
+    match selection {
+        Selection::FragmentSpread(spread) => {
+            if spread.name.span.contains(position_span) {
+                Some(spread.name.value)
+            } else {
+                None
+            }
+        }
+        Selection::LinkedField(field) => {
+            find_fragment_name_in_selections(&field.selections, position_span)
+        }
+        Selection::InlineFragment(fragment) => {
+            find_fragment_name_in_selections(&fragment.selections, position_span)
+        }
+        Selection::ScalarField(_) => None,
+    }
+}
+
+/// Finds the locations of every spread of the fragment `fragment_name` across
+/// all of the given `Programs`. The same spread can be part of multiple projects
+/// (e.g. when it's defined in a base project), so the locations are deduped.
+/// Returns an empty list for fragments that are defined but never spread.
+pub fn find_fragment_references<'a, 'schema: 'a>(
+    fragment_name: StringKey,
+    project_programs: impl Iterator<Item = &'a Programs<'schema>>,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let mut finder = FragmentSpreadFinder {
+        fragment_name,
+        locations: Default::default(),
+    };
+    for programs in project_programs {
+        finder.visit_program(&programs.source);
+        if include_declaration {
+            if let Some(fragment) = programs.source.fragment(fragment_name) {
+                finder.locations.insert(fragment.name.location);
+            }
+        }
+    }
+    finder.locations.into_iter().collect()
+}
+
+struct FragmentSpreadFinder {
+    fragment_name: StringKey,
+    locations: BTreeSet<Location>,
+}
+
+impl Visitor for FragmentSpreadFinder {
+    const NAME: &'static str = "FragmentSpreadFinder";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn visit_fragment_spread(&mut self, spread: &FragmentSpread) {
+        if spread.fragment.item == self.fragment_name {
+            self.locations.insert(spread.fragment.location);
+        }
+    }
+}
+
+pub fn send_references_response(
+    locations: Vec<LSPLocation>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&locations).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use graphql_ir::{build, Program};
+    use interner::Intern;
+    use schema::{build_schema, Schema};
+
+    /// The programs of a project built from the documents of the given files, all its
+    /// programs are the source program
+    pub(crate) fn programs_for_documents<'schema>(
+        schema: &'schema Schema,
+        documents: &[(&str, &str)],
+    ) -> Programs<'schema> {
+        let mut definitions = vec![];
+        for (file_name, text) in documents {
+            definitions.extend(parse(text, FileKey::new(file_name)).unwrap().definitions);
+        }
+        let program = Program::from_definitions(schema, build(schema, &definitions).unwrap());
+        Programs {
+            source: program.clone(),
+            reader: program.clone(),
+            normalization: program.clone(),
+            operation_text: program.clone(),
+            typegen: program,
+            operation_ids: Default::default(),
+        }
+    }
+
+    /// The file and text of each of the `locations` in the `documents`, sorted. The
+    /// text is trimmed, as the spans of names include the whitespace around them.
+    pub(crate) fn located_texts(
+        locations: &[Location],
+        documents: &[(&str, &str)],
+    ) -> Vec<(String, String)> {
+        let mut located_texts: Vec<(String, String)> = locations
+            .iter()
+            .map(|location| {
+                let file_name = location.file().lookup();
+                let (_, text) = documents
+                    .iter()
+                    .find(|(document_file_name, _)| *document_file_name == file_name)
+                    .unwrap();
+                let (start, length) = location.span().as_usize();
+                (
+                    file_name.to_string(),
+                    text[start..start + length].trim().to_string(),
+                )
+            })
+            .collect();
+        located_texts.sort();
+        located_texts
+    }
+
+    #[test]
+    fn test_find_fragment_references_across_documents() {
+        let documents: &[(&str, &str)] = &[
+            ("User.js", "fragment User_user on User { name }"),
+            ("Query.js", "query UserQuery { me { ...User_user } }"),
+            (
+                "Friends.js",
+                "fragment Friends_user on User { friends { ... on User { ...User_user } } }",
+            ),
+        ];
+        let schema =
+            build_schema("type Query { me: User } type User { name: String, friends: [User] }")
+                .unwrap();
+        let programs = programs_for_documents(&schema, documents);
+        let references = |include_declaration: bool| {
+            let locations = find_fragment_references(
+                "User_user".intern(),
+                std::iter::once(&programs),
+                include_declaration,
+            );
+            located_texts(&locations, documents)
+        };
+
+        let user_user = "User_user".to_string();
+        assert_eq!(
+            references(false),
+            vec![
+                ("Friends.js".to_string(), user_user.clone()),
+                ("Query.js".to_string(), user_user.clone()),
+            ]
+        );
+        assert_eq!(
+            references(true),
+            vec![
+                ("Friends.js".to_string(), user_user.clone()),
+                ("Query.js".to_string(), user_user.clone()),
+                ("User.js".to_string(), user_user),
+            ]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the rename language feature
+use crate::completion::{get_graphql_source_for_position, position_to_span, GraphQLSourceCache};
+use crate::lsp::{
+    Connection, ErrorCode, LSPLocation, Message, RenameParams, ResponseError, ServerRequestId,
+    ServerResponse, TextDocumentPositionParams, TextEdit, WorkspaceEdit,
+};
+use crate::references::{find_fragment_name_at_position, find_fragment_references};
+use common::{FileKey, Location, Span};
+use graphql_syntax::{parse, ExecutableDefinition, GraphQLSource};
+use interner::StringKey;
+use log::info;
+use relay_compiler::Programs;
+use std::collections::HashMap;
+
+/// The definition a rename request can be made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameTarget {
+    Fragment(StringKey),
+    Operation(StringKey),
+}
+
+impl RenameTarget {
+    pub fn name(self) -> StringKey {
+        match self {
+            RenameTarget::Fragment(name) | RenameTarget::Operation(name) => name,
+        }
+    }
+}
+
+/// Returns the fragment or operation to rename, if the rename request occurs on the
+/// name of an operation definition, fragment definition or fragment spread.
+pub fn get_rename_target(
+    params: &RenameParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> Option<RenameTarget> {
+    let TextDocumentPositionParams {
+        text_document,
+        position,
+    } = &params.text_document_position;
+    let url = &text_document.uri;
+
+    let graphql_source = get_graphql_source_for_position(url, *position, graphql_source_cache)?;
+    let document = match parse(&graphql_source.text, FileKey::new(&url.to_string())) {
+        Ok(document) => document,
+        Err(err) => {
+            info!(
+                "Failed to parse the target GraphQL source for rename: {:?}",
+                err
+            );
+            return None;
+        }
+    };
+    let position_span = position_to_span(*position, &graphql_source)?;
+    let operation_name = document
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            ExecutableDefinition::Operation(operation) => operation
+                .name
+                .as_ref()
+                .filter(|name| name.span.contains(position_span))
+                .map(|name| name.value),
+            ExecutableDefinition::Fragment(_) => None,
+        });
+    match operation_name {
+        Some(operation_name) => Some(RenameTarget::Operation(operation_name)),
+        None => find_fragment_name_at_position(document, position_span).map(RenameTarget::Fragment),
+    }
+}
+
+/// Finds the locations of the name of the renamed definition and of all its usages
+/// across the given `Programs`. Operations can't be referenced by other definitions,
+/// so only their definition is renamed.
+pub fn find_rename_locations<'a, 'schema: 'a>(
+    target: RenameTarget,
+    project_programs: impl Iterator<Item = &'a Programs<'schema>>,
+) -> Vec<Location> {
+    match target {
+        RenameTarget::Fragment(fragment_name) => {
+            find_fragment_references(fragment_name, project_programs, true)
+        }
+        RenameTarget::Operation(operation_name) => {
+            let mut locations: Vec<Location> = project_programs
+                .filter_map(|programs| programs.source.operation(operation_name))
+                .map(|operation| operation.name.location)
+                .collect();
+            // The operations of a base project are part of each project extending it
+            locations.sort();
+            locations.dedup();
+            locations
+        }
+    }
+}
+
+/// Narrows the location of a name to the name itself, as the spans of names include
+/// the whitespace and comments around them, which the rename must keep
+pub fn name_location(location: &Location, name: StringKey, source: &GraphQLSource) -> Location {
+    let (start, length) = location.span().as_usize();
+    match source.text[start..start + length].find(name.lookup()) {
+        Some(offset) => location.with_span(Span::from_usize(start + offset, name.lookup().len())),
+        None => *location,
+    }
+}
+
+/// Whether `name` is a valid GraphQL name, i.e. matches `/[_A-Za-z][_0-9A-Za-z]*/`,
+/// and isn't reserved for introspection by starting with `__`
+pub fn is_valid_graphql_name(name: &str) -> bool {
+    if name.starts_with("__") {
+        return false;
+    }
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first == '_' || first.is_ascii_alphabetic() => {
+            chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+        }
+        _ => false,
+    }
+}
+
+/// Creates the edit replacing each of the `locations` with the `new_name`
+pub fn create_rename_edit(locations: Vec<LSPLocation>, new_name: &str) -> WorkspaceEdit {
+    let mut changes: HashMap<_, Vec<TextEdit>> = HashMap::new();
+    for location in locations {
+        changes.entry(location.uri).or_default().push(TextEdit {
+            range: location.range,
+            new_text: new_name.to_string(),
+        });
+    }
+    WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+    }
+}
+
+pub fn send_rename_response(
+    edit: Option<WorkspaceEdit>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&edit).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+/// Responds to a rename request for a `new_name` that isn't a valid GraphQL name
+pub fn send_invalid_rename_response(
+    new_name: &str,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let response = ServerResponse {
+        id: request_id,
+        error: Some(ResponseError {
+            code: ErrorCode::InvalidParams as i32,
+            message: format!("`{}` is not a valid GraphQL name", new_name),
+            data: None,
+        }),
+        result: None,
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{Position, Range, Url};
+    use crate::references::tests::{located_texts, programs_for_documents};
+    use interner::Intern;
+    use schema::build_schema;
+
+    const DOCUMENTS: &[(&str, &str)] = &[
+        ("User.js", "fragment User_user on User { name }"),
+        ("Query.js", "query UserQuery { me { ...User_user } }"),
+        (
+            "Friends.js",
+            "fragment Friends_user on User { friends { ...User_user } }",
+        ),
+    ];
+
+    #[test]
+    fn test_rename_fragment_used_across_documents() {
+        let schema =
+            build_schema("type Query { me: User } type User { name: String, friends: [User] }")
+                .unwrap();
+        let programs = programs_for_documents(&schema, DOCUMENTS);
+
+        let locations = find_rename_locations(
+            RenameTarget::Fragment("User_user".intern()),
+            std::iter::once(&programs),
+        );
+        let user_user = "User_user".to_string();
+        assert_eq!(
+            located_texts(&locations, DOCUMENTS),
+            vec![
+                ("Friends.js".to_string(), user_user.clone()),
+                ("Query.js".to_string(), user_user.clone()),
+                ("User.js".to_string(), user_user),
+            ]
+        );
+
+        let operation_locations = find_rename_locations(
+            RenameTarget::Operation("UserQuery".intern()),
+            std::iter::once(&programs),
+        );
+        assert_eq!(
+            located_texts(&operation_locations, DOCUMENTS),
+            vec![("Query.js".to_string(), "UserQuery".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_name_location_leaves_out_surrounding_whitespace() {
+        let (_, text) = DOCUMENTS[1];
+        let schema =
+            build_schema("type Query { me: User } type User { name: String, friends: [User] }")
+                .unwrap();
+        let programs = programs_for_documents(&schema, DOCUMENTS);
+        let location = find_rename_locations(
+            RenameTarget::Fragment("User_user".intern()),
+            std::iter::once(&programs),
+        )
+        .into_iter()
+        .find(|location| location.file().lookup() == "Query.js")
+        .unwrap();
+
+        let source = GraphQLSource::new(text, 0, 0);
+        let (start, length) = name_location(&location, "User_user".intern(), &source)
+            .span()
+            .as_usize();
+        assert_eq!(start, text.find("User_user").unwrap());
+        assert_eq!(&text[start..start + length], "User_user");
+    }
+
+    #[test]
+    fn test_create_rename_edit_groups_edits_by_document() {
+        let location = |file: &str, character: u64| {
+            LSPLocation::new(
+                Url::parse(&format!("file:///root/{}", file)).unwrap(),
+                Range::new(Position::new(0, character), Position::new(0, character + 9)),
+            )
+        };
+        let edit = create_rename_edit(
+            vec![
+                location("User.js", 9),
+                location("Query.js", 26),
+                location("Query.js", 50),
+            ],
+            "Profile_user",
+        );
+
+        let changes = edit.changes.unwrap();
+        assert_eq!(changes.len(), 2);
+        let query_edits = &changes[&Url::parse("file:///root/Query.js").unwrap()];
+        assert_eq!(query_edits.len(), 2);
+        assert!(query_edits
+            .iter()
+            .all(|text_edit| text_edit.new_text == "Profile_user"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_names() {
+        assert!(is_valid_graphql_name("User_user"));
+        assert!(is_valid_graphql_name("_user"));
+        assert!(is_valid_graphql_name("user2"));
+        assert!(!is_valid_graphql_name(""));
+        assert!(!is_valid_graphql_name("2user"));
+        assert!(!is_valid_graphql_name("__user"));
+        assert!(!is_valid_graphql_name("__"));
+        assert!(!is_valid_graphql_name("User-user"));
+        assert!(!is_valid_graphql_name("Usér"));
+
+        let (connection, client) = Connection::memory();
+        send_invalid_rename_response(
+            "2user",
+            ServerRequestId::from("rename".to_string()),
+            &connection,
+        );
+        match client.receiver.try_recv().unwrap() {
+            Message::Response(response) => {
+                let error = response.error.unwrap();
+                assert_eq!(error.code, ErrorCode::InvalidParams as i32);
+                assert_eq!(error.message, "`2user` is not a valid GraphQL name");
+                assert!(response.result.is_none());
+            }
+            message => panic!("Expected a response, got {:?}", message),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for the `relay/restart` command, rebuilding the state of the compiler
+//! without restarting the server.
+use crate::lsp::{
+    Connection, ErrorCode, ExecuteCommandParams, Message, ResponseError, ServerRequestId,
+    ServerResponse,
+};
+use relay_compiler::errors::Error as CompilerError;
+
+/// The name of the command rebuilding the compiler state, the schemas and checking all
+/// projects again
+pub const RESTART_COMMAND: &str = "relay/restart";
+
+pub fn is_restart_command(params: &ExecuteCommandParams) -> bool {
+    params.command == RESTART_COMMAND
+}
+
+/// Responds to the command once the projects were checked again, or with the error that
+/// prevented rebuilding the compiler state
+pub fn send_restart_response(
+    result: Result<(), CompilerError>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let response = match result {
+        Ok(()) => ServerResponse {
+            id: request_id,
+            error: None,
+            result: Some(serde_json::Value::Null),
+        },
+        Err(err) => ServerResponse {
+            id: request_id,
+            error: Some(ResponseError {
+                code: ErrorCode::InternalError as i32,
+                message: format!("Failed to restart Relay: {}", err),
+                data: None,
+            }),
+            result: None,
+        },
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
@@ -0,0 +1,41 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for persisting the compiler state across server restarts
+use log::info;
+use md5::{Digest, Md5};
+use relay_compiler::compiler_state::CompilerState;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Returns the path of the file caching the compiler state of a config. The path is
+/// keyed on the root directory and the contents of the config file, so a changed
+/// config never loads the state of the previous one. The key is an md5 hash, which is
+/// stable across builds of the server unlike the `DefaultHasher`.
+pub fn saved_state_path(root_dir: &Path, config_path: &Path) -> Option<PathBuf> {
+    let config_contents = fs::read(config_path).ok()?;
+    let mut md5 = Md5::new();
+    md5.input(root_dir.to_string_lossy().as_bytes());
+    md5.input(b"\0");
+    md5.input(&config_contents);
+    Some(env::temp_dir().join(format!(
+        "relay-lsp-state-{}.json",
+        hex::encode(md5.result())
+    )))
+}
+
+/// Writes the compiler state to the `saved_state_path`. Only states with a Watchman
+/// clock are written, since loading a saved state queries the changes since the clock.
+pub fn persist_compiler_state(compiler_state: &CompilerState, saved_state_path: &PathBuf) {
+    if compiler_state.clock.is_none() {
+        return;
+    }
+    if let Err(err) = compiler_state.serialize_to_file(saved_state_path) {
+        info!("Failed to persist the compiler state: {:?}", err);
+    }
+}
@@ -0,0 +1,483 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the selection ranges used to expand the selection from
+//! the token at the cursor to the enclosing nodes
+use crate::completion::{get_graphql_source_for_position, position_to_span, GraphQLSourceCache};
+use crate::lsp::{
+    Connection, Message, Position, Range, SelectionRange, SelectionRangeParams, ServerRequestId,
+    ServerResponse,
+};
+use common::{FileKey, Span};
+use graphql_syntax::{
+    parse, Alias, Argument, ConstantValue, Directive, ExecutableDefinition, GraphQLSource, List,
+    Selection, Token, TypeAnnotation, Value, VariableDefinition,
+};
+use log::info;
+
+/// Returns a selection range for each requested position: the innermost range is the
+/// token at the position, and each `parent` is the range of the enclosing node, e.g.
+/// the argument, the field, the selection set, ..., the operation or fragment, and
+/// finally the whole GraphQL source. Positions outside of the GraphQL sources of the
+/// document get an empty range at the position.
+pub fn get_selection_ranges(
+    params: &SelectionRangeParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> Vec<SelectionRange> {
+    let url = &params.text_document.uri;
+    let file_key = FileKey::new(&url.to_string());
+    params
+        .positions
+        .iter()
+        .map(|&position| {
+            get_graphql_source_for_position(url, position, graphql_source_cache)
+                .and_then(|graphql_source| {
+                    selection_range_for_position(graphql_source, position, file_key)
+                })
+                .unwrap_or_else(|| SelectionRange {
+                    range: Range::new(position, position),
+                    parent: None,
+                })
+        })
+        .collect()
+}
+
+fn selection_range_for_position(
+    source: &GraphQLSource,
+    position: Position,
+    file_key: FileKey,
+) -> Option<SelectionRange> {
+    let position_span = position_to_span(position, source)?;
+    let mut selection_range = SelectionRange {
+        range: source.to_range(),
+        parent: None,
+    };
+    let document = match parse(&source.text, file_key) {
+        Ok(document) => document,
+        Err(err) => {
+            // Only the whole source can be selected while it can't be parsed
+            info!(
+                "Failed to parse a GraphQL source for selection ranges: {:?}",
+                err
+            );
+            return Some(selection_range);
+        }
+    };
+    let mut collector = EnclosingSpanCollector {
+        position_span,
+        spans: vec![],
+    };
+    for definition in &document.definitions {
+        collector.collect_definition(definition);
+    }
+    for span in collector.spans {
+        let range = span.to_range(&source.text, source.line_index, source.column_index);
+        // Nodes with a single token, e.g. a field without arguments, have the same
+        // range as their token
+        if range != selection_range.range {
+            selection_range = SelectionRange {
+                range,
+                parent: Some(Box::new(selection_range)),
+            };
+        }
+    }
+    Some(selection_range)
+}
+
+/// Collects the spans of the nodes containing the position, from the outermost to the
+/// innermost. The spans of the nodes are joined from the inner spans of their first
+/// and last tokens, so that they don't include the surrounding whitespace and comments.
+struct EnclosingSpanCollector {
+    position_span: Span,
+    spans: Vec<Span>,
+}
+
+impl EnclosingSpanCollector {
+    /// Adds `span` if it contains the position, the nodes within it should only be
+    /// visited in that case. A node right after the position, e.g. the next argument
+    /// in `(a: 1,b: 2)`, is skipped as it isn't within the last added node.
+    fn enter(&mut self, span: Span) -> bool {
+        if span.contains(self.position_span)
+            && self.spans.last().map_or(true, |last| last.contains(span))
+        {
+            self.spans.push(span);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn collect_definition(&mut self, definition: &ExecutableDefinition) {
+        match definition {
+            ExecutableDefinition::Operation(operation) => {
+                let start = match &operation.operation {
+                    Some((token, _)) => token,
+                    None => &operation.selections.start,
+                };
+                if !self.enter(join(start, &operation.selections.end)) {
+                    return;
+                }
+                if let Some(name) = &operation.name {
+                    self.enter(name.token.inner_span);
+                }
+                if let Some(variable_definitions) = &operation.variable_definitions {
+                    if self.enter(list_span(variable_definitions)) {
+                        for variable_definition in &variable_definitions.items {
+                            self.collect_variable_definition(variable_definition);
+                        }
+                    }
+                }
+                self.collect_directives(&operation.directives);
+                self.collect_selections(&operation.selections);
+            }
+            ExecutableDefinition::Fragment(fragment) => {
+                if !self.enter(join(&fragment.fragment, &fragment.selections.end)) {
+                    return;
+                }
+                self.enter(fragment.name.token.inner_span);
+                let type_condition = &fragment.type_condition;
+                if self.enter(join(&type_condition.on, &type_condition.type_.token)) {
+                    self.enter(type_condition.type_.token.inner_span);
+                }
+                self.collect_directives(&fragment.directives);
+                self.collect_selections(&fragment.selections);
+            }
+        }
+    }
+
+    fn collect_variable_definition(&mut self, variable_definition: &VariableDefinition) {
+        let end = match (
+            variable_definition.directives.last(),
+            &variable_definition.default_value,
+        ) {
+            (Some(directive), _) => directive_span(directive),
+            (None, Some(default_value)) => constant_value_span(&default_value.value),
+            (None, None) => type_annotation_span(&variable_definition.type_),
+        };
+        let name = &variable_definition.name;
+        if !self.enter(join_spans(name.token.inner_span, end)) {
+            return;
+        }
+        self.enter(name.token.inner_span);
+        self.collect_type_annotation(&variable_definition.type_);
+        if let Some(default_value) = &variable_definition.default_value {
+            self.collect_constant_value(&default_value.value);
+        }
+        self.collect_directives(&variable_definition.directives);
+    }
+
+    fn collect_type_annotation(&mut self, type_annotation: &TypeAnnotation) {
+        if !self.enter(type_annotation_span(type_annotation)) {
+            return;
+        }
+        match type_annotation {
+            TypeAnnotation::Named(_) => {}
+            TypeAnnotation::List(list) => self.collect_type_annotation(&list.type_),
+            TypeAnnotation::NonNull(non_null) => self.collect_type_annotation(&non_null.type_),
+        }
+    }
+
+    fn collect_selections(&mut self, selections: &List<Selection>) {
+        if !self.enter(list_span(selections)) {
+            return;
+        }
+        for selection in &selections.items {
+            match selection {
+                Selection::LinkedField(field) => {
+                    let start = match &field.alias {
+                        Some(alias) => &alias.alias.token,
+                        None => &field.name.token,
+                    };
+                    if self.enter(join(start, &field.selections.end)) {
+                        self.collect_field_name(&field.alias, &field.name.token);
+                        self.collect_arguments(&field.arguments);
+                        self.collect_directives(&field.directives);
+                        self.collect_selections(&field.selections);
+                    }
+                }
+                Selection::ScalarField(field) => {
+                    let start = match &field.alias {
+                        Some(alias) => alias.alias.token.inner_span,
+                        None => field.name.token.inner_span,
+                    };
+                    let end = match (field.directives.last(), &field.arguments) {
+                        (Some(directive), _) => directive_span(directive),
+                        (None, Some(arguments)) => list_span(arguments),
+                        (None, None) => field.name.token.inner_span,
+                    };
+                    if self.enter(join_spans(start, end)) {
+                        self.collect_field_name(&field.alias, &field.name.token);
+                        self.collect_arguments(&field.arguments);
+                        self.collect_directives(&field.directives);
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    if self.enter(join(&fragment.spread, &fragment.selections.end)) {
+                        if let Some(type_condition) = &fragment.type_condition {
+                            if self.enter(join(&type_condition.on, &type_condition.type_.token)) {
+                                self.enter(type_condition.type_.token.inner_span);
+                            }
+                        }
+                        self.collect_directives(&fragment.directives);
+                        self.collect_selections(&fragment.selections);
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    let end = match spread.directives.last() {
+                        Some(directive) => directive_span(directive),
+                        None => spread.name.token.inner_span,
+                    };
+                    if self.enter(join_spans(spread.spread.inner_span, end)) {
+                        self.enter(spread.name.token.inner_span);
+                        self.collect_directives(&spread.directives);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the span of the alias and name of a field, followed by the span of the
+    /// one containing the position
+    fn collect_field_name(&mut self, alias: &Option<Alias>, name: &Token) {
+        if let Some(alias) = alias {
+            if self.enter(join(&alias.alias.token, name)) {
+                self.enter(alias.alias.token.inner_span);
+            }
+        }
+        self.enter(name.inner_span);
+    }
+
+    fn collect_directives(&mut self, directives: &[Directive]) {
+        for directive in directives {
+            if self.enter(directive_span(directive)) {
+                self.enter(join(&directive.at, &directive.name.token));
+                self.collect_arguments(&directive.arguments);
+            }
+        }
+    }
+
+    fn collect_arguments(&mut self, arguments: &Option<List<Argument>>) {
+        if let Some(arguments) = arguments {
+            if self.enter(list_span(arguments)) {
+                for argument in &arguments.items {
+                    self.collect_argument(argument);
+                }
+            }
+        }
+    }
+
+    fn collect_argument(&mut self, argument: &Argument) {
+        if self.enter(join_spans(
+            argument.name.token.inner_span,
+            value_span(&argument.value),
+        )) {
+            self.enter(argument.name.token.inner_span);
+            self.collect_value(&argument.value);
+        }
+    }
+
+    fn collect_value(&mut self, value: &Value) {
+        if !self.enter(value_span(value)) {
+            return;
+        }
+        match value {
+            Value::Constant(value) => self.collect_constant_value(value),
+            Value::Variable(_) => {}
+            Value::List(list) => {
+                for item in &list.items {
+                    self.collect_value(item);
+                }
+            }
+            Value::Object(object) => {
+                for argument in &object.items {
+                    self.collect_argument(argument);
+                }
+            }
+        }
+    }
+
+    fn collect_constant_value(&mut self, value: &ConstantValue) {
+        if !self.enter(constant_value_span(value)) {
+            return;
+        }
+        match value {
+            ConstantValue::List(list) => {
+                for item in &list.items {
+                    self.collect_constant_value(item);
+                }
+            }
+            ConstantValue::Object(object) => {
+                for argument in &object.items {
+                    if self.enter(join_spans(
+                        argument.name.token.inner_span,
+                        constant_value_span(&argument.value),
+                    )) {
+                        self.enter(argument.name.token.inner_span);
+                        self.collect_constant_value(&argument.value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The span from the start of `start` to the end of `end`
+fn join_spans(start: Span, end: Span) -> Span {
+    Span::new(start.start, end.start + end.length - start.start)
+}
+
+fn join(start: &Token, end: &Token) -> Span {
+    join_spans(start.inner_span, end.inner_span)
+}
+
+fn list_span<T>(list: &List<T>) -> Span {
+    join(&list.start, &list.end)
+}
+
+fn directive_span(directive: &Directive) -> Span {
+    match &directive.arguments {
+        Some(arguments) => join_spans(directive.at.inner_span, list_span(arguments)),
+        None => join(&directive.at, &directive.name.token),
+    }
+}
+
+fn type_annotation_span(type_annotation: &TypeAnnotation) -> Span {
+    match type_annotation {
+        TypeAnnotation::Named(name) => name.token.inner_span,
+        TypeAnnotation::List(list) => join(&list.open, &list.close),
+        TypeAnnotation::NonNull(non_null) => join_spans(
+            type_annotation_span(&non_null.type_),
+            non_null.exclamation.inner_span,
+        ),
+    }
+}
+
+fn value_span(value: &Value) -> Span {
+    match value {
+        Value::Constant(value) => constant_value_span(value),
+        Value::Variable(variable) => variable.token.inner_span,
+        Value::List(list) => list_span(list),
+        Value::Object(object) => list_span(object),
+    }
+}
+
+fn constant_value_span(value: &ConstantValue) -> Span {
+    match value {
+        ConstantValue::Int(value) => value.token.inner_span,
+        ConstantValue::Float(value) => value.token.inner_span,
+        ConstantValue::String(value) => value.token.inner_span,
+        ConstantValue::Boolean(value) => value.token.inner_span,
+        ConstantValue::Null(token) => token.inner_span,
+        ConstantValue::Enum(value) => value.token.inner_span,
+        ConstantValue::List(list) => list_span(list),
+        ConstantValue::Object(object) => list_span(object),
+    }
+}
+
+pub fn send_selection_ranges_response(
+    selection_ranges: Vec<SelectionRange>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&selection_ranges).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ranges from the innermost to the outermost, as
+    /// `(start_line, start_character, end_line, end_character)`
+    fn ranges_at(text: &str, line: u64, character: u64) -> Vec<(u64, u64, u64, u64)> {
+        let source = GraphQLSource::new(text, 0, 0);
+        let mut selection_range = selection_range_for_position(
+            &source,
+            Position::new(line, character),
+            FileKey::new("test.graphql"),
+        );
+        let mut ranges = vec![];
+        while let Some(SelectionRange { range, parent }) = selection_range {
+            ranges.push((
+                range.start.line,
+                range.start.character,
+                range.end.line,
+                range.end.character,
+            ));
+            selection_range = parent.map(|parent| *parent);
+        }
+        ranges
+    }
+
+    #[test]
+    fn test_selection_ranges_of_field() {
+        let text = "query Foo {
+  me {
+    name
+    profilePicture(size: 32) { uri }
+  }
+}
+";
+        // The cursor is on `name`
+        assert_eq!(
+            ranges_at(text, 2, 6),
+            vec![
+                (2, 4, 2, 8),
+                (1, 5, 4, 3),
+                (1, 2, 4, 3),
+                (0, 10, 5, 1),
+                (0, 0, 5, 1),
+                (0, 0, 6, 0),
+            ]
+        );
+        // The cursor is on `32`
+        assert_eq!(
+            ranges_at(text, 3, 26),
+            vec![
+                (3, 25, 3, 27),
+                (3, 19, 3, 27),
+                (3, 18, 3, 28),
+                (3, 4, 3, 36),
+                (1, 5, 4, 3),
+                (1, 2, 4, 3),
+                (0, 10, 5, 1),
+                (0, 0, 5, 1),
+                (0, 0, 6, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selection_ranges_of_fragment_spread() {
+        let text = "fragment Foo on User {
+  ...Bar @include(if: $cond)
+}";
+        // The cursor is on `include`
+        assert_eq!(
+            ranges_at(text, 1, 12),
+            vec![
+                (1, 9, 1, 17),
+                (1, 9, 1, 28),
+                (1, 2, 1, 28),
+                (0, 21, 2, 1),
+                (0, 0, 2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selection_ranges_of_unparsable_source() {
+        let text = "query Foo {\n  me {\n";
+        assert_eq!(ranges_at(text, 1, 3), vec![(0, 0, 2, 0)]);
+    }
+}
@@ -0,0 +1,369 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the semantic tokens language feature
+use crate::completion::GraphQLSourceCache;
+use crate::lsp::{
+    Connection, Message, SemanticTokens, SemanticTokensLegend, SemanticTokensParams,
+    ServerRequestId, ServerResponse,
+};
+use common::{FileKey, Span};
+use graphql_syntax::{
+    parse, Argument, Directive, ExecutableDefinition, GraphQLSource, List, Selection, Token,
+    TypeAnnotation, TypeCondition, Value, VariableDefinition,
+};
+use log::info;
+
+/// The kinds of tokens that are classified, in the order of the legend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenType {
+    Keyword,
+    Type,
+    Field,
+    Argument,
+    Variable,
+    Directive,
+}
+
+impl TokenType {
+    /// The name of the token type in the legend, from the predefined LSP token types
+    fn legend_name(self) -> &'static str {
+        match self {
+            TokenType::Keyword => "keyword",
+            TokenType::Type => "type",
+            TokenType::Field => "property",
+            TokenType::Argument => "parameter",
+            TokenType::Variable => "variable",
+            TokenType::Directive => "decorator",
+        }
+    }
+}
+
+const TOKEN_TYPES: [TokenType; 6] = [
+    TokenType::Keyword,
+    TokenType::Type,
+    TokenType::Field,
+    TokenType::Argument,
+    TokenType::Variable,
+    TokenType::Directive,
+];
+
+/// The legend of the semantic tokens sent to the client
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES
+            .iter()
+            .map(|token_type| token_type.legend_name().to_string())
+            .collect(),
+        token_modifiers: vec![],
+    }
+}
+
+/// Classifies the tokens of every GraphQL source in the document. The tokens are encoded
+/// relative to each other as described by the LSP spec, their positions are relative to the
+/// whole document rather than the GraphQL source they are part of.
+pub fn get_semantic_tokens(
+    params: SemanticTokensParams,
+    graphql_source_cache: &GraphQLSourceCache,
+) -> SemanticTokens {
+    let url = params.text_document.uri;
+    let mut tokens = vec![];
+    if let Some(graphql_sources) = graphql_source_cache.get(&url) {
+        for graphql_source in graphql_sources {
+            match parse(&graphql_source.text, FileKey::new(&url.to_string())) {
+                Ok(document) => {
+                    let mut collector = TokenCollector {
+                        source: graphql_source,
+                        tokens: &mut tokens,
+                    };
+                    for definition in &document.definitions {
+                        collector.collect_definition(definition);
+                    }
+                }
+                Err(err) => {
+                    // Skip sources that can't be parsed, but keep the tokens of the others
+                    info!(
+                        "Failed to parse a GraphQL source for semantic tokens: {:?}",
+                        err
+                    );
+                }
+            }
+        }
+    }
+    SemanticTokens {
+        result_id: None,
+        data: encode_tokens(tokens),
+    }
+}
+
+/// A classified token with its absolute position in the document
+struct AbsoluteToken {
+    line: u32,
+    character: u32,
+    length: u32,
+    token_type: TokenType,
+}
+
+struct TokenCollector<'a> {
+    source: &'a GraphQLSource,
+    tokens: &'a mut Vec<AbsoluteToken>,
+}
+
+impl<'a> TokenCollector<'a> {
+    fn collect_definition(&mut self, definition: &ExecutableDefinition) {
+        match definition {
+            ExecutableDefinition::Operation(operation) => {
+                if let Some((token, _)) = &operation.operation {
+                    self.push_token(token, TokenType::Keyword);
+                }
+                if let Some(variable_definitions) = &operation.variable_definitions {
+                    for variable_definition in &variable_definitions.items {
+                        self.collect_variable_definition(variable_definition);
+                    }
+                }
+                self.collect_directives(&operation.directives);
+                self.collect_selections(&operation.selections);
+            }
+            ExecutableDefinition::Fragment(fragment) => {
+                self.push_token(&fragment.fragment, TokenType::Keyword);
+                self.collect_type_condition(&fragment.type_condition);
+                self.collect_directives(&fragment.directives);
+                self.collect_selections(&fragment.selections);
+            }
+        }
+    }
+
+    fn collect_variable_definition(&mut self, variable_definition: &VariableDefinition) {
+        self.push_token(&variable_definition.name.token, TokenType::Variable);
+        self.collect_type_annotation(&variable_definition.type_);
+        self.collect_directives(&variable_definition.directives);
+    }
+
+    fn collect_type_annotation(&mut self, type_annotation: &TypeAnnotation) {
+        match type_annotation {
+            TypeAnnotation::Named(name) => self.push_token(&name.token, TokenType::Type),
+            TypeAnnotation::List(list) => self.collect_type_annotation(&list.type_),
+            TypeAnnotation::NonNull(non_null) => self.collect_type_annotation(&non_null.type_),
+        }
+    }
+
+    fn collect_type_condition(&mut self, type_condition: &TypeCondition) {
+        self.push_token(&type_condition.on, TokenType::Keyword);
+        self.push_token(&type_condition.type_.token, TokenType::Type);
+    }
+
+    fn collect_selections(&mut self, selections: &List<Selection>) {
+        for selection in &selections.items {
+            match selection {
+                Selection::LinkedField(field) => {
+                    if let Some(alias) = &field.alias {
+                        self.push_token(&alias.alias.token, TokenType::Field);
+                    }
+                    self.push_token(&field.name.token, TokenType::Field);
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                    self.collect_selections(&field.selections);
+                }
+                Selection::ScalarField(field) => {
+                    if let Some(alias) = &field.alias {
+                        self.push_token(&alias.alias.token, TokenType::Field);
+                    }
+                    self.push_token(&field.name.token, TokenType::Field);
+                    self.collect_arguments(&field.arguments);
+                    self.collect_directives(&field.directives);
+                }
+                Selection::InlineFragment(fragment) => {
+                    if let Some(type_condition) = &fragment.type_condition {
+                        self.collect_type_condition(type_condition);
+                    }
+                    self.collect_directives(&fragment.directives);
+                    self.collect_selections(&fragment.selections);
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.collect_directives(&spread.directives);
+                }
+            }
+        }
+    }
+
+    fn collect_directives(&mut self, directives: &[Directive]) {
+        for directive in directives {
+            // The `@` is highlighted as part of the directive name
+            let start = directive.at.inner_span.start;
+            let name_span = directive.name.token.inner_span;
+            self.push_span(
+                Span::new(start, name_span.start + name_span.length - start),
+                TokenType::Directive,
+            );
+            self.collect_arguments(&directive.arguments);
+        }
+    }
+
+    fn collect_arguments(&mut self, arguments: &Option<List<Argument>>) {
+        if let Some(arguments) = arguments {
+            for argument in &arguments.items {
+                self.push_token(&argument.name.token, TokenType::Argument);
+                self.collect_value(&argument.value);
+            }
+        }
+    }
+
+    fn collect_value(&mut self, value: &Value) {
+        match value {
+            Value::Variable(variable) => self.push_token(&variable.token, TokenType::Variable),
+            Value::List(list) => {
+                for item in &list.items {
+                    self.collect_value(item);
+                }
+            }
+            Value::Object(fields) => {
+                for field in &fields.items {
+                    self.push_token(&field.name.token, TokenType::Argument);
+                    self.collect_value(&field.value);
+                }
+            }
+            Value::Constant(_) => {}
+        }
+    }
+
+    fn push_token(&mut self, token: &Token, token_type: TokenType) {
+        self.push_span(token.inner_span, token_type);
+    }
+
+    fn push_span(&mut self, span: Span, token_type: TokenType) {
+        // Tokens can't contain line breaks, so only the start position is needed
+        let range = span.to_range(
+            &self.source.text,
+            self.source.line_index,
+            self.source.column_index,
+        );
+        self.tokens.push(AbsoluteToken {
+            line: range.start.line as u32,
+            character: range.start.character as u32,
+            length: span.length,
+            token_type,
+        });
+    }
+}
+
+/// Encodes the tokens as an array of integers, where each token is five integers: the
+/// line relative to the previous token, the start character relative to the previous
+/// token if on the same line, the length, the index of the token type in the legend,
+/// and the token modifiers.
+fn encode_tokens(mut tokens: Vec<AbsoluteToken>) -> Vec<u32> {
+    tokens.sort_by_key(|token| (token.line, token.character));
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let mut previous_line = 0;
+    let mut previous_character = 0;
+    for token in tokens {
+        let delta_line = token.line - previous_line;
+        let delta_character = if delta_line == 0 {
+            token.character - previous_character
+        } else {
+            token.character
+        };
+        let token_type_index = TOKEN_TYPES
+            .iter()
+            .position(|token_type| *token_type == token.token_type)
+            .unwrap() as u32;
+        data.extend_from_slice(&[
+            delta_line,
+            delta_character,
+            token.length,
+            token_type_index,
+            0,
+        ]);
+        previous_line = token.line;
+        previous_character = token.character;
+    }
+    data
+}
+
+/// Responds with the semantic tokens, or without a result if the client can't render them
+pub fn send_semantic_tokens_response(
+    tokens: Option<SemanticTokens>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&tokens).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(line: u32, character: u32, length: u32, token_type: TokenType) -> AbsoluteToken {
+        AbsoluteToken {
+            line,
+            character,
+            length,
+            token_type,
+        }
+    }
+
+    fn source_tokens(source: &GraphQLSource) -> Vec<u32> {
+        let document = parse(&source.text, FileKey::new("test.js")).unwrap();
+        let mut tokens = vec![];
+        let mut collector = TokenCollector {
+            source,
+            tokens: &mut tokens,
+        };
+        for definition in &document.definitions {
+            collector.collect_definition(definition);
+        }
+        encode_tokens(tokens)
+    }
+
+    #[test]
+    fn test_encode_tokens_on_the_same_line() {
+        // The tokens are sorted, their start is relative to the previous token
+        let tokens = vec![
+            token(0, 6, 3, TokenType::Field),
+            token(0, 0, 5, TokenType::Keyword),
+            token(0, 12, 2, TokenType::Argument),
+        ];
+        assert_eq!(
+            encode_tokens(tokens),
+            vec![0, 0, 5, 0, 0, 0, 6, 3, 2, 0, 0, 6, 2, 3, 0]
+        );
+    }
+
+    #[test]
+    fn test_encode_tokens_after_a_line_change() {
+        // The start of the first token of a line is relative to the start of the line
+        let tokens = vec![
+            token(0, 8, 4, TokenType::Type),
+            token(1, 4, 4, TokenType::Field),
+            token(3, 2, 5, TokenType::Variable),
+        ];
+        assert_eq!(
+            encode_tokens(tokens),
+            vec![0, 8, 4, 1, 0, 1, 4, 4, 2, 0, 2, 2, 5, 4, 0]
+        );
+    }
+
+    #[test]
+    fn test_tokens_of_a_multi_line_document() {
+        // The source starts at the 11th character of the 4th line of the document
+        let source = GraphQLSource::new("fragment F on User {\n  name\n}", 3, 10);
+        assert_eq!(
+            source_tokens(&source),
+            vec![
+                3, 10, 8, 0, 0, // fragment
+                0, 11, 2, 0, 0, // on
+                0, 3, 4, 1, 0, // User
+                1, 2, 4, 2, 0, // name
+            ]
+        );
+    }
+}
@@ -8,57 +8,200 @@
 use std::error::Error;
 
 use crate::lsp::{
-    Completion, CompletionOptions, Connection, DidChangeTextDocument, DidCloseTextDocument,
-    DidOpenTextDocument, InitializeParams, LSPBridgeMessage, Message, Notification, Request,
-    ServerCapabilities, ServerNotification, ServerRequest, ServerRequestId,
-    TextDocumentSyncCapability, TextDocumentSyncKind, WorkDoneProgressOptions,
+    Cancel, CancelParams, CodeActionProviderCapability, CodeActionRequest, Completion,
+    CompletionOptions, Connection, DidChangeTextDocument, DidChangeWatchedFiles,
+    DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument, DocumentHighlightRequest,
+    DocumentSymbolRequest, ErrorCode, ExecuteCommand, ExecuteCommandOptions, Exit,
+    FoldingRangeProviderCapability, FoldingRangeRequest, HoverRequest, Initialize,
+    InitializeParams, Initialized, InlayHintRequest, LSPBridgeMessage, Message, Notification,
+    NumberOrString, References, Rename, RenameProviderCapability, Request, ResolveCompletionItem,
+    ResponseError, SaveOptions, SelectionRangeProviderCapability, SelectionRangeRequest,
+    SemanticTokensRequest, ServerCapabilities, ServerNotification, ServerRequest, ServerRequestId,
+    ServerResponse, Shutdown, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, WorkDoneProgressOptions, WorkspaceSymbol,
 };
 
-use relay_compiler::FileSource;
+use relay_compiler::compiler_state::CompilerState;
+use relay_compiler::errors::Error as CompilerError;
+use relay_compiler::errors::Result as CompilerResult;
+use relay_compiler::{FileSource, FileSourceSubscription};
 
 use relay_compiler::config::Config;
 
-use crate::lsp::show_info_message;
+use crate::lsp::{show_error_message, show_info_message};
 
-use common::ConsoleLogger;
-use common::PerfLogger;
+use common::{PerfLogEvent, PerfLogger};
 use log::info;
+use serde_json::json;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::select;
 use tokio::sync::{mpsc, Notify};
 
-use crate::lsp_compiler::LSPCompiler;
+use crate::completion::COMPLETION_TRIGGER_CHARACTERS;
+use crate::error_reporting::{report_config_error, report_empty_query_result};
+use crate::logging::{forward_perf_logs, LSPPerfLogger};
+use crate::lsp_compiler::{LSPCompiler, DEFAULT_CHANGES_DEBOUNCE_INTERVAL};
 
-use crate::text_documents::initialize_compiler_if_contains_graphql;
+use crate::saved_state::{persist_compiler_state, saved_state_path};
+
+use crate::print_operation::PRINT_OPERATION_COMMAND;
+
+use crate::restart::RESTART_COMMAND;
+use crate::stats::STATS_COMMAND;
+use crate::version::{ServerInfo, VERSION_COMMAND};
+
+use crate::semantic_tokens::semantic_tokens_legend;
+
+use crate::state::{NegotiatedCapabilities, ServerState};
+
+use crate::text_documents::{graphql_file_extensions, initialize_compiler_if_contains_graphql};
 
 /// Initializes an LSP connection, handling the `initize` message and `initialized` notification
-/// handshake.
+/// handshake. Returns the params of the client and the capabilities negotiated from them.
 pub fn initialize(
     connection: &Connection,
-) -> Result<InitializeParams, Box<dyn Error + Sync + Send>> {
+) -> Result<(InitializeParams, NegotiatedCapabilities), Box<dyn Error + Sync + Send>> {
+    let mut capabilities = NegotiatedCapabilities::default();
+    let params = initialize_handshake(connection, |params| {
+        capabilities = NegotiatedCapabilities::from_initialize_params(params);
+        json!({
+            "capabilities": server_capabilities(&capabilities),
+            "serverInfo": ServerInfo::current(),
+        })
+    })?;
+    let params: InitializeParams = serde_json::from_value(params).unwrap();
+    Ok((params, capabilities))
+}
+
+/// The capabilities of the server, the features the client doesn't support, see
+/// `NegotiatedCapabilities`, aren't advertised.
+fn server_capabilities(capabilities: &NegotiatedCapabilities) -> serde_json::Value {
     let mut server_capabilities = ServerCapabilities::default();
     // Enable text document syncing so we can know when files are opened/changed/saved/closed
-    server_capabilities.text_document_sync =
-        Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::Full));
+    server_capabilities.text_document_sync = Some(TextDocumentSyncCapability::Options(
+        TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::Full),
+            will_save: None,
+            will_save_wait_until: None,
+            save: Some(SaveOptions {
+                include_text: Some(false),
+            }),
+        },
+    ));
+
+    server_capabilities.hover_provider = Some(true);
+
+    server_capabilities.references_provider = Some(true);
+
+    server_capabilities.document_symbol_provider = Some(true);
+
+    server_capabilities.document_highlight_provider = Some(true);
+
+    server_capabilities.folding_range_provider = Some(FoldingRangeProviderCapability::Simple(true));
+
+    server_capabilities.selection_range_provider =
+        Some(SelectionRangeProviderCapability::Simple(true));
+
+    server_capabilities.workspace_symbol_provider = Some(true);
+
+    server_capabilities.code_action_provider = Some(CodeActionProviderCapability::Simple(true));
+
+    server_capabilities.rename_provider = Some(RenameProviderCapability::Simple(true));
 
     server_capabilities.completion_provider = Some(CompletionOptions {
         resolve_provider: Some(true),
-        trigger_characters: None,
+        trigger_characters: Some(
+            COMPLETION_TRIGGER_CHARACTERS
+                .iter()
+                .map(|chr| chr.to_string())
+                .collect(),
+        ),
         work_done_progress_options: WorkDoneProgressOptions {
             work_done_progress: None,
         },
     });
 
-    let server_capabilities = serde_json::to_value(&server_capabilities).unwrap();
-    let params = connection.initialize(server_capabilities)?;
-    let params: InitializeParams = serde_json::from_value(params).unwrap();
-    Ok(params)
+    server_capabilities.execute_command_provider = Some(ExecuteCommandOptions {
+        commands: vec![
+            PRINT_OPERATION_COMMAND.to_string(),
+            RESTART_COMMAND.to_string(),
+            STATS_COMMAND.to_string(),
+            VERSION_COMMAND.to_string(),
+        ],
+        work_done_progress_options: WorkDoneProgressOptions {
+            work_done_progress: None,
+        },
+    });
+
+    let mut server_capabilities = serde_json::to_value(&server_capabilities).unwrap();
+    // The semantic tokens capability isn't part of the `ServerCapabilities` of lsp_types
+    if capabilities.semantic_tokens {
+        server_capabilities["semanticTokensProvider"] = json!({
+            "legend": semantic_tokens_legend(),
+            "full": true,
+        });
+    }
+    // Neither is the inlay hints capability
+    if capabilities.inlay_hints {
+        server_capabilities["inlayHintProvider"] = json!(true);
+    }
+    server_capabilities
+}
+
+/// Responds to the `initialize` request with the result `initialize_result` builds from
+/// its params and waits for the `initialized` notification, like `Connection::initialize`
+/// which only responds with fixed capabilities. Requests received before are rejected as
+/// the server isn't initialized yet. Returns the params of the `initialize` request.
+fn initialize_handshake(
+    connection: &Connection,
+    initialize_result: impl FnOnce(&serde_json::Value) -> serde_json::Value,
+) -> Result<serde_json::Value, Box<dyn Error + Sync + Send>> {
+    let (request_id, params) = loop {
+        match connection.receiver.recv()? {
+            Message::Request(req) if req.method == Initialize::METHOD => {
+                break (req.id, req.params)
+            }
+            Message::Request(req) => {
+                let response = ServerResponse {
+                    id: req.id,
+                    error: Some(ResponseError {
+                        code: ErrorCode::ServerNotInitialized as i32,
+                        message: format!("Expected an initialize request, got {:?}", req.method),
+                        data: None,
+                    }),
+                    result: None,
+                };
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) if notification.method == Exit::METHOD => {
+                return Err("Exited before the server was initialized".into());
+            }
+            message => info!("Ignoring {:?} before the server was initialized", message),
+        }
+    };
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(initialize_result(&params)),
+    };
+    connection.sender.send(Message::Response(response))?;
+    match connection.receiver.recv()? {
+        Message::Notification(notification) if notification.method == Initialized::METHOD => {
+            Ok(params)
+        }
+        message => Err(format!("Expected an initialized notification, got {:?}", message).into()),
+    }
 }
 
-/// Run the main server loop
-pub async fn run(
+/// Run the main server loop, logging the perf events of the compiler with `perf_logger`,
+/// e.g. the `ConsoleLogger`
+pub async fn run<P: PerfLogger>(
     connection: Connection,
-    _params: InitializeParams,
+    params: InitializeParams,
+    capabilities: NegotiatedCapabilities,
+    perf_logger: P,
 ) -> Result<(), Box<dyn Error + Sync + Send>> {
     show_info_message("Relay Language Server Started!", &connection)?;
     info!("Running language server");
@@ -71,9 +214,17 @@ pub async fn run(
     // Thread for the LSP message loop
     let compiler_notifier = compiler_notify.clone();
 
+    // A `Notify` instance used to signal that the client told the server to exit,
+    // which can happen before the compiler is initialized.
+    let exit_notify = Arc::new(Notify::new());
+    let exit_notifier = exit_notify.clone();
+
     // A channel to communicate between the LSP message loop and the compiler loop
     let (mut lsp_tx, lsp_rx) = mpsc::channel::<LSPBridgeMessage>(100);
 
+    let graphql_file_extensions = graphql_file_extensions(&params);
+    let message_loop_graphql_file_extensions = graphql_file_extensions.clone();
+
     tokio::spawn(async move {
         // Cache for the extracted GraphQL sources
         for msg in receiver {
@@ -86,6 +237,95 @@ pub async fn run(
                             .send(LSPBridgeMessage::CompletionRequest { request_id, params })
                             .await
                             .ok();
+                    } else if req.method == ResolveCompletionItem::METHOD {
+                        let (request_id, params) =
+                            extract_request_params::<ResolveCompletionItem>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::ResolveCompletionItem { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == HoverRequest::METHOD {
+                        let (request_id, params) = extract_request_params::<HoverRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::HoverRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == References::METHOD {
+                        let (request_id, params) = extract_request_params::<References>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::ReferencesRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == DocumentSymbolRequest::METHOD {
+                        let (request_id, params) =
+                            extract_request_params::<DocumentSymbolRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::DocumentSymbolRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == DocumentHighlightRequest::METHOD {
+                        let (request_id, params) =
+                            extract_request_params::<DocumentHighlightRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::DocumentHighlightRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == FoldingRangeRequest::METHOD {
+                        let (request_id, params) =
+                            extract_request_params::<FoldingRangeRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::FoldingRangeRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == SelectionRangeRequest::METHOD {
+                        let (request_id, params) =
+                            extract_request_params::<SelectionRangeRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::SelectionRangeRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == WorkspaceSymbol::METHOD {
+                        let (request_id, params) = extract_request_params::<WorkspaceSymbol>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::WorkspaceSymbolRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == CodeActionRequest::METHOD {
+                        let (request_id, params) = extract_request_params::<CodeActionRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::CodeActionRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == Rename::METHOD {
+                        let (request_id, params) = extract_request_params::<Rename>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::RenameRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == Shutdown::METHOD {
+                        lsp_tx
+                            .send(LSPBridgeMessage::ShutdownRequest { request_id: req.id })
+                            .await
+                            .ok();
+                    } else if req.method == SemanticTokensRequest::METHOD {
+                        let (request_id, params) =
+                            extract_request_params::<SemanticTokensRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::SemanticTokensRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == InlayHintRequest::METHOD {
+                        let (request_id, params) = extract_request_params::<InlayHintRequest>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::InlayHintRequest { request_id, params })
+                            .await
+                            .ok();
+                    } else if req.method == ExecuteCommand::METHOD {
+                        let (request_id, params) = extract_request_params::<ExecuteCommand>(req);
+                        lsp_tx
+                            .send(LSPBridgeMessage::ExecuteCommandRequest { request_id, params })
+                            .await
+                            .ok();
                     }
                 }
                 Message::Notification(notif) => {
@@ -94,6 +334,7 @@ pub async fn run(
                             let params = extract_notif_params::<DidOpenTextDocument>(notif);
                             initialize_compiler_if_contains_graphql(
                                 &params,
+                                &message_loop_graphql_file_extensions,
                                 compiler_notifier.clone(),
                             );
                             lsp_tx
@@ -108,6 +349,20 @@ pub async fn run(
                                 .await
                                 .ok();
                         }
+                        method if method == DidSaveTextDocument::METHOD => {
+                            let params = extract_notif_params::<DidSaveTextDocument>(notif);
+                            lsp_tx
+                                .send(LSPBridgeMessage::DidSaveTextDocument(params))
+                                .await
+                                .ok();
+                        }
+                        method if method == DidChangeWatchedFiles::METHOD => {
+                            let params = extract_notif_params::<DidChangeWatchedFiles>(notif);
+                            lsp_tx
+                                .send(LSPBridgeMessage::DidChangeWatchedFiles(params))
+                                .await
+                                .ok();
+                        }
                         method if method == DidCloseTextDocument::METHOD => {
                             let params = extract_notif_params::<DidCloseTextDocument>(notif);
                             lsp_tx
@@ -115,6 +370,21 @@ pub async fn run(
                                 .await
                                 .ok();
                         }
+                        method if method == Cancel::METHOD => {
+                            let CancelParams { id } = extract_notif_params::<Cancel>(notif);
+                            let id = match id {
+                                NumberOrString::Number(id) => ServerRequestId::from(id),
+                                NumberOrString::String(id) => ServerRequestId::from(id),
+                            };
+                            lsp_tx
+                                .send(LSPBridgeMessage::CancelRequest { id })
+                                .await
+                                .ok();
+                        }
+                        method if method == Exit::METHOD => {
+                            exit_notifier.notify();
+                            lsp_tx.send(LSPBridgeMessage::Exit).await.ok();
+                        }
                         _ => {
                             // Notifications we don't care about
                         }
@@ -129,28 +399,122 @@ pub async fn run(
 
     info!("Waiting for compiler to initialize...");
 
-    compiler_notify.notified().await;
-    let config = load_config();
-    let setup_event = ConsoleLogger.create_event("lsp_compiler_setup");
-    let file_source = FileSource::connect(&config, &setup_event).await.unwrap();
-    let (compiler_state, subscription) = file_source
-        .subscribe(&setup_event, &ConsoleLogger)
-        .await
-        .unwrap();
-    let schemas = LSPCompiler::build_schemas(&config, &compiler_state, &setup_event);
+    select! {
+        _ = compiler_notify.notified() => {}
+        _ = exit_notify.notified() => {
+            info!("Exiting before the compiler was initialized");
+            return Ok(());
+        }
+    }
+    let (root_dir, config_path) = config_paths();
+    let saved_state_path = saved_state_path(&root_dir, &config_path);
+    let mut config = match load_config(root_dir.clone(), config_path.clone()) {
+        Ok(config) => config,
+        Err(err) => {
+            // The compiler can't be started until the config is fixed and the server is
+            // restarted
+            report_config_error(
+                &err,
+                &connection,
+                &mut ServerState::new(root_dir, capabilities),
+            );
+            wait_for_exit(&connection, lsp_rx).await;
+            return Ok(());
+        }
+    };
+    config.load_saved_state_file = saved_state_path.clone().filter(|path| path.exists());
+    let perf_logger =
+        LSPPerfLogger::new(perf_logger, &connection.sender, forward_perf_logs(&params));
+    let setup_event = perf_logger.create_event("lsp_compiler_setup");
+    let fallback_config;
+    let (config, compiler_state, subscription) =
+        match subscribe(&config, &setup_event, &perf_logger).await {
+            Ok((compiler_state, subscription)) => (&config, compiler_state, subscription),
+            Err(err) if config.load_saved_state_file.is_some() => {
+                // The saved state may be corrupted or from another version, start from scratch
+                info!(
+                    "Failed to load the saved state, building from scratch: {:?}",
+                    err
+                );
+                fallback_config = match load_config(root_dir.clone(), config_path) {
+                    Ok(config) => config,
+                    Err(err) => {
+                        report_config_error(
+                            &err,
+                            &connection,
+                            &mut ServerState::new(root_dir, capabilities),
+                        );
+                        wait_for_exit(&connection, lsp_rx).await;
+                        return Ok(());
+                    }
+                };
+                match subscribe(&fallback_config, &setup_event, &perf_logger).await {
+                    Ok((compiler_state, subscription)) => {
+                        (&fallback_config, compiler_state, subscription)
+                    }
+                    Err(err) => {
+                        report_subscribe_error(&err, &connection);
+                        wait_for_exit(&connection, lsp_rx).await;
+                        return Ok(());
+                    }
+                }
+            }
+            Err(CompilerError::EmptyQueryResult) => {
+                // There is nothing to check, the server stays idle until it's restarted
+                report_empty_query_result(&config, &connection);
+                wait_for_exit(&connection, lsp_rx).await;
+                return Ok(());
+            }
+            Err(err) => {
+                report_subscribe_error(&err, &connection);
+                wait_for_exit(&connection, lsp_rx).await;
+                return Ok(());
+            }
+        };
+    if let Some(saved_state_path) = &saved_state_path {
+        persist_compiler_state(&compiler_state, saved_state_path);
+    }
+    let schemas = LSPCompiler::<P>::build_schemas(&config, &compiler_state, &setup_event);
     let mut lsp_compiler = LSPCompiler::new(
-        &schemas,
-        &config,
+        schemas,
+        config,
         subscription,
         compiler_state,
         lsp_rx,
         connection,
+        capabilities,
+        DEFAULT_CHANGES_DEBOUNCE_INTERVAL,
+        graphql_file_extensions,
+        saved_state_path,
+        perf_logger,
     );
     lsp_compiler.watch().await.unwrap();
     Ok(())
 }
 
-fn load_config() -> Config {
+/// Connects to the file source and queries the initial compiler state, which is
+/// loaded from the config's `load_saved_state_file` if set
+async fn subscribe<'config>(
+    config: &'config Config,
+    setup_event: &impl PerfLogEvent,
+    perf_logger: &impl PerfLogger,
+) -> CompilerResult<(CompilerState, FileSourceSubscription<'config>)> {
+    let file_source = FileSource::connect(config, setup_event).await?;
+    file_source.subscribe(setup_event, perf_logger).await
+}
+
+/// Reports that the compiler state couldn't be queried from the file source, after which
+/// the server can't check anything until it's restarted
+fn report_subscribe_error(error: &CompilerError, connection: &Connection) {
+    show_error_message(
+        format!("Relay: Failed to subscribe to the file source: {}", error),
+        connection,
+    )
+    .ok();
+}
+
+/// Returns the root directory and the path of the config file
+fn config_paths() -> (PathBuf, PathBuf) {
     // TODO(brandondail) don't hardcode the test project config here
     let home = std::env::var("HOME").unwrap();
     let config_path = PathBuf::from(format!(
@@ -158,10 +522,44 @@ fn load_config() -> Config {
         home
     ));
     let root_dir = PathBuf::from(format!("{}/fbsource", home));
-    let mut config = Config::load(root_dir, config_path).unwrap();
+    (root_dir, config_path)
+}
+
+fn load_config(root_dir: PathBuf, config_path: PathBuf) -> CompilerResult<Config> {
+    let mut config = Config::load(root_dir, config_path)?;
     // Don't write artifacts by default
     config.write_artifacts = false;
-    config
+    Ok(config)
+}
+
+/// Keeps the server alive while the compiler can't be started, e.g. because the config
+/// is invalid, until the client tells the server to exit. The shutdown request is
+/// responded to as usual, the other requests fail since there is nothing to answer
+/// them with.
+async fn wait_for_exit(connection: &Connection, mut lsp_rx: mpsc::Receiver<LSPBridgeMessage>) {
+    while let Some(message) = lsp_rx.recv().await {
+        let response = match message {
+            LSPBridgeMessage::ShutdownRequest { request_id } => ServerResponse {
+                id: request_id,
+                error: None,
+                result: Some(serde_json::Value::Null),
+            },
+            LSPBridgeMessage::Exit => return,
+            message => match message.request_id() {
+                Some(request_id) => ServerResponse {
+                    id: request_id.clone(),
+                    error: Some(ResponseError {
+                        code: ErrorCode::InternalError as i32,
+                        message: "Relay isn't running, see the previous error message".to_string(),
+                        data: None,
+                    }),
+                    result: None,
+                },
+                None => continue,
+            },
+        };
+        connection.sender.send(Message::Response(response)).ok();
+    }
 }
 
 fn extract_notif_params<N>(notif: ServerNotification) -> N::Params
@@ -5,33 +5,174 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 use crate::lsp::publish_diagnostic;
-use crate::lsp::{Connection, PublishDiagnosticsParams, Url};
+use crate::lsp::{
+    Connection, Diagnostic, NumberOrString, PublishDiagnosticsParams, ServerRequestId, Url,
+};
+use crate::stats::CheckStats;
+use crate::version::ServerInfo;
+use relay_compiler::compiler_state::ProjectName;
+use serde_json::Value;
+
+/// The features the client supports, negotiated from the client capabilities of the
+/// `initialize` request. Features the client doesn't support aren't used, e.g. no
+/// semantic tokens are sent to a client that can't render them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NegotiatedCapabilities {
+    /// Whether the client can show `$/progress` notifications for server initiated progress
+    pub work_done_progress: bool,
+    /// Whether the client can render semantic tokens
+    pub semantic_tokens: bool,
+    /// Whether the client can show inlay hints
+    pub inlay_hints: bool,
+    /// Whether the client accepts code actions, rather than only commands, in response
+    /// to code action requests
+    pub code_action_literals: bool,
+    /// Whether the client can expand snippets in completion items, e.g. the tab stop
+    /// of `friends(first: $1)`
+    pub completion_snippets: bool,
+}
+
+impl NegotiatedCapabilities {
+    /// Negotiates the capabilities from the params of the `initialize` request. The
+    /// semantic tokens and inlay hints capabilities aren't part of the
+    /// `ClientCapabilities` of lsp_types, so they are read from the raw params. Clients
+    /// may also enable inlay hints with the experimental `inlayHints` capability.
+    pub fn from_initialize_params(params: &Value) -> Self {
+        let capabilities = &params["capabilities"];
+        let is_set = |capability: &Value| !capability.is_null();
+        NegotiatedCapabilities {
+            work_done_progress: capabilities["window"]["workDoneProgress"]
+                .as_bool()
+                .unwrap_or(false),
+            semantic_tokens: is_set(&capabilities["textDocument"]["semanticTokens"]),
+            inlay_hints: is_set(&capabilities["textDocument"]["inlayHint"])
+                || capabilities["experimental"]["inlayHints"]
+                    .as_bool()
+                    .unwrap_or(false),
+            code_action_literals: is_set(
+                &capabilities["textDocument"]["codeAction"]["codeActionLiteralSupport"],
+            ),
+            completion_snippets: capabilities["textDocument"]["completion"]["completionItem"]
+                ["snippetSupport"]
+                .as_bool()
+                .unwrap_or(false),
+        }
+    }
+}
 
 pub struct ServerState {
-    urls_with_active_diagnostics: HashSet<Url>,
+    /// Published diagnostics of each document, by the project they were reported for.
+    /// Diagnostics that don't belong to a project, like config errors and unused
+    /// fragments, are reported for `None`.
+    published_diagnostics: HashMap<Url, HashMap<Option<ProjectName>, Vec<Diagnostic>>>,
+    /// Requests that were received but not responded to, and weren't cancelled
+    in_flight_requests: HashSet<ServerRequestId>,
     pub root_dir: PathBuf,
+    pub capabilities: NegotiatedCapabilities,
+    /// The timings of the last check of the projects, returned by `relay/stats`
+    pub last_check_stats: Option<CheckStats>,
+    /// The version of the server, returned by `relay/version`
+    pub server_info: ServerInfo,
 }
 
 impl ServerState {
-    pub fn new(root_dir: PathBuf) -> Self {
+    pub fn new(root_dir: PathBuf, capabilities: NegotiatedCapabilities) -> Self {
         ServerState {
-            urls_with_active_diagnostics: HashSet::default(),
+            published_diagnostics: HashMap::default(),
+            in_flight_requests: HashSet::default(),
             root_dir,
+            capabilities,
+            last_check_stats: None,
+            server_info: ServerInfo::current(),
         }
     }
 
-    pub fn register_url_with_diagnostics(&mut self, url: Url) {
-        self.urls_with_active_diagnostics.insert(url);
+    /// Tracks a request until it's responded to, so it can be cancelled by the client
+    pub fn start_request(&mut self, request_id: ServerRequestId) {
+        self.in_flight_requests.insert(request_id);
+    }
+
+    /// Cancels an in-flight request, no response is sent for it afterwards
+    pub fn cancel_request(&mut self, request_id: &ServerRequestId) {
+        self.in_flight_requests.remove(request_id);
+    }
+
+    /// Stops tracking a request that is about to be responded to. Returns `false` if
+    /// the request was cancelled, in which case no response should be sent.
+    pub fn finish_request(&mut self, request_id: &ServerRequestId) -> bool {
+        self.in_flight_requests.remove(request_id)
+    }
+
+    /// Replaces all diagnostics reported for `project_name`. Only the documents that had
+    /// or now have diagnostics for that project are published again; publishing a
+    /// document includes the diagnostics other projects reported for it.
+    pub fn publish_diagnostics(
+        &mut self,
+        project_name: Option<ProjectName>,
+        diagnostics: HashMap<Url, Vec<Diagnostic>>,
+        connection: &Connection,
+    ) {
+        let cleared_urls = self
+            .published_diagnostics
+            .iter_mut()
+            .filter_map(|(url, project_diagnostics)| {
+                project_diagnostics.remove(&project_name)?;
+                Some(url.clone())
+            })
+            .collect();
+        self.update_diagnostics(project_name, diagnostics, cleared_urls, connection);
     }
 
+    /// Clears the diagnostics of all documents, reported for any project
     pub fn clear_diagnostics(&mut self, connection: &Connection) {
-        for url in self.urls_with_active_diagnostics.drain() {
+        let cleared_urls = self
+            .published_diagnostics
+            .drain()
+            .map(|(url, _)| url)
+            .collect();
+        self.update_diagnostics(None, HashMap::new(), cleared_urls, connection);
+    }
+
+    /// Replaces the diagnostics reported for `project_name` in the given documents only,
+    /// diagnostics of that project in other documents are kept.
+    pub fn publish_document_diagnostics(
+        &mut self,
+        project_name: Option<ProjectName>,
+        diagnostics: HashMap<Url, Vec<Diagnostic>>,
+        connection: &Connection,
+    ) {
+        self.update_diagnostics(project_name, diagnostics, HashSet::new(), connection);
+    }
+
+    fn update_diagnostics(
+        &mut self,
+        project_name: Option<ProjectName>,
+        diagnostics: HashMap<Url, Vec<Diagnostic>>,
+        mut changed_urls: HashSet<Url>,
+        connection: &Connection,
+    ) {
+        for (url, diagnostics) in diagnostics {
+            self.published_diagnostics
+                .entry(url.clone())
+                .or_default()
+                .insert(project_name, diagnostics);
+            changed_urls.insert(url);
+        }
+
+        for url in changed_urls {
+            let diagnostics = match self.published_diagnostics.get(&url) {
+                Some(project_diagnostics) => dedupe_diagnostics(project_diagnostics.values()),
+                None => vec![],
+            };
+            if diagnostics.is_empty() {
+                self.published_diagnostics.remove(&url);
+            }
             let params = PublishDiagnosticsParams {
-                diagnostics: vec![],
+                diagnostics,
                 uri: url,
                 version: None,
             };
@@ -39,3 +180,218 @@ impl ServerState {
         }
     }
 }
+
+/// Merges the diagnostics that multiple projects reported for the same document, e.g.
+/// when the document contains fragments shared by the projects. Diagnostics with the
+/// same range, severity, source and message are published once, with the codes, e.g.
+/// the projects reporting them, and the related information of all of them.
+fn dedupe_diagnostics<'a>(
+    project_diagnostics: impl Iterator<Item = &'a Vec<Diagnostic>>,
+) -> Vec<Diagnostic> {
+    let mut deduped_diagnostics: Vec<Diagnostic> = Vec::new();
+    for diagnostic in project_diagnostics.flatten() {
+        let existing_diagnostic = deduped_diagnostics.iter_mut().find(|existing_diagnostic| {
+            existing_diagnostic.range == diagnostic.range
+                && existing_diagnostic.severity == diagnostic.severity
+                && existing_diagnostic.source == diagnostic.source
+                && existing_diagnostic.message == diagnostic.message
+        });
+        let existing_diagnostic = match existing_diagnostic {
+            Some(existing_diagnostic) => existing_diagnostic,
+            None => {
+                deduped_diagnostics.push(diagnostic.clone());
+                continue;
+            }
+        };
+        existing_diagnostic.code = merge_codes(&existing_diagnostic.code, &diagnostic.code);
+        if let Some(related_information) = &diagnostic.related_information {
+            let existing_related_information = existing_diagnostic
+                .related_information
+                .get_or_insert_with(Vec::new);
+            for information in related_information {
+                if !existing_related_information.contains(information) {
+                    existing_related_information.push(information.clone());
+                }
+            }
+        }
+    }
+    deduped_diagnostics
+}
+
+/// Joins the distinct codes of deduped diagnostics sorted, e.g. `first, second`, as
+/// the projects of a document are checked in any order
+fn merge_codes(
+    existing_code: &Option<NumberOrString>,
+    code: &Option<NumberOrString>,
+) -> Option<NumberOrString> {
+    let (existing_code, code) = match (existing_code, code) {
+        (Some(existing_code), Some(code)) => (code_to_string(existing_code), code_to_string(code)),
+        (Some(_), None) => return existing_code.clone(),
+        (None, _) => return code.clone(),
+    };
+    let mut codes: Vec<&str> = existing_code.split(", ").collect();
+    codes.push(&code);
+    codes.sort();
+    codes.dedup();
+    Some(NumberOrString::String(codes.join(", ")))
+}
+
+fn code_to_string(code: &NumberOrString) -> String {
+    match code {
+        NumberOrString::String(code) => code.clone(),
+        NumberOrString::Number(code) => code.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::{
+        DiagnosticRelatedInformation, DiagnosticSeverity, LSPLocation, Message, Position, Range,
+    };
+    use interner::Intern;
+    use serde_json::json;
+
+    fn diagnostic(related_file: &str) -> Diagnostic {
+        Diagnostic {
+            code: None,
+            message: "Unknown field 'nam'".to_string(),
+            range: Range::new(Position::new(2, 4), Position::new(2, 7)),
+            related_information: Some(vec![DiagnosticRelatedInformation {
+                location: LSPLocation::new(
+                    Url::parse(related_file).unwrap(),
+                    Range::new(Position::new(0, 0), Position::new(0, 1)),
+                ),
+                message: "Unknown field 'nam'".to_string(),
+            }]),
+            severity: Some(DiagnosticSeverity::Error),
+            source: None,
+            tags: None,
+        }
+    }
+
+    fn last_published_diagnostics(client: &Connection) -> PublishDiagnosticsParams {
+        let mut last_params = None;
+        while let Ok(message) = client.receiver.try_recv() {
+            if let Message::Notification(notification) = message {
+                last_params = Some(serde_json::from_value(notification.params).unwrap());
+            }
+        }
+        last_params.expect("Expected diagnostics to be published")
+    }
+
+    #[test]
+    fn test_dedupes_diagnostics_of_shared_document() {
+        let (connection, client) = Connection::memory();
+        let mut server_state =
+            ServerState::new(PathBuf::from("/root"), NegotiatedCapabilities::default());
+        let url = Url::parse("file:///root/shared/UserFragment.js").unwrap();
+
+        for (project_name, related_file) in &[
+            ("first", "file:///root/first/Query.js"),
+            ("second", "file:///root/second/Query.js"),
+        ] {
+            let mut diagnostics = HashMap::new();
+            diagnostics.insert(url.clone(), vec![diagnostic(related_file)]);
+            server_state.publish_diagnostics(Some(project_name.intern()), diagnostics, &connection);
+        }
+
+        let params = last_published_diagnostics(&client);
+        assert_eq!(params.uri, url);
+        assert_eq!(params.diagnostics.len(), 1);
+        let mut related_files: Vec<_> = params.diagnostics[0]
+            .related_information
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|information| information.location.uri.to_string())
+            .collect();
+        related_files.sort();
+        assert_eq!(
+            related_files,
+            vec![
+                "file:///root/first/Query.js",
+                "file:///root/second/Query.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedupes_diagnostics_of_shared_document_with_project_codes() {
+        let (connection, client) = Connection::memory();
+        let mut server_state =
+            ServerState::new(PathBuf::from("/root"), NegotiatedCapabilities::default());
+        let url = Url::parse("file:///root/shared/UserFragment.js").unwrap();
+
+        for (project_name, related_file) in &[
+            ("first", "file:///root/first/Query.js"),
+            ("second", "file:///root/second/Query.js"),
+        ] {
+            let mut diagnostics = HashMap::new();
+            let mut other_source_diagnostic = diagnostic(related_file);
+            other_source_diagnostic.source = Some("eslint".to_string());
+            diagnostics.insert(
+                url.clone(),
+                vec![
+                    Diagnostic {
+                        code: Some(NumberOrString::String(project_name.to_string())),
+                        ..diagnostic(related_file)
+                    },
+                    other_source_diagnostic,
+                ],
+            );
+            server_state.publish_diagnostics(Some(project_name.intern()), diagnostics, &connection);
+        }
+
+        let params = last_published_diagnostics(&client);
+        let mut codes: Vec<_> = params
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.source.clone(), diagnostic.code.clone()))
+            .collect();
+        codes.sort_by_key(|(source, _)| source.clone());
+        // Only the diagnostics of the same source are merged, with the codes of both
+        assert_eq!(
+            codes,
+            vec![
+                (
+                    None,
+                    Some(NumberOrString::String("first, second".to_string()))
+                ),
+                (Some("eslint".to_string()), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_negotiates_capabilities() {
+        assert_eq!(
+            NegotiatedCapabilities::from_initialize_params(&json!({ "capabilities": {} })),
+            NegotiatedCapabilities::default()
+        );
+        assert_eq!(
+            NegotiatedCapabilities::from_initialize_params(&json!({
+                "capabilities": {
+                    "window": { "workDoneProgress": true },
+                    "textDocument": {
+                        "semanticTokens": { "requests": { "full": true } },
+                        "codeAction": {
+                            "codeActionLiteralSupport": {
+                                "codeActionKind": { "valueSet": ["quickfix"] }
+                            }
+                        },
+                        "completion": { "completionItem": { "snippetSupport": true } }
+                    },
+                    "experimental": { "inlayHints": true }
+                }
+            })),
+            NegotiatedCapabilities {
+                work_done_progress: true,
+                semantic_tokens: true,
+                inlay_hints: true,
+                code_action_literals: true,
+                completion_snippets: true,
+            }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for the `relay/stats` command, returning the timings of the last check
+//! of the projects.
+use crate::lsp::{Connection, ExecuteCommandParams, Message, ServerRequestId, ServerResponse};
+use relay_compiler::compiler_state::ProjectName;
+use serde::Serialize;
+use std::time::Duration;
+
+/// The name of the command returning the timings of the last check
+pub const STATS_COMMAND: &str = "relay/stats";
+
+pub fn is_stats_command(params: &ExecuteCommandParams) -> bool {
+    params.command == STATS_COMMAND
+}
+
+/// The timings of a check of the projects, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckStats {
+    /// Time to parse the GraphQL sources of all projects
+    pub parse_sources_ms: f64,
+    /// Time to look up the schemas of the checked projects
+    pub schema_lookup_ms: f64,
+    /// The checked projects, in the order they were checked
+    pub projects: Vec<ProjectCheckStats>,
+    pub total_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCheckStats {
+    pub project_name: String,
+    pub check_ms: f64,
+    pub has_errors: bool,
+}
+
+impl CheckStats {
+    pub fn add_project(&mut self, project_name: ProjectName, duration: Duration, has_errors: bool) {
+        self.projects.push(ProjectCheckStats {
+            project_name: project_name.to_string(),
+            check_ms: to_milliseconds(duration),
+            has_errors,
+        });
+    }
+}
+
+pub fn to_milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Responds with the timings of the last check, or `null` if the projects weren't
+/// checked yet
+pub fn send_stats_response(
+    stats: Option<&CheckStats>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(stats).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interner::Intern;
+    use serde_json::json;
+
+    #[test]
+    fn test_serialize_check_stats() {
+        let mut stats = CheckStats {
+            parse_sources_ms: 12.5,
+            schema_lookup_ms: 0.0,
+            projects: vec![],
+            total_ms: 40.0,
+        };
+        stats.add_project("public".intern(), Duration::from_millis(25), true);
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap(),
+            json!({
+                "parseSourcesMs": 12.5,
+                "schemaLookupMs": 0.0,
+                "projects": [
+                    {
+                        "projectName": "public",
+                        "checkMs": 25.0,
+                        "hasErrors": true,
+                    }
+                ],
+                "totalMs": 40.0,
+            })
+        );
+    }
+}
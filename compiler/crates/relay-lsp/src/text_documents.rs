@@ -9,7 +9,7 @@
 
 use crate::lsp::{
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-    TextDocumentItem, Url,
+    InitializeParams, TextDocumentItem, Url,
 };
 
 use graphql_syntax::GraphQLSource;
@@ -19,20 +19,42 @@ use tokio::sync::Notify;
 
 pub type GraphQLTextDocumentCache = std::collections::HashMap<Url, Vec<GraphQLSource>>;
 
+/// Extensions of the documents that consist entirely of GraphQL, rather than containing
+/// `graphql` tagged templates.
+pub const DEFAULT_GRAPHQL_FILE_EXTENSIONS: [&str; 2] = ["graphql", "gql"];
+
+/// Returns the extensions of GraphQL documents, which clients can configure with the
+/// `graphqlFileExtensions` initialization option.
+pub fn graphql_file_extensions(params: &InitializeParams) -> Vec<String> {
+    params
+        .initialization_options
+        .as_ref()
+        .and_then(|options| options.get("graphqlFileExtensions"))
+        .and_then(|extensions| serde_json::from_value(extensions.clone()).ok())
+        .unwrap_or_else(|| {
+            DEFAULT_GRAPHQL_FILE_EXTENSIONS
+                .iter()
+                .map(|extension| extension.to_string())
+                .collect()
+        })
+}
+
 pub fn initialize_compiler_if_contains_graphql(
     params: &DidOpenTextDocumentParams,
+    graphql_file_extensions: &[String],
     compiler_init_notify: Arc<Notify>,
 ) {
     let DidOpenTextDocumentParams { text_document } = params;
-    let TextDocumentItem { text, .. } = text_document;
+    let TextDocumentItem { text, uri, .. } = text_document;
 
-    if extract_graphql_sources(&text).is_some() {
+    if extract_graphql_sources(uri, &text, graphql_file_extensions).is_some() {
         compiler_init_notify.notify();
     }
 }
 
 pub fn on_did_open_text_document(
     params: DidOpenTextDocumentParams,
+    graphql_file_extensions: &[String],
     graphql_source_cache: &mut GraphQLTextDocumentCache,
 ) {
     info!("Did open text document!");
@@ -40,7 +62,7 @@ pub fn on_did_open_text_document(
     let TextDocumentItem { text, uri, .. } = text_document;
 
     // First we check to see if this document has any GraphQL documents.
-    let graphql_sources = match extract_graphql_sources(&text) {
+    let graphql_sources = match extract_graphql_sources(&uri, &text, graphql_file_extensions) {
         Some(sources) => sources,
         // Exit early if there are no sources
         None => return,
@@ -60,6 +82,7 @@ pub fn on_did_close_text_document(
 
 pub fn on_did_change_text_document(
     params: DidChangeTextDocumentParams,
+    graphql_file_extensions: &[String],
     graphql_source_cache: &mut GraphQLTextDocumentCache,
 ) {
     info!("Did change text document!");
@@ -75,19 +98,33 @@ pub fn on_did_change_text_document(
         .expect("content_changes should always be non-empty");
 
     // First we check to see if this document has any GraphQL documents.
-    let graphql_sources = match extract_graphql_sources(&content_change.text) {
-        Some(sources) => sources,
-        // Exit early if there are no sources
-        None => return,
-    };
+    let graphql_sources =
+        match extract_graphql_sources(&uri, &content_change.text, graphql_file_extensions) {
+            Some(sources) => sources,
+            // Exit early if there are no sources
+            None => return,
+        };
 
     // Update the GraphQL sources for this document
     graphql_source_cache.insert(uri, graphql_sources);
 }
 
 /// Returns a set of *non-empty* GraphQL sources if they exist in a file. Returns `None`
-/// if extracting fails or there are no GraphQL chunks in the file.
-fn extract_graphql_sources(source: &str) -> Option<Vec<GraphQLSource>> {
+/// if extracting fails or there are no GraphQL chunks in the file. Documents with one of
+/// the `graphql_file_extensions` are a single GraphQL source starting at the beginning
+/// of the document.
+fn extract_graphql_sources(
+    uri: &Url,
+    source: &str,
+    graphql_file_extensions: &[String],
+) -> Option<Vec<GraphQLSource>> {
+    if is_graphql_document(uri, graphql_file_extensions) {
+        return if source.trim().is_empty() {
+            None
+        } else {
+            Some(vec![GraphQLSource::new(source, 0, 0)])
+        };
+    }
     match extract_graphql::parse_chunks(source) {
         Ok(chunks) => {
             if chunks.is_empty() {
@@ -99,3 +136,13 @@ fn extract_graphql_sources(source: &str) -> Option<Vec<GraphQLSource>> {
         Err(_) => None,
     }
 }
+
+fn is_graphql_document(uri: &Url, graphql_file_extensions: &[String]) -> bool {
+    let path = uri.path();
+    match path.rfind('.') {
+        Some(separator) if !path[separator..].contains('/') => graphql_file_extensions
+            .iter()
+            .any(|extension| *extension == path[separator + 1..]),
+        _ => false,
+    }
+}
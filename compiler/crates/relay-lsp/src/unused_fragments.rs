@@ -0,0 +1,202 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for reporting unused fragments and the code action removing them
+use crate::error_reporting::DIAGNOSTIC_SOURCE;
+use crate::lsp::{
+    CodeAction, CodeActionOrCommand, CodeActionParams, Connection, Diagnostic, DiagnosticSeverity,
+    DiagnosticTag, Message, NumberOrString, Position, Range, ServerRequestId, ServerResponse,
+    TextEdit, WorkspaceEdit,
+};
+use common::WithLocation;
+use graphql_ir::{FragmentSpread, Visitor};
+use graphql_syntax::{parse, ExecutableDefinition, GraphQLSource};
+use interner::StringKey;
+use relay_compiler::Programs;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The code of the diagnostics for unused fragments, used to find the diagnostics
+/// a "Remove unused fragment" code action can be offered for.
+const UNUSED_FRAGMENT_DIAGNOSTIC_CODE: &str = "relay-unused-fragment";
+
+/// Finds the names of the fragments that are defined but never spread. The spreads
+/// of all the given `Programs` are considered, since a fragment can be defined in
+/// one project (e.g. a base project) and spread in another one.
+pub fn find_unused_fragments<'a, 'schema: 'a>(
+    project_programs: impl Iterator<Item = &'a Programs<'schema>>,
+) -> Vec<WithLocation<StringKey>> {
+    let project_programs: Vec<&Programs<'_>> = project_programs.collect();
+    let mut collector = FragmentSpreadCollector {
+        spread_fragment_names: Default::default(),
+    };
+    for programs in &project_programs {
+        collector.visit_program(&programs.source);
+    }
+    // The fragments of a base project are part of the programs of each project
+    // extending it, so they are deduped by location
+    let mut unused_fragments = BTreeMap::new();
+    for programs in &project_programs {
+        for fragment in programs.source.fragments() {
+            if !collector
+                .spread_fragment_names
+                .contains(&fragment.name.item)
+            {
+                unused_fragments.insert(fragment.name.location, fragment.name.item);
+            }
+        }
+    }
+    unused_fragments
+        .into_iter()
+        .map(|(location, name)| WithLocation::new(location, name))
+        .collect()
+}
+
+struct FragmentSpreadCollector {
+    spread_fragment_names: HashSet<StringKey>,
+}
+
+impl Visitor for FragmentSpreadCollector {
+    const NAME: &'static str = "FragmentSpreadCollector";
+    const VISIT_ARGUMENTS: bool = false;
+    const VISIT_DIRECTIVES: bool = false;
+
+    fn visit_fragment_spread(&mut self, spread: &FragmentSpread) {
+        self.spread_fragment_names.insert(spread.fragment.item);
+    }
+}
+
+/// Creates the diagnostic for an unused fragment defined in `source`. The range of the
+/// diagnostic covers the whole fragment definition, which is what the code action removes.
+pub fn unused_fragment_diagnostic(
+    fragment_name: &WithLocation<StringKey>,
+    source: &GraphQLSource,
+) -> Option<Diagnostic> {
+    let document = parse(&source.text, fragment_name.location.file()).ok()?;
+    let fragment = document
+        .definitions
+        .into_iter()
+        .find_map(|definition| match definition {
+            ExecutableDefinition::Fragment(fragment)
+                if fragment.name.value == fragment_name.item =>
+            {
+                Some(fragment)
+            }
+            _ => None,
+        })?;
+    let range =
+        fragment
+            .location
+            .span()
+            .to_range(&source.text, source.line_index, source.column_index);
+    Some(Diagnostic {
+        code: Some(NumberOrString::String(
+            UNUSED_FRAGMENT_DIAGNOSTIC_CODE.to_string(),
+        )),
+        message: format!("The fragment `{}` is never spread", fragment_name.item),
+        range,
+        related_information: None,
+        severity: Some(DiagnosticSeverity::Warning),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        tags: Some(vec![DiagnosticTag::Unnecessary]),
+    })
+}
+
+/// Returns a "Remove unused fragment" code action for each unused fragment diagnostic
+/// in the requested range, deleting the text of the fragment definition.
+pub fn get_code_actions(params: CodeActionParams) -> Vec<CodeActionOrCommand> {
+    let uri = params.text_document.uri;
+    params
+        .context
+        .diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            diagnostic.code
+                == Some(NumberOrString::String(
+                    UNUSED_FRAGMENT_DIAGNOSTIC_CODE.to_string(),
+                ))
+                && ranges_intersect(&diagnostic.range, &params.range)
+        })
+        .map(|diagnostic| {
+            let mut changes = HashMap::new();
+            changes.insert(
+                uri.clone(),
+                vec![TextEdit {
+                    range: diagnostic.range,
+                    new_text: String::new(),
+                }],
+            );
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Remove unused fragment".to_string(),
+                kind: Some("quickfix".to_string()),
+                diagnostics: Some(vec![diagnostic]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                }),
+                command: None,
+                is_preferred: Some(true),
+            })
+        })
+        .collect()
+}
+
+fn ranges_intersect(a: &Range, b: &Range) -> bool {
+    position_key(&a.start) <= position_key(&b.end) && position_key(&b.start) <= position_key(&a.end)
+}
+
+fn position_key(position: &Position) -> (u64, u64) {
+    (position.line, position.character)
+}
+
+pub fn send_code_action_response(
+    code_actions: Vec<CodeActionOrCommand>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&code_actions).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::references::tests::programs_for_documents;
+    use schema::build_schema;
+
+    #[test]
+    fn test_reports_only_fragments_that_are_never_spread() {
+        let documents: &[(&str, &str)] = &[
+            ("User.js", "fragment User_user on User { name }"),
+            ("Unused.js", "fragment Unused_user on User { name }"),
+            ("Query.js", "query UserQuery { me { ...User_user } }"),
+        ];
+        let schema = build_schema("type Query { me: User } type User { name: String }").unwrap();
+        let programs = programs_for_documents(&schema, documents);
+
+        let unused_fragments = find_unused_fragments(std::iter::once(&programs));
+        assert_eq!(unused_fragments.len(), 1);
+        assert_eq!(unused_fragments[0].item.lookup(), "Unused_user");
+        assert_eq!(unused_fragments[0].location.file().lookup(), "Unused.js");
+
+        let source = GraphQLSource::new(documents[1].1, 0, 0);
+        let diagnostic = unused_fragment_diagnostic(&unused_fragments[0], &source).unwrap();
+        assert_eq!(
+            diagnostic.message,
+            "The fragment `Unused_user` is never spread"
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::Warning));
+        assert_eq!(
+            diagnostic.range,
+            Range::new(Position::new(0, 0), Position::new(0, 37))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for the `relay/version` command, returning the version of the server
+//! that is also reported in the `serverInfo` of the `initialize` result.
+use crate::lsp::{Connection, ExecuteCommandParams, Message, ServerRequestId, ServerResponse};
+use serde::Serialize;
+
+/// The name of the command returning the version of the server
+pub const VERSION_COMMAND: &str = "relay/version";
+
+pub fn is_version_command(params: &ExecuteCommandParams) -> bool {
+    params.command == VERSION_COMMAND
+}
+
+/// The name and version of the server, see `ServerInfo::version`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerInfo {
+    pub name: String,
+    pub version: String,
+}
+
+impl ServerInfo {
+    pub fn current() -> Self {
+        ServerInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: ServerInfo::version(env!("CARGO_PKG_VERSION"), env!("RELAY_LSP_GIT_HASH")),
+        }
+    }
+
+    /// The version of the crate with the commit it was built from as build metadata,
+    /// e.g. `1.2.3+0123abc`, as set by `build.rs`
+    fn version(crate_version: &str, git_hash: &str) -> String {
+        if git_hash == "unknown" {
+            crate_version.to_string()
+        } else {
+            format!("{}+{}", crate_version, git_hash)
+        }
+    }
+}
+
+pub fn send_version_response(
+    server_info: &ServerInfo,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(server_info).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_serialize_server_info() {
+        let server_info = ServerInfo {
+            name: "relay-lsp".to_string(),
+            version: "1.2.3+0123abc".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&server_info).unwrap(),
+            json!({
+                "name": "relay-lsp",
+                "version": "1.2.3+0123abc",
+            })
+        );
+    }
+
+    #[test]
+    fn test_version_includes_known_git_hash() {
+        assert_eq!(ServerInfo::version("1.2.3", "0123abc"), "1.2.3+0123abc");
+        assert_eq!(ServerInfo::version("1.2.3", "unknown"), "1.2.3");
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Utilities for providing the workspace symbols language feature
+use crate::lsp::{
+    Connection, Message, ServerRequestId, ServerResponse, SymbolInformation, SymbolKind,
+};
+use common::Location;
+use interner::StringKey;
+use relay_compiler::Programs;
+use std::collections::BTreeMap;
+
+/// An operation or fragment matching the query of a workspace symbol request
+pub struct WorkspaceSymbol {
+    pub name: StringKey,
+    pub kind: SymbolKind,
+    pub location: Location,
+}
+
+/// How a definition name matches the query, better matches are ranked first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum NameMatch {
+    Prefix,
+    Substring,
+}
+
+/// Finds the operations and fragments of the given `Programs` whose name contains the
+/// `query`, ignoring case. Names starting with the query are ranked before the names
+/// only containing it, and names with the same rank are sorted alphabetically.
+pub fn find_workspace_symbols<'a, 'schema: 'a>(
+    query: &str,
+    project_programs: impl Iterator<Item = &'a Programs<'schema>>,
+) -> Vec<WorkspaceSymbol> {
+    // The definitions of a base project are part of the programs of each project
+    // extending it, so they are deduped by location
+    let mut definitions = BTreeMap::new();
+    for programs in project_programs {
+        for operation in programs.source.operations() {
+            definitions.insert(
+                operation.name.location,
+                (operation.name.item, SymbolKind::Function),
+            );
+        }
+        for fragment in programs.source.fragments() {
+            definitions.insert(
+                fragment.name.location,
+                (fragment.name.item, SymbolKind::Struct),
+            );
+        }
+    }
+
+    let query = query.to_lowercase();
+    let mut symbols: Vec<(NameMatch, WorkspaceSymbol)> = definitions
+        .into_iter()
+        .filter_map(|(location, (name, kind))| {
+            let name_match = match_name(&name.lookup().to_lowercase(), &query)?;
+            Some((
+                name_match,
+                WorkspaceSymbol {
+                    name,
+                    kind,
+                    location,
+                },
+            ))
+        })
+        .collect();
+    symbols.sort_by(|(a_match, a), (b_match, b)| {
+        a_match
+            .cmp(b_match)
+            .then_with(|| a.name.lookup().cmp(b.name.lookup()))
+    });
+    symbols.into_iter().map(|(_, symbol)| symbol).collect()
+}
+
+fn match_name(name: &str, query: &str) -> Option<NameMatch> {
+    if name.starts_with(query) {
+        Some(NameMatch::Prefix)
+    } else if name.contains(query) {
+        Some(NameMatch::Substring)
+    } else {
+        None
+    }
+}
+
+pub fn send_workspace_symbols_response(
+    symbols: Vec<SymbolInformation>,
+    request_id: ServerRequestId,
+    connection: &Connection,
+) {
+    let result = serde_json::to_value(&symbols).unwrap();
+    let response = ServerResponse {
+        id: request_id,
+        error: None,
+        result: Some(result),
+    };
+    connection.sender.send(Message::Response(response)).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::references::tests::programs_for_documents;
+    use schema::build_schema;
+
+    const DOCUMENTS: &[(&str, &str)] = &[
+        ("User.js", "fragment User_user on User { name }"),
+        (
+            "Query.js",
+            "query UserQuery { me { ...User_user } } query ProfileUserQuery { me { name } }",
+        ),
+        (
+            "Friends.js",
+            "fragment Friends_user on User { friends { name } }",
+        ),
+    ];
+
+    fn symbol_names(query: &str) -> Vec<&'static str> {
+        let schema =
+            build_schema("type Query { me: User } type User { name: String, friends: [User] }")
+                .unwrap();
+        let programs = programs_for_documents(&schema, DOCUMENTS);
+        find_workspace_symbols(query, std::iter::once(&programs))
+            .iter()
+            .map(|symbol| symbol.name.lookup())
+            .collect()
+    }
+
+    #[test]
+    fn test_ranks_prefix_matches_before_substring_matches() {
+        assert_eq!(
+            symbol_names("user"),
+            vec!["UserQuery", "User_user", "Friends_user", "ProfileUserQuery"]
+        );
+        assert_eq!(symbol_names("missing"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_matches_names_ignoring_case() {
+        assert_eq!(symbol_names("USERQ"), vec!["UserQuery", "ProfileUserQuery"]);
+        assert_eq!(symbol_names("friends_USER"), vec!["Friends_user"]);
+    }
+
+    #[test]
+    fn test_empty_query_matches_all_definitions() {
+        assert_eq!(
+            symbol_names(""),
+            vec!["Friends_user", "ProfileUserQuery", "UserQuery", "User_user"]
+        );
+    }
+}
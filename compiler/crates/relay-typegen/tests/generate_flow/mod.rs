@@ -27,6 +27,9 @@ pub fn transform_fixture(fixture: &Fixture) -> Result<String, String> {
         program,
         &Default::default(),
         &*OSS_CONNECTION_INTERFACE,
+        false,
+        false,
+        None,
         &ConsoleLogger,
     )
     .unwrap();
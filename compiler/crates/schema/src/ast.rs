@@ -52,6 +52,7 @@ pub enum Definition {
     },
     DirectiveDefinition {
         name: StringKey,
+        description: Option<StringKey>,
         arguments: Vec<InputValueDefinition>,
         repeatable: bool,
         locations: Vec<DirectiveLocation>,
@@ -530,6 +530,7 @@ impl Schema {
             }
             ast::Definition::DirectiveDefinition {
                 name,
+                description,
                 arguments,
                 repeatable: _repeatable,
                 locations,
@@ -546,6 +547,7 @@ impl Schema {
                     *name,
                     Directive {
                         name: *name,
+                        description: *description,
                         arguments,
                         locations: locations.clone(),
                         is_extension,
@@ -1074,6 +1076,7 @@ impl TypeReference {
 #[derive(Clone, Debug)]
 pub struct Directive {
     pub name: StringKey,
+    pub description: Option<StringKey>,
     pub arguments: ArgumentDefinitions,
     pub locations: Vec<DirectiveLocation>,
     pub is_extension: bool,
@@ -17,6 +17,7 @@ mod definitions;
 mod errors;
 mod lexer;
 mod parser;
+mod relay_directives;
 mod token;
 
 pub use ast::{
@@ -31,6 +32,10 @@ pub use definitions::{
 pub use errors::{Result, SchemaError};
 use lexer::Lexer;
 use parser::Parser;
+pub use relay_directives::{
+    get_relay_directive_argument, RelayDirectiveArgument, RelayDirectiveArgumentKind,
+    RELAY_DIRECTIVE_ARGUMENTS,
+};
 
 pub const BUILTINS: &str = include_str!("./builtins.graphql");
 
@@ -162,7 +162,7 @@ impl<'a> Parser<'a> {
      *   - InputObjectTypeDefinition
      */
     fn parse_type_system_definition(&mut self) -> Result<Definition> {
-        let _desc = self.parse_description();
+        let description = self.parse_description();
         let keyword_token = self.peek();
         match keyword_token {
             TokenKind::Name("schema") => self.parse_schema_definition(),
@@ -172,7 +172,7 @@ impl<'a> Parser<'a> {
             TokenKind::Name("union") => self.parse_union_type_definition(),
             TokenKind::Name("enum") => self.parse_enum_type_definition(),
             TokenKind::Name("input") => self.parse_input_object_type_definition(),
-            TokenKind::Name("directive") => self.parse_directive_definition(),
+            TokenKind::Name("directive") => self.parse_directive_definition(description),
             TokenKind::Name("extend") => self.parse_type_system_extension(),
             token => Err(SchemaError::Syntax(format!("Unexpected token {:?}", token))),
         }
@@ -181,11 +181,10 @@ impl<'a> Parser<'a> {
     /**
      * Description : StringValue
      */
-    fn parse_description(&mut self) -> Option<&str> {
-        // TODO actually return the description
+    fn parse_description(&mut self) -> Option<StringKey> {
         let value = match self.peek() {
-            TokenKind::Str(value) => Some(*value),
-            TokenKind::BlockString(value) => Some(*value),
+            TokenKind::Str(value) => Some(value.trim().intern()),
+            TokenKind::BlockString(value) => Some(dedent_block_string(value).intern()),
             _ => None,
         };
         if value.is_some() {
@@ -560,7 +559,7 @@ impl<'a> Parser<'a> {
      * DirectiveDefinition :
      *   - Description? directive @ Name ArgumentsDefinition? `repeatable`? on DirectiveLocations
      */
-    fn parse_directive_definition(&mut self) -> Result<Definition> {
+    fn parse_directive_definition(&mut self, description: Option<StringKey>) -> Result<Definition> {
         self.expect_keyword("directive")?;
         self.expect_token(TokenKind::At)?;
         let name = self.parse_name()?;
@@ -571,6 +570,7 @@ impl<'a> Parser<'a> {
         // TODO add directives
         Ok(Definition::DirectiveDefinition {
             name,
+            description,
             arguments,
             repeatable,
             locations,
@@ -883,3 +883,36 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+/// Removes the common indentation and the leading and trailing blank lines of
+/// a block string, e.g. the description of a definition spanning multiple lines.
+fn dedent_block_string(value: &str) -> String {
+    let lines: Vec<&str> = value.lines().collect();
+    let common_indent = lines
+        .iter()
+        .skip(1)
+        .map(|line| (line, block_string_indent(line)))
+        .filter(|(line, indent)| *indent < line.len())
+        .map(|(_, indent)| indent)
+        .min()
+        .unwrap_or(0);
+    let dedented: Vec<&str> = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            if index == 0 {
+                line.trim_start()
+            } else {
+                // The indent only counts single byte characters, so this is a char boundary
+                &line[common_indent.min(block_string_indent(line))..]
+            }
+        })
+        .collect();
+    dedented.join("\n").trim_matches('\n').to_string()
+}
+
+/// The number of leading spaces and tabs of a line, which are the only whitespace
+/// characters indenting a block string
+fn block_string_indent(line: &str) -> usize {
+    line.chars().take_while(|c| *c == ' ' || *c == '\t').count()
+}
@@ -0,0 +1,116 @@
+/*
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! The arguments of the directives of `RELAY_EXTENSIONS` that are read by the
+//! compiler rather than sent to the server, and therefore only accept literal
+//! values. They are shared by the validations and the completions of the
+//! language server.
+
+use interner::StringKey;
+use std::fmt;
+
+/// The kind of literal accepted by an argument of a Relay directive
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RelayDirectiveArgumentKind {
+    Boolean,
+    String,
+}
+
+impl fmt::Display for RelayDirectiveArgumentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayDirectiveArgumentKind::Boolean => write!(f, "a boolean literal"),
+            RelayDirectiveArgumentKind::String => write!(f, "a string literal"),
+        }
+    }
+}
+
+/// An argument of a Relay directive only accepting a literal value
+#[derive(Clone, Copy, Debug)]
+pub struct RelayDirectiveArgument {
+    pub directive_name: &'static str,
+    pub argument_name: &'static str,
+    pub kind: RelayDirectiveArgumentKind,
+}
+
+pub const RELAY_DIRECTIVE_ARGUMENTS: &[RelayDirectiveArgument] = &[
+    RelayDirectiveArgument {
+        directive_name: "relay",
+        argument_name: "mask",
+        kind: RelayDirectiveArgumentKind::Boolean,
+    },
+    RelayDirectiveArgument {
+        directive_name: "relay",
+        argument_name: "plural",
+        kind: RelayDirectiveArgumentKind::Boolean,
+    },
+    RelayDirectiveArgument {
+        directive_name: "refetchable",
+        argument_name: "queryName",
+        kind: RelayDirectiveArgumentKind::String,
+    },
+    RelayDirectiveArgument {
+        directive_name: "match",
+        argument_name: "key",
+        kind: RelayDirectiveArgumentKind::String,
+    },
+    RelayDirectiveArgument {
+        directive_name: "module",
+        argument_name: "name",
+        kind: RelayDirectiveArgumentKind::String,
+    },
+];
+
+/// Finds the literal argument `argument_name` of the Relay directive `directive_name`
+pub fn get_relay_directive_argument(
+    directive_name: StringKey,
+    argument_name: StringKey,
+) -> Option<&'static RelayDirectiveArgument> {
+    let directive_name = directive_name.lookup();
+    let argument_name = argument_name.lookup();
+    RELAY_DIRECTIVE_ARGUMENTS.iter().find(|argument| {
+        argument.directive_name == directive_name && argument.argument_name == argument_name
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_schema_with_extensions, RELAY_EXTENSIONS};
+    use interner::Intern;
+
+    #[test]
+    fn test_relay_directive_arguments_are_defined() {
+        let schema =
+            build_schema_with_extensions(&["type Query { id: ID }"], &[RELAY_EXTENSIONS]).unwrap();
+        for argument in RELAY_DIRECTIVE_ARGUMENTS {
+            let directive = schema
+                .get_directive(argument.directive_name.intern())
+                .unwrap_or_else(|| panic!("Unknown directive @{}", argument.directive_name));
+            assert!(
+                directive
+                    .arguments
+                    .iter()
+                    .any(|definition| definition.name.lookup() == argument.argument_name),
+                "Unknown argument `{}` of @{}",
+                argument.argument_name,
+                argument.directive_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_relay_directive_argument() {
+        assert_eq!(
+            get_relay_directive_argument("relay".intern(), "plural".intern())
+                .map(|argument| argument.kind),
+            Some(RelayDirectiveArgumentKind::Boolean)
+        );
+        assert!(get_relay_directive_argument("relay".intern(), "other".intern()).is_none());
+        assert!(get_relay_directive_argument("include".intern(), "if".intern()).is_none());
+    }
+}